@@ -8,15 +8,18 @@
 //!
 //! - [`keys`] - BIP39 seed phrase and key derivation
 //! - [`crypto`] - Encryption/decryption primitives
+//! - [`settings`] - Encrypted roaming settings in the user's FS5 tree
 
 pub mod crypto;
 pub mod keys;
+pub mod settings;
 
 pub use crypto::{
     decrypt_chunk, decrypt_xchacha20poly1305, decrypt_xchacha20poly1305_with_nonce, encrypt_chunk,
     encrypt_xchacha20poly1305_with_nonce, hash_blake3,
 };
 pub use keys::{DerivedKeys, SyncKeys};
+pub use settings::{RememberedNode, Settings, SettingsError};
 
 #[cfg(feature = "std")]
 pub use crypto::encrypt_xchacha20poly1305;
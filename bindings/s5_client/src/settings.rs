@@ -0,0 +1,303 @@
+//! Roaming client settings stored in the user's own FS5 tree.
+//!
+//! Settings live as small JSON files in the encrypted [`SETTINGS_DIR`]
+//! directory at the root of the tree. Each value is stored inline in the
+//! directory metadata, so it is encrypted with the directory and travels
+//! with the same registry entry as the rest of the tree: every device
+//! opening the tree from the same seed phrase sees the same settings.
+//!
+//! ## Layout
+//!
+//! ```text
+//! .config/
+//!     remembered_nodes.json   Vec<RememberedNode>
+//!     ui.json                 BTreeMap<String, String>
+//!     device_names.json       BTreeMap<String, String>
+//!     <key>.json              anything set through Settings::set
+//! ```
+//!
+//! Writes are last-writer-wins per key, like any other FS5 file.
+
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use s5_fs::{CursorKind, FS5, FileRef};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+/// The settings directory at the root of the tree.
+pub const SETTINGS_DIR: &str = ".config";
+
+/// Largest encoded value accepted by [`Settings::set_raw`]. Values are
+/// stored inline in directory metadata, so they must stay small.
+pub const MAX_VALUE_LEN: usize = 64 * 1024;
+
+/// Key of the [`RememberedNode`] list.
+pub const REMEMBERED_NODES: &str = "remembered_nodes";
+/// Key of the UI preference map.
+pub const UI_PREFERENCES: &str = "ui";
+/// Key of the device id → display name map.
+pub const DEVICE_NAMES: &str = "device_names";
+
+/// Errors that can occur reading or writing settings.
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Invalid settings key {0:?}: use letters, digits, '-', '_' or '.'")]
+    InvalidKey(String),
+    #[error("Settings value for {key} is {len} bytes; the limit is {MAX_VALUE_LEN}")]
+    TooLarge { key: String, len: usize },
+    #[error("Settings file for {0} is not stored inline")]
+    NotInline(String),
+    #[error("Malformed settings value for {key}: {source}")]
+    Malformed {
+        key: String,
+        source: serde_json::Error,
+    },
+    #[error("Storage error: {0}")]
+    Storage(String),
+}
+
+/// A remote node the client has connected to before.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RememberedNode {
+    /// Iroh node id of the remote node.
+    pub node_id: String,
+    /// User-chosen label.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Seconds since epoch of the last successful connection.
+    #[serde(default)]
+    pub last_connected: Option<u64>,
+}
+
+/// Typed access to the settings directory of an [`FS5`] tree.
+///
+/// Setters save the tree before returning, so a value is durable (and
+/// visible to other devices) once the call succeeds.
+pub struct Settings<'a> {
+    fs: &'a FS5,
+}
+
+impl<'a> Settings<'a> {
+    pub fn new(fs: &'a FS5) -> Self {
+        Self { fs }
+    }
+
+    /// Raw bytes stored under `key`, if set.
+    pub async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, SettingsError> {
+        let path = value_path(key)?;
+        let Some(file_ref) = self.fs.file_get(&path).await else {
+            return Ok(None);
+        };
+        match file_ref.inline_data() {
+            Some(data) => Ok(Some(data.to_vec())),
+            None => Err(SettingsError::NotInline(key.to_string())),
+        }
+    }
+
+    /// Stores `value` under `key`, replacing any previous value.
+    pub async fn set_raw(&self, key: &str, value: Vec<u8>) -> Result<(), SettingsError> {
+        let path = value_path(key)?;
+        if value.len() > MAX_VALUE_LEN {
+            return Err(SettingsError::TooLarge {
+                key: key.to_string(),
+                len: value.len(),
+            });
+        }
+        // Idempotent; keeps the directory encrypted even on a tree whose
+        // root was created before settings existed.
+        self.fs
+            .create_dir(SETTINGS_DIR, true)
+            .await
+            .map_err(storage)?;
+        self.fs
+            .file_put(&path, FileRef::new_inline_blob(Bytes::from(value)))
+            .await
+            .map_err(storage)?;
+        self.fs.save().await.map_err(storage)
+    }
+
+    /// Removes `key`. Returns whether it was set.
+    pub async fn remove(&self, key: &str) -> Result<bool, SettingsError> {
+        let path = value_path(key)?;
+        if !self.fs.file_exists(&path).await {
+            return Ok(false);
+        }
+        self.fs.file_delete(&path).await.map_err(storage)?;
+        self.fs.save().await.map_err(storage)?;
+        Ok(true)
+    }
+
+    /// All keys that currently have a value, sorted.
+    pub async fn keys(&self) -> Result<Vec<String>, SettingsError> {
+        if !self.has_settings_dir().await? {
+            return Ok(Vec::new());
+        }
+        let mut keys = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let (entries, next) = self
+                .fs
+                .list_at(SETTINGS_DIR, cursor.as_deref(), 1000)
+                .await
+                .map_err(storage)?;
+            keys.extend(entries.into_iter().filter_map(|(name, kind)| {
+                let key = name.strip_suffix(".json")?;
+                (matches!(kind, CursorKind::File) && valid_key(key)).then(|| key.to_string())
+            }));
+            match next {
+                Some(c) => cursor = Some(c),
+                None => break,
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// The JSON value under `key`, decoded as `T`.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SettingsError> {
+        match self.get_raw(key).await? {
+            Some(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(|source| {
+                SettingsError::Malformed {
+                    key: key.to_string(),
+                    source,
+                }
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Stores `value` under `key` as JSON.
+    pub async fn set<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), SettingsError> {
+        let bytes = serde_json::to_vec(value).map_err(|source| SettingsError::Malformed {
+            key: key.to_string(),
+            source,
+        })?;
+        self.set_raw(key, bytes).await
+    }
+
+    pub async fn remembered_nodes(&self) -> Result<Vec<RememberedNode>, SettingsError> {
+        Ok(self.get(REMEMBERED_NODES).await?.unwrap_or_default())
+    }
+
+    pub async fn set_remembered_nodes(
+        &self,
+        nodes: &[RememberedNode],
+    ) -> Result<(), SettingsError> {
+        self.set(REMEMBERED_NODES, nodes).await
+    }
+
+    /// Adds `node`, or replaces the entry with the same `node_id`.
+    pub async fn remember_node(&self, node: RememberedNode) -> Result<(), SettingsError> {
+        let mut nodes = self.remembered_nodes().await?;
+        match nodes.iter_mut().find(|n| n.node_id == node.node_id) {
+            Some(existing) => *existing = node,
+            None => nodes.push(node),
+        }
+        self.set_remembered_nodes(&nodes).await
+    }
+
+    pub async fn ui_preferences(&self) -> Result<BTreeMap<String, String>, SettingsError> {
+        Ok(self.get(UI_PREFERENCES).await?.unwrap_or_default())
+    }
+
+    /// Sets one UI preference, or clears it when `value` is `None`.
+    pub async fn set_ui_preference(
+        &self,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), SettingsError> {
+        let mut prefs = self.ui_preferences().await?;
+        match value {
+            Some(v) => prefs.insert(name.to_string(), v.to_string()),
+            None => prefs.remove(name),
+        };
+        self.set(UI_PREFERENCES, &prefs).await
+    }
+
+    /// Device id → display name.
+    pub async fn device_names(&self) -> Result<BTreeMap<String, String>, SettingsError> {
+        Ok(self.get(DEVICE_NAMES).await?.unwrap_or_default())
+    }
+
+    /// Names the device `device_id`, or forgets its name when `name` is
+    /// `None`.
+    pub async fn set_device_name(
+        &self,
+        device_id: &str,
+        name: Option<&str>,
+    ) -> Result<(), SettingsError> {
+        let mut names = self.device_names().await?;
+        match name {
+            Some(n) => names.insert(device_id.to_string(), n.to_string()),
+            None => names.remove(device_id),
+        };
+        self.set(DEVICE_NAMES, &names).await
+    }
+
+    async fn has_settings_dir(&self) -> Result<bool, SettingsError> {
+        let (entries, mut cursor) = self.fs.list(None, 1000).await.map_err(storage)?;
+        let mut entries = entries;
+        loop {
+            if entries
+                .iter()
+                .any(|(name, kind)| name == SETTINGS_DIR && matches!(kind, CursorKind::Directory))
+            {
+                return Ok(true);
+            }
+            let Some(c) = cursor else {
+                return Ok(false);
+            };
+            (entries, cursor) = self.fs.list(Some(&c), 1000).await.map_err(storage)?;
+        }
+    }
+}
+
+fn value_path(key: &str) -> Result<String, SettingsError> {
+    if !valid_key(key) {
+        return Err(SettingsError::InvalidKey(key.to_string()));
+    }
+    Ok(format!("{SETTINGS_DIR}/{key}.json"))
+}
+
+/// Keys map to file names, so they must not contain `/` or start with `.`.
+fn valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 128
+        && !key.starts_with('.')
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+fn storage(e: impl std::fmt::Display) -> SettingsError {
+    SettingsError::Storage(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_map_to_single_file_names() {
+        assert_eq!(value_path("ui").unwrap(), ".config/ui.json");
+        assert_eq!(
+            value_path("editor.font-size_2").unwrap(),
+            ".config/editor.font-size_2.json"
+        );
+        for bad in [
+            "",
+            ".hidden",
+            "a/b",
+            "../x",
+            "white space",
+            &"k".repeat(129),
+        ] {
+            assert!(value_path(bad).is_err(), "{bad:?} accepted");
+        }
+    }
+}
@@ -3,21 +3,24 @@
 //! All public functions and types in this module will be exposed to Dart.
 //! This API mirrors s5_wasm for feature parity.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use bytes::Bytes;
 use iroh::{endpoint::presets, Endpoint, SecretKey};
 use s5_blobs::RemoteBlobStore;
-use s5_client::DerivedKeys;
+use s5_client::{DerivedKeys, Settings};
 use s5_core::{blob::location::BlobLocation, blob::BlobStore, Hash, StreamKey};
-use s5_fs::{CursorKind, DirActorContext, FileRef, SigningKey, FS5};
+use s5_fs::{
+    Capability, CursorKind, DirActorContext, FileRef, Journal, JournalOp, SigningKey, FS5,
+};
 use s5_registry::RemoteRegistry;
 use tokio::sync::RwLock;
 
 pub use flutter_rust_bridge::frb;
 
 /// Maximum size for inline blobs (stored directly in directory metadata).
-const INLINE_BLOB_THRESHOLD: usize = 4096;
+const INLINE_BLOB_THRESHOLD: usize = s5_core::blob::import::DEFAULT_INLINE_THRESHOLD as usize;
 
 /// Block size for chunked encryption of large files.
 const ENCRYPTION_BLOCK_SIZE: u64 = 256 * 1024; // 256 KiB
@@ -49,6 +52,17 @@ impl From<s5_client::keys::KeyError> for S5Error {
     }
 }
 
+impl From<s5_client::SettingsError> for S5Error {
+    fn from(e: s5_client::SettingsError) -> Self {
+        match e {
+            s5_client::SettingsError::InvalidKey(_) | s5_client::SettingsError::TooLarge { .. } => {
+                S5Error::InvalidInput(e.to_string())
+            }
+            _ => S5Error::StorageError(e.to_string()),
+        }
+    }
+}
+
 impl From<anyhow::Error> for S5Error {
     fn from(e: anyhow::Error) -> Self {
         S5Error::InternalError(e.to_string())
@@ -161,6 +175,65 @@ pub struct DirectoryListing {
     pub directories: Vec<String>,
 }
 
+// ============================================================================
+// Journal
+// ============================================================================
+
+/// An unsaved change recorded in the journal.
+#[frb(dart_metadata = ("freezed"))]
+pub struct PendingChange {
+    /// `put`, `delete` or `create_dir`
+    pub kind: String,
+    pub path: String,
+}
+
+impl From<JournalOp> for PendingChange {
+    fn from(op: JournalOp) -> Self {
+        let kind = match &op {
+            JournalOp::Put { .. } => "put",
+            JournalOp::Delete { .. } => "delete",
+            JournalOp::CreateDir { .. } => "create_dir",
+        };
+        Self {
+            kind: kind.to_string(),
+            path: op.path().to_string(),
+        }
+    }
+}
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+/// A remote node remembered in the roaming settings.
+#[frb(dart_metadata = ("freezed"))]
+pub struct RememberedNode {
+    pub node_id: String,
+    pub label: Option<String>,
+    /// Last successful connection (seconds since epoch)
+    pub last_connected: Option<u64>,
+}
+
+impl From<s5_client::RememberedNode> for RememberedNode {
+    fn from(n: s5_client::RememberedNode) -> Self {
+        Self {
+            node_id: n.node_id,
+            label: n.label,
+            last_connected: n.last_connected,
+        }
+    }
+}
+
+impl From<RememberedNode> for s5_client::RememberedNode {
+    fn from(n: RememberedNode) -> Self {
+        Self {
+            node_id: n.node_id,
+            label: n.label,
+            last_connected: n.last_connected,
+        }
+    }
+}
+
 // ============================================================================
 // S5 Client
 // ============================================================================
@@ -169,6 +242,9 @@ struct S5ClientInner {
     #[allow(dead_code)]
     keys: DerivedKeys,
     endpoint: Endpoint,
+    /// False for shared roots, which borrow their opener's endpoint.
+    owns_endpoint: bool,
+    remote_id: iroh::EndpointId,
     blobs_client: s5_blobs::Client,
     fs: FS5,
 }
@@ -246,6 +322,8 @@ impl S5Client {
             inner: Arc::new(RwLock::new(Some(S5ClientInner {
                 keys,
                 endpoint,
+                owns_endpoint: true,
+                remote_id,
                 blobs_client,
                 fs,
             }))),
@@ -306,32 +384,40 @@ impl S5Client {
         }
         .map_err(|e| S5Error::StorageError(format!("Failed to list directory: {}", e)))?;
 
-        let mut files = Vec::new();
+        let mut file_names = Vec::new();
         let mut directories = Vec::new();
 
         for (name, kind) in entries {
             match kind {
-                CursorKind::File => {
-                    let full_path = if path.is_empty() || path == "/" {
-                        name.clone()
-                    } else {
-                        format!("{}/{}", path, name)
-                    };
-                    if let Some(file_ref) = inner.fs.file_get(&full_path).await {
-                        let file_ref_json =
-                            serde_json::to_string(&file_ref).unwrap_or_else(|_| "{}".to_string());
-                        files.push(FileEntry {
-                            name,
-                            file_ref_json,
-                            size: file_ref.size,
-                            media_type: file_ref.media_type.clone(),
-                            timestamp: file_ref.timestamp,
-                        });
-                    }
-                }
-                CursorKind::Directory => {
-                    directories.push(name);
+                CursorKind::File => file_names.push(name),
+                CursorKind::Directory => directories.push(name),
+            }
+        }
+
+        let full_paths: Vec<String> = file_names
+            .iter()
+            .map(|name| {
+                if path.is_empty() || path == "/" {
+                    name.clone()
+                } else {
+                    format!("{}/{}", path, name)
                 }
+            })
+            .collect();
+        let file_refs = inner.fs.stat_many(&full_paths).await;
+
+        let mut files = Vec::new();
+        for (name, file_ref) in file_names.into_iter().zip(file_refs) {
+            if let Some(file_ref) = file_ref {
+                let file_ref_json =
+                    serde_json::to_string(&file_ref).unwrap_or_else(|_| "{}".to_string());
+                files.push(FileEntry {
+                    name,
+                    file_ref_json,
+                    size: file_ref.size,
+                    media_type: file_ref.media_type.clone(),
+                    timestamp: file_ref.timestamp,
+                });
             }
         }
 
@@ -355,6 +441,31 @@ impl S5Client {
         }
     }
 
+    /// Get the metadata of many files at once, as JSON, in the order of
+    /// `paths`; `None` for paths with no file.
+    pub async fn stat_many(&self, paths: Vec<String>) -> Result<Vec<Option<String>>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        inner
+            .fs
+            .stat_many(&paths)
+            .await
+            .into_iter()
+            .map(|file_ref| {
+                file_ref
+                    .map(|file_ref| {
+                        serde_json::to_string(&file_ref).map_err(|e| {
+                            S5Error::InternalError(format!("Serialization failed: {}", e))
+                        })
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
+
     /// Check if a file exists.
     pub async fn file_exists(&self, path: String) -> Result<bool, S5Error> {
         let guard = self.inner.read().await;
@@ -667,6 +778,209 @@ impl S5Client {
         Ok(())
     }
 
+    /// Connect like [`S5Client::connect`], journaling unsaved changes to the
+    /// redb file at `journal_path`.
+    ///
+    /// Changes a previous session made but never saved (e.g. because the
+    /// app was killed) are replayed and saved before this returns.
+    pub async fn connect_with_journal(
+        seed_phrase: String,
+        remote_node_id: String,
+        journal_path: String,
+    ) -> Result<S5Client, S5Error> {
+        let client = Self::connect(seed_phrase, remote_node_id).await?;
+        {
+            let mut guard = client.inner.write().await;
+            let inner = guard
+                .as_mut()
+                .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+            let root_id = inner.keys.sync_keys.public_key;
+            let journal = Journal::open_redb(&journal_path, &root_id)
+                .map_err(|e| S5Error::StorageError(format!("Failed to open journal: {}", e)))?;
+            inner.fs = inner.fs.clone().with_journal(journal);
+            inner
+                .fs
+                .replay_journal()
+                .await
+                .map_err(|e| S5Error::StorageError(format!("Failed to replay journal: {}", e)))?;
+        }
+        Ok(client)
+    }
+
+    /// Changes made since the last successful save, oldest first. Empty
+    /// unless connected with [`S5Client::connect_with_journal`].
+    pub async fn pending_changes(&self) -> Result<Vec<PendingChange>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(inner
+            .fs
+            .pending_changes()
+            .into_iter()
+            .map(PendingChange::from)
+            .collect())
+    }
+
+    /// Open a root someone shared with us from its capability string.
+    ///
+    /// Returns a separate, read-only client for the shared root that reuses
+    /// this client's connection. The share's peer hint decides which node
+    /// serves the root; without one, this client's remote node is used.
+    pub async fn open_shared(&self, capability: String) -> Result<S5Client, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        let mut capability: Capability = capability
+            .parse()
+            .map_err(|e| S5Error::InvalidInput(format!("Invalid share: {}", e)))?;
+        let remote_id = match capability.peer {
+            Some(peer) => iroh::EndpointId::from_bytes(&peer)
+                .map_err(|e| S5Error::InvalidInput(format!("Invalid peer in share: {}", e)))?,
+            None => inner.remote_id,
+        };
+        capability.peer = Some(*remote_id.as_bytes());
+
+        let (ctx, blobs_client) = capability
+            .remote_context(inner.endpoint.clone())
+            .map_err(|e| S5Error::ConnectionError(format!("Failed to open share: {}", e)))?;
+
+        Ok(S5Client {
+            inner: Arc::new(RwLock::new(Some(S5ClientInner {
+                keys: inner.keys.clone(),
+                endpoint: inner.endpoint.clone(),
+                owns_endpoint: false,
+                remote_id,
+                blobs_client,
+                fs: FS5::open(ctx),
+            }))),
+            public_key_hex: self.public_key_hex.clone(),
+            node_id: self.node_id.clone(),
+        })
+    }
+
+    /// Get a roaming setting from `.config/` as a JSON string.
+    pub async fn settings_get(&self, key: String) -> Result<Option<String>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        let value: Option<serde_json::Value> = Settings::new(&inner.fs).get(&key).await?;
+        Ok(value.map(|v| v.to_string()))
+    }
+
+    /// Set a roaming setting from a JSON string.
+    pub async fn settings_set(&self, key: String, value_json: String) -> Result<(), S5Error> {
+        let value: serde_json::Value = serde_json::from_str(&value_json)
+            .map_err(|e| S5Error::InvalidInput(format!("Invalid JSON: {}", e)))?;
+
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(Settings::new(&inner.fs).set(&key, &value).await?)
+    }
+
+    /// Remove a roaming setting. Returns whether it was set.
+    pub async fn settings_remove(&self, key: String) -> Result<bool, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(Settings::new(&inner.fs).remove(&key).await?)
+    }
+
+    /// All roaming setting keys, sorted.
+    pub async fn settings_keys(&self) -> Result<Vec<String>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(Settings::new(&inner.fs).keys().await?)
+    }
+
+    /// Remote nodes remembered across devices.
+    pub async fn remembered_nodes(&self) -> Result<Vec<RememberedNode>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        let nodes = Settings::new(&inner.fs).remembered_nodes().await?;
+        Ok(nodes.into_iter().map(Into::into).collect())
+    }
+
+    /// Remember a remote node, replacing any entry with the same id.
+    pub async fn remember_node(&self, node: RememberedNode) -> Result<(), S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(Settings::new(&inner.fs).remember_node(node.into()).await?)
+    }
+
+    /// UI preferences (name → value).
+    pub async fn ui_preferences(&self) -> Result<HashMap<String, String>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        let prefs = Settings::new(&inner.fs).ui_preferences().await?;
+        Ok(prefs.into_iter().collect())
+    }
+
+    /// Set a UI preference, or clear it when `value` is null.
+    pub async fn set_ui_preference(
+        &self,
+        name: String,
+        value: Option<String>,
+    ) -> Result<(), S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(Settings::new(&inner.fs)
+            .set_ui_preference(&name, value.as_deref())
+            .await?)
+    }
+
+    /// Device names (device id → name).
+    pub async fn device_names(&self) -> Result<HashMap<String, String>, S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        let names = Settings::new(&inner.fs).device_names().await?;
+        Ok(names.into_iter().collect())
+    }
+
+    /// Name a device, or forget its name when `name` is null.
+    pub async fn set_device_name(
+        &self,
+        device_id: String,
+        name: Option<String>,
+    ) -> Result<(), S5Error> {
+        let guard = self.inner.read().await;
+        let inner = guard
+            .as_ref()
+            .ok_or_else(|| S5Error::ConnectionError("Not connected".to_string()))?;
+
+        Ok(Settings::new(&inner.fs)
+            .set_device_name(&device_id, name.as_deref())
+            .await?)
+    }
+
     /// Disconnect from the remote node.
     pub async fn disconnect(&self) -> Result<(), S5Error> {
         let mut guard = self.inner.write().await;
@@ -674,7 +988,9 @@ impl S5Client {
             // Save pending changes
             let _ = inner.fs.flush().await;
             // Close endpoint
-            inner.endpoint.close().await;
+            if inner.owns_endpoint {
+                inner.endpoint.close().await;
+            }
         }
         Ok(())
     }
@@ -27,12 +27,14 @@ use std::sync::Arc;
 use bytes::Bytes;
 use iroh::{Endpoint, SecretKey, endpoint::presets};
 use s5_blobs::RemoteBlobStore;
-use s5_client::DerivedKeys;
+use s5_client::{DerivedKeys, RememberedNode, Settings, SettingsError};
 use s5_core::{Hash, StreamKey, blob::BlobStore, blob::location::BlobLocation};
-use s5_fs::{CursorKind, DirActorContext, FS5, FileRef, SigningKey};
+use s5_fs::{Capability, CursorKind, DirActorContext, FS5, FileRef, Journal, SigningKey};
 use s5_registry::RemoteRegistry;
 use wasm_bindgen::prelude::*;
 
+use crate::journal::IdbJournalBackend;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -45,7 +47,7 @@ macro_rules! console_log {
 
 /// Maximum size for inline blobs (stored directly in directory metadata).
 /// Larger files are encrypted and stored separately in the blob store.
-const INLINE_BLOB_THRESHOLD: usize = 4096;
+const INLINE_BLOB_THRESHOLD: usize = s5_core::blob::import::DEFAULT_INLINE_THRESHOLD as usize;
 
 /// Block size for chunked encryption of large files.
 const ENCRYPTION_BLOCK_SIZE: u64 = 256 * 1024; // 256 KiB
@@ -251,11 +253,30 @@ impl S5Client {
             registry,
         );
 
-        // Open FS5 instance
-        let fs = FS5::open(ctx);
+        // Open FS5 instance, journaling unsaved changes to IndexedDB
+        let root_id = hex::encode(self.keys.sync_keys.public_key);
+        let journal = match crate::journal::load(&root_id).await {
+            Ok(persisted) => Journal::new(IdbJournalBackend::new(root_id), persisted.as_deref())
+                .unwrap_or_else(|e| {
+                    console_log!("Discarding unreadable journal: {}", e);
+                    Journal::in_memory()
+                }),
+            Err(e) => {
+                console_log!("IndexedDB unavailable, journal kept in memory: {:?}", e);
+                Journal::in_memory()
+            }
+        };
+        let fs = FS5::open(ctx).with_journal(journal);
 
         console_log!("FS5 initialized with remote-only backend!");
 
+        // Re-apply changes a previous session made but never saved
+        match fs.replay_journal().await {
+            Ok(0) => {}
+            Ok(n) => console_log!("Replayed {} unsaved changes from the journal", n),
+            Err(e) => console_log!("Replaying journal failed: {}", e),
+        }
+
         self.endpoint = Some(endpoint);
         self.blobs_client = Some(blobs_client.clone());
         self.fs = Some(fs);
@@ -353,24 +374,32 @@ impl S5Client {
                 .map_err(|e| JsError::new(&format!("Failed to list directory: {}", e)))?
         };
 
-        let mut files_map = BTreeMap::new();
+        let mut file_names = Vec::new();
         let mut dir_names = Vec::new();
 
         for (name, kind) in entries {
             match kind {
-                CursorKind::File => {
-                    let full_path = if path.is_empty() || path == "/" {
-                        name.clone()
-                    } else {
-                        format!("{}/{}", path, name)
-                    };
-                    if let Some(file_ref) = fs.file_get(&full_path).await {
-                        files_map.insert(name, file_ref);
-                    }
-                }
-                CursorKind::Directory => {
-                    dir_names.push(name);
+                CursorKind::File => file_names.push(name),
+                CursorKind::Directory => dir_names.push(name),
+            }
+        }
+
+        let full_paths: Vec<String> = file_names
+            .iter()
+            .map(|name| {
+                if path.is_empty() || path == "/" {
+                    name.clone()
+                } else {
+                    format!("{}/{}", path, name)
                 }
+            })
+            .collect();
+        let file_refs = fs.stat_many(&full_paths).await;
+
+        let mut files_map = BTreeMap::new();
+        for (name, file_ref) in file_names.into_iter().zip(file_refs) {
+            if let Some(file_ref) = file_ref {
+                files_map.insert(name, file_ref);
             }
         }
 
@@ -395,6 +424,20 @@ impl S5Client {
         }
     }
 
+    /// Get the metadata (FileRef) of many files at once, as an array in
+    /// the order of `paths` with `null` for paths with no file
+    #[wasm_bindgen]
+    pub async fn stat_many(&self, paths: Vec<String>) -> Result<JsValue, JsError> {
+        let fs = self
+            .fs
+            .as_ref()
+            .ok_or_else(|| JsError::new("Not connected. Call connect() first."))?;
+
+        let file_refs = fs.stat_many(&paths).await;
+        serde_wasm_bindgen::to_value(&file_refs)
+            .map_err(|e| JsError::new(&format!("Failed to serialize FileRefs: {}", e)))
+    }
+
     /// Create a new directory
     #[wasm_bindgen]
     pub async fn create_directory(&self, path: &str) -> Result<(), JsError> {
@@ -798,6 +841,180 @@ impl S5Client {
         Ok(fs.file_exists(path).await)
     }
 
+    /// Open a root someone shared with us from its capability string
+    ///
+    /// Returns a separate, read-only client for the shared root that reuses
+    /// this client's connection. The share's peer hint decides which node
+    /// serves the root; without one, this client's remote node is used.
+    #[wasm_bindgen]
+    pub fn open_shared(&self, capability: &str) -> Result<S5Client, JsError> {
+        let endpoint = self
+            .endpoint
+            .as_ref()
+            .ok_or_else(|| JsError::new("Not connected. Call connect() first."))?;
+
+        let mut capability: Capability = capability
+            .parse()
+            .map_err(|e| JsError::new(&format!("Invalid share: {}", e)))?;
+        let peer = match capability.peer {
+            Some(peer) => iroh::EndpointId::from_bytes(&peer)
+                .map_err(|e| JsError::new(&format!("Invalid peer in share: {}", e)))?,
+            None => self
+                .remote_node_id
+                .parse()
+                .map_err(|e| JsError::new(&format!("Invalid remote node ID: {}", e)))?,
+        };
+        capability.peer = Some(*peer.as_bytes());
+
+        console_log!("Opening shared root via {}", peer.fmt_short());
+
+        let (ctx, blobs_client) = capability
+            .remote_context(endpoint.clone())
+            .map_err(|e| JsError::new(&format!("Failed to open share: {}", e)))?;
+
+        Ok(S5Client {
+            keys: self.keys.clone(),
+            remote_node_id: peer.to_string(),
+            // The endpoint stays owned by this client.
+            endpoint: None,
+            blobs_client: Some(blobs_client),
+            fs: Some(FS5::open(ctx)),
+        })
+    }
+
+    /// Changes made since the last successful save, oldest first
+    ///
+    /// Returns `[{op: "put" | "delete" | "create_dir", path, ...}]`. These
+    /// are replayed on the next `connect()` if the page closes before they
+    /// are saved.
+    #[wasm_bindgen]
+    pub fn pending_changes(&self) -> Result<JsValue, JsError> {
+        let fs = self
+            .fs
+            .as_ref()
+            .ok_or_else(|| JsError::new("Not connected. Call connect() first."))?;
+        to_json_value(&fs.pending_changes())
+    }
+
+    /// Get a roaming setting from `.config/`, or `null` if unset
+    #[wasm_bindgen]
+    pub async fn settings_get(&self, key: &str) -> Result<JsValue, JsError> {
+        let value: Option<serde_json::Value> =
+            self.settings()?.get(key).await.map_err(settings_err)?;
+        match value {
+            Some(v) => to_json_value(&v),
+            None => Ok(JsValue::NULL),
+        }
+    }
+
+    /// Set a roaming setting; `value` must be JSON-serializable
+    #[wasm_bindgen]
+    pub async fn settings_set(&self, key: &str, value: JsValue) -> Result<(), JsError> {
+        let value: serde_json::Value = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| JsError::new(&format!("Invalid settings value: {}", e)))?;
+        self.settings()?
+            .set(key, &value)
+            .await
+            .map_err(settings_err)
+    }
+
+    /// Remove a roaming setting. Returns whether it was set.
+    #[wasm_bindgen]
+    pub async fn settings_remove(&self, key: &str) -> Result<bool, JsError> {
+        self.settings()?.remove(key).await.map_err(settings_err)
+    }
+
+    /// All roaming setting keys, sorted
+    #[wasm_bindgen]
+    pub async fn settings_keys(&self) -> Result<Vec<String>, JsError> {
+        self.settings()?.keys().await.map_err(settings_err)
+    }
+
+    /// Remote nodes remembered across devices: `[{node_id, label, last_connected}]`
+    #[wasm_bindgen]
+    pub async fn remembered_nodes(&self) -> Result<JsValue, JsError> {
+        let nodes = self
+            .settings()?
+            .remembered_nodes()
+            .await
+            .map_err(settings_err)?;
+        to_json_value(&nodes)
+    }
+
+    /// Remember a remote node, replacing any entry with the same id
+    #[wasm_bindgen]
+    pub async fn remember_node(
+        &self,
+        node_id: &str,
+        label: Option<String>,
+        last_connected: Option<u64>,
+    ) -> Result<(), JsError> {
+        self.settings()?
+            .remember_node(RememberedNode {
+                node_id: node_id.to_string(),
+                label,
+                last_connected,
+            })
+            .await
+            .map_err(settings_err)
+    }
+
+    /// UI preferences as a `{name: value}` object
+    #[wasm_bindgen]
+    pub async fn ui_preferences(&self) -> Result<JsValue, JsError> {
+        let prefs = self
+            .settings()?
+            .ui_preferences()
+            .await
+            .map_err(settings_err)?;
+        to_json_value(&prefs)
+    }
+
+    /// Set a UI preference, or clear it with `undefined`
+    #[wasm_bindgen]
+    pub async fn set_ui_preference(
+        &self,
+        name: &str,
+        value: Option<String>,
+    ) -> Result<(), JsError> {
+        self.settings()?
+            .set_ui_preference(name, value.as_deref())
+            .await
+            .map_err(settings_err)
+    }
+
+    /// Device names as a `{device_id: name}` object
+    #[wasm_bindgen]
+    pub async fn device_names(&self) -> Result<JsValue, JsError> {
+        let names = self
+            .settings()?
+            .device_names()
+            .await
+            .map_err(settings_err)?;
+        to_json_value(&names)
+    }
+
+    /// Name a device, or forget its name with `undefined`
+    #[wasm_bindgen]
+    pub async fn set_device_name(
+        &self,
+        device_id: &str,
+        name: Option<String>,
+    ) -> Result<(), JsError> {
+        self.settings()?
+            .set_device_name(device_id, name.as_deref())
+            .await
+            .map_err(settings_err)
+    }
+
+    fn settings(&self) -> Result<Settings<'_>, JsError> {
+        let fs = self
+            .fs
+            .as_ref()
+            .ok_or_else(|| JsError::new("Not connected. Call connect() first."))?;
+        Ok(Settings::new(fs))
+    }
+
     /// Disconnect from the remote node
     #[wasm_bindgen]
     pub async fn disconnect(&mut self) -> Result<(), JsError> {
@@ -823,6 +1040,17 @@ impl S5Client {
     }
 }
 
+fn settings_err(e: SettingsError) -> JsError {
+    JsError::new(&format!("Settings error: {}", e))
+}
+
+/// Serializes maps as plain objects rather than JS `Map`s.
+fn to_json_value<T: serde::Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&format!("Serialization failed: {}", e)))
+}
+
 /// Static methods for S5Client
 #[wasm_bindgen]
 impl S5Client {
@@ -0,0 +1,114 @@
+//! IndexedDB backend for the FS5 write-ahead journal.
+//!
+//! One object store keyed by root id (hex of the root's public key) holds
+//! the CBOR-encoded pending operations. IndexedDB is async while
+//! [`JournalBackend::store`] is not, so writes are issued in the
+//! background; the in-memory journal stays authoritative for the session.
+
+use js_sys::{Promise, Uint8Array};
+use s5_fs::FSResult;
+use s5_fs::journal::JournalBackend;
+use wasm_bindgen::{JsCast, JsValue, closure::Closure};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+const DB_NAME: &str = "s5-journal";
+const DB_VERSION: u32 = 1;
+const STORE: &str = "journal";
+
+/// Mirrors one root's journal into IndexedDB.
+pub struct IdbJournalBackend {
+    root: String,
+}
+
+impl IdbJournalBackend {
+    pub fn new(root: String) -> Self {
+        Self { root }
+    }
+}
+
+impl JournalBackend for IdbJournalBackend {
+    fn store(&self, encoded: &[u8]) -> FSResult<()> {
+        let root = self.root.clone();
+        let value = Uint8Array::from(encoded);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = put(&root, &value).await {
+                web_sys::console::warn_1(&JsValue::from_str(&format!(
+                    "Writing FS5 journal failed: {:?}",
+                    e
+                )));
+            }
+        });
+        Ok(())
+    }
+}
+
+/// The journal persisted for `root` by an earlier session, if any.
+pub async fn load(root: &str) -> Result<Option<Vec<u8>>, JsValue> {
+    let db = open_db().await?;
+    let store = db.transaction_with_str(STORE)?.object_store(STORE)?;
+    let value = request(&store.get(&JsValue::from_str(root))?).await?;
+    db.close();
+    if value.is_undefined() || value.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(value.unchecked_into::<Uint8Array>().to_vec()))
+}
+
+async fn put(root: &str, value: &Uint8Array) -> Result<(), JsValue> {
+    let db = open_db().await?;
+    let store = db
+        .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)?
+        .object_store(STORE)?;
+    request(&store.put_with_key(value, &JsValue::from_str(root))?).await?;
+    db.close();
+    Ok(())
+}
+
+async fn open_db() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("no window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB unavailable"))?;
+    let open: IdbOpenDbRequest = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+    let on_upgrade = Closure::once_into_js(move |event: web_sys::Event| {
+        let db = event
+            .target()
+            .and_then(|t| t.dyn_into::<IdbOpenDbRequest>().ok())
+            .and_then(|r| r.result().ok())
+            .map(|db| db.unchecked_into::<IdbDatabase>());
+        if let Some(db) = db {
+            let _ = db.create_object_store(STORE);
+        }
+    });
+    open.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+    Ok(request(&open).await?.unchecked_into())
+}
+
+/// Resolves with the request's result once it succeeds.
+async fn request(req: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_success = {
+            let req = req.clone();
+            Closure::once_into_js(move |_: web_sys::Event| {
+                let result = req.result().unwrap_or(JsValue::UNDEFINED);
+                let _ = resolve.call1(&JsValue::NULL, &result);
+            })
+        };
+        let on_error = {
+            let req = req.clone();
+            Closure::once_into_js(move |_: web_sys::Event| {
+                let error = req
+                    .error()
+                    .ok()
+                    .flatten()
+                    .map(JsValue::from)
+                    .unwrap_or(JsValue::NULL);
+                let _ = reject.call1(&JsValue::NULL, &error);
+            })
+        };
+        req.set_onsuccess(Some(on_success.unchecked_ref()));
+        req.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
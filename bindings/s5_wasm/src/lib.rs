@@ -9,6 +9,7 @@
 use wasm_bindgen::prelude::*;
 
 mod client;
+mod journal;
 
 // Re-export from s5_client with WASM wrappers
 pub use client::*;
@@ -1,9 +1,10 @@
 use anyhow::{Context, Result, anyhow};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
+use s5_core::blob::layout::STORE_MANIFEST_PATH;
 use s5_core::blob::location::BlobLocation;
 use s5_core::blob::store::BlobStore;
-use s5_core::store::{StoreFeatures, StoreResult};
+use s5_core::store::{ObjectMeta, ObjectMetaStream, StoreFeatures, StoreResult};
 use std::path::{Path, PathBuf};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncSeekExt};
@@ -18,24 +19,113 @@ pub mod prune;
 /// cached blobs and racing them breaks the atomic-write contract.
 pub(crate) const TMP_SUBDIR: &str = ".tmp";
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
 pub struct LocalStoreConfig {
     pub base_path: String,
+    /// Spread files over `ab/cd/` directories (two levels, 65536 leaves)
+    /// instead of laying keys out as-is under `base_path`. Keys seen by
+    /// callers don't change; files written before this was enabled are
+    /// moved into place on first access or by
+    /// [`LocalStore::migrate_to_fan_out`].
+    #[serde(default)]
+    pub fan_out: bool,
+    /// Free bytes on the filesystem below which writes that would create a
+    /// new key (a new blob) are refused with [`InsufficientDiskSpace`].
+    /// Overwrites of existing keys — registry entries, the store manifest —
+    /// still go through. Unset: no check.
+    #[serde(default)]
+    pub low_watermark_bytes: Option<u64>,
+    /// Free bytes below which every write is refused, so the last of the
+    /// disk stays available to the node's own databases. Unset: no check.
+    #[serde(default)]
+    pub critical_watermark_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LocalStore {
     base_path: PathBuf,
+    fan_out: bool,
+    low_watermark: Option<u64>,
+    critical_watermark: Option<u64>,
     // TODO copy_files: bool,
 }
 
+/// How close the filesystem under a [`LocalStore`] is to its watermarks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiskSpaceLevel {
+    /// Above both watermarks (or none configured).
+    Ok,
+    /// Below [`LocalStoreConfig::low_watermark_bytes`]: new blobs refused.
+    Low,
+    /// Below [`LocalStoreConfig::critical_watermark_bytes`]: all writes
+    /// refused.
+    Critical,
+}
+
+impl std::fmt::Display for DiskSpaceLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Ok => "ok",
+            Self::Low => "low",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// Space on the filesystem holding a [`LocalStore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub total_bytes: u64,
+    /// Free to unprivileged writers.
+    pub available_bytes: u64,
+    pub low_watermark_bytes: Option<u64>,
+    pub critical_watermark_bytes: Option<u64>,
+    pub level: DiskSpaceLevel,
+}
+
+/// A write was refused because the store's filesystem is below a
+/// watermark.
+///
+/// Returned inside [`StoreResult`] errors; use
+/// `err.downcast_ref::<InsufficientDiskSpace>()` to tell it apart.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error(
+    "disk space {level} under {}: {available_bytes} bytes free, {watermark_bytes} required",
+    path.display()
+)]
+pub struct InsufficientDiskSpace {
+    pub path: PathBuf,
+    pub level: DiskSpaceLevel,
+    pub available_bytes: u64,
+    pub watermark_bytes: u64,
+}
+
 impl LocalStore {
     pub fn new(base_path: impl Into<PathBuf>) -> Self {
         LocalStore {
             base_path: base_path.into(),
+            fan_out: false,
+            low_watermark: None,
+            critical_watermark: None,
         }
     }
 
+    /// See [`LocalStoreConfig::fan_out`].
+    pub fn with_fan_out(mut self, fan_out: bool) -> Self {
+        self.fan_out = fan_out;
+        self
+    }
+
+    /// See [`LocalStoreConfig::low_watermark_bytes`] and
+    /// [`LocalStoreConfig::critical_watermark_bytes`].
+    pub fn with_watermarks(mut self, low: Option<u64>, critical: Option<u64>) -> Self {
+        self.low_watermark = low;
+        self.critical_watermark = critical;
+        self
+    }
+
     pub fn to_blob_store(self) -> BlobStore {
         BlobStore::new(self)
     }
@@ -43,10 +133,75 @@ impl LocalStore {
     pub fn create(config: LocalStoreConfig) -> Self {
         LocalStore {
             base_path: config.base_path.into(),
+            fan_out: config.fan_out,
+            low_watermark: config.low_watermark_bytes,
+            critical_watermark: config.critical_watermark_bytes,
             // TODO copy_files: config.copy_files,
         }
     }
 
+    /// Total and free space of the filesystem `base_path` is on (or will
+    /// be, for a store not yet written to), against the watermarks.
+    pub fn disk_usage(&self) -> StoreResult<DiskUsage> {
+        let existing = self
+            .base_path
+            .ancestors()
+            .find(|p| p.exists())
+            .unwrap_or(Path::new("."));
+        let stats =
+            fs4::statvfs(existing).with_context(|| format!("statvfs {}", existing.display()))?;
+        Ok(DiskUsage {
+            total_bytes: stats.total_space(),
+            available_bytes: stats.available_space(),
+            low_watermark_bytes: self.low_watermark,
+            critical_watermark_bytes: self.critical_watermark,
+            level: self.level(stats.available_space()),
+        })
+    }
+
+    fn level(&self, available: u64) -> DiskSpaceLevel {
+        if self.critical_watermark.is_some_and(|w| available < w) {
+            DiskSpaceLevel::Critical
+        } else if self.low_watermark.is_some_and(|w| available < w) {
+            DiskSpaceLevel::Low
+        } else {
+            DiskSpaceLevel::Ok
+        }
+    }
+
+    /// Refuse a write of `incoming` bytes to `full_path` when it would
+    /// leave the filesystem below a watermark: any write below the
+    /// critical one, a write creating a new file below the low one.
+    async fn check_space(&self, full_path: &Path, incoming: u64) -> StoreResult<()> {
+        if self.low_watermark.is_none() && self.critical_watermark.is_none() {
+            return Ok(());
+        }
+        let usage = self.disk_usage()?;
+        let after = usage.available_bytes.saturating_sub(incoming);
+        let refused = match self.level(after) {
+            DiskSpaceLevel::Ok => None,
+            DiskSpaceLevel::Low if tokio::fs::try_exists(full_path).await? => None,
+            DiskSpaceLevel::Low => self.low_watermark.map(|w| (DiskSpaceLevel::Low, w)),
+            DiskSpaceLevel::Critical => self
+                .critical_watermark
+                .map(|w| (DiskSpaceLevel::Critical, w)),
+        };
+        match refused {
+            None => Ok(()),
+            Some((level, watermark_bytes)) => Err(InsufficientDiskSpace {
+                path: self.base_path.clone(),
+                level,
+                available_bytes: usage.available_bytes,
+                watermark_bytes,
+            }
+            .into()),
+        }
+    }
+
+    /// Where `path` lives on disk: `base_path/ab/cd/<path>` with fan-out
+    /// (`abcd` being the first two bytes of the key's BLAKE3 hash, so any
+    /// key spreads evenly), `base_path/<path>` without. Staging files under
+    /// [`TMP_SUBDIR`] and the store manifest are never fanned out.
     fn resolve_path(&self, path: &str) -> StoreResult<PathBuf> {
         if path.contains("..") || path.starts_with('/') {
             return Err(anyhow!(
@@ -54,8 +209,119 @@ impl LocalStore {
                 path
             ));
         }
-        Ok(self.base_path.join(path))
+        if self.fans_out(path) {
+            Ok(self.base_path.join(shard_prefix(path)).join(path))
+        } else {
+            Ok(self.base_path.join(path))
+        }
+    }
+
+    fn fans_out(&self, path: &str) -> bool {
+        self.fan_out && !Path::new(path).starts_with(TMP_SUBDIR) && path != STORE_MANIFEST_PATH
+    }
+
+    /// [`Self::resolve_path`], first moving a file still at its pre-fan-out
+    /// location into place. Every operation on an existing key goes through
+    /// here, so a flat store migrates itself as it is used.
+    async fn locate(&self, path: &str) -> StoreResult<PathBuf> {
+        let full_path = self.resolve_path(path)?;
+        if !self.fans_out(path) {
+            return Ok(full_path);
+        }
+        let legacy = self.base_path.join(path);
+        if tokio::fs::try_exists(&full_path).await? || !tokio::fs::try_exists(&legacy).await? {
+            return Ok(full_path);
+        }
+        if let Some(parent) = full_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        match tokio::fs::rename(&legacy, &full_path).await {
+            Ok(()) => {}
+            // Moved by a concurrent caller in the meantime.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(full_path)
+    }
+
+    /// Moves every file of a flat store into the fan-out layout and
+    /// removes the directories this leaves empty. Returns the number of
+    /// files moved. Only needed to migrate eagerly; [`Store`] operations
+    /// find (and move) flat files on their own.
+    ///
+    /// [`Store`]: s5_core::store::Store
+    pub async fn migrate_to_fan_out(&self) -> StoreResult<u64> {
+        if !self.fan_out {
+            return Err(anyhow!("fan-out is not enabled for this store"));
+        }
+        let base_path = self.base_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            let mut dirs = Vec::new();
+            for entry in WalkDir::new(&base_path).min_depth(1) {
+                let entry = entry?;
+                let relative = entry.path().strip_prefix(&base_path)?.to_path_buf();
+                if relative.starts_with(TMP_SUBDIR) {
+                    continue;
+                }
+                if entry.file_type().is_dir() {
+                    dirs.push(entry.into_path());
+                } else if relative != Path::new(STORE_MANIFEST_PATH)
+                    && key_for_file(&relative, true) == key_from_relative(&relative)
+                {
+                    files.push(relative);
+                }
+            }
+            for relative in &files {
+                let target = base_path
+                    .join(shard_prefix(&key_from_relative(relative)))
+                    .join(relative);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(base_path.join(relative), &target)?;
+            }
+            // Deepest first, so parents are empty once their children go;
+            // directories still holding files stay.
+            for dir in dirs.iter().rev() {
+                let _ = std::fs::remove_dir(dir);
+            }
+            Ok(files.len() as u64)
+        })
+        .await?
+    }
+}
+
+/// `ab/cd` for `key`: its first two BLAKE3 bytes in hex.
+fn shard_prefix(key: &str) -> String {
+    let hash = s5_core::Hash::new(key.as_bytes());
+    let bytes = hash.as_bytes();
+    format!("{:02x}/{:02x}", bytes[0], bytes[1])
+}
+
+fn key_from_relative(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The store key of a file at `relative` (to `base_path`). With fan-out, a
+/// file under `ab/cd/` is only taken as fanned out when `ab/cd` is the
+/// shard of the rest of its path; anything else is a flat file awaiting
+/// migration and keeps its path as key.
+fn key_for_file(relative: &Path, fan_out: bool) -> String {
+    let key = key_from_relative(relative);
+    if fan_out
+        && let Some((shard, rest)) = key.get(..6).zip(key.get(6..))
+        && shard.ends_with('/')
+        && !rest.is_empty()
+        && shard_prefix(rest) == shard[..5]
+    {
+        return rest.to_string();
     }
+    key
 }
 
 #[async_trait::async_trait]
@@ -66,7 +332,8 @@ impl s5_core::store::Store for LocalStore {
         path: &str,
         stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
     ) -> StoreResult<()> {
-        let full_path = self.resolve_path(path)?;
+        let full_path = self.locate(path).await?;
+        self.check_space(&full_path, 0).await?;
         if let Some(parent) = full_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
@@ -88,12 +355,13 @@ impl s5_core::store::Store for LocalStore {
 
     /// Checks if a file exists at the given path.
     async fn exists(&self, path: &str) -> StoreResult<bool> {
-        let full_path = self.resolve_path(path)?;
+        let full_path = self.locate(path).await?;
         tokio::fs::try_exists(&full_path).await.map_err(Into::into)
     }
 
     async fn put_bytes(&self, path: &str, bytes: Bytes) -> StoreResult<()> {
-        let full_path = self.resolve_path(path)?;
+        let full_path = self.locate(path).await?;
+        self.check_space(&full_path, bytes.len() as u64).await?;
         if let Some(parent) = full_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
@@ -146,7 +414,7 @@ impl s5_core::store::Store for LocalStore {
         max_len: Option<u64>,
     ) -> StoreResult<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>>
     {
-        let full_path = self.resolve_path(path)?;
+        let full_path = self.locate(path).await?;
         let mut file = File::open(&full_path).await?;
 
         if offset > 0 {
@@ -170,7 +438,7 @@ impl s5_core::store::Store for LocalStore {
         offset: u64,
         max_len: Option<u64>,
     ) -> StoreResult<Bytes> {
-        let full_path = self.resolve_path(path)?;
+        let full_path = self.locate(path).await?;
         let mut file = File::open(&full_path).await?;
         let file_len = file.metadata().await?.len();
 
@@ -195,7 +463,7 @@ impl s5_core::store::Store for LocalStore {
     }
 
     async fn delete(&self, path: &str) -> StoreResult<()> {
-        let full_path = self.resolve_path(path)?;
+        let full_path = self.locate(path).await?;
         match tokio::fs::metadata(&full_path).await {
             Ok(_metadata) => {
                 tokio::fs::remove_file(&full_path).await?;
@@ -207,8 +475,8 @@ impl s5_core::store::Store for LocalStore {
     }
 
     async fn rename(&self, old_path: &str, new_path: &str) -> StoreResult<()> {
-        let old_full_path = self.resolve_path(old_path)?;
-        let new_full_path = self.resolve_path(new_path)?;
+        let old_full_path = self.locate(old_path).await?;
+        let new_full_path = self.locate(new_path).await?;
 
         if let Some(parent) = new_full_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
@@ -223,12 +491,12 @@ impl s5_core::store::Store for LocalStore {
     }
 
     async fn size(&self, path: &str) -> StoreResult<u64> {
-        Ok(std::fs::metadata(&self.resolve_path(path)?)?.len())
+        Ok(std::fs::metadata(self.locate(path).await?)?.len())
     }
 
     async fn modified(&self, path: &str) -> StoreResult<Option<std::time::SystemTime>> {
         Ok(Some(
-            std::fs::metadata(&self.resolve_path(path)?)?.modified()?,
+            std::fs::metadata(self.locate(path).await?)?.modified()?,
         ))
     }
 
@@ -237,6 +505,7 @@ impl s5_core::store::Store for LocalStore {
     ) -> StoreResult<Box<dyn Stream<Item = Result<String, std::io::Error>> + Send + Unpin + 'static>>
     {
         let base_path = self.base_path.clone();
+        let fan_out = self.fan_out;
         let walker = WalkDir::new(&base_path).into_iter();
         let stream = futures::stream::iter(walker).filter_map(move |entry| {
             let item = match entry {
@@ -248,8 +517,43 @@ impl s5_core::store::Store for LocalStore {
                     if relative_path.starts_with(TMP_SUBDIR) {
                         None
                     } else {
-                        let key = relative_path.to_string_lossy().into_owned();
-                        Some(Ok(key))
+                        Some(Ok(key_for_file(relative_path, fan_out)))
+                    }
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(std::io::Error::other(e.to_string()))),
+            };
+            futures::future::ready(item)
+        });
+
+        Ok(Box::new(stream))
+    }
+
+    async fn list_with_meta(&self) -> StoreResult<ObjectMetaStream> {
+        let base_path = self.base_path.clone();
+        let fan_out = self.fan_out;
+        let walker = WalkDir::new(&base_path).into_iter();
+        let stream = futures::stream::iter(walker).filter_map(move |entry| {
+            let item = match entry {
+                Ok(entry) if entry.file_type().is_file() => {
+                    let relative_path = entry.path().strip_prefix(&base_path).unwrap();
+                    if relative_path.starts_with(TMP_SUBDIR) {
+                        None
+                    } else {
+                        // One `stat` per file during the walk, instead of a
+                        // `locate` and `metadata` per listed key afterwards.
+                        Some(
+                            entry
+                                .metadata()
+                                .map_err(|e| std::io::Error::other(e.to_string()))
+                                .and_then(|meta| {
+                                    Ok(ObjectMeta {
+                                        path: key_for_file(relative_path, fan_out),
+                                        size: meta.len(),
+                                        modified: Some(meta.modified()?),
+                                    })
+                                }),
+                        )
                     }
                 }
                 Ok(_) => None,
@@ -262,7 +566,8 @@ impl s5_core::store::Store for LocalStore {
     }
 
     async fn reflink_file_to(&self, source: &std::path::Path, dest_path: &str) -> StoreResult<()> {
-        let full_dest = self.resolve_path(dest_path)?;
+        let full_dest = self.locate(dest_path).await?;
+        self.check_space(&full_dest, 0).await?;
         if let Some(parent) = full_dest.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
@@ -319,4 +624,130 @@ mod tests {
         let store = LocalStore::new(temp_dir.path());
         StoreTests::new(&store).run_all().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_local_store_fan_out() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = LocalStore::new(temp_dir.path()).with_fan_out(true);
+        StoreTests::new(&store).run_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn flat_store_migrates_to_fan_out_with_stable_keys() {
+        use s5_core::store::Store;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let flat = LocalStore::new(temp_dir.path());
+        let keys = ["a.txt", "dir/b.txt", "dir/nested/c.txt", "ab/cd/d.txt"];
+        for key in keys {
+            flat.put_bytes(key, Bytes::from(key)).await.unwrap();
+        }
+
+        let store = LocalStore::new(temp_dir.path()).with_fan_out(true);
+        let mut listed: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .map(|k| k.unwrap())
+            .collect()
+            .await;
+        listed.sort();
+        let mut expected: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        // Touching a key moves it into place.
+        assert_eq!(
+            store.open_read_bytes("a.txt", 0, None).await.unwrap(),
+            "a.txt"
+        );
+        assert!(!temp_dir.path().join("a.txt").exists());
+        assert!(
+            temp_dir
+                .path()
+                .join(shard_prefix("a.txt"))
+                .join("a.txt")
+                .exists()
+        );
+
+        assert_eq!(store.migrate_to_fan_out().await.unwrap(), 3);
+        assert!(!temp_dir.path().join("dir").exists());
+        for key in keys {
+            assert_eq!(store.open_read_bytes(key, 0, None).await.unwrap(), key);
+        }
+        let mut listed: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .map(|k| k.unwrap())
+            .collect()
+            .await;
+        listed.sort();
+        assert_eq!(listed, expected);
+        assert_eq!(store.migrate_to_fan_out().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn watermarks_refuse_new_blobs_then_all_writes() {
+        use s5_core::store::Store;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let plain = LocalStore::new(temp_dir.path());
+        plain
+            .put_bytes("registry", Bytes::from("v1"))
+            .await
+            .unwrap();
+        assert_eq!(plain.disk_usage().unwrap().level, DiskSpaceLevel::Ok);
+
+        // Low watermark above what is free: new keys refused, existing
+        // keys may still be rewritten.
+        let low = LocalStore::new(temp_dir.path()).with_watermarks(Some(u64::MAX), None);
+        assert_eq!(low.disk_usage().unwrap().level, DiskSpaceLevel::Low);
+        let err = low.put_bytes("blob", Bytes::from("x")).await.unwrap_err();
+        let refused = err.downcast_ref::<InsufficientDiskSpace>().unwrap();
+        assert_eq!(refused.level, DiskSpaceLevel::Low);
+        assert_eq!(refused.watermark_bytes, u64::MAX);
+        let stream = futures::stream::iter([Ok(Bytes::from("x"))]);
+        let err = low.put_stream("blob", Box::new(stream)).await.unwrap_err();
+        assert!(err.downcast_ref::<InsufficientDiskSpace>().is_some());
+        low.put_bytes("registry", Bytes::from("v2")).await.unwrap();
+
+        // Critical: nothing goes through.
+        let critical =
+            LocalStore::new(temp_dir.path()).with_watermarks(Some(u64::MAX), Some(u64::MAX));
+        let err = critical
+            .put_bytes("registry", Bytes::from("v3"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InsufficientDiskSpace>().unwrap().level,
+            DiskSpaceLevel::Critical
+        );
+        assert_eq!(
+            plain.open_read_bytes("registry", 0, None).await.unwrap(),
+            "v2"
+        );
+    }
+
+    #[tokio::test]
+    async fn interrupted_import_leaves_no_blob_or_temp_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let blobs = LocalStore::new(temp_dir.path()).to_blob_store();
+
+        let stream = futures::stream::iter([
+            Ok(Bytes::from(vec![7u8; 64 * 1024])),
+            Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "client went away",
+            )),
+        ]);
+        blobs.import_stream(Box::new(stream)).await.unwrap_err();
+
+        assert!(blobs.list_hashes().await.unwrap().is_empty());
+        let tmp = temp_dir.path().join(TMP_SUBDIR);
+        let leftovers = std::fs::read_dir(&tmp)
+            .map(|dir| dir.filter(|e| e.as_ref().unwrap().path().is_file()).count())
+            .unwrap_or(0);
+        assert_eq!(leftovers, 0);
+    }
 }
@@ -29,6 +29,8 @@
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use s5_core::blob::layout::STORE_MANIFEST_PATH;
+
 /// Tunables for one prune pass plus the periodic cadence used by
 /// [`spawn`]. `Copy` — cheap to hand to each blocking pass.
 #[derive(Debug, Clone, Copy)]
@@ -144,7 +146,8 @@ pub fn prune_once(base_path: &Path, cfg: &PruneConfig) -> (u64, u64) {
 /// Recursive (size, mtime) collector. Skips `{base}/.tmp`
 /// ([`crate::TMP_SUBDIR`]) — the atomic-write staging dir; those are
 /// in-flight uploads, not cached blobs, and racing them would break the
-/// tmp+rename contract.
+/// tmp+rename contract. Also skips the store manifest, which is the
+/// oldest file in any store and would otherwise be evicted first.
 fn collect(root: &Path, dir: &Path, out: &mut Vec<(PathBuf, SystemTime, u64)>, total: &mut u64) {
     let Ok(rd) = std::fs::read_dir(dir) else {
         return;
@@ -157,6 +160,8 @@ fn collect(root: &Path, dir: &Path, out: &mut Vec<(PathBuf, SystemTime, u64)>, t
                 continue;
             }
             collect(root, &path, out, total);
+        } else if dir == root && entry.file_name() == STORE_MANIFEST_PATH {
+            continue;
         } else if ft.is_file()
             && let Ok(meta) = entry.metadata()
         {
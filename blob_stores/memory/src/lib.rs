@@ -12,6 +12,10 @@
 //!   here with O(1) reads; cold blobs evict and fall through. Pure
 //!   access-pattern eviction — no path prefixes, no pinning, no
 //!   recency rules — so it adapts to whatever traffic each peer sees.
+//! - [`MemoryStore::builder`] — the same budgeted backend with the
+//!   knobs exposed: plain LRU instead of TinyLFU ([`MemoryEviction`])
+//!   and a callback per evicted entry, for callers that track what
+//!   fell out of the cache.
 //!
 //! The `Store` trait is path-based; inside a `BlobStore` wrapper paths
 //! are deterministic hash-derived identifiers, so caching by path is
@@ -20,6 +24,8 @@
 use bytes::Bytes;
 use dashmap::DashMap;
 use futures::stream::{self, Stream, TryStreamExt};
+use moka::notification::RemovalCause;
+use moka::policy::EvictionPolicy;
 use moka::sync::Cache as MokaCache;
 use s5_core::{
     blob::location::BlobLocation,
@@ -27,6 +33,7 @@ use s5_core::{
 };
 
 use std::io;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Live metrics for a budgeted store. `None` for an unbounded one
@@ -41,6 +48,77 @@ pub struct MemoryStoreStats {
     pub hits: u64,
     /// Lifetime cache misses observed at `open_read_bytes`.
     pub misses: u64,
+    /// Lifetime entries evicted to stay within the budget (explicit
+    /// deletes and overwrites are not counted).
+    pub evictions: u64,
+}
+
+/// Which entry a budgeted store drops when an insert exceeds the budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MemoryEviction {
+    /// Frequency-aware W-TinyLFU: a one-off scan does not flush entries
+    /// that are read over and over. The default.
+    #[default]
+    TinyLfu,
+    /// Least recently used. Admits every insert, so a freshly written
+    /// entry is always readable right after the write.
+    Lru,
+}
+
+/// Called with the path and byte length of each entry evicted to stay
+/// within the budget. Runs inline with cache maintenance (on whichever
+/// thread triggered it); keep it cheap.
+pub type EvictionListener = Arc<dyn Fn(&str, u64) + Send + Sync>;
+
+/// Builds a budgeted [`MemoryStore`]. See [`MemoryStore::builder`].
+pub struct MemoryStoreBuilder {
+    budget_bytes: u64,
+    eviction: MemoryEviction,
+    on_evict: Option<EvictionListener>,
+}
+
+impl MemoryStoreBuilder {
+    pub fn eviction(mut self, eviction: MemoryEviction) -> Self {
+        self.eviction = eviction;
+        self
+    }
+
+    /// Notifies `listener` of every entry evicted for space.
+    pub fn on_evict(mut self, listener: impl Fn(&str, u64) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(listener));
+        self
+    }
+
+    pub fn build(self) -> MemoryStore {
+        let evictions = Arc::new(AtomicU64::new(0));
+        let counter = evictions.clone();
+        let on_evict = self.on_evict;
+        let policy = match self.eviction {
+            MemoryEviction::TinyLfu => EvictionPolicy::tiny_lfu(),
+            MemoryEviction::Lru => EvictionPolicy::lru(),
+        };
+        let cache = MokaCache::builder()
+            .weigher(|_k: &String, v: &Bytes| u32::try_from(v.len()).unwrap_or(u32::MAX))
+            .max_capacity(self.budget_bytes)
+            .eviction_policy(policy)
+            .eviction_listener(move |k: Arc<String>, v: Bytes, cause| {
+                if cause == RemovalCause::Size {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    if let Some(on_evict) = &on_evict {
+                        on_evict(&k, v.len() as u64);
+                    }
+                }
+            })
+            .build();
+        MemoryStore {
+            backend: Backend::Budgeted {
+                cache,
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                evictions,
+            },
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -54,6 +132,8 @@ enum Backend {
         cache: MokaCache<String, Bytes>,
         hits: AtomicU64,
         misses: AtomicU64,
+        /// Shared with the cache's eviction listener.
+        evictions: Arc<AtomicU64>,
     },
 }
 
@@ -75,16 +155,25 @@ impl MemoryStore {
     /// frequency sketch). Weights are exact (`Bytes::len()`); the slack
     /// from `Bytes::clone()` is shared refcount, not extra weight.
     pub fn with_budget(budget_bytes: u64) -> Self {
-        let cache = MokaCache::builder()
-            .weigher(|_k: &String, v: &Bytes| u32::try_from(v.len()).unwrap_or(u32::MAX))
-            .max_capacity(budget_bytes)
-            .build();
-        Self {
-            backend: Backend::Budgeted {
-                cache,
-                hits: AtomicU64::new(0),
-                misses: AtomicU64::new(0),
-            },
+        Self::builder(budget_bytes).build()
+    }
+
+    /// A budgeted store capped at `budget_bytes`, with a choice of
+    /// eviction policy and an optional eviction callback.
+    pub fn builder(budget_bytes: u64) -> MemoryStoreBuilder {
+        MemoryStoreBuilder {
+            budget_bytes,
+            eviction: MemoryEviction::default(),
+            on_evict: None,
+        }
+    }
+
+    /// Applies pending evictions now (moka otherwise batches them), so
+    /// [`Self::stats`] and eviction callbacks are up to date. No-op for an
+    /// unbounded store.
+    pub fn run_pending_evictions(&self) {
+        if let Backend::Budgeted { cache, .. } = &self.backend {
+            cache.run_pending_tasks();
         }
     }
 
@@ -97,11 +186,13 @@ impl MemoryStore {
                 cache,
                 hits,
                 misses,
+                evictions,
             } => Some(MemoryStoreStats {
                 weighted_size: cache.weighted_size(),
                 entry_count: cache.entry_count(),
                 hits: hits.load(Ordering::Relaxed),
                 misses: misses.load(Ordering::Relaxed),
+                evictions: evictions.load(Ordering::Relaxed),
             }),
         }
     }
@@ -191,6 +282,7 @@ impl s5_core::store::Store for MemoryStore {
                 cache,
                 hits,
                 misses,
+                ..
             } => match cache.get(path) {
                 Some(b) => {
                     hits.fetch_add(1, Ordering::Relaxed);
@@ -377,6 +469,45 @@ mod tests {
         assert!(store.exists("same").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn lru_evicts_least_recent_and_notifies() {
+        let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let store = MemoryStore::builder(3 * 1024)
+            .eviction(MemoryEviction::Lru)
+            .on_evict({
+                let evicted = evicted.clone();
+                move |path, len| evicted.lock().unwrap().push((path.to_string(), len))
+            })
+            .build();
+        for key in ["a", "b", "c"] {
+            store
+                .put_bytes(key, Bytes::from(vec![0u8; 1024]))
+                .await
+                .unwrap();
+            store.run_pending_evictions();
+        }
+        // Touch `a` so `b` is the least recently used.
+        store.open_read_bytes("a", 0, None).await.unwrap();
+        store.run_pending_evictions();
+        store
+            .put_bytes("d", Bytes::from(vec![0u8; 1024]))
+            .await
+            .unwrap();
+        store.run_pending_evictions();
+
+        assert_eq!(*evicted.lock().unwrap(), [("b".to_string(), 1024)]);
+        assert!(!store.exists("b").await.unwrap());
+        for key in ["a", "c", "d"] {
+            assert!(store.exists(key).await.unwrap(), "{key} evicted");
+        }
+        assert_eq!(store.stats().unwrap().evictions, 1);
+
+        // Deletes are not evictions.
+        store.delete("a").await.unwrap();
+        store.run_pending_evictions();
+        assert_eq!(evicted.lock().unwrap().len(), 1);
+    }
+
     /// A downstream stats consumer depends on this exact contract:
     /// budgeted → `Some`, unbounded → `None`.
     #[tokio::test]
@@ -4,7 +4,7 @@ use futures::Stream;
 use s3::{Bucket, Region, creds::Credentials};
 use s5_core::{
     blob::location::BlobLocation,
-    store::{StoreFeatures, StoreResult},
+    store::{ObjectMeta, ObjectMetaStream, StoreFeatures, StoreResult},
 };
 use tokio_util::io::{ReaderStream, StreamReader};
 
@@ -172,6 +172,31 @@ impl s5_core::store::Store for S3Store {
         let stream = futures::stream::iter(paths.into_iter().map(Ok));
         Ok(Box::new(stream))
     }
+
+    async fn list_with_meta(&self) -> StoreResult<ObjectMetaStream> {
+        let results = self.bucket.list("".to_string(), None).await?;
+        // `ListObjectsV2` returns key, size and mtime for every object, so
+        // the metadata costs no requests beyond the listing itself.
+        let objects: Vec<Result<ObjectMeta, std::io::Error>> = results
+            .into_iter()
+            .flat_map(|res| res.contents)
+            .map(|obj| {
+                Ok(ObjectMeta {
+                    modified: parse_last_modified(&obj.last_modified),
+                    path: obj.key,
+                    size: obj.size,
+                })
+            })
+            .collect();
+
+        Ok(Box::new(futures::stream::iter(objects)))
+    }
+}
+
+/// Parse the RFC 3339 `LastModified` of a listed object. An unparseable
+/// value is reported as unknown, which GC treats as too young to delete.
+fn parse_last_modified(value: &str) -> Option<std::time::SystemTime> {
+    humantime::parse_rfc3339_weak(value).ok()
 }
 
 #[cfg(test)]
@@ -196,4 +221,14 @@ mod tests {
         let store = S3Store::create(config);
         StoreTests::new(&store).run_all().await.unwrap();
     }
+
+    #[test]
+    fn last_modified_parses_list_timestamps() {
+        let t = parse_last_modified("2009-10-12T17:50:30.000Z").unwrap();
+        assert_eq!(
+            t.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+            1_255_369_830
+        );
+        assert_eq!(parse_last_modified("yesterday"), None);
+    }
 }
@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use reqwest::header::{CONTENT_LENGTH, LAST_MODIFIED};
 use s5_core::blob::BlobStore;
+use s5_core::blob::import::DEFAULT_INLINE_THRESHOLD;
 use s5_fs::{FS5, FileRef};
 use scraper::{Html, Selector};
 use std::sync::Arc;
@@ -19,6 +20,9 @@ pub struct HttpImporter {
     /// When false, keys always use the full global path
     /// (`scheme/host/path`) regardless of base URL.
     use_base_relative_keys: bool,
+    /// Responses of at most this many bytes are stored inline in their
+    /// `FileRef` instead of the blob store (0 disables inlining).
+    inline_threshold: u64,
 }
 
 impl HttpImporter {
@@ -36,9 +40,16 @@ impl HttpImporter {
             blob_store,
             base_url,
             use_base_relative_keys,
+            inline_threshold: DEFAULT_INLINE_THRESHOLD,
         })
     }
 
+    /// Sets the inline cutoff (default [`DEFAULT_INLINE_THRESHOLD`]);
+    /// pass 0 to always write responses to the blob store.
+    pub fn set_inline_threshold(&mut self, inline_threshold: u64) {
+        self.inline_threshold = inline_threshold;
+    }
+
     /// Recursively imports content from a given URL.
     ///
     /// This function first checks if the content needs updating by sending a `HEAD`
@@ -207,7 +218,8 @@ impl HttpImporter {
         Ok(())
     }
 
-    /// Imports a file into the BlobStore and adds a reference to the DirV1.
+    /// Imports a file into the BlobStore (or inline, if small) and adds a
+    /// reference to the DirV1.
     async fn handle_file(
         &self,
         _url: Url,
@@ -215,14 +227,18 @@ impl HttpImporter {
         res: reqwest::Response,
         last_modified: Option<DateTime<chrono::FixedOffset>>,
     ) -> anyhow::Result<()> {
-        let blob_id = self
+        if let Some(len) = res.content_length() {
+            self.fs.check_quota(&key, len).await?;
+        }
+        let imported = self
             .blob_store
-            .import_stream(Box::new(
-                res.bytes_stream().map(|c| c.map_err(std::io::Error::other)),
-            ))
+            .import_stream_or_inline(
+                Box::new(res.bytes_stream().map(|c| c.map_err(std::io::Error::other))),
+                self.inline_threshold,
+            )
             .await?;
 
-        let mut file_ref: FileRef = blob_id.into();
+        let mut file_ref: FileRef = imported.into();
 
         // Use the file's modification time if available, otherwise use the current time.
         let ts = last_modified
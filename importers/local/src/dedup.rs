@@ -0,0 +1,90 @@
+//! Hard-link and duplicate-content detection for one importer.
+//!
+//! Photo and music libraries often hold the same file several times, as
+//! hard links or as copies. Every path still gets its own `FileRef`, but
+//! all of them point at one blob: a hard link to a file already imported
+//! reuses its `FileRef` without being read, and a copy whose hash is
+//! already known is not written to the blob store again.
+
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::MetadataExt;
+use std::sync::Mutex;
+
+use s5_fs::FileRef;
+
+/// Hard links and duplicates found so far, and the bytes they did not
+/// cost.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DedupReport {
+    /// Files recorded as another link to an inode already imported.
+    pub hard_links: u64,
+    /// Files whose contents matched a file imported before them.
+    pub duplicates: u64,
+    /// Total size of those files.
+    pub bytes_saved: u64,
+}
+
+#[derive(Default)]
+struct State {
+    /// `FileRef`s of imported files with more than one link, by
+    /// `(dev, ino)`.
+    inodes: HashMap<(u64, u64), FileRef>,
+    /// Sizes of imported (non-inline) files. A new file of one of these
+    /// sizes is hashed before it is stored.
+    sizes: HashSet<u64>,
+    hashes: HashSet<[u8; 32]>,
+    report: DedupReport,
+}
+
+#[derive(Default)]
+pub(crate) struct DedupTracker {
+    state: Mutex<State>,
+}
+
+impl DedupTracker {
+    pub(crate) fn report(&self) -> DedupReport {
+        self.state.lock().unwrap().report.clone()
+    }
+
+    /// The `FileRef` of an already imported link to the same inode, counted
+    /// as a hard link.
+    pub(crate) fn hard_link(&self, meta: &std::fs::Metadata) -> Option<FileRef> {
+        if meta.nlink() < 2 {
+            return None;
+        }
+        let mut state = self.state.lock().unwrap();
+        let file_ref = state.inodes.get(&(meta.dev(), meta.ino()))?.clone();
+        state.report.hard_links += 1;
+        state.report.bytes_saved += file_ref.size;
+        Some(file_ref)
+    }
+
+    /// Whether a file of `size` bytes may duplicate one already imported.
+    pub(crate) fn size_seen(&self, size: u64) -> bool {
+        self.state.lock().unwrap().sizes.contains(&size)
+    }
+
+    pub(crate) fn hash_seen(&self, hash: &[u8; 32]) -> bool {
+        self.state.lock().unwrap().hashes.contains(hash)
+    }
+
+    /// Remember an imported file, counting it as a duplicate when its
+    /// contents were seen before. Inline files are skipped: they live in
+    /// the directory metadata, not the blob store.
+    pub(crate) fn record(&self, meta: &std::fs::Metadata, file_ref: &FileRef) {
+        if file_ref.inline_data().is_some() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if meta.nlink() > 1 {
+            state
+                .inodes
+                .insert((meta.dev(), meta.ino()), file_ref.clone());
+        }
+        if !state.hashes.insert(file_ref.hash) {
+            state.report.duplicates += 1;
+            state.report.bytes_saved += file_ref.size;
+        }
+        state.sizes.insert(file_ref.size);
+    }
+}
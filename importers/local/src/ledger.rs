@@ -0,0 +1,111 @@
+//! Persistent hash ledger for repeated imports.
+//!
+//! The FS5 metadata check in [`LocalFileSystemImporter`] only helps when a
+//! file keeps its key: after a rename, a move or a changed key prefix, or
+//! when importing into a fresh root, every file is read and hashed again.
+//! The ledger remembers the hash of each file it has seen by identity and
+//! version — device, inode, size and mtime — so those files can be
+//! recorded without reading their contents.
+//!
+//! [`LocalFileSystemImporter`]: crate::LocalFileSystemImporter
+
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use redb::{Database, ReadableDatabase, TableDefinition};
+
+/// Table: [`LedgerKey`] bytes -> BLAKE3 hash of the file's contents.
+const FILE_TO_HASH: TableDefinition<&[u8; 36], &[u8; 32]> = TableDefinition::new("file_to_hash");
+
+/// Identity and version of a file: `dev`, `ino`, `size`, mtime seconds
+/// (all `u64`) and mtime nanoseconds (`u32`), big-endian. A file that is
+/// written to in place gets a new size or mtime and so a new key.
+type LedgerKey = [u8; 36];
+
+fn ledger_key(meta: &std::fs::Metadata) -> LedgerKey {
+    let mut key = [0u8; 36];
+    key[0..8].copy_from_slice(&meta.dev().to_be_bytes());
+    key[8..16].copy_from_slice(&meta.ino().to_be_bytes());
+    key[16..24].copy_from_slice(&meta.size().to_be_bytes());
+    key[24..32].copy_from_slice(&meta.mtime().to_be_bytes());
+    key[32..36].copy_from_slice(&(meta.mtime_nsec() as u32).to_be_bytes());
+    key
+}
+
+/// Hashes of previously imported files, kept in a redb file.
+#[derive(Clone)]
+pub struct HashLedger {
+    db: Arc<Database>,
+}
+
+impl std::fmt::Debug for HashLedger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashLedger").finish()
+    }
+}
+
+impl HashLedger {
+    /// Open or create the ledger database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let db = Database::create(path)
+            .with_context(|| format!("Failed to create ledger at {:?}", path))?;
+        {
+            let write_txn = db.begin_write()?;
+            {
+                let _ = write_txn.open_table(FILE_TO_HASH)?;
+            }
+            write_txn.commit()?;
+        }
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// The hash recorded for the file `meta` describes, if any.
+    pub fn get(&self, meta: &std::fs::Metadata) -> Result<Option<[u8; 32]>> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(FILE_TO_HASH)?;
+        Ok(table.get(&ledger_key(meta))?.map(|hash| *hash.value()))
+    }
+
+    /// Record that the file `meta` describes hashes to `hash`.
+    pub fn insert(&self, meta: &std::fs::Metadata, hash: [u8; 32]) -> Result<()> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(FILE_TO_HASH)?;
+            table.insert(&ledger_key(meta), &hash)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_follows_inode_across_renames_until_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = HashLedger::open(dir.path().join("ledger.redb")).unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, b"one").unwrap();
+        let hash = *blake3::hash(b"one").as_bytes();
+        ledger
+            .insert(&std::fs::metadata(&file).unwrap(), hash)
+            .unwrap();
+
+        let moved = dir.path().join("b.txt");
+        std::fs::rename(&file, &moved).unwrap();
+        let meta = std::fs::metadata(&moved).unwrap();
+        assert_eq!(ledger.get(&meta).unwrap(), Some(hash));
+
+        std::fs::write(&moved, b"two!").unwrap();
+        let meta = std::fs::metadata(&moved).unwrap();
+        assert_eq!(ledger.get(&meta).unwrap(), None);
+    }
+}
@@ -1,13 +1,21 @@
 use anyhow::{Context, anyhow};
 use futures::{StreamExt, TryStreamExt};
 use ignore::{DirEntry, WalkBuilder};
+use s5_core::BlobsRead;
 use s5_core::blob::BlobStore;
+use s5_core::blob::import::DEFAULT_INLINE_THRESHOLD;
 use s5_fs::{FS5, FileRef};
 use std::io::Read;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::{os::unix::fs::MetadataExt, path::PathBuf};
 
+mod dedup;
+pub mod ledger;
+
+pub use dedup::DedupReport;
+pub use ledger::HashLedger;
+
 /// Progress counters for import operations.
 #[derive(Default)]
 pub struct ImportProgress {
@@ -46,8 +54,15 @@ pub struct LocalFileSystemImporter {
     /// knows the target tree is fresh and wants to avoid a `file_get`
     /// round-trip per file.
     always_import: bool,
+    /// Files of at most this many bytes are stored inline in their
+    /// `FileRef` instead of the blob store (0 disables inlining).
+    inline_threshold: u64,
     /// Optional progress tracking
     progress: Option<Arc<ImportProgress>>,
+    /// Optional hash ledger consulted before reading a file's contents.
+    ledger: Option<HashLedger>,
+    /// Hard links and duplicate contents seen so far.
+    dedup: dedup::DedupTracker,
 }
 
 impl LocalFileSystemImporter {
@@ -59,6 +74,14 @@ impl LocalFileSystemImporter {
         self.always_import = always_import;
     }
 
+    /// Sets the inline cutoff for blob-store imports (default
+    /// [`DEFAULT_INLINE_THRESHOLD`]). Small files otherwise each cost a
+    /// store object; inlined ones live in the directory metadata.
+    /// Pass 0 to always write to the blob store.
+    pub fn set_inline_threshold(&mut self, inline_threshold: u64) {
+        self.inline_threshold = inline_threshold;
+    }
+
     /// Creates a new `LocalImporter`.
     ///
     /// # Arguments
@@ -88,7 +111,10 @@ impl LocalFileSystemImporter {
             ignore_vcs,
             check_cachedir_tag,
             always_import: false,
+            inline_threshold: DEFAULT_INLINE_THRESHOLD,
             progress: None,
+            ledger: None,
+            dedup: Default::default(),
         })
     }
 
@@ -113,7 +139,10 @@ impl LocalFileSystemImporter {
             ignore_vcs,
             check_cachedir_tag,
             always_import: false,
+            inline_threshold: DEFAULT_INLINE_THRESHOLD,
             progress: None,
+            ledger: None,
+            dedup: Default::default(),
         })
     }
 
@@ -122,6 +151,20 @@ impl LocalFileSystemImporter {
         self.progress = Some(progress);
     }
 
+    /// Sets a [`HashLedger`] shared across runs. Files the ledger already
+    /// knows (same device, inode, size and mtime) are recorded under their
+    /// known hash without being read, as long as the blob is still in the
+    /// blob store; every file hashed is added to it.
+    pub fn set_ledger(&mut self, ledger: HashLedger) {
+        self.ledger = Some(ledger);
+    }
+
+    /// Hard links and duplicate files found by this importer so far, and
+    /// the space they did not take up in the blob store.
+    pub fn dedup_report(&self) -> DedupReport {
+        self.dedup.report()
+    }
+
     /// Recursively imports files from the configured `base_path`.
     ///
     /// This function walks the directory tree starting from `base_path`, processing
@@ -262,21 +305,88 @@ impl LocalFileSystemImporter {
 
         log::info!("Importing file: {}", key);
 
+        if let ImportMode::BlobStore(_) = &self.mode {
+            self.fs.check_quota(&key, meta.len()).await?;
+        }
+        let file_ref = if let Some(file_ref) = self.dedup.hard_link(&meta) {
+            log::debug!("{} is a hard link to an imported file", key);
+            file_ref
+        } else {
+            let file_ref = match self.known_file_ref(&meta).await? {
+                Some(file_ref) => {
+                    log::debug!("Reusing ledger hash for {}", key);
+                    file_ref
+                }
+                None => self.read_file_ref(path, &meta).await?,
+            };
+            self.dedup.record(&meta, &file_ref);
+            file_ref
+        };
+
+        self.fs.file_put(&key, file_ref.clone()).await?;
+
+        // Update progress
+        if let Some(ref progress) = self.progress {
+            progress.files_processed.fetch_add(1, Ordering::Relaxed);
+            progress
+                .bytes_processed
+                .fetch_add(file_ref.size, Ordering::Relaxed);
+        }
+
+        log::info!("Successfully imported file: {}", key);
+        Ok(())
+    }
+
+    /// The `FileRef` for a file the ledger already knows, without reading
+    /// it. `None` when there is no ledger, the file is unknown or changed,
+    /// it would be inlined (which needs its bytes), or its blob is no longer
+    /// in the blob store.
+    async fn known_file_ref(&self, meta: &std::fs::Metadata) -> anyhow::Result<Option<FileRef>> {
+        let Some(ledger) = &self.ledger else {
+            return Ok(None);
+        };
+        if let ImportMode::BlobStore(_) = &self.mode
+            && meta.len() <= self.inline_threshold
+        {
+            return Ok(None);
+        }
+        let Some(hash) = ledger.get(meta)? else {
+            return Ok(None);
+        };
+        if let ImportMode::BlobStore(blob_store) = &self.mode
+            && !blob_store.blob_contains(hash.into()).await?
+        {
+            return Ok(None);
+        }
+        let mut file_ref = FileRef::new(hash.into(), meta.len());
+        file_ref.timestamp = Some(meta.mtime().try_into()?);
+        file_ref.timestamp_subsec_nanos = Some(meta.mtime_nsec().try_into()?);
+        Ok(Some(file_ref))
+    }
+
+    /// Read, hash and (in blob-store mode) store a file, recording its hash
+    /// in the ledger. A file the size of one already imported is hashed
+    /// first and not stored again when its contents are a duplicate.
+    async fn read_file_ref(
+        &self,
+        path: &std::path::Path,
+        meta: &std::fs::Metadata,
+    ) -> anyhow::Result<FileRef> {
         let file_ref = match &self.mode {
-            ImportMode::BlobStore(blob_store) => {
-                // Normal mode: import file into blob store
-                let blob_id = blob_store
-                    .import_file(path.to_path_buf(), |_processed| Ok(()))
-                    .await
-                    .with_context(|| {
-                        format!("Failed to import file into blob store: {:?}", path)
-                    })?;
-
-                let mut file_ref: FileRef = blob_id.into();
-                file_ref.timestamp = Some(meta.mtime().try_into()?);
-                file_ref.timestamp_subsec_nanos = Some(meta.mtime_nsec().try_into()?);
-                file_ref
+            ImportMode::BlobStore(blob_store)
+                if meta.len() > self.inline_threshold && self.dedup.size_seen(meta.len()) =>
+            {
+                let hash = hash_file(path).await?;
+                if self.dedup.hash_seen(&hash) {
+                    let mut file_ref = FileRef::new(hash.into(), meta.len());
+                    file_ref.timestamp = Some(meta.mtime().try_into()?);
+                    file_ref.timestamp_subsec_nanos = Some(meta.mtime_nsec().try_into()?);
+                    file_ref
+                } else {
+                    self.store_file(blob_store, path, meta).await?
+                }
             }
+            ImportMode::BlobStore(blob_store) => self.store_file(blob_store, path, meta).await?,
             ImportMode::IndexOnly => {
                 // Index-only mode: hash file without copying to blob store
                 let hash = hash_file(path).await?;
@@ -298,18 +408,30 @@ impl LocalFileSystemImporter {
             }
         };
 
-        self.fs.file_put(&key, file_ref.clone()).await?;
-
-        // Update progress
-        if let Some(ref progress) = self.progress {
-            progress.files_processed.fetch_add(1, Ordering::Relaxed);
-            progress
-                .bytes_processed
-                .fetch_add(file_ref.size, Ordering::Relaxed);
+        if let Some(ledger) = &self.ledger
+            && file_ref.inline_data().is_none()
+        {
+            ledger.insert(meta, file_ref.hash)?;
         }
+        Ok(file_ref)
+    }
 
-        log::info!("Successfully imported file: {}", key);
-        Ok(())
+    /// Import a file into the blob store (or inline it).
+    async fn store_file(
+        &self,
+        blob_store: &BlobStore,
+        path: &std::path::Path,
+        meta: &std::fs::Metadata,
+    ) -> anyhow::Result<FileRef> {
+        let imported = blob_store
+            .import_file_or_inline(path.to_path_buf(), self.inline_threshold, |_| Ok(()))
+            .await
+            .with_context(|| format!("Failed to import file into blob store: {:?}", path))?;
+
+        let mut file_ref: FileRef = imported.into();
+        file_ref.timestamp = Some(meta.mtime().try_into()?);
+        file_ref.timestamp_subsec_nanos = Some(meta.mtime_nsec().try_into()?);
+        Ok(file_ref)
     }
 }
 
@@ -339,7 +461,6 @@ async fn hash_file(path: &std::path::Path) -> anyhow::Result<[u8; 32]> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use s5_core::BlobsRead;
     use s5_fs::DirContext;
     use s5_store_memory::MemoryStore;
     use std::fs::File;
@@ -367,8 +488,9 @@ mod tests {
 
         let blob_store = BlobStore::new(MemoryStore::new());
 
-        // 3. Run Importer (default: use absolute paths as keys)
-        let importer = LocalFileSystemImporter::create(
+        // 3. Run Importer (default: use absolute paths as keys), with
+        // inlining off so the tiny test files land in the blob store
+        let mut importer = LocalFileSystemImporter::create(
             fs.clone(),
             blob_store.clone(),
             4,
@@ -378,6 +500,7 @@ mod tests {
             true,
         )
         .unwrap();
+        importer.set_inline_threshold(0);
         importer
             .import_path(source_dir.path().to_path_buf())
             .await
@@ -464,6 +587,161 @@ mod tests {
         assert!(!fs.file_exists(cache_file_key).await);
     }
 
+    #[tokio::test]
+    async fn test_small_files_are_inlined() {
+        let source_dir = tempdir().unwrap();
+        std::fs::write(source_dir.path().join("small.txt"), b"small").unwrap();
+        let large = vec![7u8; DEFAULT_INLINE_THRESHOLD as usize + 1];
+        std::fs::write(source_dir.path().join("large.bin"), &large).unwrap();
+
+        let fs_dir = tempdir().unwrap();
+        let ctx = DirContext::open_local_root(fs_dir.path()).unwrap();
+        let fs = FS5::open(ctx).with_autosave(50).await.unwrap();
+        let blob_store = BlobStore::new(MemoryStore::new());
+
+        let importer = LocalFileSystemImporter::create(
+            fs.clone(),
+            blob_store.clone(),
+            4,
+            true,
+            true,
+            true,
+            true,
+        )
+        .unwrap();
+        importer
+            .import_path(source_dir.path().to_path_buf())
+            .await
+            .unwrap();
+        fs.save().await.unwrap();
+
+        let small = fs.file_get("small.txt").await.unwrap();
+        assert_eq!(small.inline_data(), Some(&b"small"[..]));
+        assert_eq!(small.hash, *blake3::hash(b"small").as_bytes());
+        assert!(!blob_store.blob_contains(small.hash.into()).await.unwrap());
+
+        let large_ref = fs.file_get("large.bin").await.unwrap();
+        assert!(large_ref.inline_data().is_none());
+        assert_eq!(
+            blob_store
+                .blob_download(large_ref.hash.into())
+                .await
+                .unwrap(),
+            large
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ledger_reuses_hashes_for_moved_files() {
+        let source_dir = tempdir().unwrap();
+        let original = source_dir.path().join("a").join("photo.raw");
+        std::fs::create_dir(original.parent().unwrap()).unwrap();
+        let content = vec![1u8; DEFAULT_INLINE_THRESHOLD as usize + 1];
+        std::fs::write(&original, &content).unwrap();
+
+        let ledger_dir = tempdir().unwrap();
+        let ledger = HashLedger::open(ledger_dir.path().join("ledger.redb")).unwrap();
+        let blob_store = BlobStore::new(MemoryStore::new());
+
+        async fn import_into_fresh_root(
+            source: &std::path::Path,
+            blob_store: &BlobStore,
+            ledger: &HashLedger,
+        ) -> (FS5, tempfile::TempDir) {
+            let fs_dir = tempdir().unwrap();
+            let ctx = DirContext::open_local_root(fs_dir.path()).unwrap();
+            let fs = FS5::open(ctx).with_autosave(50).await.unwrap();
+            let mut importer = LocalFileSystemImporter::create(
+                fs.clone(),
+                blob_store.clone(),
+                4,
+                true,
+                true,
+                true,
+                true,
+            )
+            .unwrap();
+            importer.set_ledger(ledger.clone());
+            importer.import_path(source.to_path_buf()).await.unwrap();
+            fs.save().await.unwrap();
+            (fs, fs_dir)
+        }
+
+        let (fs, _dir) = import_into_fresh_root(source_dir.path(), &blob_store, &ledger).await;
+        let first = fs.file_get("a/photo.raw").await.unwrap();
+        assert_eq!(first.hash, *blake3::hash(&content).as_bytes());
+
+        // Move the tree, then overwrite the bytes in place keeping size and
+        // mtime: a re-read would produce a different hash, the ledger
+        // returns the recorded one.
+        let moved_dir = source_dir.path().join("b");
+        std::fs::rename(original.parent().unwrap(), &moved_dir).unwrap();
+        let moved = moved_dir.join("photo.raw");
+        let mtime = std::fs::metadata(&moved).unwrap().modified().unwrap();
+        std::fs::write(&moved, vec![2u8; content.len()]).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&moved)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+
+        let (fs, _dir) = import_into_fresh_root(source_dir.path(), &blob_store, &ledger).await;
+        let second = fs.file_get("b/photo.raw").await.unwrap();
+        assert_eq!(second.hash, first.hash);
+        assert_eq!(second.timestamp, first.timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_hard_links_and_duplicates_share_one_blob() {
+        let source_dir = tempdir().unwrap();
+        let content = vec![3u8; DEFAULT_INLINE_THRESHOLD as usize + 100];
+        let original = source_dir.path().join("original.jpg");
+        std::fs::write(&original, &content).unwrap();
+        std::fs::hard_link(&original, source_dir.path().join("linked.jpg")).unwrap();
+        std::fs::write(source_dir.path().join("copy.jpg"), &content).unwrap();
+        // Same size, different contents: hashed, then stored.
+        std::fs::write(
+            source_dir.path().join("other.jpg"),
+            vec![4u8; content.len()],
+        )
+        .unwrap();
+
+        let fs_dir = tempdir().unwrap();
+        let ctx = DirContext::open_local_root(fs_dir.path()).unwrap();
+        let fs = FS5::open(ctx).with_autosave(50).await.unwrap();
+        let blob_store = BlobStore::new(MemoryStore::new());
+        // One file at a time, so which copy comes first is the only
+        // ordering question.
+        let importer = LocalFileSystemImporter::create(
+            fs.clone(),
+            blob_store.clone(),
+            1,
+            true,
+            true,
+            true,
+            true,
+        )
+        .unwrap();
+        importer
+            .import_path(source_dir.path().to_path_buf())
+            .await
+            .unwrap();
+        fs.save().await.unwrap();
+
+        let hash = *blake3::hash(&content).as_bytes();
+        for name in ["original.jpg", "linked.jpg", "copy.jpg"] {
+            assert_eq!(fs.file_get(name).await.unwrap().hash, hash, "{name}");
+        }
+        let other = fs.file_get("other.jpg").await.unwrap();
+        assert!(blob_store.blob_contains(other.hash.into()).await.unwrap());
+        assert_eq!(blob_store.list_hashes().await.unwrap().len(), 2);
+
+        let report = importer.dedup_report();
+        assert_eq!(report.hard_links + report.duplicates, 2);
+        assert_eq!(report.bytes_saved, 2 * content.len() as u64);
+    }
+
     #[tokio::test]
     async fn test_index_only_import() {
         // 1. Setup source directory with a file
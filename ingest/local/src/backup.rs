@@ -13,6 +13,7 @@
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
@@ -23,12 +24,17 @@ use ignore::WalkBuilder;
 use ignore::overrides::Override;
 use s5_core::{BlobsRead, BlobsWrite};
 use s5_fs_v2::layer::ReadableLayer;
-use s5_fs_v2::node::{ExtendedAttribute, FileType, NodeEntry, SemanticMeta, UnixMetadata};
+use s5_fs_v2::node::{
+    ExtendedAttribute, FileType, NodeEntry, SemanticMeta, SparseLayout, UnixMetadata,
+};
 use s5_fs_v2::overlay::WritableOverlay;
 use s5_fs_v2::persist::MergeStats;
 use s5_fs_v2::snapshot::Snapshot;
+use tokio::io::AsyncRead;
 use tokio_util::sync::CancellationToken;
 
+use crate::sparse;
+
 /// First-match-wins routing entry mapping a glob over the file's vault key
 /// (relative path) to a per-file `TraversalContext` override applied during
 /// import via [`Snapshot::import_stream_with_override`](s5_fs_v2::snapshot::Snapshot::import_stream_with_override).
@@ -130,6 +136,14 @@ pub struct BackupConfig {
     /// caller builds this from the same patterns it feeds the walker so both
     /// paths share one definition of "excluded". `None` = no excludes.
     pub exclude: Option<Override>,
+    /// Store sparse files without their holes. Files with holes (found via
+    /// `SEEK_HOLE`/`SEEK_DATA`) are imported as their data extents only,
+    /// and the hole layout is recorded in the entry's
+    /// [`SemanticMeta::sparse`]; restore and FUSE reads put the holes back.
+    /// Mostly useful for VM disk images and preallocated files.
+    ///
+    /// Default `false`: every file is read end to end, holes as zeros.
+    pub sparse_files: bool,
 }
 
 impl Default for BackupConfig {
@@ -143,6 +157,7 @@ impl Default for BackupConfig {
             follow_symlinks: false,
             detect_deletions: false,
             exclude: None,
+            sparse_files: false,
         }
     }
 }
@@ -739,6 +754,7 @@ fn build_semantic(
         media_type: None,
         unix,
         warc: None,
+        sparse: None,
     }
 }
 
@@ -943,8 +959,9 @@ async fn is_changed(
         }
     };
 
-    // Size check (only meaningful for regular files).
-    let prev_size = prev_entry.content.as_ref().map(|c| c.size).unwrap_or(0);
+    // Size check (only meaningful for regular files). Sparse entries store
+    // fewer bytes than the file's length, so compare the logical size.
+    let prev_size = prev_entry.file_size();
     if meta.is_file() && meta.len() != prev_size {
         tracing::info!(
             key = key,
@@ -1064,6 +1081,19 @@ where
     }
 }
 
+/// Open `path` for import: the whole file, or only its data extents when
+/// it has a sparse `layout`.
+async fn open_for_import(
+    path: &Path,
+    layout: Option<&SparseLayout>,
+) -> std::io::Result<Pin<Box<dyn AsyncRead + Send>>> {
+    let file = tokio::fs::File::open(path).await?;
+    Ok(match layout {
+        Some(layout) => Box::pin(sparse::extent_reader(file, layout.extents.clone())),
+        None => Box::pin(file),
+    })
+}
+
 /// Process a single directory entry from the walker.
 async fn process_entry(
     path: &Path,
@@ -1165,7 +1195,24 @@ async fn process_entry(
         let t_import = std::time::Instant::now();
 
         // Regular file: stream content, chunk, upload blobs.
-        let semantic = build_semantic(path, &meta, FileType::Regular, config.backup);
+        let mut semantic = build_semantic(path, &meta, FileType::Regular, config.backup);
+
+        // Sparse files: map the holes up front so the import reads only the
+        // data extents. `None` for dense files and when disabled.
+        let sparse = if config.sparse_files {
+            let layout_opt = retry_io(path, "seek_hole", || async {
+                sparse::layout(&std::fs::File::open(path)?, &meta)
+            })
+            .await?;
+            let Some(layout) = layout_opt else {
+                stats.files_errored.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            };
+            layout
+        } else {
+            None
+        };
+        semantic.sparse = sparse.clone();
 
         // First-match route lookup. None = use snapshot's default ctx.
         let route = config.routes.iter().find(|r| r.glob.is_match(key.as_str()));
@@ -1206,18 +1253,22 @@ async fn process_entry(
                 // AND we have a prev to reuse a prefix from. import_file_append
                 // self-guards (non-Fixed / no full prefix / shrank → full read)
                 // and reports actual bytes read.
-                Some(r) if r.append_only && !prev_chunks.is_empty() => prev_snapshot
-                    .import_file_append_with_override(
-                        path,
-                        blob_store,
-                        Some(semantic.clone()),
-                        &r.override_ctx,
-                        &prev_chunks,
-                    )
-                    .await
-                    .map_err(|e| std::io::Error::other(e.to_string()))?,
+                // Sparse files always take the full path: the append
+                // prefix is defined on the file, not the packed extents.
+                Some(r) if r.append_only && !prev_chunks.is_empty() && sparse.is_none() => {
+                    prev_snapshot
+                        .import_file_append_with_override(
+                            path,
+                            blob_store,
+                            Some(semantic.clone()),
+                            &r.override_ctx,
+                            &prev_chunks,
+                        )
+                        .await
+                        .map_err(|e| std::io::Error::other(e.to_string()))?
+                }
                 Some(r) => {
-                    let file = tokio::fs::File::open(path).await?;
+                    let file = open_for_import(path, sparse.as_ref()).await?;
                     let entry = prev_snapshot
                         .import_stream_with_override_and_prev(
                             file,
@@ -1232,7 +1283,7 @@ async fn process_entry(
                     (entry, n)
                 }
                 None => {
-                    let file = tokio::fs::File::open(path).await?;
+                    let file = open_for_import(path, sparse.as_ref()).await?;
                     let entry = prev_snapshot
                         .import_stream_with_prev(
                             file,
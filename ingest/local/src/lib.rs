@@ -27,6 +27,7 @@
 
 mod backup;
 mod restore;
+mod sparse;
 
 pub use backup::{
     BackupConfig, BackupResult, BackupStats, PipelineRoute, backup, backup_incremental,
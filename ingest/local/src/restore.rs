@@ -21,7 +21,7 @@ use cap_std::ambient_authority;
 use cap_std::fs::Dir;
 use futures::StreamExt;
 use futures::stream::FuturesUnordered;
-use s5_fs_v2::node::{FileType, NodeEntry};
+use s5_fs_v2::node::{FileType, NodeEntry, SparseLayout};
 use s5_fs_v2::snapshot::Snapshot;
 
 /// The setuid + setgid mode bits (`S_ISUID | S_ISGID`).
@@ -367,8 +367,11 @@ async fn restore_file_or_symlink(
         // Remove-then-write so a stale symlink left at this path by a previous
         // restore is replaced, never followed.
         let _ = root.remove_file(&key);
-        root.write(&key, content.as_ref())
-            .with_context(|| format!("writing {key}"))?;
+        match entry.sparse() {
+            Some(layout) => write_sparse(root, &key, &content, layout),
+            None => root.write(&key, content.as_ref()).map_err(Into::into),
+        }
+        .with_context(|| format!("writing {key}"))?;
 
         restore_metadata(&target_path, &entry, config);
 
@@ -380,6 +383,25 @@ async fn restore_file_or_symlink(
     Ok(())
 }
 
+/// Write a sparse file: each stored extent at its offset, then extend to
+/// the full size. The gaps are never written, so they stay holes.
+fn write_sparse(root: &Dir, key: &str, packed: &[u8], layout: &SparseLayout) -> anyhow::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    let file = root.create(key)?.into_std();
+    let mut pos = 0usize;
+    for extent in &layout.extents {
+        let end = pos + extent.len as usize;
+        let data = packed
+            .get(pos..end)
+            .context("sparse layout is longer than the stored content")?;
+        file.write_all_at(data, extent.offset)?;
+        pos = end;
+    }
+    file.set_len(layout.size)?;
+    Ok(())
+}
+
 // ===========================================================================
 // Metadata restore
 // ===========================================================================
@@ -549,6 +571,70 @@ mod tests {
         assert!(dst.join("c/d").is_dir());
     }
 
+    /// A file with holes is stored as its data extents only and restored
+    /// with the same bytes — and, where the filesystem tracks holes, with
+    /// the holes left unallocated.
+    #[tokio::test]
+    async fn sparse_file_round_trip() {
+        use s5_fs_v2::layer::ReadableLayer;
+        use std::os::unix::fs::{FileExt, MetadataExt};
+
+        let store = Arc::new(BlobStore::new(MemoryStore::new()));
+        let src_tmp = tempfile::tempdir().unwrap();
+        let src = src_tmp.path();
+        let file = std::fs::File::create(src.join("disk.img")).unwrap();
+        file.write_all_at(&[0xAB; 4096], 1 << 20).unwrap();
+        file.write_all_at(&[0xCD; 4096], 3 << 20).unwrap();
+        file.set_len(4 << 20).unwrap();
+        drop(file);
+        let expected = std::fs::read(src.join("disk.img")).unwrap();
+        let src_sparse =
+            std::fs::metadata(src.join("disk.img")).unwrap().blocks() * 512 < expected.len() as u64;
+
+        let prev = s5_fs_v2::snapshot::Snapshot::empty(
+            store.clone() as Arc<dyn s5_core::BlobsRead>,
+            s5_fs_v2::node::TraversalContext::default(),
+        );
+        let cfg = crate::backup::BackupConfig {
+            sparse_files: true,
+            ..Default::default()
+        };
+        let result = crate::backup::backup(
+            src,
+            &prev,
+            &*store,
+            &*store,
+            store.clone() as Arc<dyn s5_core::BlobsRead>,
+            &cfg,
+            WalkBuilder::new(src),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let (snap, _) = result.snapshot.expect("snapshot produced");
+
+        let entry = snap.get("disk.img").await.unwrap().unwrap();
+        assert_eq!(entry.file_size(), expected.len() as u64);
+        if src_sparse {
+            let layout = entry.sparse().expect("sparse layout recorded");
+            assert_eq!(layout.stored_len(), 2 * 4096);
+            assert_eq!(entry.content.as_ref().unwrap().size, 2 * 4096);
+        }
+
+        let dst_tmp = tempfile::tempdir().unwrap();
+        let dst = dst_tmp.path();
+        restore(&snap, dst, &RestoreConfig::default())
+            .await
+            .unwrap();
+        let restored = dst.join("disk.img");
+        assert_eq!(std::fs::read(&restored).unwrap(), expected);
+        if src_sparse {
+            let blocks = std::fs::metadata(&restored).unwrap().blocks();
+            assert!(blocks * 512 < expected.len() as u64, "holes were filled");
+        }
+    }
+
     #[test]
     fn validate_relative_key_accepts_clean_paths() {
         validate_relative_key("file.txt").unwrap();
@@ -0,0 +1,101 @@
+//! Sparse file support: find the data extents of a file with holes and
+//! stream only those.
+//!
+//! Holes are found with `lseek(SEEK_DATA / SEEK_HOLE)`. Filesystems
+//! without hole tracking report the whole file as one data extent, which
+//! is treated as dense.
+
+use std::io::Cursor;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::MetadataExt;
+
+use s5_fs_v2::node::{DataExtent, SparseLayout};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::StreamReader;
+
+/// Read size while streaming one extent.
+const READ_BUF: u64 = 1024 * 1024;
+
+/// The hole layout of `file`, or `None` if it has no holes.
+///
+/// Files whose allocated blocks cover their length are skipped without a
+/// seek, so dense files cost nothing.
+pub(crate) fn layout(
+    file: &std::fs::File,
+    meta: &std::fs::Metadata,
+) -> std::io::Result<Option<SparseLayout>> {
+    let size = meta.len();
+    if size == 0 || meta.blocks() * 512 >= size {
+        return Ok(None);
+    }
+    let fd = file.as_raw_fd();
+    let mut extents = Vec::new();
+    let mut pos = 0u64;
+    while pos < size {
+        // SAFETY: `fd` is a valid open descriptor borrowed from `file`.
+        let data = unsafe { libc::lseek(fd, pos as libc::off_t, libc::SEEK_DATA) };
+        if data < 0 {
+            let err = std::io::Error::last_os_error();
+            match err.raw_os_error() {
+                // No data past `pos`: the rest is a trailing hole.
+                Some(libc::ENXIO) => break,
+                // No hole support: treat as dense.
+                Some(libc::EINVAL) => return Ok(None),
+                _ => return Err(err),
+            }
+        }
+        // SAFETY: as above.
+        let hole = unsafe { libc::lseek(fd, data, libc::SEEK_HOLE) };
+        if hole < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let (data, hole) = (data as u64, (hole as u64).min(size));
+        if hole > data {
+            extents.push(DataExtent {
+                offset: data,
+                len: hole - data,
+            });
+        }
+        pos = hole;
+    }
+    if let [only] = extents.as_slice()
+        && only.offset == 0
+        && only.len == size
+    {
+        return Ok(None);
+    }
+    Ok(Some(SparseLayout { size, extents }))
+}
+
+/// Stream the data extents of `file` back to back, skipping the holes.
+pub(crate) fn extent_reader(
+    file: tokio::fs::File,
+    extents: Vec<DataExtent>,
+) -> impl AsyncRead + Unpin {
+    let pieces = futures::stream::try_unfold(
+        (file, extents.into_iter(), None::<DataExtent>),
+        |(mut file, mut rest, mut current)| async move {
+            let extent = match current.take() {
+                Some(extent) => extent,
+                None => {
+                    let Some(extent) = rest.next() else {
+                        return Ok(None);
+                    };
+                    file.seek(std::io::SeekFrom::Start(extent.offset)).await?;
+                    extent
+                }
+            };
+            let n = extent.len.min(READ_BUF);
+            let mut buf = vec![0u8; n as usize];
+            file.read_exact(&mut buf).await?;
+            if extent.len > n {
+                current = Some(DataExtent {
+                    offset: extent.offset + n,
+                    len: extent.len - n,
+                });
+            }
+            Ok::<_, std::io::Error>(Some((Cursor::new(buf), (file, rest, current))))
+        },
+    );
+    StreamReader::new(Box::pin(pieces))
+}
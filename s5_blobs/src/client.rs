@@ -7,13 +7,18 @@ use irpc::Client as IrpcClient;
 use irpc_iroh::IrohLazyRemoteConnection;
 use s5_core::Hash;
 
-use crate::rpc::{DeleteBlob, DownloadBlob, PinBlob, Query, QueryResponse, RpcProto, UploadBlob};
+use crate::rpc::{
+    BlobEvent, DeleteBlob, DownloadBlob, DownloadBlobVerified, ListBlobs, ListedBlob, PinBlob,
+    Query, QueryResponse, RpcProto, SubscribeBlobs, UploadBlob, VerifiedChunk,
+};
 
 use {
     anyhow::anyhow,
     async_trait::async_trait,
+    futures_util::StreamExt,
     s5_core::BlobId,
-    s5_core::blob::{BlobResult, BlobsRead, BlobsWrite},
+    s5_core::blob::{BlobResult, BlobsList, BlobsRead, BlobsWrite, HashStream},
+    std::collections::{HashSet, VecDeque},
     std::io::Cursor,
     std::path::PathBuf,
     tokio::io::AsyncRead,
@@ -21,7 +26,10 @@ use {
 };
 
 #[cfg(feature = "server")]
-use {futures::Stream, futures_util::StreamExt};
+use futures::Stream;
+
+/// Page size requested by the listing helpers; servers may clamp it.
+const LIST_PAGE: u32 = 1024;
 
 #[derive(Clone)]
 // TODO: Support multi-peer connections (pool of remote peers) with per-peer trust/health scores and reuse connections.
@@ -323,6 +331,224 @@ impl Client {
         }
         Ok(Bytes::from(buffer))
     }
+
+    /// Raw `DownloadBlobVerified` stream: a header with the bao proof,
+    /// then the block-aligned range bytes. Most callers want
+    /// [`Self::download_slice_verified`].
+    pub async fn download_verified(
+        &self,
+        hash: Hash,
+        offset: u64,
+        max_len: Option<u64>,
+    ) -> Result<irpc::channel::mpsc::Receiver<VerifiedChunk>, irpc::Error> {
+        self.inner
+            .server_streaming(
+                DownloadBlobVerified {
+                    hash: *hash.as_bytes(),
+                    offset,
+                    max_len,
+                },
+                8,
+            )
+            .await
+    }
+
+    /// Ranged download that is checked against `hash` before returning:
+    /// the peer proves the enclosing 64 KiB blocks with bao parent hashes,
+    /// so a partial read is as trustworthy as a full `blob_download`.
+    /// Fails if the peer cannot produce a proof (e.g. no outboard).
+    pub async fn download_slice_verified(
+        &self,
+        hash: Hash,
+        offset: u64,
+        max_len: Option<u64>,
+    ) -> BlobResult<Bytes> {
+        self.verified_slice(hash, offset, max_len, None).await
+    }
+
+    /// [`Self::download_slice_verified`], additionally failing if the
+    /// peer reports a size other than `known_size`.
+    pub(crate) async fn verified_slice(
+        &self,
+        hash: Hash,
+        offset: u64,
+        max_len: Option<u64>,
+        known_size: Option<u64>,
+    ) -> BlobResult<Bytes> {
+        let mut receiver = self
+            .download_verified(hash, offset, max_len)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        let (size, range, proof) = match receiver.recv().await {
+            Ok(Some(VerifiedChunk::Header {
+                size,
+                start,
+                end,
+                proof,
+            })) => (size, start..end, proof),
+            Ok(Some(VerifiedChunk::Data(_))) => {
+                return Err(anyhow!("verified download: data before header"));
+            }
+            Ok(None) => return Err(anyhow!("verified download refused for blob {hash}")),
+            Err(err) => return Err(anyhow!("verified download failed: {err}")),
+        };
+        if let Some(known) = known_size
+            && known != size
+        {
+            return Err(anyhow!(
+                "verified download: server reports {size} bytes for blob {hash}, expected {known}"
+            ));
+        }
+        // The server picks the range; make sure it covers what we asked for.
+        let expected = s5_core::bao::range::aligned_range(size, offset, max_len);
+        if range != expected {
+            return Err(anyhow!(
+                "verified download: server sent range {range:?}, expected {expected:?}"
+            ));
+        }
+
+        let mut buffer = Vec::with_capacity((range.end - range.start) as usize);
+        loop {
+            match receiver.recv().await {
+                Ok(Some(VerifiedChunk::Data(chunk))) => buffer.extend_from_slice(&chunk),
+                Ok(Some(VerifiedChunk::Header { .. })) => {
+                    return Err(anyhow!("verified download: duplicate header"));
+                }
+                Ok(None) => break,
+                Err(err) => return Err(anyhow!("verified download failed: {err}")),
+            }
+        }
+        s5_core::bao::range::verify_range(hash, size, range.clone(), &proof, &buffer)?;
+
+        let start = (offset.min(size) - range.start) as usize;
+        let end = max_len.map_or(size, |len| offset.saturating_add(len).min(size));
+        let end = (end.max(offset.min(size)) - range.start) as usize;
+        Ok(Bytes::from(buffer).slice(start..end))
+    }
+
+    /// Raw `ListBlobs` stream for one page. Most callers want
+    /// [`Self::list_blobs_page`] or the [`BlobsList`] impl.
+    pub async fn list_blobs(
+        &self,
+        cursor: Option<Hash>,
+        limit: u32,
+    ) -> Result<irpc::channel::mpsc::Receiver<Result<ListedBlob, String>>, irpc::Error> {
+        self.inner
+            .server_streaming(
+                ListBlobs {
+                    cursor: cursor.map(|h| *h.as_bytes()),
+                    limit,
+                },
+                32,
+            )
+            .await
+    }
+
+    /// One page of the peer's blobs after `cursor`, in hash order. Fails
+    /// if the peer does not allow this client to list. An empty page
+    /// means there is nothing past `cursor`.
+    pub async fn list_blobs_page(
+        &self,
+        cursor: Option<Hash>,
+        limit: u32,
+    ) -> BlobResult<Vec<ListedBlob>> {
+        let mut receiver = self
+            .list_blobs(cursor, limit)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        let mut page = Vec::new();
+        loop {
+            match receiver.recv().await {
+                Ok(Some(Ok(entry))) => page.push(entry),
+                Ok(Some(Err(err))) => return Err(anyhow!("list blobs refused: {err}")),
+                Ok(None) => break,
+                Err(err) => return Err(anyhow!("list blobs failed: {err}")),
+            }
+        }
+        Ok(page)
+    }
+
+    /// Live feed of blobs the peer stores from now on in `stores` (all
+    /// readable stores if empty). A mirror can subscribe, catch up once
+    /// with [`Self::missing_blobs`], then fetch each
+    /// [`BlobEvent::Added`] as it arrives instead of polling; on
+    /// [`BlobEvent::Lagged`] it should catch up again.
+    pub async fn subscribe_blobs(
+        &self,
+        stores: Vec<String>,
+        capacity: usize,
+    ) -> Result<irpc::channel::mpsc::Receiver<BlobEvent>, irpc::Error> {
+        self.inner
+            .server_streaming(SubscribeBlobs { stores }, capacity)
+            .await
+    }
+
+    /// Everything the peer lists that `local` does not hold, in hash
+    /// order. One listing of each side replaces a `Query` per blob, so
+    /// this is the cheap way for a replica to plan what to fetch.
+    pub async fn missing_blobs(&self, local: &dyn BlobsList) -> BlobResult<Vec<ListedBlob>> {
+        let mut have = HashSet::new();
+        let mut hashes = local.list_hashes().await?;
+        while let Some(hash) = hashes.next().await {
+            have.insert(hash?);
+        }
+
+        let mut missing = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.list_blobs_page(cursor, LIST_PAGE).await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = Some(Hash::from(last.hash));
+            missing.extend(
+                page.into_iter()
+                    .filter(|entry| !have.contains(&Hash::from(entry.hash))),
+            );
+        }
+        Ok(missing)
+    }
+}
+
+/// Streams the peer's whole listing, fetching pages as it goes.
+#[async_trait]
+impl BlobsList for Client {
+    async fn list_hashes(&self) -> BlobResult<HashStream> {
+        struct Pager {
+            client: Client,
+            cursor: Option<Hash>,
+            buffered: VecDeque<Hash>,
+            done: bool,
+        }
+        let pager = Pager {
+            client: self.clone(),
+            cursor: None,
+            buffered: VecDeque::new(),
+            done: false,
+        };
+        let stream = futures::stream::unfold(pager, |mut pager| async move {
+            loop {
+                if let Some(hash) = pager.buffered.pop_front() {
+                    return Some((Ok(hash), pager));
+                }
+                if pager.done {
+                    return None;
+                }
+                match pager.client.list_blobs_page(pager.cursor, LIST_PAGE).await {
+                    Ok(page) if page.is_empty() => return None,
+                    Ok(page) => {
+                        pager.buffered = page.iter().map(|e| Hash::from(e.hash)).collect();
+                        pager.cursor = pager.buffered.back().copied();
+                    }
+                    Err(err) => {
+                        pager.done = true;
+                        return Some((Err(err), pager));
+                    }
+                }
+            }
+        });
+        Ok(Box::new(Box::pin(stream)))
+    }
 }
 
 #[async_trait]
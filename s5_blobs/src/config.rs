@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::upload_filter::UploadFilterConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct PeerConfigBlobs {
     /// Names of stores this peer can read and query
@@ -10,4 +12,20 @@ pub struct PeerConfigBlobs {
     /// Skip per-blob pin checks for this peer (default: false).
     #[serde(default)]
     pub skip_pin_check: bool,
+    /// Let this peer enumerate its readable stores via `ListBlobs`
+    /// (default: false). Only honoured on the `peer_cfg` path; servers
+    /// with a membership `BlobAcl` never serve listings.
+    #[serde(default)]
+    pub allow_list: bool,
+    /// Confine this peer to `peers/<node id>/` inside every store it
+    /// reads, uploads to, lists or deletes from (default: false), so one
+    /// physical store can host many peers without them seeing each other's
+    /// blobs. On the `"*"` entry this gives every peer its own prefix. Only
+    /// honoured on the `peer_cfg` path.
+    #[serde(default)]
+    pub isolate: bool,
+    /// Checks every upload from this peer must pass, after any server-wide
+    /// filters (see [`crate::upload_filter`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upload_filters: Vec<UploadFilterConfig>,
 }
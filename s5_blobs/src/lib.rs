@@ -9,6 +9,12 @@
 //! - [`BlobsServer`]: a server-side handler that exposes named
 //!   blob stores over an iroh [`iroh::Endpoint`]. (requires `server` feature)
 //! - [`MultiFetcher`]: fetches blobs from multiple sources with fallback.
+//! - [`storage_proof`]: random verified-slice challenges that check a peer
+//!   still holds a blob, with per-peer reliability scores. (requires
+//!   `server` feature)
+//! - [`upload_filter`]: policy hooks (size caps, media-type denylists,
+//!   external scanners, ICAP) that can refuse uploads. (requires `server`
+//!   feature)
 //!
 //! These building blocks can be composed to run a blob-serving
 //! node and to connect remote applications or S5 nodes to it.
@@ -39,6 +45,14 @@ mod net_protocol;
 #[cfg(feature = "server")]
 pub use net_protocol::{BlobAcl, BlobsServer, PermitAllBlobAcl, ServerMode};
 
+#[cfg(feature = "server")]
+pub mod storage_proof;
+
+#[cfg(feature = "server")]
+pub mod upload_filter;
+#[cfg(feature = "server")]
+pub use upload_filter::{UploadCandidate, UploadFilter, UploadFilterConfig};
+
 mod store_remote;
 pub use store_remote::RemoteBlobStore;
 
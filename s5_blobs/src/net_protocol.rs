@@ -21,24 +21,44 @@
 // Step 3a (transport-level peer ACL via iroh 0.98 `EndpointHooks`) is
 // already in place upstream of this file in `s5_node::membership`.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+use futures_util::StreamExt;
+
 use iroh::endpoint::Connection;
 use iroh::protocol::{AcceptError, ProtocolHandler};
 use irpc_iroh::read_request;
-use s5_core::blob::BlobsRead;
+use s5_core::blob::{BlobsList, BlobsRead};
 use s5_core::pins::{PinContext, Pins};
 use s5_core::{Hash, blob::BlobStore};
 
 use crate::config::PeerConfigBlobs;
 use crate::rpc::{
-    AuthChallengeResponse, AuthProve, DeleteBlob, DownloadBlob, PinBlob, Query, QueryResponse,
-    RpcMessage, RpcProto, UploadBlob,
+    AuthChallengeResponse, AuthProve, BlobEvent, DeleteBlob, DownloadBlob, DownloadBlobVerified,
+    ListBlobs, ListedBlob, PinBlob, Query, QueryResponse, RpcMessage, RpcProto, SubscribeBlobs,
+    UploadBlob, VerifiedChunk,
 };
+use crate::upload_filter::{UploadCandidate, UploadFilter};
 
 const CHUNK_SIZE: usize = 64 * 1024; // 64k
 
+/// Upper bound on entries in one `ListBlobs` page, whatever the client asks.
+const MAX_LIST_PAGE: u32 = 1024;
+
+/// Buffered `SubscribeBlobs` events per server before slow subscribers
+/// start seeing `BlobEvent::Lagged`.
+const BLOB_EVENTS_CAPACITY: usize = 1024;
+
+/// A blob that landed in one of the server's named stores; fanned out to
+/// `SubscribeBlobs` streams.
+#[derive(Debug, Clone)]
+struct BlobAdded {
+    store: String,
+    hash: Hash,
+    size: u64,
+}
+
 /// Domain separator for the F02 binding-derivation step. Used with
 /// `blake3::derive_key` so the binding is cryptographically separated
 /// from any other use of blake3 in the system.
@@ -189,6 +209,11 @@ pub struct BlobsServer {
     /// accept loop requires the F02 challenge handshake (`Acl`) or
     /// runs anonymously over `public_blob_hashes` only (`Public`).
     mode: ServerMode,
+    /// Fanout for `SubscribeBlobs`. Shared by every clone, so uploads on
+    /// either ALPN and [`Self::announce_blob`] reach all subscribers.
+    events: tokio::sync::broadcast::Sender<BlobAdded>,
+    /// Checks run on every upload before per-peer `upload_filters`.
+    upload_filters: Arc<Vec<Arc<dyn UploadFilter>>>,
 }
 
 impl std::fmt::Debug for BlobsServer {
@@ -201,6 +226,7 @@ impl std::fmt::Debug for BlobsServer {
             )
             .field("peer_cfg", &self.peer_cfg.keys().collect::<Vec<_>>())
             .field("pinner", &self.pinner.is_some())
+            .field("upload_filters", &self.upload_filters)
             .finish()
     }
 }
@@ -219,6 +245,8 @@ impl BlobsServer {
             pinner,
             local_iroh_pubkey: [0u8; 32],
             mode: ServerMode::Acl,
+            events: tokio::sync::broadcast::channel(BLOB_EVENTS_CAPACITY).0,
+            upload_filters: Arc::default(),
         }
     }
 
@@ -240,6 +268,8 @@ impl BlobsServer {
             pinner,
             local_iroh_pubkey: [0u8; 32],
             mode: ServerMode::Acl,
+            events: tokio::sync::broadcast::channel(BLOB_EVENTS_CAPACITY).0,
+            upload_filters: Arc::default(),
         }
     }
 
@@ -266,6 +296,27 @@ impl BlobsServer {
         self
     }
 
+    /// Builder: run `filter` on every upload, whichever peer sends it.
+    /// Filters run in registration order, before the peer's own
+    /// [`PeerConfigBlobs::upload_filters`].
+    pub fn with_upload_filter(mut self, filter: Arc<dyn UploadFilter>) -> Self {
+        Arc::make_mut(&mut self.upload_filters).push(filter);
+        self
+    }
+
+    /// Tell `SubscribeBlobs` subscribers that `hash` is now in `store`.
+    /// RPC uploads announce themselves; call this for blobs the node
+    /// stores by other means (local imports, publishing) so mirrors
+    /// pick them up without polling.
+    pub fn announce_blob(&self, store: &str, hash: Hash, size: u64) {
+        // No receivers just means nobody is subscribed.
+        let _ = self.events.send(BlobAdded {
+            store: store.to_string(),
+            hash,
+            size,
+        });
+    }
+
     fn cfg_for(&self, node_key: &str) -> Option<&PeerConfigBlobs> {
         // First try an exact match for this peer's id; if not present,
         // fall back to a wildcard entry ("*") if configured.
@@ -274,6 +325,19 @@ impl BlobsServer {
             .or_else(|| self.peer_cfg.get("*"))
     }
 
+    /// The full store `name` as `node_key` sees it: confined to the peer's
+    /// `peers/<id>/` prefix when its `peer_cfg` entry sets `isolate` (and no
+    /// membership ACL overrides `peer_cfg`), the shared store otherwise.
+    fn store_for(&self, node_key: &str, name: &str) -> Option<BlobStore> {
+        let store = self.stores.get(name)?;
+        let isolate = self.acl.is_none() && self.cfg_for(node_key).is_some_and(|cfg| cfg.isolate);
+        Some(if isolate {
+            store.scoped(&s5_core::prefixed::peer_prefix(node_key))
+        } else {
+            store.clone()
+        })
+    }
+
     /// Server-side verification of an `AuthProve` message. Returns the
     /// bound ACL pubkey on success, or an error string describing why
     /// the proof was rejected.
@@ -464,6 +528,18 @@ impl ProtocolHandler for BlobsServer {
                     let _ = handle_download(self, &node_key, &principal, node_id_bytes, inner, tx)
                         .await;
                 }
+                RpcMessage::DownloadBlobVerified(msg) => {
+                    let irpc::WithChannels { inner, tx, .. } = msg;
+                    let _ = handle_download_verified(
+                        self,
+                        &node_key,
+                        &principal,
+                        node_id_bytes,
+                        inner,
+                        tx,
+                    )
+                    .await;
+                }
                 RpcMessage::DeleteBlob(msg) => {
                     let irpc::WithChannels { inner, tx, .. } = msg;
                     let _ = handle_delete(self, &node_key, node_id_bytes, inner, tx).await;
@@ -472,6 +548,22 @@ impl ProtocolHandler for BlobsServer {
                     let irpc::WithChannels { inner, tx, .. } = msg;
                     let _ = handle_pin(self, &node_key, node_id_bytes, inner, tx).await;
                 }
+                RpcMessage::ListBlobs(msg) => {
+                    let irpc::WithChannels { inner, tx, .. } = msg;
+                    let _ = handle_list(self, &node_key, inner, tx).await;
+                }
+                RpcMessage::SubscribeBlobs(msg) => {
+                    let irpc::WithChannels { inner, tx, .. } = msg;
+                    // Subscribe before the next request is read, so an
+                    // upload later on this connection is never missed. The
+                    // feed runs in its own task to keep this loop serving.
+                    let events = self.events.subscribe();
+                    let server = self.clone();
+                    let node_key = node_key.clone();
+                    tokio::spawn(async move {
+                        handle_subscribe(&server, &node_key, &principal, inner, events, tx).await;
+                    });
+                }
             }
         }
 
@@ -501,7 +593,7 @@ async fn handle_pin(
         let _ = tx.send(Err("uploads (pinning) not allowed".into())).await;
         return;
     };
-    let Some(store) = server.stores.get(store_name) else {
+    let Some(store) = server.store_for(node_key, store_name) else {
         let _ = tx.send(Err("invalid upload store".into())).await;
         return;
     };
@@ -561,8 +653,8 @@ async fn handle_query(
         let blinded_hash = query.hash;
         if let Some(cfg) = server.cfg_for(node_key) {
             for name in &cfg.readable_stores {
-                if let Some(store) = server.stores.get(name)
-                    && let Some(actual_hash) = find_blob_by_blinded_hash(store, blinded_hash).await
+                if let Some(store) = server.store_for(node_key, name)
+                    && let Some(actual_hash) = find_blob_by_blinded_hash(&store, blinded_hash).await
                 {
                     resp.exists = true;
                     resp.actual_hash = Some(*actual_hash.as_bytes());
@@ -589,7 +681,7 @@ async fn handle_query(
         {
             for name in &names {
                 // Check full stores first (they can provide locations)
-                if let Some(store) = server.stores.get(name)
+                if let Some(store) = server.store_for(node_key, name)
                     && let Ok(true) = store.contains(hash).await
                 {
                     resp.exists = true;
@@ -662,18 +754,74 @@ async fn handle_upload(
         let _ = tx.send(Err("uploads not allowed".into())).await;
         return;
     };
-    let Some(store) = server.stores.get(store_name) else {
+    let Some(store) = server.store_for(node_key, store_name) else {
         let _ = tx.send(Err("invalid upload store".into())).await;
         return;
     };
 
-    // Adapt rx into the expected Stream type for import_stream, owning the receiver.
-    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+    let mut rx = rx;
+    let filters: Vec<Arc<dyn UploadFilter>> = server
+        .upload_filters
+        .iter()
+        .cloned()
+        .chain(cfg.upload_filters.iter().map(|f| f.build()))
+        .collect();
+    // Chunks read ahead for the filters' head; replayed into the store.
+    let mut head_chunks: Vec<bytes::Bytes> = Vec::new();
+    if !filters.is_empty() {
+        let head_len = filters
+            .iter()
+            .map(|f| f.head_bytes())
+            .max()
+            .unwrap_or(0)
+            .min(usize::try_from(req.size).unwrap_or(usize::MAX));
+        let mut buffered = 0;
+        while buffered < head_len {
+            match rx.recv().await {
+                Ok(Some(chunk)) => {
+                    buffered += chunk.len();
+                    head_chunks.push(chunk);
+                }
+                _ => break,
+            }
+        }
+        let mut head = bytes::BytesMut::with_capacity(buffered);
+        for chunk in &head_chunks {
+            head.extend_from_slice(chunk);
+        }
+        head.truncate(head_len);
+        let candidate = UploadCandidate {
+            hash: Hash::from(req.expected_hash),
+            size: req.size,
+            peer: node_key.to_string(),
+            head: head.freeze(),
+        };
+        for filter in &filters {
+            if let Err(reason) = filter.check(&candidate).await {
+                tracing::info!(
+                    peer = node_key,
+                    hash = candidate.hash.fmt_short(),
+                    reason,
+                    "upload refused by filter"
+                );
+                let _ = tx
+                    .send(Err(format!("rejected by upload filter: {reason}")))
+                    .await;
+                return;
+            }
+        }
+    }
+
+    // Adapt rx into the expected Stream type for import_stream, owning the
+    // receiver, after replaying anything read ahead for the filters.
+    let head = futures_util::stream::iter(head_chunks.into_iter().map(Ok));
+    let rest = futures_util::stream::unfold(rx, |mut rx| async move {
         match rx.recv().await {
             Ok(Some(chunk)) => Some((Ok::<bytes::Bytes, std::io::Error>(chunk), rx)),
             _ => None,
         }
     });
+    let stream = head.chain(rest);
 
     // TODO(remote-blobs): once RemoteBlobStore fully owns hashing and
     // outboard computation/verification, consider tightening this path
@@ -695,6 +843,7 @@ async fn handle_upload(
                     let _ = tx.send(Err(format!("pinning failed: {e}"))).await;
                     return;
                 }
+                server.announce_blob(store_name, got_hash, got_size);
                 let _ = tx.send(Ok(())).await;
             }
         }
@@ -704,23 +853,27 @@ async fn handle_upload(
     }
 }
 
-async fn handle_download(
+/// A readable source holding a requested blob, after ACL and pin checks.
+struct DownloadSource {
+    source: Arc<dyn BlobsRead>,
+    name: Option<String>,
+    size: u64,
+    from_read_source: bool,
+}
+
+/// Shared front half of the download handlers: ACL / `peer_cfg`
+/// resolution, source lookup, the legacy pin check and the size lookup.
+/// `None` means the request is refused or the blob is unavailable; the
+/// reason is logged here.
+async fn resolve_download_source(
     server: &BlobsServer,
     node_key: &str,
     principal: &Principal,
     node_id_bytes: [u8; 32],
-    req: DownloadBlob,
-    tx: irpc::channel::mpsc::Sender<bytes::Bytes>,
-) {
-    let hash: Hash = req.hash.into();
+    hash: Hash,
+) -> Option<DownloadSource> {
     let hash_short = hash.fmt_short();
 
-    tracing::info!(
-        peer = node_key,
-        hash = hash_short,
-        "handle_download: request received"
-    );
-
     let Some(names) = server
         .resolve_readable_names(node_key, principal, &hash)
         .await
@@ -730,7 +883,7 @@ async fn handle_download(
             hash = hash_short,
             "download denied: ACL or peer_cfg refused"
         );
-        return;
+        return None;
     };
 
     // Find first readable source containing the blob (stores or read-only sources)
@@ -741,13 +894,13 @@ async fn handle_download(
 
     for name in &names {
         // Check full stores first
-        if let Some(store) = server.stores.get(name) {
+        if let Some(store) = server.store_for(node_key, name) {
             match store.contains(hash).await {
                 Ok(true) => {
                     if let Ok(sz) = store.size(hash).await {
                         size_opt = Some(sz);
                     }
-                    source_opt = Some(Arc::new(store.clone()) as Arc<dyn BlobsRead>);
+                    source_opt = Some(Arc::new(store) as Arc<dyn BlobsRead>);
                     source_name = Some(name.clone());
                     break;
                 }
@@ -809,7 +962,7 @@ async fn handle_download(
                 hash = hash_short,
                 "download denied: not pinned"
             );
-            return; // Not pinned by this user, deny download
+            return None; // Not pinned by this user, deny download
         }
     }
 
@@ -822,7 +975,7 @@ async fn handle_download(
             num_read_sources = server.read_sources.len(),
             "download: blob not found in any readable store"
         );
-        return;
+        return None;
     };
     let Some(size) = size_opt else {
         tracing::warn!(
@@ -831,6 +984,41 @@ async fn handle_download(
             source = ?source_name,
             "download: blob exists but size unknown"
         );
+        return None;
+    };
+
+    Some(DownloadSource {
+        source,
+        name: source_name,
+        size,
+        from_read_source,
+    })
+}
+
+async fn handle_download(
+    server: &BlobsServer,
+    node_key: &str,
+    principal: &Principal,
+    node_id_bytes: [u8; 32],
+    req: DownloadBlob,
+    tx: irpc::channel::mpsc::Sender<bytes::Bytes>,
+) {
+    let hash: Hash = req.hash.into();
+    let hash_short = hash.fmt_short();
+
+    tracing::info!(
+        peer = node_key,
+        hash = hash_short,
+        "handle_download: request received"
+    );
+
+    let Some(DownloadSource {
+        source,
+        name: source_name,
+        size,
+        from_read_source,
+    }) = resolve_download_source(server, node_key, principal, node_id_bytes, hash).await
+    else {
         return;
     };
 
@@ -898,6 +1086,204 @@ async fn handle_download(
     }
 }
 
+async fn handle_download_verified(
+    server: &BlobsServer,
+    node_key: &str,
+    principal: &Principal,
+    node_id_bytes: [u8; 32],
+    req: DownloadBlobVerified,
+    tx: irpc::channel::mpsc::Sender<VerifiedChunk>,
+) {
+    let hash: Hash = req.hash.into();
+    let hash_short = hash.fmt_short();
+
+    let Some(DownloadSource { source, size, .. }) =
+        resolve_download_source(server, node_key, principal, node_id_bytes, hash).await
+    else {
+        return;
+    };
+
+    let range = s5_core::bao::range::aligned_range(size, req.offset, req.max_len);
+    let proof = match source.blob_get_outboard(hash, range.clone()).await {
+        Ok(Some(proof)) => proof,
+        Ok(None) => {
+            tracing::info!(
+                peer = node_key,
+                hash = hash_short,
+                "download_verified: source cannot produce an outboard"
+            );
+            return;
+        }
+        Err(e) => {
+            tracing::warn!(
+                peer = node_key,
+                hash = hash_short,
+                error = %e,
+                "download_verified: outboard lookup failed"
+            );
+            return;
+        }
+    };
+
+    let header = VerifiedChunk::Header {
+        size,
+        start: range.start,
+        end: range.end,
+        proof,
+    };
+    if tx.send(header).await.is_err() {
+        return;
+    }
+
+    let mut pos = range.start;
+    while pos < range.end {
+        let want = std::cmp::min(CHUNK_SIZE as u64, range.end - pos);
+        match source.blob_download_slice(hash, pos, Some(want)).await {
+            Ok(bytes) if !bytes.is_empty() => {
+                pos += bytes.len() as u64;
+                if tx.send(VerifiedChunk::Data(bytes)).await.is_err() {
+                    tracing::info!(
+                        hash = hash_short,
+                        pos,
+                        "download_verified: peer disconnected"
+                    );
+                    break;
+                }
+            }
+            Ok(_) => {
+                tracing::warn!(hash = hash_short, pos, "download_verified: got empty slice");
+                break;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    hash = hash_short,
+                    pos,
+                    error = %e,
+                    "download_verified: slice read failed"
+                );
+                break;
+            }
+        }
+    }
+}
+
+async fn handle_list(
+    server: &BlobsServer,
+    node_key: &str,
+    req: ListBlobs,
+    tx: irpc::channel::mpsc::Sender<Result<ListedBlob, String>>,
+) {
+    // The membership ACL approves reads hash by hash, and served stores
+    // may hold other vaults' blobs; it cannot scope an enumeration, so
+    // listings stay on the legacy `peer_cfg` path only.
+    let cfg = match server.cfg_for(node_key) {
+        Some(cfg) if server.acl.is_none() && cfg.allow_list => cfg,
+        _ => {
+            let _ = tx.send(Err("listing not allowed".into())).await;
+            return;
+        }
+    };
+
+    let limit = req.limit.clamp(1, MAX_LIST_PAGE) as usize;
+    let cursor = req.cursor.map(Hash::from);
+    // Stores list in backend order, so keep the `limit` lowest hashes
+    // past the cursor across every readable store.
+    let mut page: BTreeMap<Hash, &BlobStore> = BTreeMap::new();
+    // Read-only sources have no enumeration; only full stores are listed.
+    let stores: Vec<BlobStore> = cfg
+        .readable_stores
+        .iter()
+        .filter_map(|name| server.store_for(node_key, name))
+        .collect();
+    for store in &stores {
+        let mut hashes = match BlobsList::list_hashes(store).await {
+            Ok(hashes) => hashes,
+            Err(e) => {
+                let _ = tx.send(Err(format!("store error: {e}"))).await;
+                return;
+            }
+        };
+        while let Some(hash) = hashes.next().await {
+            let hash = match hash {
+                Ok(hash) => hash,
+                Err(e) => {
+                    let _ = tx.send(Err(format!("store error: {e}"))).await;
+                    return;
+                }
+            };
+            if cursor.is_some_and(|c| hash <= c) || page.contains_key(&hash) {
+                continue;
+            }
+            page.insert(hash, store);
+            if page.len() > limit {
+                page.pop_last();
+            }
+        }
+    }
+
+    for (hash, store) in page {
+        // Skip entries deleted since they were listed.
+        let Ok(size) = store.size(hash).await else {
+            continue;
+        };
+        let entry = ListedBlob {
+            hash: *hash.as_bytes(),
+            size,
+        };
+        if tx.send(Ok(entry)).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn handle_subscribe(
+    server: &BlobsServer,
+    node_key: &str,
+    principal: &Principal,
+    req: SubscribeBlobs,
+    mut events: tokio::sync::broadcast::Receiver<BlobAdded>,
+    tx: irpc::channel::mpsc::Sender<BlobEvent>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        let received = tokio::select! {
+            received = events.recv() => received,
+            // Without this an idle feed would outlive its subscriber.
+            _ = tx.closed() => return,
+        };
+        let event = match received {
+            Ok(added) => {
+                if !req.stores.is_empty() && !req.stores.contains(&added.store) {
+                    continue;
+                }
+                // Same gate as a download of this hash: a peer only hears
+                // about blobs it would be allowed to fetch.
+                let readable = server
+                    .resolve_readable_names(node_key, principal, &added.hash)
+                    .await
+                    .is_some_and(|names| names.contains(&added.store));
+                if !readable {
+                    continue;
+                }
+                BlobEvent::Added {
+                    store: added.store,
+                    hash: *added.hash.as_bytes(),
+                    size: added.size,
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "blobs: subscriber lagged, events dropped");
+                BlobEvent::Lagged { skipped }
+            }
+            Err(RecvError::Closed) => return,
+        };
+        if tx.send(event).await.is_err() {
+            return; // subscriber gone
+        }
+    }
+}
+
 async fn handle_delete(
     server: &BlobsServer,
     node_key: &str,
@@ -923,7 +1309,16 @@ async fn handle_delete(
             .await
         {
             Ok(orphaned) => {
-                if orphaned {
+                // An isolated peer's copies live under its own prefix, so
+                // they go as soon as it unpins, whoever else holds the hash.
+                if cfg.isolate && server.acl.is_none() {
+                    for name in server.stores.keys() {
+                        if let Some(store) = server.store_for(node_key, name) {
+                            let _ = store.delete(hash).await;
+                        }
+                    }
+                    let _ = tx.send(Ok(true)).await;
+                } else if orphaned {
                     for store in server.stores.values() {
                         let _ = store.delete(hash).await;
                     }
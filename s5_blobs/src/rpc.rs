@@ -67,6 +67,28 @@ pub enum RpcProto {
     /// `Ok(false)` if the blob was not found, and `Err(String)` on error.
     #[rpc(tx = oneshot::Sender<Result<bool, String>>)]
     PinBlob(PinBlob),
+    /// Verified ranged download. The requested range is widened to
+    /// 64 KiB block boundaries; the server first sends a
+    /// [`VerifiedChunk::Header`] carrying the bao proof for that range,
+    /// then the range bytes as [`VerifiedChunk::Data`]. The client checks
+    /// everything against the blob hash before trusting a single byte.
+    /// The stream closes without a header if the server has no outboard
+    /// for the blob or refuses the request.
+    #[rpc(tx = mpsc::Sender<VerifiedChunk>)]
+    DownloadBlobVerified(DownloadBlobVerified),
+    /// One page of the blobs this peer may read, in ascending hash
+    /// order. Only served to peers whose `PeerConfigBlobs::allow_list`
+    /// is set; anyone else receives a single `Err` frame. An empty page
+    /// ends the listing.
+    #[rpc(tx = mpsc::Sender<Result<ListedBlob, String>>)]
+    ListBlobs(ListBlobs),
+    /// Live feed of blobs the server stores from now on, filtered by the
+    /// same read rules as downloads. The server pushes a
+    /// [`BlobEvent::Added`] per matching blob until either side drops
+    /// the stream; [`BlobEvent::Lagged`] means events were lost and the
+    /// subscriber should catch up with `ListBlobs`.
+    #[rpc(tx = mpsc::Sender<BlobEvent>)]
+    SubscribeBlobs(SubscribeBlobs),
 }
 
 /// First step of the F02 ACL challenge. Client sends a wire-format
@@ -167,6 +189,68 @@ pub struct DownloadBlob {
     pub max_len: Option<u64>,
 }
 
+/// Same request shape as [`DownloadBlob`]; a separate type because each
+/// RPC variant needs its own message type.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DownloadBlobVerified {
+    pub hash: [u8; 32],
+    pub offset: u64,
+    pub max_len: Option<u64>,
+}
+
+/// Page request for `ListBlobs`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListBlobs {
+    /// Resume after this hash (exclusive); `None` starts from the lowest.
+    pub cursor: Option<[u8; 32]>,
+    /// Maximum entries in the page. Servers clamp this to their own
+    /// page limit, so a short page does not mean the listing is done.
+    pub limit: u32,
+}
+
+/// One entry of a `ListBlobs` page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListedBlob {
+    pub hash: [u8; 32],
+    pub size: u64,
+}
+
+/// Opens a `SubscribeBlobs` feed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubscribeBlobs {
+    /// Store names to watch, e.g. the store backing a sync root. Empty
+    /// watches every store this peer may read.
+    pub stores: Vec<String>,
+}
+
+/// Event delivered over the `SubscribeBlobs` stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobEvent {
+    /// A blob was stored in `store`, by upload or by the node itself.
+    Added {
+        store: String,
+        hash: [u8; 32],
+        size: u64,
+    },
+    /// The subscriber fell behind and `skipped` events were dropped.
+    Lagged { skipped: u64 },
+}
+
+/// Frames of a `DownloadBlobVerified` response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VerifiedChunk {
+    /// Always the first frame. `start..end` is the block-aligned range
+    /// that follows; `proof` holds its pre-order parent hash pairs
+    /// (see `s5_core::bao::range`).
+    Header {
+        size: u64,
+        start: u64,
+        end: u64,
+        proof: Bytes,
+    },
+    Data(Bytes),
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 // TODO: Extend discovery responses to carry richer metadata:
 // - validity / expiry timestamp for locations (like the old Announce.timestamp).
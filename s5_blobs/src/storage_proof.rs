@@ -0,0 +1,236 @@
+//! Storage proofs: spot-checking that a peer really holds a blob.
+//!
+//! A client that paid a peer to store a blob only needs to remember its
+//! hash and size. To check on it, [`StorageChallenge::random`] picks a
+//! random slice, and [`challenge`] asks for it over `DownloadBlobVerified`.
+//! The peer must answer within a deadline with the slice bytes plus the
+//! bao proof linking them to the hash. That is only possible with
+//! the data at hand: the hash commits to every byte, and the client
+//! picks the offset fresh each time.
+//!
+//! Each [`ChallengeOutcome`] goes into a [`ReliabilityTracker`], which
+//! keeps per-peer tallies and a decaying score that callers can use to
+//! pick or drop storage peers.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use rand::RngExt;
+use s5_core::Hash;
+use serde::{Deserialize, Serialize};
+
+use crate::Client;
+
+/// A sensible `len` for [`StorageChallenge::random`]: one 64 KiB bao
+/// block, the smallest unit the peer can prove.
+pub const DEFAULT_CHALLENGE_LEN: u64 = 64 * 1024;
+
+/// Weight of the newest outcome in [`PeerReliability::score`].
+const SCORE_DECAY: f64 = 0.2;
+
+/// One slice of a blob the peer is asked to prove it holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageChallenge {
+    pub hash: Hash,
+    /// Size the blob is known to have; a peer reporting another fails.
+    pub size: u64,
+    pub offset: u64,
+    pub len: u64,
+}
+
+impl StorageChallenge {
+    /// Picks a random `len`-byte slice of the blob (the whole blob if it is
+    /// shorter).
+    pub fn random(hash: Hash, size: u64, len: u64) -> Self {
+        let len = len.clamp(1, size.max(1));
+        let offset = if size > len {
+            rand::rng().random_range(0..=size - len)
+        } else {
+            0
+        };
+        Self {
+            hash,
+            size,
+            offset,
+            len,
+        }
+    }
+}
+
+/// How a peer answered a [`StorageChallenge`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChallengeOutcome {
+    /// The peer proved the slice in `latency`.
+    Passed { latency: Duration },
+    /// The peer answered, but not with a proof of the slice: it refused,
+    /// lacked the blob or outboard, or sent bytes that failed
+    /// verification.
+    Failed { reason: String },
+    /// No valid answer before the deadline.
+    TimedOut,
+}
+
+impl ChallengeOutcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, Self::Passed { .. })
+    }
+}
+
+/// Sends `challenge` to the peer behind `client` and waits up to
+/// `deadline` for a verified answer.
+pub async fn challenge(
+    client: &Client,
+    challenge: &StorageChallenge,
+    deadline: Duration,
+) -> ChallengeOutcome {
+    let started = Instant::now();
+    let answer = client.verified_slice(
+        challenge.hash,
+        challenge.offset,
+        Some(challenge.len),
+        Some(challenge.size),
+    );
+    match tokio::time::timeout(deadline, answer).await {
+        Err(_) => ChallengeOutcome::TimedOut,
+        Ok(Err(err)) => ChallengeOutcome::Failed {
+            reason: err.to_string(),
+        },
+        Ok(Ok(bytes)) => {
+            let expected = challenge
+                .len
+                .min(challenge.size.saturating_sub(challenge.offset));
+            if bytes.len() as u64 != expected {
+                return ChallengeOutcome::Failed {
+                    reason: format!("expected {expected} bytes, got {}", bytes.len()),
+                };
+            }
+            ChallengeOutcome::Passed {
+                latency: started.elapsed(),
+            }
+        }
+    }
+}
+
+/// Challenge history of one peer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerReliability {
+    pub passed: u64,
+    pub failed: u64,
+    pub timed_out: u64,
+    /// Exponentially decaying pass rate in `0.0..=1.0`, so recent
+    /// behaviour outweighs old history. Starts at the first outcome.
+    pub score: f64,
+    /// Latency of the most recent passed challenge.
+    pub last_latency: Option<Duration>,
+}
+
+impl PeerReliability {
+    pub fn challenges(&self) -> u64 {
+        self.passed + self.failed + self.timed_out
+    }
+
+    fn record(&mut self, outcome: &ChallengeOutcome) {
+        let value = match outcome {
+            ChallengeOutcome::Passed { latency } => {
+                self.passed += 1;
+                self.last_latency = Some(*latency);
+                1.0
+            }
+            ChallengeOutcome::Failed { .. } => {
+                self.failed += 1;
+                0.0
+            }
+            ChallengeOutcome::TimedOut => {
+                self.timed_out += 1;
+                0.0
+            }
+        };
+        self.score = if self.challenges() == 1 {
+            value
+        } else {
+            self.score + SCORE_DECAY * (value - self.score)
+        };
+    }
+}
+
+/// Per-peer [`PeerReliability`], keyed by iroh node id.
+#[derive(Debug, Default)]
+pub struct ReliabilityTracker {
+    peers: DashMap<[u8; 32], PeerReliability>,
+}
+
+impl ReliabilityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds `outcome` into `peer`'s record and returns the updated record.
+    pub fn record(&self, peer: [u8; 32], outcome: &ChallengeOutcome) -> PeerReliability {
+        let mut entry = self.peers.entry(peer).or_default();
+        entry.record(outcome);
+        entry.clone()
+    }
+
+    /// Runs [`challenge`] against `peer` and records the result.
+    pub async fn challenge(
+        &self,
+        peer: [u8; 32],
+        client: &Client,
+        storage_challenge: &StorageChallenge,
+        deadline: Duration,
+    ) -> ChallengeOutcome {
+        let outcome = challenge(client, storage_challenge, deadline).await;
+        self.record(peer, &outcome);
+        outcome
+    }
+
+    pub fn get(&self, peer: &[u8; 32]) -> Option<PeerReliability> {
+        self.peers.get(peer).map(|r| r.clone())
+    }
+
+    /// All peers with at least one recorded challenge.
+    pub fn snapshot(&self) -> Vec<([u8; 32], PeerReliability)> {
+        self.peers
+            .iter()
+            .map(|r| (*r.key(), r.value().clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_challenges_stay_inside_the_blob() {
+        let hash = Hash::new(b"blob");
+        for _ in 0..100 {
+            let c = StorageChallenge::random(hash, 1_000_000, DEFAULT_CHALLENGE_LEN);
+            assert_eq!(c.len, DEFAULT_CHALLENGE_LEN);
+            assert!(c.offset + c.len <= 1_000_000);
+        }
+        let small = StorageChallenge::random(hash, 10, DEFAULT_CHALLENGE_LEN);
+        assert_eq!((small.offset, small.len), (0, 10));
+    }
+
+    #[test]
+    fn score_decays_towards_recent_outcomes() {
+        let tracker = ReliabilityTracker::new();
+        let peer = [7u8; 32];
+        let pass = ChallengeOutcome::Passed {
+            latency: Duration::from_millis(5),
+        };
+
+        assert_eq!(tracker.record(peer, &pass).score, 1.0);
+        let after_miss = tracker.record(peer, &ChallengeOutcome::TimedOut);
+        assert!((after_miss.score - 0.8).abs() < 1e-9);
+        let failed = ChallengeOutcome::Failed {
+            reason: "no outboard".into(),
+        };
+        let record = tracker.record(peer, &failed);
+        assert!(record.score < after_miss.score);
+        assert_eq!((record.passed, record.failed, record.timed_out), (1, 1, 1));
+        assert_eq!(record.last_latency, Some(Duration::from_millis(5)));
+        assert!(tracker.get(&[0u8; 32]).is_none());
+    }
+}
@@ -0,0 +1,387 @@
+//! Upload filters: policy hooks that can refuse a blob before it is stored.
+//!
+//! [`BlobsServer`](crate::BlobsServer) runs every filter registered with
+//! [`BlobsServer::with_upload_filter`](crate::BlobsServer::with_upload_filter)
+//! plus the peer's own [`PeerConfigBlobs::upload_filters`](crate::PeerConfigBlobs)
+//! against each incoming upload. A filter sees the claimed hash and size and
+//! the first [`UploadFilter::head_bytes`] bytes of the body; the first one to
+//! object rejects the upload and nothing reaches the store.
+//!
+//! Built in: [`MaxSize`], [`MediaTypeDenylist`] (magic-byte sniffing),
+//! [`CommandFilter`] (an external scanner fed the head on stdin) and
+//! [`IcapFilter`] (an ICAP `REQMOD` service such as c-icap/ClamAV).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use s5_core::Hash;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt};
+
+/// Head size used by the scanning filters when their config leaves it unset.
+pub const DEFAULT_HEAD_BYTES: usize = 64 * 1024;
+
+/// How long an external scanner may take before the upload is refused.
+pub const DEFAULT_SCAN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What a filter gets to look at.
+#[derive(Debug, Clone)]
+pub struct UploadCandidate {
+    /// Hash the uploader claims; verified only after the body is stored.
+    pub hash: Hash,
+    /// Size the uploader claims.
+    pub size: u64,
+    /// Stringified node id of the uploading peer.
+    pub peer: String,
+    /// The first `max(head_bytes)` bytes of the body over all filters (less
+    /// if the blob is shorter).
+    pub head: Bytes,
+}
+
+impl UploadCandidate {
+    /// The head as a readable stream.
+    pub fn head_reader(&self) -> impl AsyncRead + Unpin + '_ {
+        std::io::Cursor::new(&self.head[..])
+    }
+}
+
+/// An async upload policy check.
+#[async_trait::async_trait]
+pub trait UploadFilter: std::fmt::Debug + Send + Sync {
+    /// Leading body bytes this filter needs; 0 for metadata-only checks.
+    fn head_bytes(&self) -> usize {
+        0
+    }
+
+    /// `Err(reason)` refuses the upload; the reason is sent to the peer.
+    async fn check(&self, upload: &UploadCandidate) -> Result<(), String>;
+}
+
+/// Per-peer filter configuration (`[peer.<name>.blobs] upload_filters`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UploadFilterConfig {
+    /// Refuse blobs larger than `max_bytes`.
+    MaxSize { max_bytes: u64 },
+    /// Refuse blobs whose sniffed media type is listed (`type/*` wildcards
+    /// allowed).
+    DenyMediaTypes { types: Vec<String> },
+    /// Run `program` per upload; see [`CommandFilter`].
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        head_bytes: Option<usize>,
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+    /// Ask the ICAP service at `icap://<addr>/<service>`; see [`IcapFilter`].
+    Icap {
+        addr: String,
+        service: String,
+        #[serde(default)]
+        head_bytes: Option<usize>,
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+}
+
+impl UploadFilterConfig {
+    pub fn build(&self) -> Arc<dyn UploadFilter> {
+        let timeout = |secs: &Option<u64>| secs.map_or(DEFAULT_SCAN_TIMEOUT, Duration::from_secs);
+        match self {
+            Self::MaxSize { max_bytes } => Arc::new(MaxSize(*max_bytes)),
+            Self::DenyMediaTypes { types } => Arc::new(MediaTypeDenylist::new(types.clone())),
+            Self::Command {
+                program,
+                args,
+                head_bytes,
+                timeout_secs,
+            } => Arc::new(CommandFilter {
+                program: program.clone(),
+                args: args.clone(),
+                head_bytes: head_bytes.unwrap_or(DEFAULT_HEAD_BYTES),
+                timeout: timeout(timeout_secs),
+            }),
+            Self::Icap {
+                addr,
+                service,
+                head_bytes,
+                timeout_secs,
+            } => Arc::new(IcapFilter {
+                addr: addr.clone(),
+                service: service.clone(),
+                head_bytes: head_bytes.unwrap_or(DEFAULT_HEAD_BYTES),
+                timeout: timeout(timeout_secs),
+            }),
+        }
+    }
+}
+
+/// Refuses blobs larger than the given number of bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxSize(pub u64);
+
+#[async_trait::async_trait]
+impl UploadFilter for MaxSize {
+    async fn check(&self, upload: &UploadCandidate) -> Result<(), String> {
+        if upload.size > self.0 {
+            return Err(format!(
+                "blob of {} bytes exceeds the {} byte limit",
+                upload.size, self.0
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Refuses blobs whose [`sniff_media_type`] result is denied.
+#[derive(Debug, Clone)]
+pub struct MediaTypeDenylist {
+    denied: Vec<String>,
+}
+
+impl MediaTypeDenylist {
+    /// `denied` entries are exact media types or `type/*` wildcards.
+    pub fn new(denied: Vec<String>) -> Self {
+        Self { denied }
+    }
+
+    fn is_denied(&self, media_type: &str) -> bool {
+        self.denied.iter().any(|d| match d.strip_suffix("/*") {
+            Some(top) => media_type.split('/').next() == Some(top),
+            None => d == media_type,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl UploadFilter for MediaTypeDenylist {
+    fn head_bytes(&self) -> usize {
+        SNIFF_BYTES
+    }
+
+    async fn check(&self, upload: &UploadCandidate) -> Result<(), String> {
+        match sniff_media_type(&upload.head) {
+            Some(media_type) if self.is_denied(media_type) => {
+                Err(format!("media type {media_type} is not accepted"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Bytes [`sniff_media_type`] may look at.
+const SNIFF_BYTES: usize = 16;
+
+/// Best-effort media type from leading magic bytes; `None` when unknown.
+pub fn sniff_media_type(head: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x7fELF", "application/x-executable"),
+        (b"MZ", "application/vnd.microsoft.portable-executable"),
+        (b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+        (b"#!", "text/x-shellscript"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+        (b"Rar!\x1a\x07", "application/vnd.rar"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF8", "image/gif"),
+        (b"OggS", "audio/ogg"),
+        (b"ID3", "audio/mpeg"),
+        (b"fLaC", "audio/flac"),
+        (b"\x1a\x45\xdf\xa3", "video/webm"),
+    ];
+    if let Some((_, media_type)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return Some(media_type);
+    }
+    match (head.get(..4), head.get(8..12)) {
+        (Some(b"RIFF"), Some(b"WEBP")) => Some("image/webp"),
+        (Some(b"RIFF"), Some(b"WAVE")) => Some("audio/wav"),
+        (Some(b"RIFF"), Some(b"AVI ")) => Some("video/x-msvideo"),
+        _ if head.get(4..8) == Some(b"ftyp") => Some("video/mp4"),
+        _ => None,
+    }
+}
+
+/// Runs an external scanner per upload.
+///
+/// The program gets the head on stdin and `S5_UPLOAD_HASH`,
+/// `S5_UPLOAD_SIZE` and `S5_UPLOAD_PEER` in its environment. Exit status 0
+/// accepts; anything else — including failing to start or running past
+/// the timeout — refuses, with the program's stderr as the reason.
+#[derive(Debug, Clone)]
+pub struct CommandFilter {
+    pub program: String,
+    pub args: Vec<String>,
+    pub head_bytes: usize,
+    pub timeout: Duration,
+}
+
+#[async_trait::async_trait]
+impl UploadFilter for CommandFilter {
+    fn head_bytes(&self) -> usize {
+        self.head_bytes
+    }
+
+    async fn check(&self, upload: &UploadCandidate) -> Result<(), String> {
+        let run = async {
+            let mut child = tokio::process::Command::new(&self.program)
+                .args(&self.args)
+                .env("S5_UPLOAD_HASH", upload.hash.to_string())
+                .env("S5_UPLOAD_SIZE", upload.size.to_string())
+                .env("S5_UPLOAD_PEER", &upload.peer)
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                // A scanner that decides without reading all of stdin closes
+                // the pipe early; that is not an error.
+                let _ = stdin.write_all(&upload.head).await;
+            }
+            child.wait_with_output().await
+        };
+        let output = match tokio::time::timeout(self.timeout, run).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return Err(format!("scanner {} failed to run: {e}", self.program)),
+            Err(_) => return Err(format!("scanner {} timed out", self.program)),
+        };
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.trim();
+        Err(if reason.is_empty() {
+            format!("rejected by {} ({})", self.program, output.status)
+        } else {
+            reason.to_string()
+        })
+    }
+}
+
+/// Sends the head to an ICAP (RFC 3507) `REQMOD` service as the body of a
+/// `PUT /<hash>` request.
+///
+/// `204 No Content` accepts. A `200` means the service rewrote or blocked
+/// the request and refuses the upload, as does any other status, a
+/// connection failure or the timeout.
+#[derive(Debug, Clone)]
+pub struct IcapFilter {
+    /// `host:port` of the ICAP server (usually port 1344).
+    pub addr: String,
+    /// Service name, e.g. `avscan` or `srv_clamav`.
+    pub service: String,
+    pub head_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl IcapFilter {
+    fn request(&self, upload: &UploadCandidate) -> Vec<u8> {
+        let http = format!(
+            "PUT /{} HTTP/1.1\r\nHost: s5\r\nContent-Length: {}\r\n\r\n",
+            upload.hash,
+            upload.head.len()
+        );
+        let mut req = format!(
+            "REQMOD icap://{addr}/{service} ICAP/1.0\r\n\
+             Host: {addr}\r\n\
+             Allow: 204\r\n\
+             Encapsulated: req-hdr=0, req-body={body}\r\n\r\n{http}",
+            addr = self.addr,
+            service = self.service,
+            body = http.len(),
+        )
+        .into_bytes();
+        if !upload.head.is_empty() {
+            req.extend_from_slice(format!("{:x}\r\n", upload.head.len()).as_bytes());
+            req.extend_from_slice(&upload.head);
+            req.extend_from_slice(b"\r\n");
+        }
+        req.extend_from_slice(b"0\r\n\r\n");
+        req
+    }
+}
+
+#[async_trait::async_trait]
+impl UploadFilter for IcapFilter {
+    fn head_bytes(&self) -> usize {
+        self.head_bytes
+    }
+
+    async fn check(&self, upload: &UploadCandidate) -> Result<(), String> {
+        let exchange = async {
+            let mut conn = tokio::net::TcpStream::connect(&self.addr).await?;
+            conn.write_all(&self.request(upload)).await?;
+            let mut status = String::new();
+            tokio::io::BufReader::new(conn)
+                .read_line(&mut status)
+                .await?;
+            std::io::Result::Ok(status)
+        };
+        let status = match tokio::time::timeout(self.timeout, exchange).await {
+            Ok(Ok(status)) => status,
+            Ok(Err(e)) => return Err(format!("ICAP service {} unreachable: {e}", self.addr)),
+            Err(_) => return Err(format!("ICAP service {} timed out", self.addr)),
+        };
+        match status.split_whitespace().nth(1) {
+            Some("204") => Ok(()),
+            Some("200") => Err(format!("blocked by ICAP service {}", self.service)),
+            _ => Err(format!(
+                "ICAP service {} answered {:?}",
+                self.service,
+                status.trim()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(head: &'static [u8], size: u64) -> UploadCandidate {
+        UploadCandidate {
+            hash: Hash::new(head),
+            size,
+            peer: "peer".to_string(),
+            head: Bytes::from_static(head),
+        }
+    }
+
+    #[tokio::test]
+    async fn builtin_filters() {
+        let max = MaxSize(10);
+        assert!(max.check(&candidate(b"", 10)).await.is_ok());
+        assert!(max.check(&candidate(b"", 11)).await.is_err());
+
+        let deny =
+            MediaTypeDenylist::new(vec!["application/x-executable".into(), "image/*".into()]);
+        assert!(deny.check(&candidate(b"\x7fELF\x02\x01", 6)).await.is_err());
+        assert!(
+            deny.check(&candidate(b"\x89PNG\r\n\x1a\n", 8))
+                .await
+                .is_err()
+        );
+        assert!(deny.check(&candidate(b"%PDF-1.7", 8)).await.is_ok());
+        assert!(deny.check(&candidate(b"plain text", 10)).await.is_ok());
+
+        let command = |script: &str| CommandFilter {
+            program: "sh".into(),
+            args: vec!["-c".into(), script.into()],
+            head_bytes: DEFAULT_HEAD_BYTES,
+            timeout: DEFAULT_SCAN_TIMEOUT,
+        };
+        let eicar = candidate(b"EICAR-like payload", 18);
+        let grep = command("if grep -q EICAR; then echo infected >&2; exit 1; fi");
+        assert_eq!(grep.check(&eicar).await, Err("infected".to_string()));
+        assert!(grep.check(&candidate(b"clean", 5)).await.is_ok());
+        let env = command(r#"test "$S5_UPLOAD_SIZE" = 18 && test "$S5_UPLOAD_PEER" = peer"#);
+        assert!(env.check(&eicar).await.is_ok());
+    }
+}
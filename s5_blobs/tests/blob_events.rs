@@ -0,0 +1,89 @@
+//! `SubscribeBlobs`: uploads and local announcements reach subscribers
+//! that may read the store they landed in, and nobody else.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use iroh::{Endpoint, endpoint::presets};
+use s5_blobs::rpc::BlobEvent;
+use s5_blobs::{ALPN_ACL, BlobsServer, Client, PeerConfigBlobs, ServerMode};
+use s5_core::{BlobsWrite, Hash, blob::BlobStore};
+use s5_store_memory::MemoryStore;
+
+async fn next(events: &mut irpc::channel::mpsc::Receiver<BlobEvent>) -> Option<BlobEvent> {
+    let event = tokio::time::timeout(Duration::from_millis(500), events.recv()).await;
+    event.ok().map(|e| e.unwrap().expect("feed closed"))
+}
+
+#[tokio::test]
+async fn uploads_and_announcements_are_pushed_to_readers() {
+    let mut stores = HashMap::new();
+    stores.insert("mem".to_string(), BlobStore::new(MemoryStore::new()));
+    stores.insert("private".to_string(), BlobStore::new(MemoryStore::new()));
+
+    let mut peer_cfg = HashMap::new();
+    peer_cfg.insert(
+        "*".to_string(),
+        PeerConfigBlobs {
+            readable_stores: vec!["mem".to_string()],
+            store_uploads_in: Some("mem".to_string()),
+            ..Default::default()
+        },
+    );
+
+    let server_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let server = BlobsServer::new(stores, peer_cfg, None)
+        .with_mode(ServerMode::Public)
+        .with_local_iroh_pubkey(*server_endpoint.id().as_bytes());
+    let router = iroh::protocol::Router::builder(server_endpoint.clone())
+        .accept(ALPN_ACL, server.clone())
+        .spawn();
+
+    let subscriber_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let subscriber =
+        Client::connect_with_addr(subscriber_endpoint, server_endpoint.addr(), ALPN_ACL);
+    let mut events = subscriber.subscribe_blobs(Vec::new(), 8).await.unwrap();
+
+    let uploader_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let uploader = Client::connect_with_addr(uploader_endpoint, server_endpoint.addr(), ALPN_ACL);
+
+    // The uploader's first request may race the subscription opening on
+    // the server; retry until the feed is live.
+    let mut first = None;
+    for i in 0..20u8 {
+        let id = uploader
+            .blob_upload_bytes(Bytes::from(vec![i; 16]))
+            .await
+            .unwrap();
+        if let Some(event) = next(&mut events).await {
+            first = Some((id, event));
+            break;
+        }
+    }
+    let (id, event) = first.expect("subscriber never saw an upload");
+    assert_eq!(
+        event,
+        BlobEvent::Added {
+            store: "mem".to_string(),
+            hash: *id.hash.as_bytes(),
+            size: 16,
+        }
+    );
+
+    // Announcements for stores the peer can't read are filtered out, so
+    // the next event is the readable one.
+    server.announce_blob("private", Hash::new(b"secret"), 6);
+    let public = Hash::new(b"public");
+    server.announce_blob("mem", public, 6);
+    assert_eq!(
+        next(&mut events).await,
+        Some(BlobEvent::Added {
+            store: "mem".to_string(),
+            hash: *public.as_bytes(),
+            size: 6,
+        })
+    );
+
+    router.shutdown().await.unwrap();
+}
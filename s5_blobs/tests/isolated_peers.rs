@@ -0,0 +1,87 @@
+//! `isolate` on the `"*"` `peer_cfg` entry: peers sharing one physical
+//! store each land under their own `peers/<id>/` prefix and never see
+//! each other's blobs.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use iroh::{Endpoint, endpoint::presets};
+use s5_blobs::{ALPN_ACL, BlobsServer, Client, PeerConfigBlobs, ServerMode};
+use s5_core::{Hash, Store, blob::BlobStore, prefixed::peer_prefix};
+use s5_store_memory::MemoryStore;
+
+#[tokio::test]
+async fn isolated_peers_share_a_store_without_seeing_each_other() {
+    let raw: Arc<dyn Store> = Arc::new(MemoryStore::new());
+    let shared = BlobStore::from_arc(raw.clone());
+
+    let mut peer_cfg = HashMap::new();
+    peer_cfg.insert(
+        "*".to_string(),
+        PeerConfigBlobs {
+            readable_stores: vec!["shared".to_string()],
+            store_uploads_in: Some("shared".to_string()),
+            allow_list: true,
+            isolate: true,
+            ..Default::default()
+        },
+    );
+
+    let server_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let server = BlobsServer::new(
+        HashMap::from([("shared".to_string(), shared.clone())]),
+        peer_cfg,
+        None,
+    )
+    .with_mode(ServerMode::Public)
+    .with_local_iroh_pubkey(*server_endpoint.id().as_bytes());
+    let router = iroh::protocol::Router::builder(server_endpoint.clone())
+        .accept(ALPN_ACL, server)
+        .spawn();
+
+    let alice_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let bob_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let alice_id = alice_endpoint.id().to_string();
+    let alice = Client::connect_with_addr(alice_endpoint, server_endpoint.addr(), ALPN_ACL);
+    let bob = Client::connect_with_addr(bob_endpoint, server_endpoint.addr(), ALPN_ACL);
+
+    let (both, _) = alice
+        .upload_bytes(Bytes::from_static(b"both"))
+        .await
+        .unwrap();
+    let (only_alice, _) = alice
+        .upload_bytes(Bytes::from_static(b"alice"))
+        .await
+        .unwrap();
+    bob.upload_bytes(Bytes::from_static(b"both")).await.unwrap();
+
+    let listed = |entries: Vec<s5_blobs::rpc::ListedBlob>| -> BTreeSet<Hash> {
+        entries.iter().map(|e| e.hash.into()).collect()
+    };
+    assert_eq!(
+        listed(alice.list_blobs_page(None, 10).await.unwrap()),
+        BTreeSet::from([both, only_alice])
+    );
+    assert_eq!(
+        listed(bob.list_blobs_page(None, 10).await.unwrap()),
+        BTreeSet::from([both])
+    );
+    assert!(!bob.query(only_alice, BTreeSet::new()).await.unwrap().exists);
+
+    // Nothing reached the unscoped store; alice's copy sits under her prefix.
+    assert!(!shared.contains(both).await.unwrap());
+    let alice_view = shared.scoped(&peer_prefix(&alice_id));
+    assert!(alice_view.contains(only_alice).await.unwrap());
+    assert!(
+        raw.exists(&format!(
+            "{}{}",
+            peer_prefix(&alice_id),
+            shared.blob_path_for_hash(only_alice)
+        ))
+        .await
+        .unwrap()
+    );
+
+    router.shutdown().await.unwrap();
+}
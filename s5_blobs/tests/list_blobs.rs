@@ -0,0 +1,91 @@
+//! `ListBlobs` over the legacy `peer_cfg` path: only peers with
+//! `allow_list` may enumerate, pages resume from the cursor, and a
+//! replica can diff the listing against its own store.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use iroh::{Endpoint, endpoint::presets};
+use s5_blobs::{ALPN_ACL, BlobsServer, Client, PeerConfigBlobs, ServerMode};
+use s5_core::{BlobsList, Hash, blob::BlobStore};
+use s5_store_memory::MemoryStore;
+
+#[tokio::test]
+async fn listing_is_paged_and_gated_by_allow_list() {
+    let store = BlobStore::new(MemoryStore::new());
+    let mut sizes = HashMap::new();
+    for i in 0..5u8 {
+        let id = store
+            .import_bytes(Bytes::from(vec![i; 10 + i as usize]))
+            .await
+            .unwrap();
+        sizes.insert(id.hash, id.size);
+    }
+    let mut hashes: Vec<Hash> = sizes.keys().copied().collect();
+    hashes.sort();
+
+    let lister = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let other = Endpoint::builder(presets::N0).bind().await.unwrap();
+
+    let readable = PeerConfigBlobs {
+        readable_stores: vec!["mem".to_string()],
+        ..Default::default()
+    };
+    let mut peer_cfg = HashMap::new();
+    peer_cfg.insert(
+        lister.id().to_string(),
+        PeerConfigBlobs {
+            allow_list: true,
+            ..readable.clone()
+        },
+    );
+    peer_cfg.insert("*".to_string(), readable);
+
+    let server_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let server = BlobsServer::new(HashMap::from([("mem".to_string(), store)]), peer_cfg, None)
+        .with_mode(ServerMode::Public)
+        .with_local_iroh_pubkey(*server_endpoint.id().as_bytes());
+    let router = iroh::protocol::Router::builder(server_endpoint.clone())
+        .accept(ALPN_ACL, server)
+        .spawn();
+
+    let client = Client::connect_with_addr(lister, server_endpoint.addr(), ALPN_ACL);
+    let first = client.list_blobs_page(None, 2).await.unwrap();
+    let listed: Vec<Hash> = first.iter().map(|e| e.hash.into()).collect();
+    assert_eq!(listed, hashes[..2]);
+    assert!(first.iter().all(|e| sizes[&Hash::from(e.hash)] == e.size));
+    let rest = client.list_blobs_page(Some(listed[1]), 10).await.unwrap();
+    let rest: Vec<Hash> = rest.iter().map(|e| e.hash.into()).collect();
+    assert_eq!(rest, hashes[2..]);
+
+    // The streaming impl walks every page.
+    let mut all = Vec::new();
+    let mut stream = client.list_hashes().await.unwrap();
+    while let Some(hash) = futures::StreamExt::next(&mut stream).await {
+        all.push(hash.unwrap());
+    }
+    assert_eq!(all, hashes);
+
+    // A replica holding two of the blobs is missing exactly the rest.
+    let replica = BlobStore::new(MemoryStore::new());
+    for i in [1u8, 3] {
+        replica
+            .import_bytes(Bytes::from(vec![i; 10 + i as usize]))
+            .await
+            .unwrap();
+    }
+    let missing = client.missing_blobs(&replica).await.unwrap();
+    assert_eq!(missing.len(), 3);
+    for entry in &missing {
+        let hash = Hash::from(entry.hash);
+        assert!(!replica.contains(hash).await.unwrap());
+        assert!(hashes.contains(&hash));
+    }
+
+    // Peers without `allow_list` are refused, not handed an empty page.
+    let denied = Client::connect_with_addr(other, server_endpoint.addr(), ALPN_ACL);
+    let err = denied.list_blobs_page(None, 10).await.unwrap_err();
+    assert!(err.to_string().contains("not allowed"), "{err}");
+
+    router.shutdown().await.unwrap();
+}
@@ -0,0 +1,69 @@
+//! Upload filters on the legacy `peer_cfg` path: server-wide and per-peer
+//! filters refuse uploads before anything is stored, and accepted uploads
+//! arrive intact after the head was read ahead for scanning.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use iroh::{Endpoint, endpoint::presets};
+use s5_blobs::upload_filter::MaxSize;
+use s5_blobs::{ALPN_ACL, BlobsServer, Client, PeerConfigBlobs, ServerMode, UploadFilterConfig};
+use s5_core::{Hash, blob::BlobStore};
+use s5_store_memory::MemoryStore;
+
+#[tokio::test]
+async fn filters_refuse_uploads_before_storing() {
+    let store = BlobStore::new(MemoryStore::new());
+    let mut peer_cfg = HashMap::new();
+    peer_cfg.insert(
+        "*".to_string(),
+        PeerConfigBlobs {
+            store_uploads_in: Some("mem".to_string()),
+            upload_filters: vec![UploadFilterConfig::DenyMediaTypes {
+                types: vec!["application/x-executable".to_string()],
+            }],
+            ..Default::default()
+        },
+    );
+
+    let server_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let server = BlobsServer::new(
+        HashMap::from([("mem".to_string(), store.clone())]),
+        peer_cfg,
+        None,
+    )
+    .with_upload_filter(Arc::new(MaxSize(1 << 20)))
+    .with_mode(ServerMode::Public)
+    .with_local_iroh_pubkey(*server_endpoint.id().as_bytes());
+    let router = iroh::protocol::Router::builder(server_endpoint.clone())
+        .accept(ALPN_ACL, server)
+        .spawn();
+
+    let client_endpoint = Endpoint::builder(presets::N0).bind().await.unwrap();
+    let client = Client::connect_with_addr(client_endpoint, server_endpoint.addr(), ALPN_ACL);
+
+    let elf = Bytes::from_static(b"\x7fELF\x02\x01\x01 not really a binary");
+    let err = client.upload_bytes(elf.clone()).await.unwrap_err();
+    assert!(err.contains("application/x-executable"), "{err}");
+    assert!(!store.contains(Hash::new(&elf)).await.unwrap());
+
+    let big = Bytes::from(vec![0u8; (1 << 20) + 1]);
+    let err = client.upload_bytes(big.clone()).await.unwrap_err();
+    assert!(err.contains("byte limit"), "{err}");
+    assert!(!store.contains(Hash::new(&big)).await.unwrap());
+
+    // Sent in many chunks: the one read ahead for the filters must be
+    // replayed in front of the rest.
+    let ok = Bytes::from((0..300_000u32).map(|i| i as u8).collect::<Vec<_>>());
+    let hash = Hash::new(&ok);
+    let (tx, rx) = client.upload_begin(hash, ok.len() as u64, 8).await.unwrap();
+    for chunk in ok.chunks(4096) {
+        tx.send(Bytes::copy_from_slice(chunk)).await.unwrap();
+    }
+    drop(tx);
+    rx.await.unwrap().unwrap();
+    assert_eq!(store.read_as_bytes(hash, 0, None).await.unwrap(), ok);
+
+    router.shutdown().await.unwrap();
+}
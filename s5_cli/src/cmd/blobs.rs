@@ -1,6 +1,9 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use anyhow::Result;
+use futures::StreamExt;
 use s5_core::RegistryPinner;
 use s5_node::config::S5NodeConfig;
 use s5_registry_redb::RedbRegistry;
@@ -40,6 +43,7 @@ pub async fn run_blobs(
                     report.candidates.len(),
                     store
                 );
+                println!("{} bytes would be reclaimed", report.bytes_candidate);
                 for h in &report.candidates {
                     println!("{}", h);
                 }
@@ -48,8 +52,8 @@ pub async fn run_blobs(
                     eprintln!("failed to delete {}: {err}", h);
                 }
                 println!(
-                    "deleted {} blobs from local store '{}'",
-                    report.deleted, store
+                    "deleted {} blobs ({} bytes) from local store '{}'",
+                    report.deleted, report.bytes_reclaimed, store
                 );
             }
         }
@@ -63,9 +67,21 @@ pub async fn run_blobs(
             let root_key = None;
             let reachable = s5_fs::gc::collect_fs_reachable_hashes(fs_root, root_key).await?;
 
+            // One listing instead of an existence check per referenced
+            // blob. Empty objects are left-overs of interrupted writes and
+            // count as missing.
+            let mut present = HashSet::new();
+            let mut blobs = blob_store.list_with_meta().await?;
+            while let Some(item) = blobs.next().await {
+                let (h, meta) = item?;
+                if meta.size > 0 {
+                    present.insert(h);
+                }
+            }
+
             let mut missing = Vec::new();
             for h in &reachable {
-                if !blob_store.contains(*h).await? {
+                if !present.contains(h) {
                     missing.push(*h);
                 }
             }
@@ -87,6 +103,28 @@ pub async fn run_blobs(
                 }
             }
         }
+        BlobsCmd::StatsLocal { store } => {
+            let blob_store = open_store(config, &store).await?;
+
+            let (mut count, mut bytes) = (0u64, 0u64);
+            let (mut oldest, mut newest) = (None::<SystemTime>, None::<SystemTime>);
+            let mut blobs = blob_store.list_with_meta().await?;
+            while let Some(item) = blobs.next().await {
+                let (_, meta) = item?;
+                count += 1;
+                bytes += meta.size;
+                if let Some(t) = meta.modified {
+                    oldest = Some(oldest.map_or(t, |o| o.min(t)));
+                    newest = Some(newest.map_or(t, |n| n.max(t)));
+                }
+            }
+
+            println!("store '{}': {} blobs, {} bytes", store, count, bytes);
+            if let (Some(oldest), Some(newest)) = (oldest, newest) {
+                println!("oldest: {}", humantime::format_rfc3339_seconds(oldest));
+                println!("newest: {}", humantime::format_rfc3339_seconds(newest));
+            }
+        }
     }
 
     Ok(())
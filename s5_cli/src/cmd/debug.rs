@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use bytes::Bytes;
+use minicbor::Decoder;
+use s5_core::blob::location::BlobLocation;
+use s5_core::cbor::value::Value;
+use s5_core::{BlobId, StreamMessage};
+use s5_fs::dir::DirV1;
+
+use crate::DebugCmd;
+
+/// Artifact types `s5 debug decode` knows, in detection order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ArtifactKind {
+    BlobId,
+    StreamMessage,
+    Dir,
+    BlobLocation,
+    /// Generic CBOR token dump.
+    Cbor,
+}
+
+pub async fn run_debug(cmd: DebugCmd) -> Result<()> {
+    match cmd {
+        DebugCmd::Decode { input, kind } => {
+            let bytes = read_input(&input)?;
+            println!("{}", decode(&bytes, kind)?);
+        }
+    }
+    Ok(())
+}
+
+/// The raw bytes `input` stands for: the contents of the file at that
+/// path, else hex (whitespace and a `0x` prefix are ignored), else a
+/// multibase BlobId string.
+fn read_input(input: &str) -> Result<Vec<u8>> {
+    let path = Path::new(input);
+    if path.is_file() {
+        let bytes = std::fs::read(path).with_context(|| format!("reading {input}"))?;
+        // A file holding a BlobId string or hex dump is decoded like the
+        // same text on the command line.
+        return match std::str::from_utf8(&bytes) {
+            Ok(text) if !text.trim().is_empty() => Ok(parse_text(text.trim()).unwrap_or(bytes)),
+            _ => Ok(bytes),
+        };
+    }
+    parse_text(input).ok_or_else(|| anyhow!("{input:?} is neither a file, hex nor a BlobId"))
+}
+
+fn parse_text(text: &str) -> Option<Vec<u8>> {
+    let compact: String = text.split_whitespace().collect();
+    let hex = compact.strip_prefix("0x").unwrap_or(&compact);
+    if let Ok(bytes) = hex::decode(hex) {
+        return Some(bytes);
+    }
+    BlobId::parse(text).ok().map(|id| id.to_bytes())
+}
+
+/// Decodes `bytes` as `kind`, or as the first artifact type that parses
+/// cleanly when `kind` is `None`.
+fn decode(bytes: &[u8], kind: Option<ArtifactKind>) -> Result<String> {
+    if let Some(kind) = kind {
+        return decode_as(bytes, kind);
+    }
+    for kind in [
+        ArtifactKind::BlobId,
+        ArtifactKind::StreamMessage,
+        ArtifactKind::Dir,
+        ArtifactKind::BlobLocation,
+    ] {
+        if let Ok(out) = decode_as(bytes, kind) {
+            return Ok(out);
+        }
+    }
+    decode_as(bytes, ArtifactKind::Cbor)
+        .map_err(|e| anyhow!("not a known S5 artifact and not valid CBOR: {e}"))
+}
+
+fn decode_as(bytes: &[u8], kind: ArtifactKind) -> Result<String> {
+    Ok(match kind {
+        ArtifactKind::BlobId => {
+            let id = BlobId::from_bytes(bytes)?;
+            if id.to_bytes() != bytes {
+                return Err(anyhow!("trailing bytes after BlobId"));
+            }
+            format!(
+                "BlobId\n  hash: {}\n  size: {}\n  id:   {id}",
+                id.hash, id.size
+            )
+        }
+        ArtifactKind::StreamMessage => {
+            let message = StreamMessage::deserialize(Bytes::copy_from_slice(bytes))?;
+            if message.serialize() != bytes {
+                return Err(anyhow!("StreamMessage does not round-trip"));
+            }
+            format!("StreamMessage {message:#?}")
+        }
+        ArtifactKind::Dir => {
+            ensure_single_item(bytes)?;
+            format!("DirV1 {:#?}", DirV1::from_bytes(bytes)?)
+        }
+        ArtifactKind::BlobLocation => {
+            ensure_single_item(bytes)?;
+            // May embed encryption keys; this is a local debugging aid.
+            format!("BlobLocation {:#?}", BlobLocation::deserialize(bytes)?)
+        }
+        ArtifactKind::Cbor => format!("CBOR\n{}", cbor_tokens(bytes)?),
+    })
+}
+
+/// Typed CBOR decoders accept trailing garbage; detection must not.
+fn ensure_single_item(bytes: &[u8]) -> Result<()> {
+    let mut decoder = Decoder::new(bytes);
+    decoder.skip()?;
+    if decoder.position() != bytes.len() {
+        return Err(anyhow!(
+            "{} trailing bytes after CBOR item",
+            bytes.len() - decoder.position()
+        ));
+    }
+    Ok(())
+}
+
+/// One token per line, indented by nesting depth (see the `Display` impl
+/// of [`Value`] for the notation).
+fn cbor_tokens(bytes: &[u8]) -> Result<String> {
+    let mut decoder = Decoder::new(bytes);
+    let mut out = String::new();
+    // Items still expected by each open container; `None` = indefinite.
+    let mut open: Vec<Option<u64>> = Vec::new();
+    while decoder.position() < bytes.len() {
+        let value: Value = decoder.decode()?;
+        if matches!(value, Value::Break) {
+            // Closes an indefinite container, which already took its
+            // slot in the parent when it opened.
+            open.pop();
+        }
+        out.push_str(&"  ".repeat(open.len()));
+        out.push_str(&value.to_string());
+        out.push('\n');
+
+        if !matches!(value, Value::Break) {
+            // This token fills one slot of its container...
+            if let Some(Some(remaining)) = open.last_mut() {
+                *remaining -= 1;
+            }
+            // ...and may open one of its own.
+            match value {
+                Value::Array(n) => open.push(Some(n)),
+                Value::Map(n) => open.push(Some(n * 2)),
+                Value::Tag(_) => open.push(Some(1)),
+                Value::BeginArray | Value::BeginMap | Value::BeginBytes | Value::BeginString => {
+                    open.push(None)
+                }
+                _ => {}
+            }
+        }
+        while let Some(Some(0)) = open.last() {
+            open.pop();
+        }
+    }
+    Ok(out.trim_end().to_string())
+}
@@ -13,6 +13,7 @@ use crate::ImportCmd;
 pub async fn run_import(
     cmd: ImportCmd,
     target_store_name: String,
+    inline_threshold: u64,
     config: &S5NodeConfig,
     fs: &FS5,
     fs_handle: &FS5,
@@ -38,13 +39,14 @@ pub async fn run_import(
             // are `<prefix>/<relative>`.
             let use_base_relative_keys = prefix.is_some();
 
-            let http_importer = HttpImporter::create(
+            let mut http_importer = HttpImporter::create(
                 scoped_fs,
                 target_store,
                 concurrency,
                 url_parsed.clone(),
                 use_base_relative_keys,
             )?;
+            http_importer.set_inline_threshold(inline_threshold);
             http_importer.import_url(url_parsed).await?;
         }
 
@@ -117,6 +119,7 @@ pub async fn run_import(
             if always_import || std::env::var("IMPORT_ALWAYS").as_deref() == Ok("1") {
                 importer.set_always_import(true);
             }
+            importer.set_inline_threshold(inline_threshold);
 
             importer.import_path(path).await?;
         }
@@ -6,6 +6,7 @@ use s5_fs::{DirContext, FS5};
 use s5_node::config::S5NodeConfig;
 
 mod blobs;
+mod debug;
 mod import;
 mod mount;
 mod snapshots;
@@ -13,6 +14,7 @@ mod tree;
 mod util;
 
 pub use blobs::run_blobs;
+pub use debug::{ArtifactKind, run_debug};
 pub use import::run_import;
 pub use mount::run_mount;
 pub use snapshots::run_snapshots;
@@ -30,6 +32,7 @@ pub async fn run_command(
             cmd.run(node_config_file, local_data_dir)?;
             Ok(())
         }
+        crate::Commands::Debug { cmd } => run_debug(cmd).await,
         crate::Commands::Start => {
             let toml_content = std::fs::read_to_string(&node_config_file)?;
             let config: S5NodeConfig = toml::from_str(&toml_content)?;
@@ -50,8 +53,21 @@ pub async fn run_command(
             let fs_handle = fs.clone();
 
             match cmd {
-                crate::Commands::Import { cmd, target_store } => {
-                    run_import(cmd, target_store, &config, &fs, &fs_handle, &fs_root).await
+                crate::Commands::Import {
+                    cmd,
+                    target_store,
+                    inline_threshold,
+                } => {
+                    run_import(
+                        cmd,
+                        target_store,
+                        inline_threshold,
+                        &config,
+                        &fs,
+                        &fs_handle,
+                        &fs_root,
+                    )
+                    .await
                 }
                 crate::Commands::Blobs { cmd } => {
                     run_blobs(cmd, &config, &node_config_file, &fs_root).await
@@ -84,7 +100,9 @@ pub async fn run_command(
                     .await
                 }
                 crate::Commands::Tree { path } => run_tree(fs, fs_handle, path).await,
-                crate::Commands::Config { .. } | crate::Commands::Start => unreachable!(),
+                crate::Commands::Config { .. }
+                | crate::Commands::Start
+                | crate::Commands::Debug { .. } => unreachable!(),
             }
         }
     }
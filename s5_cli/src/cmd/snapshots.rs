@@ -1,14 +1,19 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use s5_core::Hash;
 use s5_fs::FS5;
+use s5_fs::restore::{RestoreOptions, RestoreProgress};
+use s5_fs::snapshots::SnapshotIndex;
 use s5_node::config::S5NodeConfig;
+use s5_store_local::{LocalStore, LocalStoreConfig};
 
+use super::util::open_store;
 use crate::SnapshotsCmd;
 
 pub async fn run_snapshots(
     cmd: SnapshotsCmd,
-    _config: &S5NodeConfig,
+    config: &S5NodeConfig,
     _node_config_file: &std::path::Path,
     fs: &FS5,
     fs_handle: &FS5,
@@ -38,7 +43,161 @@ pub async fn run_snapshots(
             fs_handle.save().await?;
             fs_handle.shutdown().await?;
         }
+        SnapshotsCmd::Restore {
+            hash,
+            from,
+            store,
+            metadata_only,
+            concurrency,
+            source_key,
+            reencrypt,
+            dest_key,
+        } => {
+            let root = parse_hash(&hash)?;
+            let source_key = source_key.as_deref().map(parse_key).transpose()?;
+            let dest_key = dest_key.as_deref().map(parse_key).transpose()?;
+            let source = open_store(config, &from).await?;
+            let content = open_store(config, &store).await?;
+            let meta = LocalStore::create(LocalStoreConfig {
+                base_path: fs_root.to_string_lossy().into(),
+                ..Default::default()
+            })
+            .to_blob_store();
+
+            let options = RestoreOptions {
+                root_key: source_key.as_ref(),
+                metadata_only,
+                concurrency,
+                reencrypt,
+                dest_root_key: dest_key.as_ref(),
+            };
+            let report = s5_fs::restore::restore_tree(
+                &source,
+                &meta,
+                &content,
+                root,
+                options,
+                &|p: &RestoreProgress| {
+                    eprint!(
+                        "\r{} dirs, {}/{} blobs, {} bytes fetched",
+                        p.dirs, p.blobs_done, p.blobs_total, p.bytes
+                    );
+                },
+            )
+            .await?;
+            eprintln!();
+
+            if !report.is_complete() {
+                for u in &report.unreachable {
+                    let kind = if u.is_dir { "dir" } else { "blob" };
+                    eprintln!("unreachable {kind}\t{}\t{}", u.hash, u.error);
+                }
+                fs_handle.shutdown().await?;
+                bail!(
+                    "{} blobs of snapshot {root} are unreachable from store '{from}'; \
+                     not recording the snapshot",
+                    report.unreachable.len()
+                );
+            }
+
+            let mut index = SnapshotIndex::open(fs_root)?;
+            let (name, _) = index.insert_snapshot(report.root);
+            index.persist()?;
+            println!(
+                "restored snapshot\t{}\t{}\t{} fetched, {} already present{}{}",
+                name,
+                report.root,
+                report.fetched,
+                report.present,
+                if metadata_only { ", metadata only" } else { "" },
+                if reencrypt {
+                    format!(", re-encrypted from {root}")
+                } else {
+                    String::new()
+                }
+            );
+            fs_handle.shutdown().await?;
+        }
+        SnapshotsCmd::Bundle { name, out, store } => {
+            let content = open_store(config, &store).await?;
+            let root_key = None;
+            let summary =
+                s5_fs::bundle::write_bundle(fs_root, &name, root_key, &content, &out).await?;
+            println!(
+                "bundled snapshot\t{}\t{}\t{} dirs, {} blobs, {} bytes\t{}",
+                summary.name,
+                summary.root,
+                summary.meta_blobs,
+                summary.content_blobs,
+                summary.bytes,
+                out.display()
+            );
+            fs_handle.shutdown().await?;
+        }
+        SnapshotsCmd::BundleDiff {
+            from,
+            to,
+            out,
+            store,
+        } => {
+            let content = open_store(config, &store).await?;
+            let root_key = None;
+            let summary =
+                s5_fs::bundle::write_diff_bundle(fs_root, &from, &to, root_key, &content, &out)
+                    .await?;
+            println!(
+                "bundled snapshot\t{}\t{}\tover {}\t{} dirs, {} blobs, {} bytes\t{}",
+                summary.name,
+                summary.root,
+                from,
+                summary.meta_blobs,
+                summary.content_blobs,
+                summary.bytes,
+                out.display()
+            );
+            fs_handle.shutdown().await?;
+        }
+        SnapshotsCmd::RestoreBundle { path, store } => {
+            let content = open_store(config, &store).await?;
+            let root_key = None;
+            let summary = s5_fs::bundle::restore_bundle(&path, fs_root, root_key, &content).await?;
+            match summary.base {
+                Some(base) => println!(
+                    "applied snapshot\t{}\t{}\tover {}\t{} dirs, {} blobs, {} bytes",
+                    summary.name,
+                    summary.root,
+                    base,
+                    summary.meta_blobs,
+                    summary.content_blobs,
+                    summary.bytes
+                ),
+                None => println!(
+                    "restored snapshot\t{}\t{}\t{} dirs, {} blobs, {} bytes",
+                    summary.name,
+                    summary.root,
+                    summary.meta_blobs,
+                    summary.content_blobs,
+                    summary.bytes
+                ),
+            }
+            fs_handle.shutdown().await?;
+        }
     }
 
     Ok(())
 }
+
+fn parse_hash(hex_hash: &str) -> Result<Hash> {
+    let bytes: [u8; 32] = hex::decode(hex_hash)
+        .context("snapshot hash is not hex")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("snapshot hash must be 32 bytes"))?;
+    Ok(Hash::from_bytes(bytes))
+}
+
+fn parse_key(hex_key: &str) -> Result<[u8; 32]> {
+    hex::decode(hex_key)
+        .context("key is not hex")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("key must be 32 bytes"))
+}
@@ -33,6 +33,10 @@ enum Commands {
     Import {
         #[arg(short, long, value_name = "STORE_NAME", default_value = "default")]
         target_store: String,
+        /// Files of at most this many bytes are kept inline in the
+        /// directory metadata instead of the blob store (0 disables).
+        #[arg(long, value_name = "BYTES", default_value_t = s5_core::blob::import::DEFAULT_INLINE_THRESHOLD)]
+        inline_threshold: u64,
         #[command(subcommand)]
         cmd: ImportCmd,
     },
@@ -74,6 +78,11 @@ enum Commands {
     },
     /// Start the S5 Node and serve all hashes from the default blob store
     Start,
+    /// Inspect raw protocol artifacts (no node config needed)
+    Debug {
+        #[command(subcommand)]
+        cmd: DebugCmd,
+    },
 }
 
 #[derive(Subcommand)]
@@ -149,6 +158,13 @@ enum BlobsCmd {
         #[arg(long, value_name = "STORE_NAME", default_value = "default")]
         store: String,
     },
+    /// Print the number of blobs in a local store, the bytes they take
+    /// up, and the oldest and newest modification times. Read-only.
+    StatsLocal {
+        /// Name of the local store in the node config (e.g. "default")
+        #[arg(long, value_name = "STORE_NAME", default_value = "default")]
+        store: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -164,6 +180,88 @@ enum SnapshotsCmd {
         #[arg(long, value_name = "NAME")]
         name: String,
     },
+    /// Copy a snapshot's directory tree and content from a store into the
+    /// local FS5 root, verifying every blob, and list it as a snapshot
+    Restore {
+        /// Root hash of the snapshot (hex)
+        #[arg(long, value_name = "HASH")]
+        hash: String,
+        /// Store to fetch the snapshot's blobs from
+        #[arg(long, value_name = "STORE_NAME")]
+        from: String,
+        /// Store to import content blobs into
+        #[arg(long, value_name = "STORE_NAME", default_value = "default")]
+        store: String,
+        /// Only restore and verify directory metadata, not file contents
+        #[arg(long, action = ArgAction::SetTrue)]
+        metadata_only: bool,
+        /// max number of concurrent blob fetches
+        #[arg(short, long, value_name = "COUNT", default_value_t = s5_fs::restore::DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+        /// Key the snapshot root is encrypted with (64 hex chars)
+        #[arg(long, value_name = "HEX")]
+        source_key: Option<String>,
+        /// Rewrite the directory tree under fresh keys instead of copying
+        /// it, so the source key can no longer open the restored root
+        #[arg(long, action = ArgAction::SetTrue)]
+        reencrypt: bool,
+        /// With --reencrypt: key to encrypt the new root with (64 hex
+        /// chars); the root is stored in plaintext if omitted
+        #[arg(long, value_name = "HEX", requires = "reencrypt")]
+        dest_key: Option<String>,
+    },
+    /// Write a snapshot, with all its metadata and content blobs, to one
+    /// self-contained file for offline transfer
+    Bundle {
+        /// Snapshot name as listed by `s5 snapshots list-fs`
+        #[arg(long, value_name = "NAME")]
+        name: String,
+        /// Bundle file to write
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+        /// Store holding the snapshot's content blobs
+        #[arg(long, value_name = "STORE_NAME", default_value = "default")]
+        store: String,
+    },
+    /// Write only what snapshot `to` adds over snapshot `from`, for
+    /// targets that already hold `from`
+    BundleDiff {
+        /// Base snapshot the target already has
+        #[arg(long, value_name = "NAME")]
+        from: String,
+        /// Snapshot to package
+        #[arg(long, value_name = "NAME")]
+        to: String,
+        /// Bundle file to write
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+        /// Store holding the snapshots' content blobs
+        #[arg(long, value_name = "STORE_NAME", default_value = "default")]
+        store: String,
+    },
+    /// Import a bundle written by `s5 snapshots bundle` or `bundle-diff`
+    /// into the local FS5 root, verifying every blob, and list it as a
+    /// snapshot
+    RestoreBundle {
+        /// Bundle file to read
+        path: PathBuf,
+        /// Store to import the content blobs into
+        #[arg(long, value_name = "STORE_NAME", default_value = "default")]
+        store: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DebugCmd {
+    /// Detect and pretty-print a StreamMessage, DirV1 snapshot,
+    /// BlobLocation or BlobId, falling back to a CBOR token dump
+    Decode {
+        /// File to read, or the artifact as hex or a BlobId string
+        input: String,
+        /// Decode as this type instead of auto-detecting
+        #[arg(long = "as", value_enum, value_name = "KIND")]
+        kind: Option<cmd::ArtifactKind>,
+    },
 }
 
 #[tokio::main]
@@ -86,6 +86,7 @@ path = "{registry}"
     // Open the store directly to manipulate it
     let local_store = LocalStore::create(LocalStoreConfig {
         base_path: store_path.to_string_lossy().into(),
+        ..Default::default()
     });
     let blob_store = BlobStore::new(local_store);
 
@@ -4,5 +4,7 @@
 //! which allows for verified streaming of content.
 //!
 //! The `outboard` module contains the logic for computing the outboard data
-//! for a given file or byte stream.
+//! for a given file or byte stream; `range` proves and verifies block-aligned
+//! byte ranges against it.
 pub mod outboard;
+pub mod range;
@@ -0,0 +1,207 @@
+//! Verified byte ranges over a pre-order outboard.
+//!
+//! A range is proven by the outboard's parent hash pairs on the path from
+//! the root to each [`S5_BLOCK_SIZE`] block the range touches, plus the raw
+//! bytes of those blocks. Ranges are widened to whole blocks
+//! ([`aligned_range`]) so every leaf is hashed in full and the proof is just
+//! parent pairs — exactly what [`range_outboard`] extracts and
+//! [`verify_range`] checks.
+
+use std::io;
+use std::ops::Range;
+
+use bao_tree::io::outboard::PreOrderOutboard;
+use bao_tree::io::sync::{DecodeResponseIter, Outboard};
+use bao_tree::iter::BaoChunk;
+use bao_tree::{BaoTree, ChunkNum, ChunkRanges};
+
+use super::outboard::S5_BLOCK_SIZE;
+use crate::Hash;
+
+const BLOCK_BYTES: u64 = S5_BLOCK_SIZE.bytes() as u64;
+
+/// Widen `offset..offset + len` (clamped to `size`) to block boundaries.
+/// Empty when `offset >= size`.
+pub fn aligned_range(size: u64, offset: u64, len: Option<u64>) -> Range<u64> {
+    if offset >= size {
+        return size..size;
+    }
+    let end = len.map_or(size, |len| offset.saturating_add(len).min(size));
+    let start = offset - offset % BLOCK_BYTES;
+    let end = end
+        .div_ceil(BLOCK_BYTES)
+        .saturating_mul(BLOCK_BYTES)
+        .min(size);
+    start..end
+}
+
+fn chunk_ranges(range: &Range<u64>) -> ChunkRanges {
+    ChunkRanges::from(ChunkNum::full_chunks(range.start)..ChunkNum::chunks(range.end))
+}
+
+fn check_aligned(size: u64, range: &Range<u64>) -> io::Result<()> {
+    if range.start == range.end && range.end <= size {
+        return Ok(());
+    }
+    let end_ok = range.end == size || range.end.is_multiple_of(BLOCK_BYTES);
+    if !range.start.is_multiple_of(BLOCK_BYTES)
+        || !end_ok
+        || range.end > size
+        || range.start > range.end
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("range {range:?} is not block-aligned for a {size}-byte blob"),
+        ));
+    }
+    Ok(())
+}
+
+/// Parent hash pairs (64 bytes each, pre-order) proving the block-aligned
+/// `range` of a blob, taken from its full pre-order `outboard`. Empty for
+/// single-block blobs, whose only node is the root itself.
+pub fn range_outboard(
+    hash: Hash,
+    size: u64,
+    outboard: &[u8],
+    range: Range<u64>,
+) -> io::Result<Vec<u8>> {
+    check_aligned(size, &range)?;
+    if range.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tree = BaoTree::new(size, S5_BLOCK_SIZE);
+    let ob = PreOrderOutboard {
+        root: hash.into(),
+        tree,
+        data: outboard,
+    };
+    let mut proof = Vec::new();
+    for item in tree.ranges_pre_order_chunks_iter_ref(&chunk_ranges(&range), 0) {
+        if let BaoChunk::Parent { node, .. } = item {
+            let (left, right) = ob.load(node)?.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "outboard too short")
+            })?;
+            proof.extend_from_slice(left.as_bytes());
+            proof.extend_from_slice(right.as_bytes());
+        }
+    }
+    Ok(proof)
+}
+
+/// Check that `data` is the block-aligned `range` of the blob `hash`,
+/// using the parent pairs from [`range_outboard`]. Fails with
+/// `InvalidData` on any mismatch or leftover bytes.
+pub fn verify_range(
+    hash: Hash,
+    size: u64,
+    range: Range<u64>,
+    proof: &[u8],
+    data: &[u8],
+) -> io::Result<()> {
+    check_aligned(size, &range)?;
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    if data.len() as u64 != range.end - range.start {
+        return Err(invalid("range data has the wrong length"));
+    }
+    if size == 0 {
+        return if hash == Hash::EMPTY {
+            Ok(())
+        } else {
+            Err(invalid("empty range of a non-empty blob"))
+        };
+    }
+    if range.is_empty() {
+        return Ok(());
+    }
+
+    // Re-interleave proof and data into the bao response encoding, then let
+    // bao-tree walk it against the root hash.
+    let tree = BaoTree::new(size, S5_BLOCK_SIZE);
+    let ranges = chunk_ranges(&range);
+    let mut encoded = Vec::with_capacity(proof.len() + data.len());
+    let mut parents = proof.chunks_exact(64);
+    for item in tree.ranges_pre_order_chunks_iter_ref(&ranges, 0) {
+        match item {
+            BaoChunk::Parent { .. } => {
+                let pair = parents.next().ok_or_else(|| invalid("proof too short"))?;
+                encoded.extend_from_slice(pair);
+            }
+            BaoChunk::Leaf {
+                start_chunk, size, ..
+            } => {
+                let start = (start_chunk.to_bytes() - range.start) as usize;
+                encoded.extend_from_slice(&data[start..start + size]);
+            }
+        }
+    }
+    if parents.next().is_some() || !parents.remainder().is_empty() {
+        return Err(invalid("proof has trailing bytes"));
+    }
+
+    for item in DecodeResponseIter::new(hash.into(), tree, io::Cursor::new(encoded), &ranges) {
+        item.map_err(|e| invalid(&format!("range verification failed: {e}")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bao::outboard::compute_outboard;
+
+    fn blob(size: usize) -> (Vec<u8>, Hash, Vec<u8>) {
+        let data: Vec<u8> = (0..size).map(|i| (i * 7 % 253) as u8).collect();
+        let (hash, ob) = compute_outboard(&data[..], size as u64, |_| Ok(())).unwrap();
+        (data, hash, ob.unwrap_or_default())
+    }
+
+    #[test]
+    fn aligned_range_widens_to_blocks() {
+        let b = BLOCK_BYTES;
+        assert_eq!(aligned_range(10 * b, b + 5, Some(10)), b..2 * b);
+        assert_eq!(
+            aligned_range(10 * b + 3, 9 * b + 1, None),
+            9 * b..10 * b + 3
+        );
+        assert_eq!(aligned_range(100, 0, Some(1)), 0..100);
+        assert!(aligned_range(100, 100, None).is_empty());
+    }
+
+    #[test]
+    fn range_proofs_verify() {
+        let b = BLOCK_BYTES as usize;
+        for size in [0, 1000, b, 5 * b + 123, 16 * b] {
+            let (data, hash, ob) = blob(size);
+            for (offset, len) in [(0, None), (0, Some(1)), (b as u64 + 1, Some(3 * b as u64))] {
+                let range = aligned_range(size as u64, offset, len);
+                let proof = range_outboard(hash, size as u64, &ob, range.clone()).unwrap();
+                let slice = &data[range.start as usize..range.end as usize];
+                verify_range(hash, size as u64, range.clone(), &proof, slice)
+                    .unwrap_or_else(|e| panic!("size {size} range {range:?}: {e}"));
+            }
+        }
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let b = BLOCK_BYTES;
+        let (data, hash, ob) = blob(6 * b as usize + 9);
+        let size = data.len() as u64;
+        let range = aligned_range(size, 2 * b, Some(2 * b));
+        let proof = range_outboard(hash, size, &ob, range.clone()).unwrap();
+        let slice = data[range.start as usize..range.end as usize].to_vec();
+
+        let mut bad_data = slice.clone();
+        bad_data[17] ^= 1;
+        assert!(verify_range(hash, size, range.clone(), &proof, &bad_data).is_err());
+
+        let mut bad_proof = proof.clone();
+        bad_proof[3] ^= 1;
+        assert!(verify_range(hash, size, range.clone(), &bad_proof, &slice).is_err());
+
+        assert!(verify_range(hash, size, range.clone(), &proof[..64], &slice).is_err());
+        assert!(verify_range(Hash::new(b"other"), size, range, &proof, &slice).is_err());
+        assert!(range_outboard(hash, size, &ob, 1..b).is_err());
+    }
+}
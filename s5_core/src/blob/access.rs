@@ -0,0 +1,41 @@
+//! Blob access-time tracking.
+//!
+//! Tiering and GC-grace policies want to know when a blob was last *read*,
+//! which the backing stores cannot tell them (object mtimes only move on
+//! write). A [`BlobStore`](super::BlobStore) configured with an
+//! [`AccessTracker`] reports every read and provide to it; the tracker is
+//! expected to batch those touches and persist them off the read path.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Hash, store::StoreResult};
+
+/// Records and answers per-blob last-access times.
+///
+/// [`touch`](Self::touch) sits on the read path, so implementations must make
+/// it cheap and non-blocking (buffer in memory, write in batches). Times are
+/// kept at whole-second resolution.
+#[async_trait::async_trait]
+pub trait AccessTracker: std::fmt::Debug + Send + Sync {
+    /// Note that `hash` was accessed at `at`. Never fails: a tracker that
+    /// cannot persist logs and drops the touch rather than failing a read.
+    fn touch(&self, hash: Hash, at: SystemTime);
+
+    /// Last recorded access of `hash`, including touches not yet flushed.
+    /// `None` if the blob was never touched since tracking began.
+    async fn last_access(&self, hash: Hash) -> StoreResult<Option<SystemTime>>;
+
+    /// Persist buffered touches now. Call on shutdown or before reading the
+    /// backing table directly.
+    async fn flush(&self) -> StoreResult<()>;
+}
+
+/// Seconds since the Unix epoch, the on-disk encoding of access times.
+pub fn to_unix_secs(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Inverse of [`to_unix_secs`].
+pub fn from_unix_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
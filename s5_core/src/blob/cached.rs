@@ -132,4 +132,12 @@ impl BlobsRead for CachedBlobsRead {
         // Streaming reads pass through — not worth caching.
         self.inner.blob_read(hash).await
     }
+
+    async fn blob_get_outboard(
+        &self,
+        hash: Hash,
+        range: std::ops::Range<u64>,
+    ) -> BlobResult<Option<Bytes>> {
+        self.inner.blob_get_outboard(hash, range).await
+    }
 }
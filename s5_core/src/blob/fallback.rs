@@ -91,4 +91,15 @@ impl BlobsRead for FallbackBlobsRead {
             Err(_) => self.secondary.blob_read(hash).await,
         }
     }
+
+    async fn blob_get_outboard(
+        &self,
+        hash: Hash,
+        range: std::ops::Range<u64>,
+    ) -> BlobResult<Option<Bytes>> {
+        match self.primary.blob_get_outboard(hash, range.clone()).await {
+            Ok(Some(proof)) => Ok(Some(proof)),
+            Ok(None) | Err(_) => self.secondary.blob_get_outboard(hash, range).await,
+        }
+    }
 }
@@ -17,6 +17,15 @@ const BLOB_TYPE_DEFAULT: u8 = 0x82;
 /// blob identifiers, ...) all spell the same byte the same way.
 pub const MULTIHASH_BLAKE3: u8 = 0x1e;
 
+/// URI prefix of the canonical [`BlobUri`] form.
+pub const BLOB_URI_PREFIX: &str = "s5://blob/";
+
+/// CID version byte (CIDv1) and the `raw` multicodec: a BlobId's hash is
+/// over the raw blob bytes, so `raw` is the only codec a CID can carry and
+/// still name the same content.
+const CID_V1: u64 = 0x01;
+const MULTICODEC_RAW: u64 = 0x55;
+
 #[derive(thiserror::Error, Debug)]
 pub enum BlobIdError {
     #[error("invalid multibase string: {0}")]
@@ -29,6 +38,12 @@ pub enum BlobIdError {
     InvalidBlobType(u8, u8),
     #[error("invalid multihash type: expected {0:#x}, got {1:#x}")]
     InvalidMultihashType(u8, u8),
+    #[error("not an s5 blob URI (expected `{BLOB_URI_PREFIX}<id>`)")]
+    InvalidUri,
+    #[error("invalid CID: {0}")]
+    InvalidCid(&'static str),
+    #[error("unsupported CID codec {0:#x} (only raw, 0x55, addresses blob bytes)")]
+    UnsupportedCidCodec(u64),
 }
 
 /// Identifier for a blob in S5.
@@ -65,7 +80,10 @@ impl BlobId {
         Self { hash, size }
     }
 
+    /// Parse a multibase-encoded BlobId (any multibase prefix, e.g. `b…`
+    /// base32, `z…` base58btc, `u…` base64url) or its `s5://blob/<id>` URI.
     pub fn parse(str: &str) -> Result<Self, BlobIdError> {
+        let str = str.strip_prefix(BLOB_URI_PREFIX).unwrap_or(str);
         let (_, bytes) = multibase::decode(str)?;
         Self::from_bytes(&bytes)
     }
@@ -139,6 +157,112 @@ impl BlobId {
     pub fn to_base64url(&self) -> String {
         multibase::encode(multibase::Base::Base64Url, self.to_bytes())
     }
+
+    /// Canonical `s5://blob/<base32 id>` URI.
+    pub fn to_uri(&self) -> String {
+        BlobUri(*self).to_string()
+    }
+
+    /// Binary CIDv1 (`raw` codec, blake3 multihash) for this blob's hash.
+    /// The size is not part of a CID and is dropped.
+    pub fn to_cid_bytes(&self) -> Vec<u8> {
+        let mut out = vec![CID_V1 as u8, MULTICODEC_RAW as u8, MULTIHASH_BLAKE3, 32];
+        out.extend_from_slice(self.hash.as_bytes());
+        out
+    }
+
+    /// CIDv1 in its usual base32 string form (`bafkr4i…`).
+    pub fn to_cid(&self) -> String {
+        multibase::encode(multibase::Base::Base32Lower, self.to_cid_bytes())
+    }
+
+    /// Rebuild a BlobId from a multibase CID string. CIDs don't carry a
+    /// size, so the caller supplies it.
+    pub fn from_cid(cid: &str, size: u64) -> Result<Self, BlobIdError> {
+        let (_, bytes) = multibase::decode(cid)?;
+        Self::from_cid_bytes(&bytes, size)
+    }
+
+    /// Inverse of [`to_cid_bytes`](Self::to_cid_bytes). Only CIDv1 with the
+    /// `raw` codec and a 32-byte blake3 multihash is accepted — any other
+    /// hash function names different bytes than a BlobId does.
+    pub fn from_cid_bytes(bytes: &[u8], size: u64) -> Result<Self, BlobIdError> {
+        let mut rest = bytes;
+        if read_varint(&mut rest)? != CID_V1 {
+            return Err(BlobIdError::InvalidCid("not a CIDv1"));
+        }
+        let codec = read_varint(&mut rest)?;
+        if codec != MULTICODEC_RAW {
+            return Err(BlobIdError::UnsupportedCidCodec(codec));
+        }
+        let multihash = read_varint(&mut rest)?;
+        if multihash != MULTIHASH_BLAKE3 as u64 {
+            return Err(BlobIdError::InvalidMultihashType(
+                MULTIHASH_BLAKE3,
+                u8::try_from(multihash).unwrap_or(u8::MAX),
+            ));
+        }
+        if read_varint(&mut rest)? != 32 {
+            return Err(BlobIdError::InvalidCid("blake3 digest must be 32 bytes"));
+        }
+        let hash: [u8; 32] = rest
+            .try_into()
+            .map_err(|_| BlobIdError::InvalidCid("digest length mismatch"))?;
+        Ok(Self::new(hash.into(), size))
+    }
+}
+
+/// Unsigned LEB128 varint as used by multiformats (at most 9 bytes).
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, BlobIdError> {
+    let mut value = 0u64;
+    for i in 0..9 {
+        let (&b, rest) = bytes
+            .split_first()
+            .ok_or(BlobIdError::InvalidCid("truncated varint"))?;
+        *bytes = rest;
+        value |= u64::from(b & 0x7f) << (7 * i);
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(BlobIdError::InvalidCid("varint too long"))
+}
+
+/// The canonical `s5://blob/<id>` URI for a [`BlobId`].
+///
+/// `Display` always writes the base32 id; `FromStr` requires the scheme
+/// prefix but accepts any multibase encoding after it.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BlobUri(pub BlobId);
+
+impl fmt::Display for BlobUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{BLOB_URI_PREFIX}{}", self.0.to_base32())
+    }
+}
+
+impl FromStr for BlobUri {
+    type Err = BlobIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s
+            .strip_prefix(BLOB_URI_PREFIX)
+            .ok_or(BlobIdError::InvalidUri)?;
+        let (_, bytes) = multibase::decode(id)?;
+        Ok(Self(BlobId::from_bytes(&bytes)?))
+    }
+}
+
+impl From<BlobId> for BlobUri {
+    fn from(id: BlobId) -> Self {
+        Self(id)
+    }
+}
+
+impl From<BlobUri> for BlobId {
+    fn from(uri: BlobUri) -> Self {
+        uri.0
+    }
 }
 
 impl fmt::Display for BlobId {
@@ -296,6 +420,64 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_blob_uri_roundtrip() {
+        let id = BlobId::new(Hash::new(b"uri"), 4096);
+        let uri = id.to_uri();
+        assert_eq!(uri, format!("s5://blob/{}", id.to_base32()));
+        assert_eq!(uri.parse::<BlobUri>().unwrap().0, id);
+        // Bare ids and URIs both parse as BlobId; any multibase after the prefix.
+        assert_eq!(BlobId::parse(&uri).unwrap(), id);
+        let b58 = format!("{BLOB_URI_PREFIX}{}", id.to_base58());
+        assert_eq!(b58.parse::<BlobUri>().unwrap().0, id);
+        assert!(matches!(
+            id.to_base32().parse::<BlobUri>(),
+            Err(BlobIdError::InvalidUri)
+        ));
+        assert!("s5://export/x".parse::<BlobUri>().is_err());
+    }
+
+    #[test]
+    fn test_blob_id_cid_roundtrip() {
+        let id = BlobId::new(Hash::new(b"cid"), 77);
+        let cid = id.to_cid();
+        // CIDv1 + raw + blake3 always renders with this base32 prefix.
+        assert!(cid.starts_with("bafkr4i"), "{cid}");
+        assert_eq!(BlobId::from_cid(&cid, 77).unwrap(), id);
+        let b58 = multibase::encode(multibase::Base::Base58Btc, id.to_cid_bytes());
+        assert_eq!(BlobId::from_cid(&b58, 77).unwrap(), id);
+    }
+
+    #[test]
+    fn test_blob_id_cid_rejects_other_hashes() {
+        let digest = [7u8; 32];
+        // sha2-256 raw CIDv1.
+        let mut sha = vec![0x01, 0x55, 0x12, 0x20];
+        sha.extend_from_slice(&digest);
+        assert!(matches!(
+            BlobId::from_cid_bytes(&sha, 0),
+            Err(BlobIdError::InvalidMultihashType(MULTIHASH_BLAKE3, 0x12))
+        ));
+        // dag-pb (0x70) blake3 — hash is over the DAG node, not the bytes.
+        let mut dag = vec![0x01, 0x70, MULTIHASH_BLAKE3, 0x20];
+        dag.extend_from_slice(&digest);
+        assert!(matches!(
+            BlobId::from_cid_bytes(&dag, 0),
+            Err(BlobIdError::UnsupportedCidCodec(0x70))
+        ));
+        // CIDv0 (bare sha2-256 multihash).
+        let mut v0 = vec![0x12, 0x20];
+        v0.extend_from_slice(&digest);
+        assert!(matches!(
+            BlobId::from_cid_bytes(&v0, 0),
+            Err(BlobIdError::InvalidCid(_))
+        ));
+        let mut short = vec![0x01, 0x55, MULTIHASH_BLAKE3, 0x20];
+        short.extend_from_slice(&digest[..31]);
+        assert!(BlobId::from_cid_bytes(&short, 0).is_err());
+        assert!(BlobId::from_cid_bytes(&[0x01, 0x80], 0).is_err());
+    }
+
     #[test]
     fn test_blob_id_error_size_too_long() {
         // More than 8 bytes for size
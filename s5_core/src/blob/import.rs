@@ -1,5 +1,5 @@
 use crate::{
-    BlobId, Hash,
+    BlobId, Hash, HashWriter,
     bao::outboard::compute_outboard,
     store::{Store, StoreResult},
 };
@@ -9,17 +9,18 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::AsyncWrite;
-use tokio_util::io::{StreamReader, SyncIoBridge};
 
 #[cfg(not(target_arch = "wasm32"))]
 use tokio_stream::StreamExt;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio_util::io::{StreamReader, SyncIoBridge};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::PathBuf;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio_util::codec::{BytesCodec, FramedRead};
 
-use super::paths::{blob_path_for_hash, obao6_path_for_hash};
+use super::layout::BlobLayout;
 
 /// Threshold below which we hash inline without spawn_blocking.
 /// blake3 on small data (~1KB) takes ~1-5 microseconds, while spawn_blocking
@@ -27,15 +28,50 @@ use super::paths::{blob_path_for_hash, obao6_path_for_hash};
 /// 16KB chosen as a conservative threshold - blake3 at 16KB is still <100us.
 const INLINE_HASH_THRESHOLD: u64 = 16 * 1024;
 
+/// Default size at or below which the `*_or_inline` imports keep content
+/// out of the blob store, for the caller to embed in its own metadata
+/// (same cutoff as the WASM and Flutter clients).
+pub const DEFAULT_INLINE_THRESHOLD: u64 = 4096;
+
+/// Outcome of [`import_file_or_inline`] / [`import_stream_or_inline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportedBlob {
+    /// Small enough to inline: nothing was written to the store.
+    Inline { id: BlobId, data: Bytes },
+    /// Written to the blob store.
+    Stored(BlobId),
+}
+
+impl ImportedBlob {
+    pub fn id(&self) -> BlobId {
+        match self {
+            ImportedBlob::Inline { id, .. } | ImportedBlob::Stored(id) => *id,
+        }
+    }
+
+    pub fn inline_data(&self) -> Option<&Bytes> {
+        match self {
+            ImportedBlob::Inline { data, .. } => Some(data),
+            ImportedBlob::Stored(_) => None,
+        }
+    }
+
+    fn inline(data: Bytes) -> Self {
+        let id = BlobId::new(Hash::new(&data), data.len() as u64);
+        ImportedBlob::Inline { id, data }
+    }
+}
+
 /// TODO(perf): expose the Bao/outboard threshold and hashing
 /// strategy as a tunable policy so different deployments can
 /// trade CPU vs metadata size explicitly.
 pub async fn import_bytes(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     bytes: bytes::Bytes,
 ) -> StoreResult<BlobId> {
-    import_bytes_inner(store, outboard_store, bytes, true).await
+    import_bytes_inner(store, layout, outboard_store, bytes, true).await
 }
 
 /// Import bytes without checking if the blob already exists.
@@ -45,14 +81,16 @@ pub async fn import_bytes(
 /// writing the same content twice is safe.
 pub async fn import_bytes_unchecked(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     bytes: bytes::Bytes,
 ) -> StoreResult<BlobId> {
-    import_bytes_inner(store, outboard_store, bytes, false).await
+    import_bytes_inner(store, layout, outboard_store, bytes, false).await
 }
 
 async fn import_bytes_inner(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     bytes: bytes::Bytes,
     check_exists: bool,
@@ -92,10 +130,10 @@ async fn import_bytes_inner(
         (blake3::hash(&bytes).into(), None)
     };
 
-    let final_path = blob_path_for_hash(hash, &store.features());
+    let final_path = layout.blob_path(hash, &store.features());
 
     // If the blob exists, the outboard does too — skip both writes.
-    if check_exists && store.exists(&final_path).await? {
+    if check_exists && is_complete(store, &final_path, size).await? {
         return Ok(BlobId { hash, size });
     }
 
@@ -104,7 +142,7 @@ async fn import_bytes_inner(
     {
         outboard_store
             .put_bytes(
-                &obao6_path_for_hash(hash, &outboard_store.features()),
+                &layout.obao6_path(hash, &outboard_store.features()),
                 outboard.clone().into(),
             )
             .await?;
@@ -114,16 +152,16 @@ async fn import_bytes_inner(
     Ok(BlobId { hash, size })
 }
 
-/// TODO(perf): consider computing Bao outboard incrementally during
-/// the initial stream write instead of re-reading via
-/// `compute_from_store`, especially for remote or slow stores.
+/// Hash and outboard are built by a [`HashWriter`] fed from the same
+/// stream that is written to the store, so the blob is read exactly once.
 pub async fn import_stream(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
 ) -> StoreResult<BlobId> {
-    let hasher = Arc::new(std::sync::Mutex::new(blake3::Hasher::new()));
-    let writer = HasherWriter {
+    let hasher = Arc::new(std::sync::Mutex::new(new_hash_writer(outboard_store)));
+    let writer = SharedHashWriter {
         hasher: hasher.clone(),
     };
     let tee_stream = TeeStream::new(stream, writer);
@@ -131,23 +169,82 @@ pub async fn import_stream(
     let temp_path = store.put_temp(Box::new(tee_stream)).await?;
     let size = store.size(&temp_path).await?;
 
-    let hash: Hash = hasher.lock().unwrap().finalize().into();
+    let hasher = std::mem::take(&mut *hasher.lock().unwrap());
+    if hasher.len() != size {
+        discard_temp(store, &temp_path).await;
+        return Err(anyhow::anyhow!(
+            "Size mismatch during import: hashed {} bytes, stored {size}",
+            hasher.len()
+        ));
+    }
+    let (hash, outboard) = hasher.finish();
+
+    let (hash, size) = finalize_import(
+        store,
+        layout,
+        outboard_store,
+        temp_path,
+        hash,
+        size,
+        outboard,
+    )
+    .await?;
 
-    let outboard = if outboard_store.is_some() {
-        let (h2, ob) =
-            compute_from_store(store, outboard_store, &temp_path, size, |_| Ok(())).await?;
-        if h2 != hash {
-            return Err(anyhow::anyhow!("Hash mismatch during import"));
-        }
-        ob
-    } else {
-        None
-    };
+    Ok(BlobId { hash, size })
+}
 
-    let (hash, size) =
-        finalize_import(store, outboard_store, temp_path, hash, size, outboard).await?;
+/// [`import_stream`], except that a stream ending within `inline_threshold`
+/// bytes is returned as [`ImportedBlob::Inline`] without touching the
+/// store. A threshold of 0 disables inlining.
+pub async fn import_stream_or_inline(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    outboard_store: &Option<Arc<dyn Store>>,
+    mut stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
+    inline_threshold: u64,
+) -> StoreResult<ImportedBlob> {
+    use tokio_stream::StreamExt;
+
+    if inline_threshold == 0 {
+        return Ok(ImportedBlob::Stored(
+            import_stream(store, layout, outboard_store, stream).await?,
+        ));
+    }
+    let mut head = bytes::BytesMut::new();
+    while head.len() as u64 <= inline_threshold {
+        match stream.next().await {
+            Some(chunk) => head.extend_from_slice(&chunk?),
+            None => return Ok(ImportedBlob::inline(head.freeze())),
+        }
+    }
+    // Over the threshold: replay what was buffered, then the rest.
+    let rest = tokio_stream::iter([Ok(head.freeze())]).chain(stream);
+    let id = import_stream(store, layout, outboard_store, Box::new(rest)).await?;
+    Ok(ImportedBlob::Stored(id))
+}
 
-    Ok(BlobId { hash, size })
+/// [`import_file`], except that a file of at most `inline_threshold` bytes
+/// is read into memory and returned as [`ImportedBlob::Inline`] without
+/// touching the store. A threshold of 0 disables inlining.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn import_file_or_inline(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    outboard_store: &Option<Arc<dyn Store>>,
+    path: PathBuf,
+    inline_threshold: u64,
+    on_progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
+) -> StoreResult<ImportedBlob> {
+    if inline_threshold > 0 && tokio::fs::metadata(&path).await?.len() <= inline_threshold {
+        let data = Bytes::from(tokio::fs::read(&path).await?);
+        // Re-checked because the file may have grown since the stat.
+        if data.len() as u64 <= inline_threshold {
+            on_progress(data.len() as u64)?;
+            return Ok(ImportedBlob::inline(data));
+        }
+    }
+    let id = import_file(store, layout, outboard_store, path, on_progress).await?;
+    Ok(ImportedBlob::Stored(id))
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -168,6 +265,7 @@ pub async fn import_stream(
 /// are unavailable (cross-device, unsupported filesystem).
 pub async fn import_file(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     path: PathBuf,
     on_progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
@@ -177,19 +275,20 @@ pub async fn import_file(
     // hash-content consistency even if the source is being written to.
     if store.features().supports_reflink
         && let Some(blob_id) =
-            try_import_file_reflink(store, outboard_store, &path, &on_progress).await?
+            try_import_file_reflink(store, layout, outboard_store, &path, &on_progress).await?
     {
         return Ok(blob_id);
         // Reflink failed (e.g. cross-device) — fall through to TeeStream
     }
 
-    import_file_teestream(store, outboard_store, path, on_progress).await
+    import_file_teestream(store, layout, outboard_store, path, on_progress).await
 }
 
 /// Reflink import path: FICLONE source → temp, hash temp, finalize.
 #[cfg(not(target_arch = "wasm32"))]
 async fn try_import_file_reflink(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     path: &std::path::Path,
     _on_progress: &(impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static),
@@ -203,43 +302,34 @@ async fn try_import_file_reflink(
     }
 
     let size = store.size(&temp_path).await?;
-    let compute_outboard_flag = outboard_store.is_some();
 
     // Step 2: hash the frozen temp file
     let stream = store.open_read_stream(&temp_path, 0, None).await?;
-    let reader = StreamReader::new(stream);
-    let reader = SyncIoBridge::new(reader);
-
-    let (hash, outboard) = tokio::task::spawn_blocking(move || {
-        if compute_outboard_flag {
-            compute_outboard(reader, size, |_| Ok(()))
-        } else {
-            use std::io::Read;
-
-            let mut hasher = blake3::Hasher::new();
-            let mut reader = std::io::BufReader::new(reader);
-            let mut buf = [0u8; 64 * 1024];
-
-            loop {
-                let n = reader.read(&mut buf)?;
-                if n == 0 {
-                    break;
-                }
-                hasher.update(&buf[..n]);
-                // Note: we don't call on_progress here because we can't
-                // move the reference into spawn_blocking. The progress
-                // callback is primarily useful for the TeeStream path.
-            }
-
-            let hash: Hash = hasher.finalize().into();
-            Ok((hash, None))
+    let reader = SyncIoBridge::new(StreamReader::new(stream));
+    // Note: no on_progress here because we can't move the reference into
+    // spawn_blocking. The progress callback is primarily useful for the
+    // TeeStream path.
+    let hasher = new_hash_writer(outboard_store);
+    let hasher = match tokio::task::spawn_blocking(move || hash_reader(reader, hasher)).await? {
+        Ok(hasher) => hasher,
+        Err(e) => {
+            discard_temp(store, &temp_path).await;
+            return Err(e.into());
         }
-    })
-    .await??;
+    };
+    let (hash, outboard) = hasher.finish();
 
     // Step 3: finalize (rename temp → final blob path, or discard if exists)
-    let (hash, size) =
-        finalize_import(store, outboard_store, temp_path, hash, size, outboard).await?;
+    let (hash, size) = finalize_import(
+        store,
+        layout,
+        outboard_store,
+        temp_path,
+        hash,
+        size,
+        outboard,
+    )
+    .await?;
 
     Ok(Some(BlobId { hash, size }))
 }
@@ -248,41 +338,16 @@ async fn try_import_file_reflink(
 #[cfg(not(target_arch = "wasm32"))]
 async fn import_file_teestream(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     path: PathBuf,
     on_progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
 ) -> StoreResult<BlobId> {
-    let meta = tokio::fs::metadata(&path).await?;
-    let size = meta.len();
-
     let (client, server) = tokio::io::duplex(64 * 1024);
-    let compute_outboard_flag = outboard_store.is_some();
-
+    let hasher = new_hash_writer(outboard_store).with_progress(on_progress);
     let compute_task = tokio::task::spawn_blocking(move || {
         let reader = SyncIoBridge::new(server);
-        if compute_outboard_flag {
-            compute_outboard(reader, size, on_progress)
-        } else {
-            use std::io::Read;
-
-            let mut hasher = blake3::Hasher::new();
-            let mut reader = std::io::BufReader::new(reader);
-            let mut buf = [0u8; 8192];
-            let mut processed: u64 = 0;
-
-            loop {
-                let n = reader.read(&mut buf)?;
-                if n == 0 {
-                    break;
-                }
-                hasher.update(&buf[..n]);
-                processed += n as u64;
-                on_progress(processed)?;
-            }
-
-            let hash: Hash = hasher.finalize().into();
-            Ok((hash, None))
-        }
+        hash_reader(reader, hasher)
     });
 
     let file = tokio::fs::File::open(&path).await?;
@@ -292,59 +357,76 @@ async fn import_file_teestream(
 
     let temp_path = store.put_temp(Box::new(tee_stream)).await?;
 
-    let (hash, outboard) = compute_task.await??;
-
-    let (hash, size) =
-        finalize_import(store, outboard_store, temp_path, hash, size, outboard).await?;
+    // The size is whatever was actually hashed (and written), so a file
+    // that grows mid-import still yields a BlobId matching the stored blob.
+    let hasher = match compute_task.await? {
+        Ok(hasher) => hasher,
+        Err(e) => {
+            discard_temp(store, &temp_path).await;
+            return Err(e.into());
+        }
+    };
+    let size = hasher.len();
+    let stored = store.size(&temp_path).await?;
+    if stored != size {
+        discard_temp(store, &temp_path).await;
+        return Err(anyhow::anyhow!(
+            "Size mismatch during import: hashed {size} bytes, stored {stored}"
+        ));
+    }
+    let (hash, outboard) = hasher.finish();
+
+    let (hash, size) = finalize_import(
+        store,
+        layout,
+        outboard_store,
+        temp_path,
+        hash,
+        size,
+        outboard,
+    )
+    .await?;
 
     Ok(BlobId { hash, size })
 }
 
-async fn compute_from_store(
-    store: &Arc<dyn Store>,
-    outboard_store: &Option<Arc<dyn Store>>,
-    path: &str,
-    size: u64,
-    progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
-) -> StoreResult<(Hash, Option<Vec<u8>>)> {
-    let stream = store.open_read_stream(path, 0, None).await?;
-    let reader = StreamReader::new(stream);
-    let reader = SyncIoBridge::new(reader);
-
-    let compute_outboard_flag = outboard_store.is_some();
-
-    let (hash, outboard) = tokio::task::spawn_blocking(move || {
-        if compute_outboard_flag {
-            compute_outboard(reader, size, progress)
-        } else {
-            use std::io::Read;
-
-            let mut hasher = blake3::Hasher::new();
-            let mut reader = std::io::BufReader::new(reader);
-            let mut buf = [0u8; 8192];
-            let mut processed: u64 = 0;
-
-            loop {
-                let n = reader.read(&mut buf)?;
-                if n == 0 {
-                    break;
-                }
-                hasher.update(&buf[..n]);
-                processed += n as u64;
-                progress(processed)?;
-            }
+fn new_hash_writer(outboard_store: &Option<Arc<dyn Store>>) -> HashWriter {
+    if outboard_store.is_some() {
+        HashWriter::with_outboard()
+    } else {
+        HashWriter::new()
+    }
+}
 
-            let hash: Hash = hasher.finalize().into();
-            Ok((hash, None))
+/// Drain `reader` through `hasher`; meant for a blocking thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn hash_reader(
+    mut reader: impl std::io::Read,
+    mut hasher: HashWriter,
+) -> std::io::Result<HashWriter> {
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
         }
-    })
-    .await??;
-
-    Ok((hash, outboard))
+        std::io::Write::write_all(&mut hasher, &buf[..n])?;
+    }
+    Ok(hasher)
 }
 
+/// Moves a verified temp upload (`hash`/`size` as computed while it was
+/// written) to its content address.
+///
+/// Stores that support rename get an atomic move, so the final path only
+/// ever holds complete blobs. Others get a copy that is read back and
+/// re-hashed before it is trusted; a copy that does not match is deleted
+/// again. A blob already at the final path is only kept if its size
+/// matches — a truncated leftover from an interrupted direct write is
+/// replaced.
 async fn finalize_import(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     outboard_store: &Option<Arc<dyn Store>>,
     temp_path: String,
     hash: Hash,
@@ -356,32 +438,89 @@ async fn finalize_import(
     {
         obao_store
             .put_bytes(
-                &obao6_path_for_hash(hash, &obao_store.features()),
+                &layout.obao6_path(hash, &obao_store.features()),
                 outboard_data.into(),
             )
             .await?;
     }
 
-    let final_path = blob_path_for_hash(hash, &store.features());
+    let final_path = layout.blob_path(hash, &store.features());
 
-    if store.exists(&final_path).await? {
+    if is_complete(store, &final_path, size).await? {
         store.delete(&temp_path).await?;
     } else if store.features().supports_rename {
         store.rename(&temp_path, &final_path).await?;
     } else {
         let stream = store.open_read_stream(&temp_path, 0, None).await?;
-        store.put_stream(&final_path, stream).await?;
-        store.delete(&temp_path).await?;
+        let copied = store.put_stream(&final_path, stream).await;
+        discard_temp(store, &temp_path).await;
+        copied?;
+        if let Err(e) = verify_stored(store, &final_path, hash, size).await {
+            if let Err(del) = store.delete(&final_path).await {
+                tracing::warn!("blobstore: failed to delete unverified {final_path}: {del}");
+            }
+            return Err(e);
+        }
     }
 
     Ok((hash, size))
 }
 
-struct HasherWriter {
-    hasher: Arc<std::sync::Mutex<blake3::Hasher>>,
+/// Whether `path` exists and holds `size` bytes. Blobs are content
+/// addressed, so a matching size at the right path means an earlier import
+/// completed; anything else is a partial write.
+async fn is_complete(store: &Arc<dyn Store>, path: &str, size: u64) -> StoreResult<bool> {
+    if !store.exists(path).await? {
+        return Ok(false);
+    }
+    let stored = store.size(path).await?;
+    if stored != size {
+        tracing::warn!("blobstore: replacing partial blob {path} ({stored} of {size} bytes)");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Re-read `path` and check it hashes to `hash` with length `size`.
+async fn verify_stored(
+    store: &Arc<dyn Store>,
+    path: &str,
+    hash: Hash,
+    size: u64,
+) -> StoreResult<()> {
+    use tokio_stream::StreamExt;
+
+    let mut stream = store.open_read_stream(path, 0, None).await?;
+    let mut hasher = blake3::Hasher::new();
+    let mut len = 0u64;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        len += chunk.len() as u64;
+        hasher.update(&chunk);
+    }
+    let actual: Hash = hasher.finalize().into();
+    if len != size || actual != hash {
+        return Err(anyhow::anyhow!(
+            "blob integrity check failed for {hash} after copy: stored {len} bytes hashing to {actual}"
+        ));
+    }
+    Ok(())
+}
+
+/// Best-effort removal of an abandoned temp upload.
+async fn discard_temp(store: &Arc<dyn Store>, temp_path: &str) {
+    if let Err(e) = store.delete(temp_path).await {
+        tracing::warn!("blobstore: failed to delete temp upload {temp_path}: {e}");
+    }
+}
+
+/// `AsyncWrite` side of [`TeeStream`] for [`import_stream`]; the hasher is
+/// shared so it can be taken back once the stream is drained.
+struct SharedHashWriter {
+    hasher: Arc<std::sync::Mutex<HashWriter>>,
 }
 
-impl AsyncWrite for HasherWriter {
+impl AsyncWrite for SharedHashWriter {
     fn poll_write(
         self: Pin<&mut Self>,
         _cx: &mut Context<'_>,
@@ -0,0 +1,271 @@
+//! How a [`BlobStore`](super::BlobStore) maps content hashes to store paths.
+//!
+//! A [`BlobLayout`] turns a [`Hash`] into the path of the blob and of its
+//! Bao outboard, and decodes blob paths back into hashes for listing. The
+//! store's [`StoreFeatures`] still decide the hash encoding (base64url on
+//! case-sensitive stores, base32 otherwise); the layout decides where the
+//! encoded hash goes.
+//!
+//! Which layout a store uses is recorded in a [`StoreManifest`] at
+//! [`STORE_MANIFEST_PATH`] the first time it is opened through
+//! [`BlobStore::open`](super::BlobStore::open). From then on the manifest
+//! wins over configuration, so a store keeps being read the way it was
+//! written even if defaults change, and tools that walk a store (GC,
+//! verify) can find out how to interpret its paths.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::paths;
+use crate::{
+    Hash,
+    store::{Store, StoreFeatures, StoreResult},
+};
+
+/// Path of the store manifest, relative to the store root.
+pub const STORE_MANIFEST_PATH: &str = "s5-store.json";
+
+/// Current [`StoreManifest::version`].
+pub const STORE_MANIFEST_VERSION: u32 = 1;
+
+/// Maps hashes to the paths blobs and outboards are stored at.
+///
+/// [`BlobLayoutSpec`] covers the built-in layouts; implement this to store
+/// blobs under a scheme of your own.
+pub trait BlobLayout: fmt::Debug + Send + Sync {
+    /// The spec this layout is recorded as in the store manifest.
+    fn spec(&self) -> BlobLayoutSpec;
+
+    fn blob_path(&self, hash: Hash, features: &StoreFeatures) -> String;
+
+    fn obao6_path(&self, hash: Hash, features: &StoreFeatures) -> String;
+
+    /// The hash a path from [`Store::list`] holds the blob of, `Ok(None)`
+    /// for paths that are not blobs under this layout (outboards, the
+    /// manifest, foreign objects).
+    fn hash_from_blob_path(
+        &self,
+        path: &str,
+        features: &StoreFeatures,
+    ) -> Result<Option<Hash>, std::io::Error>;
+}
+
+/// The built-in layouts, as selected in config and recorded in manifests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlobLayoutSpec {
+    /// Fan-out for stores that recommend small directories
+    /// ([`StoreFeatures::recommended_max_dir_size`] below 10000), flat
+    /// otherwise. What every store used before layouts were explicit; it
+    /// is resolved to [`Self::Flat`] or [`Self::FanOut`] when recorded.
+    #[default]
+    Auto,
+    /// `blob3/<hash>` and `obao6/<hash>`.
+    Flat,
+    /// `blob3/ab/cd/<rest>`; base32 hashes on case-insensitive stores get a
+    /// third level, as their alphabet is smaller.
+    FanOut,
+    /// Each content type under its own top-level prefix, for stores shared
+    /// with other data or laid out by other tools. Prefixes include their
+    /// trailing `/`, if any.
+    ContentTypePrefixed {
+        blob_prefix: String,
+        outboard_prefix: String,
+        #[serde(default)]
+        fan_out: bool,
+    },
+}
+
+impl BlobLayoutSpec {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, Self::Auto)
+    }
+
+    /// The concrete layout [`Self::Auto`] amounts to on a store with
+    /// `features`; other specs are returned unchanged.
+    pub fn resolve(&self, features: &StoreFeatures) -> Self {
+        match self {
+            Self::Auto if paths::fans_out(features) => Self::FanOut,
+            Self::Auto => Self::Flat,
+            other => other.clone(),
+        }
+    }
+
+    fn parts(&self, features: &StoreFeatures) -> (&str, &str, bool) {
+        match self {
+            Self::Auto => ("blob3/", "obao6/", paths::fans_out(features)),
+            Self::Flat => ("blob3/", "obao6/", false),
+            Self::FanOut => ("blob3/", "obao6/", true),
+            Self::ContentTypePrefixed {
+                blob_prefix,
+                outboard_prefix,
+                fan_out,
+            } => (blob_prefix, outboard_prefix, *fan_out),
+        }
+    }
+}
+
+impl BlobLayout for BlobLayoutSpec {
+    fn spec(&self) -> BlobLayoutSpec {
+        self.clone()
+    }
+
+    fn blob_path(&self, hash: Hash, features: &StoreFeatures) -> String {
+        let (prefix, _, fan_out) = self.parts(features);
+        format!("{prefix}{}", paths::encode_hash(hash, features, fan_out))
+    }
+
+    fn obao6_path(&self, hash: Hash, features: &StoreFeatures) -> String {
+        let (_, prefix, fan_out) = self.parts(features);
+        format!("{prefix}{}", paths::encode_hash(hash, features, fan_out))
+    }
+
+    fn hash_from_blob_path(
+        &self,
+        path: &str,
+        features: &StoreFeatures,
+    ) -> Result<Option<Hash>, std::io::Error> {
+        let (prefix, _, _) = self.parts(features);
+        match path.strip_prefix(prefix) {
+            Some(rest) => paths::decode_hash(rest, features),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Metadata a [`BlobStore`](super::BlobStore) keeps about the store it
+/// writes to, at [`STORE_MANIFEST_PATH`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoreManifest {
+    pub version: u32,
+    pub layout: BlobLayoutSpec,
+}
+
+impl StoreManifest {
+    pub fn new(layout: BlobLayoutSpec) -> Self {
+        Self {
+            version: STORE_MANIFEST_VERSION,
+            layout,
+        }
+    }
+
+    /// The manifest of `store`, `None` for stores written before manifests
+    /// existed (or never opened through [`BlobStore::open`](super::BlobStore::open)).
+    pub async fn load(store: &dyn Store) -> StoreResult<Option<Self>> {
+        if !store.exists(STORE_MANIFEST_PATH).await? {
+            return Ok(None);
+        }
+        let bytes = store.open_read_bytes(STORE_MANIFEST_PATH, 0, None).await?;
+        let manifest: Self = serde_json::from_slice(&bytes)
+            .map_err(|e| anyhow::anyhow!("invalid {STORE_MANIFEST_PATH}: {e}"))?;
+        if manifest.version > STORE_MANIFEST_VERSION {
+            return Err(anyhow::anyhow!(
+                "{STORE_MANIFEST_PATH} has version {}, this build understands up to {STORE_MANIFEST_VERSION}",
+                manifest.version
+            ));
+        }
+        Ok(Some(manifest))
+    }
+
+    pub async fn save(&self, store: &dyn Store) -> StoreResult<()> {
+        let bytes = serde_json::to_vec_pretty(self)?;
+        store.put_bytes(STORE_MANIFEST_PATH, bytes.into()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_round_trip_hashes() {
+        let hash = Hash::new(b"layout");
+        let prefixed = BlobLayoutSpec::ContentTypePrefixed {
+            blob_prefix: "data/blobs/".into(),
+            outboard_prefix: "data/outboards/".into(),
+            fan_out: true,
+        };
+        for case_sensitive in [true, false] {
+            let features = StoreFeatures {
+                case_sensitive,
+                recommended_max_dir_size: u64::MAX,
+                ..Default::default()
+            };
+            for layout in [
+                BlobLayoutSpec::Flat,
+                BlobLayoutSpec::FanOut,
+                prefixed.clone(),
+            ] {
+                let path = layout.blob_path(hash, &features);
+                assert_eq!(
+                    layout.hash_from_blob_path(&path, &features).unwrap(),
+                    Some(hash),
+                    "{layout:?} {path}"
+                );
+                let obao = layout.obao6_path(hash, &features);
+                assert_eq!(layout.hash_from_blob_path(&obao, &features).unwrap(), None);
+            }
+        }
+
+        let features = StoreFeatures {
+            case_sensitive: true,
+            recommended_max_dir_size: u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(
+            BlobLayoutSpec::Flat
+                .blob_path(hash, &features)
+                .matches('/')
+                .count(),
+            1
+        );
+        assert_eq!(
+            BlobLayoutSpec::FanOut
+                .blob_path(hash, &features)
+                .matches('/')
+                .count(),
+            3
+        );
+        assert!(
+            prefixed
+                .blob_path(hash, &features)
+                .starts_with("data/blobs/")
+        );
+        // Auto keeps the pre-layout paths.
+        assert_eq!(
+            BlobLayoutSpec::Auto.blob_path(hash, &features),
+            paths::blob_path_for_hash(hash, &features)
+        );
+        assert_eq!(
+            BlobLayoutSpec::Auto.resolve(&features),
+            BlobLayoutSpec::Flat
+        );
+    }
+
+    #[test]
+    fn manifest_json_is_stable() {
+        let manifest = StoreManifest::new(BlobLayoutSpec::ContentTypePrefixed {
+            blob_prefix: "b/".into(),
+            outboard_prefix: "o/".into(),
+            fan_out: false,
+        });
+        let json = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "layout": {
+                    "kind": "content_type_prefixed",
+                    "blob_prefix": "b/",
+                    "outboard_prefix": "o/",
+                    "fan_out": false,
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<StoreManifest>(json).unwrap(),
+            manifest
+        );
+    }
+}
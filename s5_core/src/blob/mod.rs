@@ -1,15 +1,21 @@
+pub mod access;
 pub mod cached;
 pub mod fallback;
 pub mod identifier;
 pub mod import;
+pub mod layout;
 pub mod location;
+mod outboard;
 pub mod paths;
 pub mod read;
 pub mod store;
 pub mod tee;
 pub mod verify;
 
-pub use identifier::BlobId;
+pub use access::AccessTracker;
+pub use identifier::{BlobId, BlobUri};
+pub use import::ImportedBlob;
+pub use layout::{BlobLayout, BlobLayoutSpec, StoreManifest};
 pub use location::BlobLocation;
 pub use store::BlobStore;
 pub use verify::{VerifyingReader, verify_bytes};
@@ -19,6 +25,7 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use futures::Stream;
 use std::io;
+use std::ops::Range;
 use tokio::io::AsyncRead;
 
 /// Stream of reachable hashes consumed by `BlobsDelete::blob_retain`.
@@ -99,6 +106,21 @@ pub trait BlobsRead: Sync + Send {
     /// Returns an async reader for the blob contents, verified against
     /// `hash` by EOF (see the trait-level integrity contract).
     async fn blob_read(&self, hash: Hash) -> BlobResult<Box<dyn AsyncRead + Send + Unpin>>;
+
+    /// Bao parent hash pairs proving `range`, which must be block-aligned
+    /// (see [`crate::bao::range::aligned_range`]); check the range's bytes
+    /// against them with [`crate::bao::range::verify_range`]. Empty for
+    /// single-block blobs.
+    ///
+    /// `Ok(None)` (the default) means this backend can't produce outboards;
+    /// callers fall back to unverified slices or full downloads.
+    async fn blob_get_outboard(
+        &self,
+        _hash: Hash,
+        _range: Range<u64>,
+    ) -> BlobResult<Option<Bytes>> {
+        Ok(None)
+    }
 }
 
 /// High-level async write interface for content-addressed blobs.
@@ -0,0 +1,207 @@
+//! Outboard lookup for [`BlobStore`](super::BlobStore): memory cache, then
+//! the `obao6/` sidecar in the outboard store, then regeneration from the
+//! blob itself.
+//!
+//! Imports already write the sidecar when an outboard store is configured,
+//! but blobs that arrived another way (older imports, copies between
+//! stores, stores opened without outboards) have none. The first request
+//! for such a blob's outboard hashes it once, checks the root against the
+//! content address and persists the result, so later range proofs are a
+//! sidecar read.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+
+use super::layout::BlobLayout;
+use crate::bao::outboard::S5_BLOCK_SIZE;
+use crate::store::{Store, StoreResult};
+use crate::{Hash, HashWriter};
+
+/// Outboards at or below this size are kept in memory (256 bytes of
+/// outboard per MiB of blob, so this covers blobs up to ~64 MiB).
+const MAX_CACHED_OUTBOARD: usize = 16 * 1024;
+/// Total bytes of cached outboards per `BlobStore`.
+const OUTBOARD_CACHE_BUDGET: usize = 4 * 1024 * 1024;
+
+/// Byte-budgeted FIFO cache of small outboards. Entries are keyed by
+/// content hash, so they never go stale; eviction is only about memory.
+#[derive(Debug, Default)]
+pub(crate) struct OutboardCache {
+    inner: Mutex<CacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    entries: HashMap<Hash, Bytes>,
+    order: VecDeque<Hash>,
+    bytes: usize,
+}
+
+impl OutboardCache {
+    fn get(&self, hash: &Hash) -> Option<Bytes> {
+        self.inner.lock().unwrap().entries.get(hash).cloned()
+    }
+
+    fn insert(&self, hash: Hash, outboard: Bytes) {
+        if outboard.len() > MAX_CACHED_OUTBOARD {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.entries.contains_key(&hash) {
+            return;
+        }
+        while inner.bytes + outboard.len() > OUTBOARD_CACHE_BUDGET {
+            let Some(old) = inner.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = inner.entries.remove(&old) {
+                inner.bytes -= evicted.len();
+            }
+        }
+        inner.bytes += outboard.len();
+        inner.order.push_back(hash);
+        inner.entries.insert(hash, outboard);
+    }
+
+    pub(crate) fn remove(&self, hash: &Hash) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(evicted) = inner.entries.remove(hash) {
+            inner.bytes -= evicted.len();
+            inner.order.retain(|h| h != hash);
+        }
+    }
+}
+
+/// Length of the pre-order outboard of a `size`-byte blob: one 64-byte
+/// parent pair per internal node of the block tree.
+fn outboard_len(size: u64) -> u64 {
+    size.div_ceil(S5_BLOCK_SIZE.bytes() as u64)
+        .saturating_sub(1)
+        * 64
+}
+
+/// Full pre-order outboard of `hash`, or `None` when the blob fits in a
+/// single block and needs none.
+pub(crate) async fn load_or_generate(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    outboard_store: &Option<Arc<dyn Store>>,
+    cache: &OutboardCache,
+    hash: Hash,
+    size: u64,
+) -> StoreResult<Option<Bytes>> {
+    let expected_len = outboard_len(size);
+    if expected_len == 0 {
+        return Ok(None);
+    }
+    if let Some(hit) = cache.get(&hash) {
+        return Ok(Some(hit));
+    }
+
+    if let Some(obao_store) = outboard_store {
+        let path = layout.obao6_path(hash, &obao_store.features());
+        if obao_store.exists(&path).await? {
+            let sidecar = obao_store.open_read_bytes(&path, 0, None).await?;
+            if sidecar.len() as u64 == expected_len {
+                cache.insert(hash, sidecar.clone());
+                return Ok(Some(sidecar));
+            }
+            tracing::warn!(
+                hash = %hash.fmt_short(),
+                len = sidecar.len(),
+                expected_len,
+                "blobstore: discarding outboard sidecar with wrong length"
+            );
+        }
+    }
+
+    let (root, outboard) = hash_blob(store, layout, hash).await?;
+    if root != hash {
+        return Err(anyhow::anyhow!(
+            "blob {hash} is corrupt: content hashes to {root}"
+        ));
+    }
+    let outboard: Bytes = outboard.unwrap_or_default().into();
+
+    if let Some(obao_store) = outboard_store {
+        let path = layout.obao6_path(hash, &obao_store.features());
+        if let Err(err) = obao_store.put_bytes(&path, outboard.clone()).await {
+            // The outboard is still correct; it just gets rebuilt next time.
+            tracing::warn!("blobstore: failed to persist outboard for {hash}: {err}");
+        }
+    }
+    cache.insert(hash, outboard.clone());
+    Ok(Some(outboard))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn hash_blob(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    hash: Hash,
+) -> StoreResult<(Hash, Option<Vec<u8>>)> {
+    let stream = store
+        .open_read_stream(&layout.blob_path(hash, &store.features()), 0, None)
+        .await?;
+    let reader = tokio_util::io::SyncIoBridge::new(tokio_util::io::StreamReader::new(stream));
+    let hasher = tokio::task::spawn_blocking(move || {
+        super::import::hash_reader(reader, HashWriter::with_outboard())
+    })
+    .await??;
+    Ok(hasher.finish())
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn hash_blob(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    hash: Hash,
+) -> StoreResult<(Hash, Option<Vec<u8>>)> {
+    use tokio_stream::StreamExt;
+
+    let mut stream = store
+        .open_read_stream(&layout.blob_path(hash, &store.features()), 0, None)
+        .await?;
+    let mut hasher = HashWriter::with_outboard();
+    while let Some(chunk) = stream.next().await {
+        hasher.update(&chunk?);
+    }
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_respects_budget_and_size_cap() {
+        let cache = OutboardCache::default();
+        let big = Bytes::from(vec![0u8; MAX_CACHED_OUTBOARD + 1]);
+        cache.insert(Hash::new(b"big"), big);
+        assert!(cache.get(&Hash::new(b"big")).is_none());
+
+        let entry = Bytes::from(vec![1u8; MAX_CACHED_OUTBOARD]);
+        let n = OUTBOARD_CACHE_BUDGET / MAX_CACHED_OUTBOARD;
+        for i in 0..=n {
+            cache.insert(Hash::new(i.to_le_bytes()), entry.clone());
+        }
+        // The oldest entry made room for the newest.
+        assert!(cache.get(&Hash::new(0usize.to_le_bytes())).is_none());
+        assert!(cache.get(&Hash::new(n.to_le_bytes())).is_some());
+        assert!(cache.inner.lock().unwrap().bytes <= OUTBOARD_CACHE_BUDGET);
+
+        cache.remove(&Hash::new(n.to_le_bytes()));
+        assert!(cache.get(&Hash::new(n.to_le_bytes())).is_none());
+    }
+
+    #[test]
+    fn outboard_len_counts_parent_pairs() {
+        let block = S5_BLOCK_SIZE.bytes() as u64;
+        assert_eq!(outboard_len(0), 0);
+        assert_eq!(outboard_len(block), 0);
+        assert_eq!(outboard_len(block + 1), 64);
+        assert_eq!(outboard_len(5 * block), 4 * 64);
+    }
+}
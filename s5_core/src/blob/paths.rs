@@ -1,7 +1,24 @@
+//! Hash encoding shared by the [`BlobLayout`](super::layout::BlobLayout)s.
+//!
+//! The free `*_for_hash` functions are the [`BlobLayoutSpec::Auto`]
+//! layout, for callers without a `BlobStore` at hand.
+//!
+//! [`BlobLayoutSpec::Auto`]: super::layout::BlobLayoutSpec::Auto
+
 use crate::{Hash, store::StoreFeatures};
 use base64::Engine;
 
+/// Whether the [`BlobLayoutSpec::Auto`](super::layout::BlobLayoutSpec::Auto)
+/// layout fans out on a store with `features`.
+pub(crate) fn fans_out(features: &StoreFeatures) -> bool {
+    features.recommended_max_dir_size < 10000
+}
+
 pub fn path_for_hash(hash: Hash, features: &StoreFeatures) -> String {
+    encode_hash(hash, features, fans_out(features))
+}
+
+pub(crate) fn encode_hash(hash: Hash, features: &StoreFeatures, fan_out: bool) -> String {
     let hash_str = if features.case_sensitive {
         base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hash)
     } else {
@@ -10,7 +27,7 @@ pub fn path_for_hash(hash: Hash, features: &StoreFeatures) -> String {
         String::from_utf8(output).unwrap()
     };
 
-    if features.recommended_max_dir_size < 10000 {
+    if fan_out {
         if features.case_sensitive {
             format!("{}/{}/{}", &hash_str[0..2], &hash_str[2..4], &hash_str[4..],)
         } else {
@@ -39,11 +56,17 @@ pub fn hash_from_blob_path(
     path: &str,
     features: &StoreFeatures,
 ) -> Result<Option<Hash>, std::io::Error> {
-    const PREFIX: &str = "blob3/";
-    if !path.starts_with(PREFIX) {
-        return Ok(None);
+    match path.strip_prefix("blob3/") {
+        Some(rest) => decode_hash(rest, features),
+        None => Ok(None),
     }
-    let rest = &path[PREFIX.len()..];
+}
+
+/// Decodes an encoded hash (fanned out or not) back into a [`Hash`].
+pub(crate) fn decode_hash(
+    rest: &str,
+    features: &StoreFeatures,
+) -> Result<Option<Hash>, std::io::Error> {
     let encoded: String = rest.chars().filter(|&c| c != '/').collect();
     if encoded.is_empty() {
         return Ok(None);
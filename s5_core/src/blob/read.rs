@@ -7,73 +7,79 @@ use bytes::Bytes;
 use std::sync::Arc;
 use tokio_util::io::StreamReader;
 
-use super::paths::{blob_path_for_hash, obao6_path_for_hash};
+use super::layout::BlobLayout;
 
 pub async fn read_as_bytes(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     hash: Hash,
     offset: u64,
     max_len: Option<u64>,
 ) -> StoreResult<Bytes> {
     store
-        .open_read_bytes(
-            &blob_path_for_hash(hash, &store.features()),
-            offset,
-            max_len,
-        )
+        .open_read_bytes(&layout.blob_path(hash, &store.features()), offset, max_len)
         .await
 }
 
 pub async fn read_stream(
     store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
     hash: Hash,
 ) -> StoreResult<Box<dyn tokio::io::AsyncRead + Send + Unpin>> {
     let stream = store
-        .open_read_stream(&blob_path_for_hash(hash, &store.features()), 0, None)
+        .open_read_stream(&layout.blob_path(hash, &store.features()), 0, None)
         .await?;
     Ok(Box::new(StreamReader::new(stream)))
 }
 
-pub async fn provide(store: &Arc<dyn Store>, hash: Hash) -> StoreResult<Vec<BlobLocation>> {
+pub async fn provide(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    hash: Hash,
+) -> StoreResult<Vec<BlobLocation>> {
     store
-        .provide(&blob_path_for_hash(hash, &store.features()))
+        .provide(&layout.blob_path(hash, &store.features()))
         .await
 }
 
 pub async fn provide_obao6(
     outboard_store: &Option<Arc<dyn Store>>,
+    layout: &dyn BlobLayout,
     hash: Hash,
 ) -> StoreResult<Vec<BlobLocation>> {
     if let Some(obao_store) = outboard_store {
         obao_store
-            .provide(&obao6_path_for_hash(hash, &obao_store.features()))
+            .provide(&layout.obao6_path(hash, &obao_store.features()))
             .await
     } else {
         Ok(vec![])
     }
 }
 
-pub async fn contains(store: &Arc<dyn Store>, hash: Hash) -> StoreResult<bool> {
+pub async fn contains(
+    store: &Arc<dyn Store>,
+    layout: &dyn BlobLayout,
+    hash: Hash,
+) -> StoreResult<bool> {
     store
-        .exists(&blob_path_for_hash(hash, &store.features()))
+        .exists(&layout.blob_path(hash, &store.features()))
         .await
 }
 
 pub async fn contains_obao6(
     outboard_store: &Option<Arc<dyn Store>>,
+    layout: &dyn BlobLayout,
     hash: Hash,
 ) -> StoreResult<bool> {
     if let Some(obao_store) = outboard_store {
         obao_store
-            .exists(&obao6_path_for_hash(hash, &obao_store.features()))
+            .exists(&layout.obao6_path(hash, &obao_store.features()))
             .await
     } else {
         Ok(false)
     }
 }
 
-pub async fn size(store: &Arc<dyn Store>, hash: Hash) -> StoreResult<u64> {
-    store
-        .size(&blob_path_for_hash(hash, &store.features()))
-        .await
+pub async fn size(store: &Arc<dyn Store>, layout: &dyn BlobLayout, hash: Hash) -> StoreResult<u64> {
+    store.size(&layout.blob_path(hash, &store.features())).await
 }
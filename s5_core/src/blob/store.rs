@@ -14,22 +14,28 @@ use crate::{
     blob::{
         BlobResult, BlobsDelete, BlobsList, BlobsRead, BlobsWrite, HashStream, ReachableStream,
     },
-    store::{Store, StoreFeatures, StoreResult},
+    store::{ObjectMeta, Store, StoreFeatures, StoreResult},
 };
 
-use super::import;
+use super::access::AccessTracker;
+use super::import::{self, ImportedBlob};
+use super::layout::{BlobLayout, BlobLayoutSpec, STORE_MANIFEST_PATH, StoreManifest};
+use super::outboard::{self, OutboardCache};
 use super::paths;
 use super::read;
 
 /// High-level blob API built on top of a generic `Store`.
 ///
 /// `BlobStore` organizes content-addressed blobs under deterministic
-/// paths derived from their `Hash` and can optionally store Bao
-/// outboard data alongside the main blob data.
+/// paths derived from their `Hash` (see [`BlobLayout`]) and can optionally
+/// store Bao outboard data alongside the main blob data.
 #[derive(Debug, Clone)]
 pub struct BlobStore {
     store: Arc<dyn Store>,
     outboard_store: Option<Arc<dyn Store>>,
+    outboard_cache: Arc<OutboardCache>,
+    layout: Arc<dyn BlobLayout>,
+    access: Option<Arc<dyn AccessTracker>>,
 }
 
 impl BlobStore {
@@ -43,6 +49,9 @@ impl BlobStore {
         Self {
             store: store.clone(),
             outboard_store: Some(store),
+            outboard_cache: Arc::default(),
+            layout: Arc::new(BlobLayoutSpec::Auto),
+            access: None,
         }
     }
 
@@ -53,6 +62,9 @@ impl BlobStore {
         Self {
             store: store.clone(),
             outboard_store: Some(store),
+            outboard_cache: Arc::default(),
+            layout: Arc::new(BlobLayoutSpec::Auto),
+            access: None,
         }
     }
 
@@ -76,6 +88,9 @@ impl BlobStore {
         Self {
             store,
             outboard_store,
+            outboard_cache: Arc::default(),
+            layout: Arc::new(BlobLayoutSpec::Auto),
+            access: None,
         }
     }
 
@@ -89,6 +104,9 @@ impl BlobStore {
         Self {
             store,
             outboard_store: None,
+            outboard_cache: Arc::default(),
+            layout: Arc::new(BlobLayoutSpec::Auto),
+            access: None,
         }
     }
 
@@ -106,11 +124,107 @@ impl BlobStore {
         Self {
             store,
             outboard_store,
+            outboard_cache: Arc::default(),
+            layout: Arc::new(BlobLayoutSpec::Auto),
+            access: None,
         }
     }
 
+    /// Open `store` with the layout recorded in its manifest
+    /// ([`STORE_MANIFEST_PATH`]), writing one for `layout` (resolved
+    /// against the store's features) if there is none yet.
+    ///
+    /// Fails when the manifest names a different layout than an explicitly
+    /// configured (non-[`BlobLayoutSpec::Auto`]) `layout`: blobs written
+    /// under one layout are invisible under another. A manifest that cannot
+    /// be written (read-only stores) is logged and the layout used anyway.
+    pub async fn open(
+        store: Arc<dyn Store>,
+        layout: BlobLayoutSpec,
+        with_outboard: bool,
+    ) -> StoreResult<Self> {
+        let layout = match StoreManifest::load(&*store).await? {
+            Some(manifest) => {
+                if !layout.is_auto() && manifest.layout != layout {
+                    return Err(anyhow::anyhow!(
+                        "store is laid out as {:?} (per {STORE_MANIFEST_PATH}), not the configured {layout:?}",
+                        manifest.layout
+                    ));
+                }
+                manifest.layout
+            }
+            None => {
+                let resolved = layout.resolve(&store.features());
+                if let Err(err) = StoreManifest::new(resolved.clone()).save(&*store).await {
+                    tracing::warn!("blobstore: failed to write {STORE_MANIFEST_PATH}: {err}");
+                }
+                resolved
+            }
+        };
+        Ok(Self::from_arc_with_outboard(store, with_outboard).with_layout(layout))
+    }
+
+    /// Use `layout` instead of [`BlobLayoutSpec::Auto`]. Unlike
+    /// [`Self::open`], this neither reads nor writes the store manifest.
+    pub fn with_layout(mut self, layout: impl BlobLayout + 'static) -> Self {
+        self.layout = Arc::new(layout);
+        self
+    }
+
+    /// The same blob store confined to keys under `prefix` of both the data
+    /// and outboard backends (see [`PrefixedStore`](crate::PrefixedStore)).
+    /// Layout and access tracker carry over; the store manifest is not
+    /// consulted, since the scoped view shares its parent's layout.
+    pub fn scoped(&self, prefix: &str) -> Self {
+        let scope = |store: &Arc<dyn Store>| -> Arc<dyn Store> {
+            Arc::new(crate::PrefixedStore::new(store.clone(), prefix))
+        };
+        Self {
+            store: scope(&self.store),
+            outboard_store: self.outboard_store.as_ref().map(scope),
+            outboard_cache: Arc::default(),
+            layout: self.layout.clone(),
+            access: self.access.clone(),
+        }
+    }
+
+    /// Report reads and provides to `tracker`, making
+    /// [`Self::blob_last_access`] answerable.
+    pub fn with_access_tracker(mut self, tracker: Arc<dyn AccessTracker>) -> Self {
+        self.access = Some(tracker);
+        self
+    }
+
+    /// The access tracker, if this store was built with one.
+    pub fn access_tracker(&self) -> Option<&Arc<dyn AccessTracker>> {
+        self.access.as_ref()
+    }
+
+    /// Record an access of `hash` now. Reads and provides do this
+    /// themselves; call it for accesses that bypass the `BlobStore` (e.g.
+    /// serving a blob straight from a provided location). No-op without a
+    /// tracker.
+    pub fn touch(&self, hash: Hash) {
+        if let Some(tracker) = &self.access {
+            tracker.touch(hash, std::time::SystemTime::now());
+        }
+    }
+
+    /// When `hash` was last read or provided, per the access tracker.
+    /// `None` without a tracker or when no access was recorded.
+    pub async fn blob_last_access(&self, hash: Hash) -> StoreResult<Option<std::time::SystemTime>> {
+        match &self.access {
+            Some(tracker) => tracker.last_access(hash).await,
+            None => Ok(None),
+        }
+    }
+
+    pub fn layout(&self) -> &dyn BlobLayout {
+        &*self.layout
+    }
+
     pub fn blob_path_for_hash(&self, hash: Hash) -> String {
-        paths::blob_path_for_hash(hash, &self.store.features())
+        self.layout.blob_path(hash, &self.store.features())
     }
 
     pub fn obao6_path_for_hash(&self, hash: Hash) -> String {
@@ -118,9 +232,12 @@ impl BlobStore {
             self.outboard_store.is_some(),
             "outboard_store must be present when computing obao6 path"
         );
-        paths::obao6_path_for_hash(hash, &self.outboard_store.as_ref().unwrap().features())
+        self.layout
+            .obao6_path(hash, &self.outboard_store.as_ref().unwrap().features())
     }
 
+    /// Decodes a path under the [`BlobLayoutSpec::Auto`] layout; see
+    /// [`BlobLayout::hash_from_blob_path`] for other layouts.
     pub fn hash_from_blob_path(
         path: &str,
         features: &StoreFeatures,
@@ -130,6 +247,7 @@ impl BlobStore {
 
     /// Deletes a blob and its associated outboard data from the store.
     pub async fn delete(&self, hash: Hash) -> StoreResult<()> {
+        self.outboard_cache.remove(&hash);
         // Delete the main blob data.
         self.store.delete(&self.blob_path_for_hash(hash)).await?;
 
@@ -149,7 +267,7 @@ impl BlobStore {
     }
 
     pub async fn size(&self, hash: Hash) -> StoreResult<u64> {
-        read::size(&self.store, hash).await
+        read::size(&self.store, &*self.layout, hash).await
     }
 
     /// Last-modification time of the blob's backing object, if the store
@@ -160,19 +278,43 @@ impl BlobStore {
     }
 
     pub async fn contains(&self, hash: Hash) -> StoreResult<bool> {
-        read::contains(&self.store, hash).await
+        read::contains(&self.store, &*self.layout, hash).await
     }
 
     pub async fn contains_obao6(&self, hash: Hash) -> StoreResult<bool> {
-        read::contains_obao6(&self.outboard_store, hash).await
+        read::contains_obao6(&self.outboard_store, &*self.layout, hash).await
+    }
+
+    /// Full pre-order bao outboard of a stored blob, `None` for blobs that
+    /// fit in one block.
+    ///
+    /// Served from memory for small outboards, else from the `obao6/`
+    /// sidecar; a missing sidecar is regenerated from the blob (checking it
+    /// still hashes to `hash`) and written back when an outboard store is
+    /// configured.
+    pub async fn outboard(&self, hash: Hash) -> StoreResult<Option<Bytes>> {
+        let size = self.size(hash).await?;
+        outboard::load_or_generate(
+            &self.store,
+            &*self.layout,
+            &self.outboard_store,
+            &self.outboard_cache,
+            hash,
+            size,
+        )
+        .await
     }
 
     pub async fn provide(&self, hash: Hash) -> StoreResult<Vec<BlobLocation>> {
-        read::provide(&self.store, hash).await
+        let locations = read::provide(&self.store, &*self.layout, hash).await?;
+        if !locations.is_empty() {
+            self.touch(hash);
+        }
+        Ok(locations)
     }
 
     pub async fn provide_obao6(&self, hash: Hash) -> StoreResult<Vec<BlobLocation>> {
-        read::provide_obao6(&self.outboard_store, hash).await
+        read::provide_obao6(&self.outboard_store, &*self.layout, hash).await
     }
 
     pub async fn read_as_bytes(
@@ -181,19 +323,23 @@ impl BlobStore {
         offset: u64,
         max_len: Option<u64>,
     ) -> StoreResult<Bytes> {
-        read::read_as_bytes(&self.store, hash, offset, max_len).await
+        let bytes = read::read_as_bytes(&self.store, &*self.layout, hash, offset, max_len).await?;
+        self.touch(hash);
+        Ok(bytes)
     }
 
     pub async fn read_stream(
         &self,
         hash: Hash,
     ) -> StoreResult<Box<dyn tokio::io::AsyncRead + Send + Unpin>> {
-        read::read_stream(&self.store, hash).await
+        let reader = read::read_stream(&self.store, &*self.layout, hash).await?;
+        self.touch(hash);
+        Ok(reader)
     }
 
     /// Insert an in-memory blob of bytes to the blob store
     pub async fn import_bytes(&self, bytes: bytes::Bytes) -> StoreResult<BlobId> {
-        import::import_bytes(&self.store, &self.outboard_store, bytes).await
+        import::import_bytes(&self.store, &*self.layout, &self.outboard_store, bytes).await
     }
 
     /// Insert an in-memory blob without checking if it already exists.
@@ -207,7 +353,8 @@ impl BlobStore {
     /// If the blob already exists, this will overwrite it (which is usually fine
     /// for content-addressed storage since the content is identical).
     pub async fn import_bytes_unchecked(&self, bytes: bytes::Bytes) -> StoreResult<BlobId> {
-        import::import_bytes_unchecked(&self.store, &self.outboard_store, bytes).await
+        import::import_bytes_unchecked(&self.store, &*self.layout, &self.outboard_store, bytes)
+            .await
     }
 
     /// Import a blob from a stream of bytes.
@@ -215,7 +362,7 @@ impl BlobStore {
         &self,
         stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
     ) -> StoreResult<BlobId> {
-        import::import_stream(&self.store, &self.outboard_store, stream).await
+        import::import_stream(&self.store, &*self.layout, &self.outboard_store, stream).await
     }
 
     /// Imports a file from a local path.
@@ -225,7 +372,52 @@ impl BlobStore {
         path: PathBuf,
         on_progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
     ) -> StoreResult<BlobId> {
-        import::import_file(&self.store, &self.outboard_store, path, on_progress).await
+        import::import_file(
+            &self.store,
+            &*self.layout,
+            &self.outboard_store,
+            path,
+            on_progress,
+        )
+        .await
+    }
+
+    /// Like [`Self::import_stream`], but content of at most
+    /// `inline_threshold` bytes is handed back instead of stored
+    /// (see [`import::DEFAULT_INLINE_THRESHOLD`]; 0 disables inlining).
+    pub async fn import_stream_or_inline(
+        &self,
+        stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
+        inline_threshold: u64,
+    ) -> StoreResult<ImportedBlob> {
+        import::import_stream_or_inline(
+            &self.store,
+            &*self.layout,
+            &self.outboard_store,
+            stream,
+            inline_threshold,
+        )
+        .await
+    }
+
+    /// Like [`Self::import_file`], but files of at most `inline_threshold`
+    /// bytes are read and handed back instead of stored.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn import_file_or_inline(
+        &self,
+        path: PathBuf,
+        inline_threshold: u64,
+        on_progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
+    ) -> StoreResult<ImportedBlob> {
+        import::import_file_or_inline(
+            &self.store,
+            &*self.layout,
+            &self.outboard_store,
+            path,
+            inline_threshold,
+            on_progress,
+        )
+        .await
     }
 
     /// All blob hashes currently stored under the `blob3/` prefix, collected.
@@ -243,6 +435,27 @@ impl BlobStore {
         Ok(hashes)
     }
 
+    /// Every stored blob with the size and mtime of its backing object,
+    /// from one [`Store::list_with_meta`] walk. Non-blob paths are skipped
+    /// as in [`BlobsList::list_hashes`]; `ObjectMeta::path` is the blob's
+    /// path in the backing store.
+    pub async fn list_with_meta(
+        &self,
+    ) -> StoreResult<Box<dyn Stream<Item = StoreResult<(Hash, ObjectMeta)>> + Send + Unpin>> {
+        let features = self.store.features();
+        let layout = self.layout.clone();
+        let inner = self.store.list_with_meta().await?;
+        let stream = inner.filter_map(move |item| match item {
+            Ok(meta) => match layout.hash_from_blob_path(&meta.path, &features) {
+                Ok(Some(hash)) => Some(Ok((hash, meta))),
+                Ok(None) => None,
+                Err(e) => Some(Err(anyhow::Error::from(e))),
+            },
+            Err(e) => Some(Err(anyhow::Error::from(e))),
+        });
+        Ok(Box::new(stream))
+    }
+
     /// Ensures all pending writes are durably persisted to storage.
     ///
     /// Call this before creating snapshots or on shutdown to guarantee
@@ -252,6 +465,9 @@ impl BlobStore {
         if let Some(ref outboard) = self.outboard_store {
             outboard.sync().await?;
         }
+        if let Some(ref tracker) = self.access {
+            tracker.flush().await?;
+        }
         Ok(())
     }
 }
@@ -290,6 +506,28 @@ impl BlobsRead for BlobStore {
         let inner = self.read_stream(hash).await?;
         Ok(Box::new(super::VerifyingReader::new(hash, inner)))
     }
+
+    async fn blob_get_outboard(
+        &self,
+        hash: Hash,
+        range: std::ops::Range<u64>,
+    ) -> StoreResult<Option<Bytes>> {
+        let size = self.size(hash).await?;
+        let Some(full) = outboard::load_or_generate(
+            &self.store,
+            &*self.layout,
+            &self.outboard_store,
+            &self.outboard_cache,
+            hash,
+            size,
+        )
+        .await?
+        else {
+            return Ok(Some(Bytes::new()));
+        };
+        let proof = crate::bao::range::range_outboard(hash, size, &full, range)?;
+        Ok(Some(proof.into()))
+    }
 }
 
 #[async_trait::async_trait]
@@ -370,17 +608,19 @@ impl BlobsDelete for BlobStore {
 
 #[async_trait::async_trait]
 impl BlobsList for BlobStore {
-    /// Walk the backing store's listing, decoding each `blob3/` path back to
+    /// Walk the backing store's listing, decoding each blob path back to
     /// its `Hash` and skipping non-blob entries (outboards, foreign paths)
     /// lazily. This is the single enumeration path; the inherent
     /// [`BlobStore::list_hashes`] just collects it.
     async fn list_hashes(&self) -> BlobResult<HashStream> {
         let features = self.store.features();
+        let layout = self.layout.clone();
         let inner = self.store.list().await?;
-        // `StoreFeatures` is `Copy`, so the closure captures it by value; the
-        // decode is synchronous, so `tokio_stream`'s sync `filter_map` fits.
+        // `StoreFeatures` is `Copy` and the layout an `Arc`, so the closure
+        // owns both; the decode is synchronous, so `tokio_stream`'s sync
+        // `filter_map` fits.
         let stream = inner.filter_map(move |item| match item {
-            Ok(path) => match Self::hash_from_blob_path(&path, &features) {
+            Ok(path) => match layout.hash_from_blob_path(&path, &features) {
                 Ok(Some(hash)) => Some(Ok(hash)),
                 Ok(None) => None, // non-blob path (outboard / foreign): skip
                 Err(e) => Some(Err(anyhow::Error::from(e))),
@@ -403,6 +643,8 @@ mod tests {
         features: StoreFeatures,
         entries: std::sync::Arc<Mutex<Vec<String>>>,
         files: std::sync::Arc<Mutex<HashMap<String, Bytes>>>,
+        /// Simulates a backend that cuts `blob3/` writes short.
+        truncate_blob_writes: std::sync::Arc<std::sync::atomic::AtomicBool>,
     }
 
     impl TestStore {
@@ -413,6 +655,7 @@ mod tests {
                     features,
                     entries: entries.clone(),
                     files: std::sync::Arc::new(Mutex::new(HashMap::new())),
+                    truncate_blob_writes: Default::default(),
                 },
                 entries,
             )
@@ -427,18 +670,32 @@ mod tests {
     impl Store for TestStore {
         async fn put_stream(
             &self,
-            _path: &str,
-            _stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
+            path: &str,
+            mut stream: Box<
+                dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static,
+            >,
         ) -> StoreResult<()> {
-            unimplemented!("put_stream not used in tests");
+            let mut buf = Vec::new();
+            while let Some(chunk) = stream.next().await {
+                buf.extend_from_slice(&chunk?);
+            }
+            if path.starts_with("blob3/")
+                && self
+                    .truncate_blob_writes
+                    .load(std::sync::atomic::Ordering::Relaxed)
+            {
+                buf.truncate(buf.len() / 2);
+            }
+            self.insert_bytes(path.to_string(), buf.into());
+            Ok(())
         }
 
         fn features(&self) -> StoreFeatures {
             self.features
         }
 
-        async fn exists(&self, _path: &str) -> StoreResult<bool> {
-            unimplemented!("exists not used in tests");
+        async fn exists(&self, path: &str) -> StoreResult<bool> {
+            Ok(self.files.lock().unwrap().contains_key(path))
         }
 
         async fn put_bytes(&self, _path: &str, _bytes: Bytes) -> StoreResult<()> {
@@ -448,13 +705,18 @@ mod tests {
 
         async fn open_read_stream(
             &self,
-            _path: &str,
-            _offset: u64,
-            _max_len: Option<u64>,
+            path: &str,
+            offset: u64,
+            max_len: Option<u64>,
         ) -> StoreResult<
             Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
         > {
-            unimplemented!("open_read_stream not used in tests");
+            let bytes = self.open_read_bytes(path, offset, max_len).await?;
+            let chunks: Vec<_> = bytes
+                .chunks(10_000)
+                .map(|c| Ok(Bytes::copy_from_slice(c)))
+                .collect();
+            Ok(Box::new(tokio_stream::iter(chunks)))
         }
 
         async fn open_read_bytes(
@@ -475,8 +737,8 @@ mod tests {
             Ok(bytes.slice(start..start + len))
         }
 
-        async fn size(&self, _path: &str) -> StoreResult<u64> {
-            unimplemented!("size not used in tests");
+        async fn size(&self, path: &str) -> StoreResult<u64> {
+            Ok(self.open_read_bytes(path, 0, None).await?.len() as u64)
         }
 
         async fn list(
@@ -489,12 +751,17 @@ mod tests {
             Ok(Box::new(stream))
         }
 
-        async fn delete(&self, _path: &str) -> StoreResult<()> {
-            unimplemented!("delete not used in tests");
+        async fn delete(&self, path: &str) -> StoreResult<()> {
+            self.files.lock().unwrap().remove(path);
+            Ok(())
         }
 
-        async fn rename(&self, _old_path: &str, _new_path: &str) -> StoreResult<()> {
-            unimplemented!("rename not used in tests");
+        async fn rename(&self, old_path: &str, new_path: &str) -> StoreResult<()> {
+            let mut files = self.files.lock().unwrap();
+            if let Some(bytes) = files.remove(old_path) {
+                files.insert(new_path.to_string(), bytes);
+            }
+            Ok(())
         }
 
         async fn provide(
@@ -577,6 +844,191 @@ mod tests {
         assert!(err.to_string().contains("blob integrity check failed for"));
     }
 
+    #[tokio::test]
+    async fn outboard_is_regenerated_persisted_and_serves_range_proofs() {
+        let features = StoreFeatures {
+            supports_rename: true,
+            case_sensitive: true,
+            recommended_max_dir_size: u64::MAX,
+            ..Default::default()
+        };
+        let (store, _) = TestStore::new(features);
+        let (obao_store, _) = TestStore::new(features);
+        let blob_store = BlobStore::with_outboard(store.clone(), Some(obao_store.clone()));
+
+        let block = crate::bao::outboard::S5_BLOCK_SIZE.bytes();
+        let bytes = Bytes::from((0..5 * block + 77).map(|i| i as u8).collect::<Vec<_>>());
+        let hash = Hash::new(&bytes);
+        // Written behind the BlobStore's back, so no sidecar exists yet.
+        store.insert_bytes(blob_store.blob_path_for_hash(hash), bytes.clone());
+        let obao_path = crate::blob::paths::obao6_path_for_hash(hash, &features);
+        assert!(!obao_store.exists(&obao_path).await.unwrap());
+
+        let outboard = blob_store.outboard(hash).await.unwrap().unwrap();
+        assert_eq!(outboard.len(), 5 * 64);
+        assert_eq!(
+            obao_store
+                .open_read_bytes(&obao_path, 0, None)
+                .await
+                .unwrap(),
+            outboard
+        );
+
+        let size = bytes.len() as u64;
+        let range = crate::bao::range::aligned_range(size, 2 * block as u64 + 3, Some(10));
+        let proof = blob_store
+            .blob_get_outboard(hash, range.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let data = &bytes[range.start as usize..range.end as usize];
+        crate::bao::range::verify_range(hash, size, range, &proof, data).unwrap();
+    }
+
+    #[tokio::test]
+    async fn open_records_layout_and_honours_it_later() {
+        let features = StoreFeatures {
+            supports_rename: true,
+            case_sensitive: true,
+            recommended_max_dir_size: u64::MAX,
+            ..Default::default()
+        };
+        let (store, entries) = TestStore::new(features);
+        let store: Arc<dyn Store> = Arc::new(store);
+
+        let blob_store = BlobStore::open(store.clone(), BlobLayoutSpec::FanOut, false)
+            .await
+            .unwrap();
+        let id = blob_store
+            .import_bytes(Bytes::from_static(b"fanned out"))
+            .await
+            .unwrap();
+        let path = blob_store.blob_path_for_hash(id.hash);
+        assert_eq!(path.matches('/').count(), 3);
+        assert_eq!(
+            StoreManifest::load(&*store).await.unwrap().unwrap().layout,
+            BlobLayoutSpec::FanOut
+        );
+
+        // `Auto` would be flat on this store, but the manifest says otherwise.
+        let reopened = BlobStore::open(store.clone(), BlobLayoutSpec::Auto, false)
+            .await
+            .unwrap();
+        assert_eq!(reopened.layout().spec(), BlobLayoutSpec::FanOut);
+        entries
+            .lock()
+            .unwrap()
+            .extend([path, STORE_MANIFEST_PATH.to_string()]);
+        assert_eq!(reopened.list_hashes().await.unwrap(), vec![id.hash]);
+
+        let err = BlobStore::open(store, BlobLayoutSpec::Flat, false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("FanOut"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn small_streams_are_inlined_instead_of_stored() {
+        let features = StoreFeatures {
+            supports_rename: true,
+            case_sensitive: true,
+            recommended_max_dir_size: u64::MAX,
+            ..Default::default()
+        };
+        let (store, _) = TestStore::new(features);
+        let blob_store = BlobStore::without_outboard(store.clone());
+        let chunks = |data: &'static [u8]| {
+            let parts: Vec<_> = data.chunks(3).map(|c| Ok(Bytes::from_static(c))).collect();
+            Box::new(tokio_stream::iter(parts))
+        };
+
+        let small = blob_store
+            .import_stream_or_inline(chunks(b"tiny file"), 16)
+            .await
+            .unwrap();
+        assert_eq!(small.inline_data().unwrap().as_ref(), b"tiny file");
+        assert_eq!(small.id().hash, Hash::new(b"tiny file"));
+        assert!(store.files.lock().unwrap().is_empty());
+
+        let data: &'static [u8] = b"a file well over the inline threshold";
+        let large = blob_store
+            .import_stream_or_inline(chunks(data), 16)
+            .await
+            .unwrap();
+        assert!(large.inline_data().is_none());
+        assert_eq!(large.id().size, data.len() as u64);
+        assert_eq!(
+            blob_store.blob_download(large.id().hash).await.unwrap(),
+            data
+        );
+
+        let disabled = blob_store
+            .import_stream_or_inline(chunks(b"tiny file"), 0)
+            .await
+            .unwrap();
+        assert!(matches!(disabled, ImportedBlob::Stored(_)));
+    }
+
+    fn temp_files(store: &TestStore) -> Vec<String> {
+        let files = store.files.lock().unwrap();
+        files
+            .keys()
+            .filter(|p| p.starts_with(".tmp/"))
+            .cloned()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn staged_import_replaces_truncated_blob() {
+        let features = StoreFeatures {
+            supports_rename: true,
+            case_sensitive: true,
+            recommended_max_dir_size: u64::MAX,
+            ..Default::default()
+        };
+        let (store, _) = TestStore::new(features);
+        let blob_store = BlobStore::without_outboard(store.clone());
+
+        let data = Bytes::from_static(b"a blob whose first upload was interrupted");
+        let hash = Hash::new(&data);
+        // Left behind by a crashed direct write; `exists()` alone trusts it.
+        store.insert_bytes(blob_store.blob_path_for_hash(hash), data.slice(..7));
+
+        let id = blob_store
+            .import_stream(Box::new(tokio_stream::iter([Ok(data.clone())])))
+            .await
+            .unwrap();
+        assert_eq!(id.hash, hash);
+        assert_eq!(blob_store.blob_download(hash).await.unwrap(), data);
+        assert!(temp_files(&store).is_empty());
+    }
+
+    #[tokio::test]
+    async fn copy_finalize_deletes_blob_failing_verification() {
+        let features = StoreFeatures {
+            supports_rename: false,
+            case_sensitive: true,
+            recommended_max_dir_size: u64::MAX,
+            ..Default::default()
+        };
+        let (store, _) = TestStore::new(features);
+        let blob_store = BlobStore::without_outboard(store.clone());
+        let data = Bytes::from_static(b"copied into place without rename");
+        let stream = || Box::new(tokio_stream::iter([Ok(data.clone())]));
+
+        let id = blob_store.import_stream(stream()).await.unwrap();
+        assert_eq!(blob_store.blob_download(id.hash).await.unwrap(), data);
+        blob_store.delete(id.hash).await.unwrap();
+
+        store
+            .truncate_blob_writes
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        let err = blob_store.import_stream(stream()).await.unwrap_err();
+        assert!(err.to_string().contains("blob integrity check failed"));
+        assert!(!blob_store.contains(id.hash).await.unwrap());
+        assert!(temp_files(&store).is_empty());
+    }
+
     #[tokio::test]
     async fn list_hashes_roundtrip_case_insensitive_segmented() {
         let features = StoreFeatures {
@@ -8,7 +8,7 @@ use bytes::Bytes;
 use futures_core::Stream;
 
 use crate::blob::location::BlobLocation;
-use crate::store::{Store, StoreFeatures, StoreResult};
+use crate::store::{ObjectMetaStream, Store, StoreFeatures, StoreResult};
 
 /// A read-through cache decorator over any two [`Store`]s.
 ///
@@ -188,6 +188,10 @@ impl Store for CachingStore {
         self.inner.list().await
     }
 
+    async fn list_with_meta(&self) -> StoreResult<ObjectMetaStream> {
+        self.inner.list_with_meta().await
+    }
+
     async fn delete(&self, path: &str) -> StoreResult<()> {
         // Invalidate the cache, then delete durably.
         let _ = self.cache.delete(path).await;
@@ -119,6 +119,178 @@ impl fmt::Display for Hash {
     }
 }
 
+/// Progress callback: receives the total number of bytes written so far.
+/// Returning an error aborts the write that triggered it.
+type ProgressFn = Box<dyn Fn(u64) -> std::io::Result<()> + Send + Sync>;
+
+/// Incremental BLAKE3 hasher that can build the bao outboard in the same
+/// pass.
+///
+/// Feed it with [`update`](Self::update) or through [`std::io::Write`];
+/// [`finish`](Self::finish) returns the same `(hash, outboard)` pair as
+/// [`compute_outboard`](crate::bao::outboard::compute_outboard), but
+/// without knowing the size up front and without re-reading the data,
+/// so stream imports hash and build verification data in one read.
+///
+/// With an outboard, the writer keeps one chaining value per
+/// [`S5_BLOCK_SIZE`](crate::bao::outboard::S5_BLOCK_SIZE) block (32 bytes
+/// per 64 KiB — half the size of the outboard it produces) plus the current
+/// partial block.
+pub struct HashWriter {
+    state: HashState,
+    len: u64,
+    progress: Option<ProgressFn>,
+}
+
+enum HashState {
+    Plain(Box<blake3::Hasher>),
+    Outboard {
+        /// Chaining values of every completed block, in order.
+        blocks: Vec<blake3::Hash>,
+        /// The current block. A full block is only hashed once more data
+        /// arrives, since the last block of a single-block blob is the root.
+        pending: Vec<u8>,
+    },
+}
+
+const OUTBOARD_BLOCK_BYTES: usize = crate::bao::outboard::S5_BLOCK_SIZE.bytes();
+
+impl HashWriter {
+    /// Hash only.
+    pub fn new() -> Self {
+        Self {
+            state: HashState::Plain(Box::new(blake3::Hasher::new())),
+            len: 0,
+            progress: None,
+        }
+    }
+
+    /// Hash and build the pre-order bao outboard.
+    pub fn with_outboard() -> Self {
+        Self {
+            state: HashState::Outboard {
+                blocks: Vec::new(),
+                pending: Vec::with_capacity(OUTBOARD_BLOCK_BYTES),
+            },
+            len: 0,
+            progress: None,
+        }
+    }
+
+    /// Report the running byte count after every write.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Bytes hashed so far.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn update(&mut self, mut buf: &[u8]) {
+        self.len += buf.len() as u64;
+        match &mut self.state {
+            HashState::Plain(hasher) => {
+                hasher.update(buf);
+            }
+            HashState::Outboard { blocks, pending } => {
+                while !buf.is_empty() {
+                    if pending.len() == OUTBOARD_BLOCK_BYTES {
+                        let start_chunk = (blocks.len() * OUTBOARD_BLOCK_BYTES / 1024) as u64;
+                        blocks.push(block_cv(start_chunk, pending));
+                        pending.clear();
+                    }
+                    let take = (OUTBOARD_BLOCK_BYTES - pending.len()).min(buf.len());
+                    pending.extend_from_slice(&buf[..take]);
+                    buf = &buf[take..];
+                }
+            }
+        }
+    }
+
+    /// Final hash, plus the outboard when built with
+    /// [`with_outboard`](Self::with_outboard) and the data spans more than
+    /// one block (a single block needs no outboard).
+    pub fn finish(self) -> (Hash, Option<Vec<u8>>) {
+        match self.state {
+            HashState::Plain(hasher) => (hasher.finalize().into(), None),
+            HashState::Outboard {
+                mut blocks,
+                pending,
+            } => {
+                if blocks.is_empty() {
+                    return (Hash::new(&pending), None);
+                }
+                let start_chunk = (blocks.len() * OUTBOARD_BLOCK_BYTES / 1024) as u64;
+                blocks.push(block_cv(start_chunk, &pending));
+                let mut outboard = Vec::with_capacity((blocks.len() - 1) * 64);
+                let root = build_pre_order(&blocks, true, &mut outboard);
+                (root.into(), Some(outboard))
+            }
+        }
+    }
+}
+
+impl Default for HashWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::io::Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        if let Some(progress) = &self.progress {
+            progress(self.len)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Non-root chaining value of one block starting at `start_chunk`.
+fn block_cv(start_chunk: u64, data: &[u8]) -> blake3::Hash {
+    use blake3::hazmat::HasherExt;
+    let mut hasher = blake3::Hasher::new();
+    hasher.set_input_offset(start_chunk * 1024);
+    hasher.update(data);
+    hasher.finalize_non_root().into()
+}
+
+/// Fold block chaining values into the BLAKE3 tree, appending each parent's
+/// `(left, right)` pair to `out` in pre-order. The left subtree always
+/// holds the largest power-of-two number of blocks smaller than the total,
+/// which is where BLAKE3 splits once every block but the last is full.
+fn build_pre_order(blocks: &[blake3::Hash], is_root: bool, out: &mut Vec<u8>) -> blake3::Hash {
+    use blake3::hazmat::{Mode, merge_subtrees_non_root, merge_subtrees_root};
+    if let [only] = blocks {
+        return *only;
+    }
+    let split = 1usize << (usize::BITS - 1 - (blocks.len() - 1).leading_zeros());
+    let slot = out.len();
+    out.extend_from_slice(&[0u8; 64]);
+    let left = build_pre_order(&blocks[..split], false, out);
+    let right = build_pre_order(&blocks[split..], false, out);
+    out[slot..slot + 32].copy_from_slice(left.as_bytes());
+    out[slot + 32..slot + 64].copy_from_slice(right.as_bytes());
+    if is_root {
+        merge_subtrees_root(left.as_bytes(), right.as_bytes(), Mode::Hash)
+    } else {
+        merge_subtrees_non_root(left.as_bytes(), right.as_bytes(), Mode::Hash).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,4 +349,57 @@ mod tests {
         assert!(debug.starts_with("Hash("));
         assert!(debug.contains(&hash.to_hex()));
     }
+
+    #[test]
+    fn test_hash_writer_matches_compute_outboard() {
+        use std::io::Write;
+
+        let block = OUTBOARD_BLOCK_BYTES;
+        for size in [
+            0,
+            1,
+            1023,
+            block,
+            block + 1,
+            2 * block,
+            3 * block + 17,
+            8 * block,
+            13 * block + 999,
+        ] {
+            let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let expected =
+                crate::bao::outboard::compute_outboard(&data[..], size as u64, |_| Ok(())).unwrap();
+
+            let mut writer = HashWriter::with_outboard();
+            // Uneven writes so block boundaries fall mid-buffer.
+            for piece in data.chunks(7919) {
+                writer.write_all(piece).unwrap();
+            }
+            assert_eq!(writer.len(), size as u64);
+            assert_eq!(writer.finish(), expected, "size {size}");
+
+            let mut plain = HashWriter::new();
+            plain.update(&data);
+            assert_eq!(plain.finish(), (Hash::new(&data), None), "size {size}");
+        }
+    }
+
+    #[test]
+    fn test_hash_writer_progress() {
+        use std::io::Write;
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let mut writer = HashWriter::new().with_progress(move |n| {
+            log.lock().unwrap().push(n);
+            Ok(())
+        });
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![6, 11]);
+
+        let mut failing = HashWriter::new().with_progress(|_| Err(std::io::Error::other("stop")));
+        assert!(failing.write(b"x").is_err());
+    }
 }
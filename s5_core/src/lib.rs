@@ -12,6 +12,8 @@
 //! - Blob locations and related types (`blob::location::BlobLocation`, etc.)
 //! - Stream and registry message types (`stream::types::StreamKey`,
 //!   `stream::types::StreamMessage`, `stream::types::MessageType`, etc.)
+//! - Typed registry payloads (`stream::payload::RootHead`, `PinSet`,
+//!   `ProviderRecord`, `PeerDescriptor`)
 //!
 //! These types are used directly in network protocols and persistent
 //! metadata; changes to them are considered protocol changes.
@@ -45,6 +47,7 @@ pub mod crypto;
 pub mod hash;
 pub mod identity;
 pub mod pins;
+pub mod prefixed;
 // Store trait is available on all platforms
 pub mod store;
 pub mod stream;
@@ -57,11 +60,11 @@ pub mod testutil;
 // --- Core Public Surface ---
 
 // Blob identifiers & locations (always available - protocol types)
-pub use blob::identifier::{BlobId, MULTIHASH_BLAKE3};
+pub use blob::identifier::{BlobId, BlobUri, MULTIHASH_BLAKE3};
 pub use blob::location::BlobLocation;
 
 // Hash type (always available - protocol type)
-pub use hash::Hash;
+pub use hash::{Hash, HashWriter};
 
 // DID-based identity (always available - protocol types)
 pub use identity::{Did, IdentityBundle};
@@ -83,7 +86,8 @@ pub use blob::{
 
 // Storage traits (available on all platforms)
 pub use caching::CachingStore;
-pub use store::{Store, StoreFeatures, StoreResult};
+pub use prefixed::PrefixedStore;
+pub use store::{ObjectMeta, ObjectMetaStream, Store, StoreFeatures, StoreResult};
 
 // --- Native-only exports ---
 
@@ -1,5 +1,6 @@
 use super::{PinContext, Pins};
 use crate::stream::RegistryApi;
+use crate::stream::payload::{PinSet, RegistryPayload};
 use crate::stream::types::MessageType;
 use crate::{StreamKey, StreamMessage};
 use anyhow::{Result, anyhow};
@@ -148,9 +149,10 @@ impl<R: RegistryApi + Send + Sync + 'static> RegistryPinner<R> {
     async fn get_internal(&self, key: &StreamKey) -> Result<(HashSet<PinContext>, u64)> {
         if let Some(msg) = self.registry.get(key).await? {
             let pinners: HashSet<PinContext> = if let Some(data) = &msg.data {
-                let vec: Vec<PinContext> =
-                    minicbor::decode(data).map_err(|e| anyhow!("CBOR decode failed: {}", e))?;
-                vec.into_iter().collect()
+                PinSet::from_cbor_unchecked(data)
+                    .map_err(|e| anyhow!("{}", e))?
+                    .into_iter()
+                    .collect()
             } else {
                 HashSet::new()
             };
@@ -175,10 +177,7 @@ impl<R: RegistryApi + Send + Sync + 'static> RegistryPinner<R> {
             return self.registry.delete(&key).await;
         }
 
-        let mut pinners_vec: Vec<PinContext> = pinners.into_iter().collect();
-        pinners_vec.sort(); // Sort for deterministic output
-        let data_vec = minicbor::to_vec(&pinners_vec)?;
-        let data_bytes: Bytes = data_vec.into();
+        let data_bytes: Bytes = pinners.into_iter().collect::<PinSet>().to_cbor().into();
         let hash = crate::Hash::new(&data_bytes);
 
         // Construct the StreamMessage.
@@ -0,0 +1,157 @@
+//! `PrefixedStore` — a [`Store`] view confined to one key prefix of another.
+//!
+//! Lets a host keep many tenants (peers) in one physical bucket or
+//! directory: each tenant's `BlobStore` sits on a `PrefixedStore` over the
+//! shared backend, so its blobs, listings and deletes stay under
+//! `peers/<id>/` and per-tenant GC and quota accounting is a prefix scan.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_core::Stream;
+use tokio_stream::StreamExt;
+
+use crate::blob::location::BlobLocation;
+use crate::store::{ObjectMeta, ObjectMetaStream, Store, StoreFeatures, StoreResult};
+
+/// Key prefix under which [`PrefixedStore::for_peer`] confines a peer.
+pub fn peer_prefix(peer: &str) -> String {
+    format!("peers/{peer}/")
+}
+
+/// A [`Store`] that prepends a fixed prefix to every key before handing it
+/// to `inner`, and only lists keys under that prefix (with it stripped).
+#[derive(Debug)]
+pub struct PrefixedStore {
+    inner: std::sync::Arc<dyn Store>,
+    prefix: String,
+}
+
+impl PrefixedStore {
+    /// Confine `inner` to keys under `prefix`. A trailing `/` is added when
+    /// missing, so `"a"` never also matches keys under `"ab/"`.
+    pub fn new(inner: std::sync::Arc<dyn Store>, prefix: impl Into<String>) -> Self {
+        let mut prefix = prefix.into();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        Self { inner, prefix }
+    }
+
+    /// Confine `inner` to `peers/<peer>/` (see [`peer_prefix`]).
+    pub fn for_peer(inner: std::sync::Arc<dyn Store>, peer: &str) -> Self {
+        Self::new(inner, peer_prefix(peer))
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    fn key(&self, path: &str) -> String {
+        format!("{}{path}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl Store for PrefixedStore {
+    fn features(&self) -> StoreFeatures {
+        self.inner.features()
+    }
+
+    async fn exists(&self, path: &str) -> StoreResult<bool> {
+        self.inner.exists(&self.key(path)).await
+    }
+
+    async fn put_bytes(&self, path: &str, bytes: Bytes) -> StoreResult<()> {
+        self.inner.put_bytes(&self.key(path), bytes).await
+    }
+
+    async fn put_stream(
+        &self,
+        path: &str,
+        stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
+    ) -> StoreResult<()> {
+        self.inner.put_stream(&self.key(path), stream).await
+    }
+
+    async fn open_read_stream(
+        &self,
+        path: &str,
+        offset: u64,
+        max_len: Option<u64>,
+    ) -> StoreResult<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>>
+    {
+        self.inner
+            .open_read_stream(&self.key(path), offset, max_len)
+            .await
+    }
+
+    async fn open_read_bytes(
+        &self,
+        path: &str,
+        offset: u64,
+        max_len: Option<u64>,
+    ) -> StoreResult<Bytes> {
+        self.inner
+            .open_read_bytes(&self.key(path), offset, max_len)
+            .await
+    }
+
+    async fn size(&self, path: &str) -> StoreResult<u64> {
+        self.inner.size(&self.key(path)).await
+    }
+
+    async fn list(
+        &self,
+    ) -> StoreResult<Box<dyn Stream<Item = Result<String, std::io::Error>> + Send + Unpin + 'static>>
+    {
+        let prefix = self.prefix.clone();
+        let inner = self.inner.list().await?;
+        Ok(Box::new(inner.filter_map(move |item| match item {
+            Ok(path) => path.strip_prefix(&prefix).map(|p| Ok(p.to_string())),
+            Err(e) => Some(Err(e)),
+        })))
+    }
+
+    async fn list_with_meta(&self) -> StoreResult<ObjectMetaStream> {
+        let prefix = self.prefix.clone();
+        let inner = self.inner.list_with_meta().await?;
+        Ok(Box::new(inner.filter_map(move |item| match item {
+            Ok(meta) => meta.path.strip_prefix(&prefix).map(|p| {
+                Ok(ObjectMeta {
+                    path: p.to_string(),
+                    ..meta
+                })
+            }),
+            Err(e) => Some(Err(e)),
+        })))
+    }
+
+    async fn delete(&self, path: &str) -> StoreResult<()> {
+        self.inner.delete(&self.key(path)).await
+    }
+
+    async fn rename(&self, old_path: &str, new_path: &str) -> StoreResult<()> {
+        self.inner
+            .rename(&self.key(old_path), &self.key(new_path))
+            .await
+    }
+
+    async fn provide(&self, path: &str) -> StoreResult<Vec<BlobLocation>> {
+        self.inner.provide(&self.key(path)).await
+    }
+
+    async fn sync(&self) -> StoreResult<()> {
+        self.inner.sync().await
+    }
+
+    async fn modified(&self, path: &str) -> StoreResult<Option<std::time::SystemTime>> {
+        self.inner.modified(&self.key(path)).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn reflink_file_to(&self, source: &std::path::Path, dest_path: &str) -> StoreResult<()> {
+        self.inner
+            .reflink_file_to(source, &self.key(dest_path))
+            .await
+    }
+}
@@ -49,6 +49,33 @@ pub trait Store: std::fmt::Debug + Send + Sync + 'static {
         &self,
     ) -> StoreResult<Box<dyn Stream<Item = Result<String, std::io::Error>> + Send + Unpin + 'static>>;
 
+    /// Returns a stream of all objects in the store with their size and
+    /// modification time, as [`list`](Store::list) would enumerate them.
+    ///
+    /// For GC, verification and usage statistics, which would otherwise
+    /// follow every listed path with a [`size`](Store::size) and a
+    /// [`modified`](Store::modified) call. The default does exactly that,
+    /// so it costs no less than the per-path calls; backends whose listing
+    /// already carries the metadata (a directory walk, an S3
+    /// `ListObjectsV2` page) should override.
+    async fn list_with_meta(&self) -> StoreResult<ObjectMetaStream> {
+        use futures::StreamExt;
+
+        let mut paths = self.list().await?;
+        let mut objects = Vec::new();
+        while let Some(path) = paths.next().await {
+            let path = path?;
+            let size = self.size(&path).await.map_err(std::io::Error::other)?;
+            let modified = self.modified(&path).await.map_err(std::io::Error::other)?;
+            objects.push(Ok(ObjectMeta {
+                path,
+                size,
+                modified,
+            }));
+        }
+        Ok(Box::new(futures::stream::iter(objects)))
+    }
+
     async fn delete(&self, path: &str) -> StoreResult<()>;
 
     async fn rename(&self, old_path: &str, new_path: &str) -> StoreResult<()>;
@@ -62,13 +89,20 @@ pub trait Store: std::fmt::Debug + Send + Sync + 'static {
 
     /// Stores a stream to a temporary location and returns the path.
     ///
-    /// The default implementation generates a random path in a `.tmp` directory.
+    /// The default implementation generates a random path in a `.tmp` directory
+    /// and removes it again if the write fails, so an interrupted upload
+    /// leaves nothing behind.
     async fn put_temp(
         &self,
         stream: Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static>,
     ) -> StoreResult<String> {
         let path = format!(".tmp/{}", uuid::Uuid::new_v4());
-        self.put_stream(&path, stream).await?;
+        if let Err(e) = self.put_stream(&path, stream).await {
+            if let Err(del) = self.delete(&path).await {
+                tracing::warn!("store: failed to delete partial temp upload {path}: {del}");
+            }
+            return Err(e);
+        }
         Ok(path)
     }
 
@@ -148,6 +182,22 @@ pub trait Store: std::fmt::Debug + Send + Sync + 'static {
     }
 }
 
+/// One object yielded by [`Store::list_with_meta`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMeta {
+    /// Store-relative path, as [`Store::list`] yields it.
+    pub path: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Last-modification time; `None` where [`Store::modified`] would
+    /// return `None`.
+    pub modified: Option<std::time::SystemTime>,
+}
+
+/// Stream returned by [`Store::list_with_meta`].
+pub type ObjectMetaStream =
+    Box<dyn Stream<Item = Result<ObjectMeta, std::io::Error>> + Send + Unpin + 'static>;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct StoreFeatures {
     pub supports_rename: bool,
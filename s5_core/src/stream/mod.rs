@@ -8,8 +8,12 @@
 //! The APIs are designed around the unified `StreamMessage` data structure.
 
 // Protocol types (always available)
+pub mod payload;
 pub mod types;
 
+pub use payload::{
+    PayloadError, PeerDescriptor, PinSet, ProviderRecord, RegistryPayload, RootHead,
+};
 pub use types::{StreamKey, StreamMessage};
 
 use anyhow::Result;
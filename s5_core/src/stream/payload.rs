@@ -0,0 +1,532 @@
+//! Typed CBOR payloads for common registry entries.
+//!
+//! A registry entry only commits to `hash` and optional inline `data`; what
+//! those bytes *mean* is a convention between writer and reader. This module
+//! pins the conventions down for the entries every S5 implementation ends up
+//! writing, so nobody hand-rolls a byte layout:
+//!
+//! - [`RootHead`] — the current root of an FS5 tree and what it replaced.
+//! - [`PinSet`] — the pinners of a blob, as stored by `RegistryPinner`
+//!   under `StreamKey::Blake3HashPin`.
+//! - [`ProviderRecord`] — "node X serves blob H until T".
+//! - [`PeerDescriptor`] — how to reach a node (relay + direct addresses).
+//!
+//! Records are CBOR maps with a leading `version` field, except [`PinSet`],
+//! which keeps the bare sorted array `RegistryPinner` has always written.
+//! Decoding runs [`RegistryPayload::validate`], and every encoding must fit
+//! in [`MAX_INLINE_DATA_SIZE`] so it can ride inline in the entry.
+
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use minicbor::{Decode, Encode};
+
+use super::types::{MAX_INLINE_DATA_SIZE, StreamMessage};
+use crate::Hash;
+use crate::pins::PinContext;
+
+/// Errors from encoding, decoding or validating a registry payload.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum PayloadError {
+    #[error("CBOR decode failed: {0}")]
+    Decode(#[from] minicbor::decode::Error),
+
+    #[error("unsupported {kind} version {version}")]
+    UnsupportedVersion { kind: &'static str, version: u16 },
+
+    #[error("encoded {kind} is {size} bytes (max: {max})")]
+    TooLarge {
+        kind: &'static str,
+        size: usize,
+        max: usize,
+    },
+
+    #[error("invalid {kind}: {reason}")]
+    Invalid {
+        kind: &'static str,
+        reason: &'static str,
+    },
+
+    #[error("registry entry carries no inline data")]
+    MissingData,
+
+    #[error("inline data does not hash to the entry hash")]
+    HashMismatch,
+}
+
+/// A typed value stored as the inline data of a registry entry.
+pub trait RegistryPayload: Sized {
+    /// Short name used in error messages.
+    const KIND: &'static str;
+
+    /// Structural checks beyond what CBOR decoding enforces.
+    fn validate(&self) -> Result<(), PayloadError>;
+
+    fn to_cbor(&self) -> Vec<u8>;
+
+    fn from_cbor_unchecked(bytes: &[u8]) -> Result<Self, PayloadError>;
+
+    /// Validate and encode, refusing anything that can't be stored inline.
+    fn encode_payload(&self) -> Result<Bytes, PayloadError> {
+        self.validate()?;
+        let bytes = self.to_cbor();
+        if bytes.len() > MAX_INLINE_DATA_SIZE {
+            return Err(PayloadError::TooLarge {
+                kind: Self::KIND,
+                size: bytes.len(),
+                max: MAX_INLINE_DATA_SIZE,
+            });
+        }
+        Ok(bytes.into())
+    }
+
+    /// Decode and validate.
+    fn decode_payload(bytes: &[u8]) -> Result<Self, PayloadError> {
+        if bytes.len() > MAX_INLINE_DATA_SIZE {
+            return Err(PayloadError::TooLarge {
+                kind: Self::KIND,
+                size: bytes.len(),
+                max: MAX_INLINE_DATA_SIZE,
+            });
+        }
+        let value = Self::from_cbor_unchecked(bytes)?;
+        value.validate()?;
+        Ok(value)
+    }
+
+    /// Decode the inline data of `message`, checking it against the
+    /// entry's hash first. Signature checks are the receive path's job.
+    fn from_message(message: &StreamMessage) -> Result<Self, PayloadError> {
+        let data = message.data.as_ref().ok_or(PayloadError::MissingData)?;
+        if Hash::new(data) != message.hash {
+            return Err(PayloadError::HashMismatch);
+        }
+        Self::decode_payload(data)
+    }
+}
+
+fn check_version(kind: &'static str, version: u16, current: u16) -> Result<(), PayloadError> {
+    if version == current {
+        Ok(())
+    } else {
+        Err(PayloadError::UnsupportedVersion { kind, version })
+    }
+}
+
+fn invalid(kind: &'static str, reason: &'static str) -> PayloadError {
+    PayloadError::Invalid { kind, reason }
+}
+
+macro_rules! cbor_map_payload {
+    ($ty:ty) => {
+        fn to_cbor(&self) -> Vec<u8> {
+            minicbor::to_vec(self).expect("CBOR encoding into Vec is infallible")
+        }
+
+        fn from_cbor_unchecked(bytes: &[u8]) -> Result<Self, PayloadError> {
+            Ok(minicbor::decode::<$ty>(bytes)?)
+        }
+    };
+}
+
+/// Head of an FS5 tree: the root directory blob plus a back-pointer to the
+/// root it replaced, so readers can walk history without a separate index.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cbor(map)]
+pub struct RootHead {
+    #[n(0)]
+    pub version: u16,
+    /// BLAKE3 of the root directory blob.
+    #[n(1)]
+    #[cbor(with = "minicbor::bytes")]
+    pub root_hash: [u8; 32],
+    /// Root this head replaced, `None` for the first publish.
+    #[n(2)]
+    #[cbor(with = "minicbor::bytes")]
+    pub previous: Option<[u8; 32]>,
+    /// Writer's wall clock at publish time, milliseconds since the epoch.
+    /// Informational only; ordering comes from the entry revision.
+    #[n(3)]
+    pub timestamp_ms: u64,
+}
+
+impl RootHead {
+    pub const CURRENT_VERSION: u16 = 1;
+
+    pub fn new(root_hash: Hash, previous: Option<Hash>, timestamp_ms: u64) -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            root_hash: root_hash.into(),
+            previous: previous.map(Into::into),
+            timestamp_ms,
+        }
+    }
+
+    pub fn root_hash(&self) -> Hash {
+        self.root_hash.into()
+    }
+
+    pub fn previous(&self) -> Option<Hash> {
+        self.previous.map(Into::into)
+    }
+}
+
+impl RegistryPayload for RootHead {
+    const KIND: &'static str = "root head";
+
+    fn validate(&self) -> Result<(), PayloadError> {
+        check_version(Self::KIND, self.version, Self::CURRENT_VERSION)?;
+        if self.previous == Some(self.root_hash) {
+            return Err(invalid(Self::KIND, "previous root equals current root"));
+        }
+        Ok(())
+    }
+
+    cbor_map_payload!(RootHead);
+}
+
+/// Pinners of one blob. Encoded as the sorted, duplicate-free CBOR array of
+/// [`PinContext`]s that `RegistryPinner` writes, so the bytes (and therefore
+/// the entry hash) are a pure function of the set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PinSet {
+    pinners: BTreeSet<PinContext>,
+}
+
+impl PinSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if `context` was not already present.
+    pub fn insert(&mut self, context: PinContext) -> bool {
+        self.pinners.insert(context)
+    }
+
+    /// Returns `true` if `context` was present.
+    pub fn remove(&mut self, context: &PinContext) -> bool {
+        self.pinners.remove(context)
+    }
+
+    pub fn contains(&self, context: &PinContext) -> bool {
+        self.pinners.contains(context)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pinners.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pinners.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PinContext> {
+        self.pinners.iter()
+    }
+}
+
+impl FromIterator<PinContext> for PinSet {
+    fn from_iter<I: IntoIterator<Item = PinContext>>(iter: I) -> Self {
+        Self {
+            pinners: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for PinSet {
+    type Item = PinContext;
+    type IntoIter = std::collections::btree_set::IntoIter<PinContext>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pinners.into_iter()
+    }
+}
+
+impl RegistryPayload for PinSet {
+    const KIND: &'static str = "pin set";
+
+    fn validate(&self) -> Result<(), PayloadError> {
+        // An empty set is stored as a deleted entry, never as `[]`.
+        if self.pinners.is_empty() {
+            return Err(invalid(Self::KIND, "empty pin set"));
+        }
+        Ok(())
+    }
+
+    fn to_cbor(&self) -> Vec<u8> {
+        let sorted: Vec<&PinContext> = self.pinners.iter().collect();
+        minicbor::to_vec(&sorted).expect("CBOR encoding into Vec is infallible")
+    }
+
+    fn from_cbor_unchecked(bytes: &[u8]) -> Result<Self, PayloadError> {
+        let list: Vec<PinContext> = minicbor::decode(bytes)?;
+        if !list.windows(2).all(|w| w[0] < w[1]) {
+            return Err(invalid(Self::KIND, "pinners not sorted and unique"));
+        }
+        Ok(list.into_iter().collect())
+    }
+}
+
+/// Claim that `provider` serves the blob `hash` until `expires_at`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cbor(map)]
+pub struct ProviderRecord {
+    #[n(0)]
+    pub version: u16,
+    #[n(1)]
+    #[cbor(with = "minicbor::bytes")]
+    pub hash: [u8; 32],
+    /// Iroh endpoint id of the serving node.
+    #[n(2)]
+    #[cbor(with = "minicbor::bytes")]
+    pub provider: [u8; 32],
+    /// Blob size in bytes, when the provider knows it.
+    #[n(3)]
+    pub size: Option<u64>,
+    /// Unix seconds after which the claim should be ignored.
+    #[n(4)]
+    pub expires_at: u64,
+}
+
+impl ProviderRecord {
+    pub const CURRENT_VERSION: u16 = 1;
+
+    pub fn new(hash: Hash, provider: [u8; 32], size: Option<u64>, expires_at: u64) -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            hash: hash.into(),
+            provider,
+            size,
+            expires_at,
+        }
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.hash.into()
+    }
+
+    pub fn is_expired(&self, now_secs: u64) -> bool {
+        now_secs >= self.expires_at
+    }
+}
+
+impl RegistryPayload for ProviderRecord {
+    const KIND: &'static str = "provider record";
+
+    fn validate(&self) -> Result<(), PayloadError> {
+        check_version(Self::KIND, self.version, Self::CURRENT_VERSION)?;
+        if self.expires_at == 0 {
+            return Err(invalid(Self::KIND, "missing expiry"));
+        }
+        Ok(())
+    }
+
+    cbor_map_payload!(ProviderRecord);
+}
+
+/// How to dial a node: its endpoint id, an optional home relay and any
+/// direct socket addresses it listens on.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cbor(map)]
+pub struct PeerDescriptor {
+    #[n(0)]
+    pub version: u16,
+    #[n(1)]
+    #[cbor(with = "minicbor::bytes")]
+    pub node_id: [u8; 32],
+    /// Home relay URL (`https://…`).
+    #[n(2)]
+    pub relay_url: Option<String>,
+    /// Direct addresses as `ip:port` strings.
+    #[n(3)]
+    pub direct_addrs: Vec<String>,
+    /// ALPNs the node serves, so a reader can skip peers lacking a protocol.
+    #[n(4)]
+    pub alpns: Vec<String>,
+}
+
+impl PeerDescriptor {
+    pub const CURRENT_VERSION: u16 = 1;
+
+    pub fn new(node_id: [u8; 32]) -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            node_id,
+            relay_url: None,
+            direct_addrs: Vec::new(),
+            alpns: Vec::new(),
+        }
+    }
+
+    /// Direct addresses parsed as sockets. Valid descriptors always parse.
+    pub fn socket_addrs(&self) -> Vec<SocketAddr> {
+        self.direct_addrs
+            .iter()
+            .filter_map(|a| a.parse().ok())
+            .collect()
+    }
+}
+
+impl RegistryPayload for PeerDescriptor {
+    const KIND: &'static str = "peer descriptor";
+
+    fn validate(&self) -> Result<(), PayloadError> {
+        check_version(Self::KIND, self.version, Self::CURRENT_VERSION)?;
+        if self.relay_url.is_none() && self.direct_addrs.is_empty() {
+            return Err(invalid(Self::KIND, "no relay and no direct addresses"));
+        }
+        if let Some(url) = &self.relay_url
+            && !(url.starts_with("https://") || url.starts_with("http://"))
+        {
+            return Err(invalid(Self::KIND, "relay URL is not http(s)"));
+        }
+        if self
+            .direct_addrs
+            .iter()
+            .any(|a| a.parse::<SocketAddr>().is_err())
+        {
+            return Err(invalid(Self::KIND, "direct address is not ip:port"));
+        }
+        if self.alpns.iter().any(String::is_empty) {
+            return Err(invalid(Self::KIND, "empty ALPN"));
+        }
+        Ok(())
+    }
+
+    cbor_map_payload!(PeerDescriptor);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StreamKey;
+    use crate::stream::types::MessageType;
+
+    fn roundtrip<T: RegistryPayload + PartialEq + std::fmt::Debug>(value: T) {
+        let bytes = value.encode_payload().unwrap();
+        assert_eq!(T::decode_payload(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn payloads_roundtrip() {
+        roundtrip(RootHead::new(
+            Hash::new(b"root"),
+            Some(Hash::new(b"old")),
+            42,
+        ));
+        roundtrip(RootHead::new(Hash::new(b"first"), None, 0));
+        roundtrip(
+            [PinContext::LocalFsHead, PinContext::NodeId([3; 32])]
+                .into_iter()
+                .collect::<PinSet>(),
+        );
+        roundtrip(ProviderRecord::new(
+            Hash::new(b"blob"),
+            [1; 32],
+            Some(9),
+            100,
+        ));
+        let mut peer = PeerDescriptor::new([2; 32]);
+        peer.relay_url = Some("https://relay.example".into());
+        peer.direct_addrs = vec!["127.0.0.1:4433".into(), "[::1]:4433".into()];
+        peer.alpns = vec!["s5/blobs/0".into()];
+        roundtrip(peer.clone());
+        assert_eq!(peer.socket_addrs().len(), 2);
+    }
+
+    #[test]
+    fn pin_set_matches_registry_pinner_layout() {
+        let mut legacy = vec![
+            PinContext::NodeId([9; 32]),
+            PinContext::LocalFsSnapshot { root_hash: [1; 32] },
+            PinContext::LocalFsHead,
+        ];
+        legacy.sort();
+        let legacy_bytes = minicbor::to_vec(&legacy).unwrap();
+        let set: PinSet = legacy.iter().cloned().collect();
+        assert_eq!(
+            set.encode_payload().unwrap().as_ref(),
+            legacy_bytes.as_slice()
+        );
+        assert_eq!(PinSet::decode_payload(&legacy_bytes).unwrap(), set);
+    }
+
+    #[test]
+    fn validation_rejects_malformed_payloads() {
+        let bytes =
+            minicbor::to_vec([PinContext::LocalFsHead, PinContext::NodeId([2; 32])]).unwrap();
+        assert!(matches!(
+            PinSet::decode_payload(&bytes),
+            Err(PayloadError::Invalid { .. })
+        ));
+        assert!(PinSet::new().encode_payload().is_err());
+
+        let mut head = RootHead::new(Hash::new(b"r"), None, 1);
+        head.version = 2;
+        assert!(matches!(
+            RootHead::decode_payload(&head.to_cbor()),
+            Err(PayloadError::UnsupportedVersion { version: 2, .. })
+        ));
+        assert!(
+            RootHead::new(Hash::new(b"r"), Some(Hash::new(b"r")), 1)
+                .encode_payload()
+                .is_err()
+        );
+
+        assert!(
+            ProviderRecord::new(Hash::new(b"b"), [0; 32], None, 0)
+                .encode_payload()
+                .is_err()
+        );
+
+        let mut peer = PeerDescriptor::new([2; 32]);
+        assert!(peer.encode_payload().is_err());
+        peer.direct_addrs = vec!["not-an-addr".into()];
+        assert!(peer.encode_payload().is_err());
+        peer.direct_addrs = vec!["10.0.0.1:1".into()];
+        peer.relay_url = Some("ftp://relay".into());
+        assert!(peer.encode_payload().is_err());
+
+        peer.relay_url = None;
+        peer.alpns = vec!["x".repeat(MAX_INLINE_DATA_SIZE)];
+        assert!(matches!(
+            peer.encode_payload(),
+            Err(PayloadError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            PeerDescriptor::decode_payload(&[0xff]),
+            Err(PayloadError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn from_message_checks_hash() {
+        let head = RootHead::new(Hash::new(b"root"), None, 7);
+        let data = head.encode_payload().unwrap();
+        let key = StreamKey::Local([0; 32]);
+        let msg = StreamMessage::new(
+            MessageType::Registry,
+            key,
+            1,
+            Hash::new(&data),
+            Box::new([]),
+            Some(data.clone()),
+        )
+        .unwrap();
+        assert_eq!(RootHead::from_message(&msg).unwrap(), head);
+
+        let mut tampered = msg.clone();
+        tampered.hash = Hash::new(b"other");
+        assert!(matches!(
+            RootHead::from_message(&tampered),
+            Err(PayloadError::HashMismatch)
+        ));
+        tampered.data = None;
+        assert!(matches!(
+            RootHead::from_message(&tampered),
+            Err(PayloadError::MissingData)
+        ));
+    }
+}
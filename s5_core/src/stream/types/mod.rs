@@ -230,6 +230,12 @@ pub enum StreamMessageError {
 
     #[error("signature verification failed")]
     InvalidSignature,
+
+    #[error("inline data does not hash to the message hash")]
+    DataHashMismatch,
+
+    #[error("signing key does not match the message key")]
+    SigningKeyMismatch,
 }
 
 /// Errors that can occur when deserializing a `StreamKey`.
@@ -392,6 +398,29 @@ impl StreamKey {
         !matches!(self, StreamKey::Blake3HashPin(_))
     }
 
+    /// Canonical bytes a writer signs for a message under this key, or
+    /// `None` for key types that carry no signature.
+    ///
+    /// This is the single definition of the signing payload: the signer
+    /// ([`StreamMessage::sign`]) and the verifier ([`StreamMessage::new`],
+    /// [`StreamMessage::verify`]) both go through it. Independent
+    /// implementations can test against it byte for byte. For `Vault`:
+    ///
+    /// `SIG_DOMAIN_TAG_V3 || pubkey(32) || vault_id(16) || revision(8 BE)
+    ///   || MULTIHASH_BLAKE3 || hash(32) || data`
+    pub fn signing_payload(&self, revision: u64, hash: &Hash, data: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            StreamKey::Vault { pubkey, vault_id } => Some(build_vault_registry_signing_input(
+                pubkey,
+                vault_id,
+                revision,
+                hash.as_bytes(),
+                data,
+            )),
+            StreamKey::Local(_) | StreamKey::Blake3HashPin(_) => None,
+        }
+    }
+
     /// Returns the keytype byte for this variant, as it appears on the wire.
     pub fn keytype_byte(&self) -> u8 {
         match self {
@@ -483,6 +512,27 @@ fn verify_ed25519_signature(
         .map_err(|_| StreamMessageError::InvalidSignature)
 }
 
+/// Check `signature` against the key's canonical signing payload. No-op
+/// for unsigned key types (their empty signature is enforced structurally
+/// by `StreamMessage::new`).
+fn verify_key_signature(
+    key: &StreamKey,
+    revision: u64,
+    hash: &Hash,
+    signature: &[u8],
+    data: Option<&[u8]>,
+) -> Result<(), StreamMessageError> {
+    match key {
+        StreamKey::Vault { pubkey, .. } => {
+            let sign_bytes = key
+                .signing_payload(revision, hash, data.unwrap_or(&[]))
+                .expect("vault keys are signed");
+            verify_ed25519_signature(pubkey, signature, &sign_bytes)
+        }
+        StreamKey::Local(_) | StreamKey::Blake3HashPin(_) => Ok(()),
+    }
+}
+
 impl StreamMessage {
     /// Creates a new StreamMessage with validation.
     ///
@@ -552,22 +602,7 @@ impl StreamMessage {
         // the inline data fits the wire format; here we verify the
         // signature under the embedded pubkey using the exact same
         // builder the signer used, so signer/verifier bytes cannot drift.
-        match &key {
-            StreamKey::Vault { pubkey, vault_id } => {
-                let inline_data = data.as_deref().unwrap_or(&[]);
-                let sign_bytes = build_vault_registry_signing_input(
-                    pubkey,
-                    vault_id,
-                    revision,
-                    hash.as_bytes(),
-                    inline_data,
-                );
-                verify_ed25519_signature(pubkey, &signature, &sign_bytes)?;
-            }
-            StreamKey::Local(_) | StreamKey::Blake3HashPin(_) => {
-                // No signature path — already enforced above to be empty.
-            }
-        }
+        verify_key_signature(&key, revision, &hash, &signature, data.as_deref())?;
 
         Ok(Self {
             type_id,
@@ -618,6 +653,31 @@ impl StreamMessage {
         hash: Hash,
         revision: u64,
         data: Option<Bytes>,
+    ) -> Result<Self, StreamMessageError> {
+        Self::sign(
+            signing_key,
+            MessageType::Registry,
+            vault_id,
+            revision,
+            hash,
+            data,
+        )
+    }
+
+    /// Sign and construct a message of any type under
+    /// `StreamKey::Vault { pubkey(signing_key), vault_id }`.
+    ///
+    /// The general form of the sign-then-construct sequence: derive the
+    /// pubkey, sign [`StreamKey::signing_payload`], and hand everything to
+    /// [`Self::new`] (which re-verifies, so a returned message is always
+    /// valid). `data`, if present, is signed alongside `hash`.
+    pub fn sign(
+        signing_key: &SigningKey,
+        type_id: MessageType,
+        vault_id: [u8; VAULT_ID_SIZE],
+        revision: u64,
+        hash: Hash,
+        data: Option<Bytes>,
     ) -> Result<Self, StreamMessageError> {
         let verifying_key: VerifyingKey = signing_key.into();
         let pub_key_bytes = verifying_key.to_bytes();
@@ -633,7 +693,7 @@ impl StreamMessage {
         );
 
         Self::new(
-            MessageType::Registry,
+            type_id,
             StreamKey::Vault {
                 pubkey: pub_key_bytes,
                 vault_id,
@@ -645,6 +705,82 @@ impl StreamMessage {
         )
     }
 
+    /// Canonical signing payload of this message (see
+    /// [`StreamKey::signing_payload`]); `None` for unsigned key types.
+    pub fn signing_payload(&self) -> Option<Vec<u8>> {
+        self.key.signing_payload(
+            self.revision,
+            &self.hash,
+            self.data.as_deref().unwrap_or(&[]),
+        )
+    }
+
+    /// Re-check a message's authenticity and integrity.
+    ///
+    /// `new` already verifies the signature, but every field is `pub`, so
+    /// a message mutated after construction (or built by struct literal)
+    /// can be stale. This re-verifies the signature and, when inline data
+    /// is present, that it hashes to `hash`.
+    pub fn verify(&self) -> Result<(), StreamMessageError> {
+        verify_key_signature(
+            &self.key,
+            self.revision,
+            &self.hash,
+            &self.signature,
+            self.data.as_deref(),
+        )?;
+        if let Some(data) = &self.data
+            && Hash::new(data) != self.hash
+        {
+            return Err(StreamMessageError::DataHashMismatch);
+        }
+        Ok(())
+    }
+
+    /// Whether this message is signed by `signing_key`'s public half.
+    fn is_keyed_by(&self, signing_key: &SigningKey) -> bool {
+        matches!(
+            self.key,
+            StreamKey::Vault { pubkey, .. } if pubkey == signing_key.verifying_key().to_bytes()
+        )
+    }
+
+    /// Build an FS5 root-head entry: the registry pointer from a vault's
+    /// `(pubkey, vault_id)` to the hash of its current root at `revision`.
+    pub fn root_head(
+        signing_key: &SigningKey,
+        vault_id: [u8; VAULT_ID_SIZE],
+        root_hash: Hash,
+        revision: u64,
+    ) -> Result<Self, StreamMessageError> {
+        Self::sign_ed25519_registry(signing_key, vault_id, root_hash, revision)
+    }
+
+    /// Build the root-head entry that supersedes `current` (revision + 1),
+    /// or the first one (revision 1) when there is no entry yet.
+    ///
+    /// Returns `SigningKeyMismatch` if `current` belongs to another writer
+    /// or another vault — advancing it would publish under the wrong key.
+    pub fn next_root_head(
+        signing_key: &SigningKey,
+        vault_id: [u8; VAULT_ID_SIZE],
+        root_hash: Hash,
+        current: Option<&Self>,
+    ) -> Result<Self, StreamMessageError> {
+        let revision = match current {
+            None => 1,
+            Some(entry) => {
+                let same_vault =
+                    matches!(entry.key, StreamKey::Vault { vault_id: v, .. } if v == vault_id);
+                if !entry.is_keyed_by(signing_key) || !same_vault {
+                    return Err(StreamMessageError::SigningKeyMismatch);
+                }
+                entry.revision + 1
+            }
+        };
+        Self::root_head(signing_key, vault_id, root_hash, revision)
+    }
+
     /// Serializes the message for wire transport.
     ///
     /// Wire format (Registry / `StreamKey::Vault`, the v3 vault layout):
@@ -666,4 +666,115 @@ mod tests {
         );
         assert!(msg.is_ok());
     }
+
+    #[test]
+    fn sign_stream_type_roundtrips_and_verifies() {
+        use crate::Hash;
+        use ed25519_dalek::SigningKey;
+
+        let signing = SigningKey::from_bytes(&[5u8; 32]);
+        let data = Bytes::from_static(b"log entry");
+        let msg = StreamMessage::sign(
+            &signing,
+            MessageType::Stream,
+            [0x11; VAULT_ID_SIZE],
+            3,
+            Hash::new(&data),
+            Some(data),
+        )
+        .unwrap();
+        assert_eq!(msg.type_id, MessageType::Stream);
+        msg.verify().unwrap();
+
+        let parsed = StreamMessage::deserialize(msg.serialize()).unwrap();
+        assert_eq!(parsed, msg);
+        parsed.verify().unwrap();
+    }
+
+    #[test]
+    fn signing_payload_is_the_signed_bytes() {
+        use crate::Hash;
+        use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+        let signing = SigningKey::from_bytes(&[6u8; 32]);
+        let vault_id = [0x22; VAULT_ID_SIZE];
+        let msg =
+            StreamMessage::sign_ed25519_registry(&signing, vault_id, Hash::new(b"x"), 9).unwrap();
+
+        let payload = msg.signing_payload().unwrap();
+        assert!(payload.starts_with(crate::stream::types::SIG_DOMAIN_TAG_V3));
+        let sig = Signature::from_slice(&msg.signature).unwrap();
+        VerifyingKey::from(&signing)
+            .verify_strict(&payload, &sig)
+            .unwrap();
+
+        let local = create_test_message(1, 0);
+        assert!(local.signing_payload().is_none());
+    }
+
+    #[test]
+    fn verify_rejects_mutated_fields() {
+        use crate::Hash;
+        use ed25519_dalek::SigningKey;
+
+        let signing = SigningKey::from_bytes(&[7u8; 32]);
+        let data = Bytes::from_static(b"inline");
+        let msg = StreamMessage::sign_ed25519_registry_with_data(
+            &signing,
+            [0; VAULT_ID_SIZE],
+            Hash::new(&data),
+            1,
+            Some(data),
+        )
+        .unwrap();
+
+        let mut bumped = msg.clone();
+        bumped.revision += 1;
+        assert_eq!(bumped.verify(), Err(StreamMessageError::InvalidSignature));
+
+        // Correctly signed, but the inline bytes don't match the hash.
+        let mismatched = StreamMessage::sign_ed25519_registry_with_data(
+            &signing,
+            [0; VAULT_ID_SIZE],
+            Hash::new(b"other"),
+            1,
+            Some(Bytes::from_static(b"inline")),
+        )
+        .unwrap();
+        assert_eq!(
+            mismatched.verify(),
+            Err(StreamMessageError::DataHashMismatch)
+        );
+    }
+
+    #[test]
+    fn next_root_head_advances_revision() {
+        use crate::Hash;
+        use ed25519_dalek::SigningKey;
+
+        let signing = SigningKey::from_bytes(&[8u8; 32]);
+        let vault_id = [0x33; VAULT_ID_SIZE];
+        let first =
+            StreamMessage::next_root_head(&signing, vault_id, Hash::new(b"r1"), None).unwrap();
+        assert_eq!(first.revision, 1);
+        let second =
+            StreamMessage::next_root_head(&signing, vault_id, Hash::new(b"r2"), Some(&first))
+                .unwrap();
+        assert_eq!(second.revision, 2);
+        assert!(second.should_store(Some(&first)));
+
+        let stranger = SigningKey::from_bytes(&[9u8; 32]);
+        let err =
+            StreamMessage::next_root_head(&stranger, vault_id, Hash::new(b"r3"), Some(&second))
+                .unwrap_err();
+        assert_eq!(err, StreamMessageError::SigningKeyMismatch);
+        let err = StreamMessage::next_root_head(
+            &signing,
+            [0; VAULT_ID_SIZE],
+            Hash::new(b"r3"),
+            Some(&second),
+        )
+        .unwrap_err();
+        assert_eq!(err, StreamMessageError::SigningKeyMismatch);
+    }
 }
@@ -67,6 +67,7 @@ impl<'a, S: Store> StoreTests<'a, S> {
         self.test_size().await?;
         self.test_delete().await?;
         self.test_list().await?;
+        self.test_list_with_meta().await?;
         self.test_partial_read().await?;
         self.test_overwrite().await?;
 
@@ -205,6 +206,40 @@ impl<'a, S: Store> StoreTests<'a, S> {
         Ok(())
     }
 
+    /// Test listing with size and modification time.
+    pub async fn test_list_with_meta(&self) -> StoreResult<()> {
+        let files = [("meta_a.bin", 3usize), ("subdir/meta_b.bin", 4096)];
+
+        for (file, len) in &files {
+            self.store
+                .put_bytes(&self.path(file), Bytes::from(vec![7u8; *len]))
+                .await?;
+        }
+
+        let mut stream = self.store.list_with_meta().await?;
+        let mut found = std::collections::HashMap::new();
+
+        while let Some(result) = stream.next().await {
+            let meta = result?;
+            if meta.path.starts_with(&self.prefix) {
+                found.insert(meta.path.clone(), meta);
+            }
+        }
+
+        for (file, len) in &files {
+            let path = self.path(file);
+            let meta = found
+                .get(&path)
+                .unwrap_or_else(|| panic!("list_with_meta should contain {}", path));
+            assert_eq!(meta.size, *len as u64, "size of {}", path);
+            if let Some(mtime) = self.store.modified(&path).await? {
+                assert_eq!(meta.modified, Some(mtime), "mtime of {}", path);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Test partial/range reads.
     pub async fn test_partial_read(&self) -> StoreResult<()> {
         let path = self.path("partial_test.bin");
@@ -4,17 +4,22 @@ use crate::{
     FSResult,
     context::{DirContext, DirContextParentLink, DirHandlePath},
     dir::{DirV1, FileRef},
+    quota::UsageDelta,
 };
 use anyhow::{Context, anyhow};
 use s5_core::{Hash, StreamKey};
 use std::collections::HashMap;
 use tokio::sync::{mpsc, oneshot};
 
+mod encryption;
 mod listing;
 mod merge;
 mod persistence;
+pub use persistence::{ConcurrentWriteConflict, REGISTRY_SAVE_ATTEMPTS};
+mod quota;
 pub(crate) mod sharding;
 mod snapshots;
+mod stat;
 
 pub(crate) type ListResult = FSResult<(Vec<(String, crate::api::CursorKind)>, Option<String>)>;
 
@@ -117,6 +122,34 @@ pub(crate) enum ActorMessage {
         path: String,
         responder: oneshot::Sender<FSResult<DirV1>>,
     },
+    /// Looks up many files at once. Each path carries the caller's index
+    /// so the answers can be put back in order after fanning out.
+    StatMany {
+        paths: Vec<(usize, String)>,
+        responder: oneshot::Sender<stat::StatResult>,
+    },
+    /// Switches encryption on or off for the directory at `path`,
+    /// re-encoding it under the new key.
+    SetEncryption {
+        path: String,
+        enabled: bool,
+        key_source: crate::dir::EncryptionKeySource,
+        responder: oneshot::Sender<FSResult<()>>,
+    },
+    /// Reports the effective encryption policy of the directory at `path`.
+    EncryptionPolicy {
+        path: String,
+        responder: oneshot::Sender<FSResult<crate::dir::EncryptionPolicy>>,
+    },
+    /// Sets the root's quota, recounting its usage first.
+    SetQuota {
+        quota: crate::dir::Quota,
+        responder: oneshot::Sender<FSResult<()>>,
+    },
+    /// Counts live usage of this directory and everything below it.
+    SubtreeUsage {
+        responder: oneshot::Sender<FSResult<crate::dir::Usage>>,
+    },
     MergeSnapshot {
         snapshot: DirV1,
         responder: oneshot::Sender<FSResult<()>>,
@@ -136,6 +169,12 @@ pub(crate) enum ActorMessage {
 pub(crate) enum ActorMessageOp {
     /// An operation on a file, encapsulated as a `Task`.
     FileOp { task: Box<dyn Task + Send> },
+    /// Inserts or replaces a file, subject to the root's quota. Without a
+    /// responder, a rejected write is only logged.
+    PutFile {
+        file_ref: FileRef,
+        responder: Option<oneshot::Sender<FSResult<()>>>,
+    },
     /// Creates a new subdirectory.
     CreateDir {
         enable_encryption: bool,
@@ -143,6 +182,13 @@ pub(crate) enum ActorMessageOp {
     },
 }
 
+/// A child actor of a directory, as chosen by `DirActor::child_route`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ChildRoute {
+    Shard(u8),
+    Dir(String),
+}
+
 /// The actor that manages the state of a single directory.
 struct DirActor {
     pub(super) context: DirContext,
@@ -160,7 +206,9 @@ struct DirActor {
     ///
     /// For the local FS5 root (`DirContextParentLink::LocalFile`), this
     /// is used together with `DirContext.pins` to keep the live head
-    /// (`PinContext::LocalFsHead`) up to date.
+    /// (`PinContext::LocalFsHead`) up to date. For a registry-backed root
+    /// it is the registry head the state is based on, so saves can tell
+    /// when another writer moved it.
     pub(super) current_hash: Option<Hash>,
 
     /// Cached length of the last serialized directory state (unencrypted).
@@ -218,6 +266,7 @@ impl DirActor {
             // instead of an implicitly empty directory state.
             return;
         }
+        self.load_quota();
 
         while let Some(msg) = self.receiver.recv().await {
             if let ActorMessage::Shutdown { responder } = msg {
@@ -251,10 +300,10 @@ impl DirActor {
         }
     }
 
-    /// Routes a path to a child actor (either a direct subdirectory or a shard).
-    /// Returns `Some((handle, remaining_path))` if a child is found, or `None` if
-    /// the path refers to a local entry (or doesn't exist).
-    async fn route_to_child(&mut self, path: &str) -> FSResult<Option<(DirActorHandle, String)>> {
+    /// Which child actor (a direct subdirectory or a shard) owns `path`,
+    /// and the path to hand it. `None` if the path refers to a local entry
+    /// (or doesn't exist).
+    fn child_route(&self, path: &str) -> Option<(ChildRoute, String)> {
         let (dir_name, rest) = match path.split_once('/') {
             Some((d, r)) => (d, r.to_string()),
             None => (path, String::new()),
@@ -266,22 +315,37 @@ impl DirActor {
             if let Some(shards) = &self.state.header.shards
                 && shards.contains_key(&index)
             {
-                let handle = self.open_dir_shard(index, None).await?;
                 // When routing to a shard, we pass the FULL path, because the shard
                 // acts as a container for the entry.
-                return Ok(Some((handle, path.to_string())));
+                return Some((ChildRoute::Shard(index), path.to_string()));
             }
         }
 
         // Check direct child
         if self.state.dirs.contains_key(dir_name) {
-            let handle = self.open_dir(dir_name, None).await?;
             // When routing to a direct child directory, we pass the REST of the path,
             // because we have already traversed 'dir_name'.
-            return Ok(Some((handle, rest)));
+            return Some((ChildRoute::Dir(dir_name.to_string()), rest));
         }
 
-        Ok(None)
+        None
+    }
+
+    async fn open_child(&mut self, route: &ChildRoute) -> FSResult<DirActorHandle> {
+        match route {
+            ChildRoute::Shard(index) => self.open_dir_shard(*index, None).await,
+            ChildRoute::Dir(name) => self.open_dir(name, None).await,
+        }
+    }
+
+    /// Routes a path to a child actor (either a direct subdirectory or a shard).
+    /// Returns `Some((handle, remaining_path))` if a child is found, or `None` if
+    /// the path refers to a local entry (or doesn't exist).
+    async fn route_to_child(&mut self, path: &str) -> FSResult<Option<(DirActorHandle, String)>> {
+        let Some((route, rest)) = self.child_route(path) else {
+            return Ok(None);
+        };
+        Ok(Some((self.open_child(&route).await?, rest)))
     }
 
     /// Processes a single message.
@@ -302,17 +366,41 @@ impl DirActor {
                     // TODO: Add support for read-only file operations.
                     ActorMessageOp::FileOp { task } => {
                         let mut value = self.state.files.remove(&path);
+                        let before = value.clone();
                         task.execute(&mut value);
+                        self.context
+                            .quota
+                            .apply(UsageDelta::between(before.as_ref(), value.as_ref()));
                         if let Some(file_ref) = value {
                             self.state.files.insert(path.clone(), file_ref);
                             self.check_auto_promote(&path).await?;
                         }
                         self.mark_as_dirty().await;
                     }
+                    ActorMessageOp::PutFile {
+                        file_ref,
+                        responder,
+                    } => {
+                        let result = self.put_file(path.clone(), file_ref).await;
+                        match responder {
+                            Some(responder) => {
+                                let _ = responder.send(result);
+                            }
+                            None => {
+                                if let Err(err) = result {
+                                    tracing::error!("fs5: file_put failed for path {path}: {err}");
+                                }
+                            }
+                        }
+                    }
                     ActorMessageOp::CreateDir {
                         enable_encryption,
                         responder,
                     } => {
+                        if let Err(err) = self.check_child_encryption(&path, enable_encryption) {
+                            let _ = responder.send(Err(err));
+                            return Ok(());
+                        }
                         let result = self.create_dir_at(&path, enable_encryption).await;
                         let _ = responder.send(result);
                     }
@@ -410,6 +498,9 @@ impl DirActor {
                 let result = self.list_at_path(path, cursor, limit).await;
                 let _ = responder.send(result);
             }
+            ActorMessage::StatMany { paths, responder } => {
+                self.stat_many(paths, responder).await;
+            }
             ActorMessage::ExportSnapshot { responder } => {
                 let _ = responder.send(Ok(self.state.clone()));
             }
@@ -466,6 +557,27 @@ impl DirActor {
             ActorMessage::MarkAsDirty => {
                 self.mark_as_dirty().await;
             }
+            ActorMessage::SetEncryption {
+                path,
+                enabled,
+                key_source,
+                responder,
+            } => {
+                let result = self.set_encryption_at(path, enabled, key_source).await;
+                let _ = responder.send(result);
+            }
+            ActorMessage::EncryptionPolicy { path, responder } => {
+                let result = self.encryption_policy_at(path).await;
+                let _ = responder.send(result);
+            }
+            ActorMessage::SetQuota { quota, responder } => {
+                let result = self.set_quota(quota).await;
+                let _ = responder.send(result);
+            }
+            ActorMessage::SubtreeUsage { responder } => {
+                let result = self.subtree_usage().await;
+                let _ = responder.send(result);
+            }
             ActorMessage::ExportSnapshotHash { responder } => {
                 let result = self.export_snapshot_hash().await;
                 let _ = responder.send(result);
@@ -669,26 +781,34 @@ impl DirActorHandle {
         Ok(receiver.await?)
     }
 
-    /// Submits a function to be executed by the actor on a `FileRef` at the given path.
-    pub(crate) async fn execute_and_forget<F, R>(&self, path: String, f: F) -> FSResult<()>
-    where
-        F: FnOnce(&mut Value) -> R + Send + 'static,
-        R: Send + 'static,
-    {
-        let (responder, _) = oneshot::channel();
-        let task = Box::new(FunctionTask {
-            func: Box::new(f),
-            responder,
-        });
+    /// Puts `file_ref` at `path` through the quota check. With `wait`, the
+    /// result of the write (including [`crate::QuotaExceeded`]) is returned.
+    pub(crate) async fn put_file(
+        &self,
+        path: String,
+        file_ref: FileRef,
+        wait: bool,
+    ) -> FSResult<()> {
+        let (responder, receiver) = if wait {
+            let (tx, rx) = oneshot::channel();
+            (Some(tx), Some(rx))
+        } else {
+            (None, None)
+        };
         let msg = ActorMessage::PathOp {
             path,
-            op: ActorMessageOp::FileOp { task },
+            op: ActorMessageOp::PutFile {
+                file_ref,
+                responder,
+            },
         };
-
         if self.sender.send(msg).await.is_err() {
             return Err(anyhow!("Actor task has been closed."));
         }
-        Ok(())
+        match receiver {
+            Some(receiver) => receiver.await?,
+            None => Ok(()),
+        }
     }
 
     pub(crate) async fn shutdown(&self) -> FSResult<()> {
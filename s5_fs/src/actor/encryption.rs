@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, anyhow};
+use s5_core::StreamKey;
+use tokio::sync::oneshot;
+
+use crate::{
+    FSResult,
+    dir::{
+        DirRefType, DirV1, ENCRYPTION_TYPE_XCHACHA20_POLY1305, EncryptionKeySource,
+        EncryptionPolicy,
+    },
+};
+
+use super::{ActorMessage, DirActor, DirActorHandle};
+
+impl DirActor {
+    /// Like `route_to_child`, but stops one level early: `Ok(None)` means
+    /// the last component of `path` is a directory entry held by this
+    /// actor, which is where its `DirRef` (and so its encryption) lives.
+    async fn route_to_dir_entry_owner(
+        &mut self,
+        path: &str,
+    ) -> FSResult<Option<(DirActorHandle, String)>> {
+        if !path.contains('/') && self.state.dirs.contains_key(path) {
+            return Ok(None);
+        }
+        match self.route_to_child(path).await? {
+            Some(route) => Ok(Some(route)),
+            None => Err(anyhow!("directory not found: {path}")),
+        }
+    }
+
+    /// Guard for implicit directory creation: a plaintext child under an
+    /// encrypted parent would leak names and file refs that the parent
+    /// was meant to protect, so only `set_encryption` may produce one.
+    pub(super) fn check_child_encryption(
+        &self,
+        path: &str,
+        enable_encryption: bool,
+    ) -> FSResult<()> {
+        if !enable_encryption
+            && self.context.encryption_type.is_some()
+            && !self.state.dirs.contains_key(path)
+        {
+            return Err(anyhow!(
+                "refusing to create plaintext directory '{path}' under an encrypted parent; \
+                 use FS5::set_encryption to opt out explicitly"
+            ));
+        }
+        Ok(())
+    }
+
+    pub(super) async fn encryption_policy_at(
+        &mut self,
+        path: String,
+    ) -> FSResult<EncryptionPolicy> {
+        if path.is_empty() {
+            // The root has no parent to differ from.
+            return Ok(EncryptionPolicy::resolve(
+                false,
+                self.context.encryption_type.is_some(),
+            ));
+        }
+        if let Some((handle, next_path)) = self.route_to_dir_entry_owner(&path).await? {
+            let (responder, receiver) = oneshot::channel();
+            handle
+                .send_msg(ActorMessage::EncryptionPolicy {
+                    path: next_path,
+                    responder,
+                })
+                .await?;
+            return receiver.await?;
+        }
+        let dir_ref = &self.state.dirs[&path];
+        Ok(EncryptionPolicy::resolve(
+            self.context.encryption_type.is_some(),
+            dir_ref.encryption_type.is_some(),
+        ))
+    }
+
+    pub(super) async fn set_encryption_at(
+        &mut self,
+        path: String,
+        enabled: bool,
+        key_source: EncryptionKeySource,
+    ) -> FSResult<()> {
+        if path.is_empty() {
+            return Err(anyhow!(
+                "the root's encryption comes from how it was opened and cannot be changed here"
+            ));
+        }
+        if let Some((handle, next_path)) = self.route_to_dir_entry_owner(&path).await? {
+            let (responder, receiver) = oneshot::channel();
+            handle
+                .send_msg(ActorMessage::SetEncryption {
+                    path: next_path,
+                    enabled,
+                    key_source,
+                    responder,
+                })
+                .await?;
+            return receiver.await?;
+        }
+
+        let dir_ref = &self.state.dirs[&path];
+        let current_key = dir_ref
+            .encryption_type
+            .and(dir_ref.keys.as_ref())
+            .and_then(|keys| keys.get(&0x0e).copied());
+        let new_key = match (enabled, &key_source, current_key) {
+            (false, _, None) => return Ok(()),
+            (false, _, Some(_)) => None,
+            (true, EncryptionKeySource::Generate, Some(_)) => return Ok(()),
+            (true, EncryptionKeySource::Explicit(key), Some(current)) if *key == current => {
+                return Ok(());
+            }
+            (true, EncryptionKeySource::Explicit(key), _) => Some(*key),
+            (true, EncryptionKeySource::Generate, None) => {
+                use chacha20poly1305::aead::OsRng;
+                use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+                Some(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+            }
+        };
+
+        // The child's blob is sealed under its current key, so take its
+        // state out of the running actor, swap the key on the DirRef and
+        // respawn it with that state: its initial save re-encodes under
+        // the new policy.
+        let state = self.take_child_state(&path).await?;
+        let dir_ref = self
+            .state
+            .dirs
+            .get_mut(&path)
+            .context("directory vanished during re-key")?;
+        match new_key {
+            Some(key) => {
+                dir_ref.encryption_type = Some(ENCRYPTION_TYPE_XCHACHA20_POLY1305);
+                dir_ref.keys = Some(BTreeMap::from([(0x0e, key)]));
+            }
+            None => {
+                dir_ref.encryption_type = None;
+                dir_ref.keys = None;
+            }
+        }
+        let handle = self.open_dir(&path, Some(state)).await?;
+
+        // The initial save also reports its hash through our own queue, but
+        // a save processed before that message would pair the new key with
+        // the old ciphertext. Wait for a blob sealed under the new key and
+        // record it now.
+        let (responder, receiver) = oneshot::channel();
+        handle
+            .send_msg(ActorMessage::ExportSnapshotHash { responder })
+            .await?;
+        let hash = receiver.await??;
+        if let Some(dir_ref) = self.state.dirs.get_mut(&path)
+            && matches!(dir_ref.ref_type(), DirRefType::Blake3Hash)
+        {
+            dir_ref.hash = hash.into();
+        }
+        self.mark_as_dirty().await;
+        Ok(())
+    }
+
+    /// Flushes, snapshots and stops the actor for child directory `path`,
+    /// returning its state.
+    async fn take_child_state(&mut self, path: &str) -> FSResult<DirV1> {
+        let handle = self.open_dir(path, None).await?;
+        handle.save_if_dirty().await?;
+        let (responder, receiver) = oneshot::channel();
+        handle
+            .send_msg(ActorMessage::ExportSnapshot { responder })
+            .await?;
+        let state = receiver.await??;
+        handle.shutdown().await?;
+
+        self.dir_handles.remove(path);
+        if let Some(dir_ref) = self.state.dirs.get(path)
+            && matches!(dir_ref.ref_type(), DirRefType::RegistryKey)
+        {
+            self.context
+                .registry_dir_handles
+                .remove(&StreamKey::PublicKeyEd25519(dir_ref.hash));
+        }
+        Ok(state)
+    }
+}
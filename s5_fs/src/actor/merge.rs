@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use crate::dir::{DirRef, DirV1, FileRef};
+use crate::quota::UsageDelta;
 
 use super::sharding::shard_bucket_for;
 use super::{ActorMessage, DirActor};
@@ -143,7 +144,10 @@ impl DirActor {
             if let Some(local_file) = self.state.files.get(&name) {
                 if remote_ts > file_ts(local_file) {
                     // Remote dir wins over local file
-                    self.state.files.remove(&name);
+                    let removed = self.state.files.remove(&name);
+                    self.context
+                        .quota
+                        .apply(UsageDelta::between(removed.as_ref(), None));
                     self.dir_handles.remove(&name);
                     self.state.dirs.insert(name, remote_dir);
                 }
@@ -174,6 +178,9 @@ impl DirActor {
                     // Remote file (including tombstone) wins over local dir
                     self.dir_handles.remove(&name);
                     self.state.dirs.remove(&name);
+                    self.context
+                        .quota
+                        .apply(UsageDelta::between(None, Some(&remote_file)));
                     self.state.files.insert(name.clone(), remote_file);
                 }
                 continue;
@@ -183,12 +190,18 @@ impl DirActor {
             if let Some(local_file) = self.state.files.get(&name) {
                 if remote_ts > file_ts(local_file) {
                     // Remote file wins over local file
+                    self.context
+                        .quota
+                        .apply(UsageDelta::between(Some(local_file), Some(&remote_file)));
                     self.state.files.insert(name.clone(), remote_file);
                 }
                 continue;
             }
 
             // No conflict, insert
+            self.context
+                .quota
+                .apply(UsageDelta::between(None, Some(&remote_file)));
             self.state.files.insert(name, remote_file);
         }
     }
@@ -8,18 +8,34 @@ use tempfile::NamedTempFile;
 
 use crate::{
     FSResult,
-    context::DirContextParentLink,
+    context::{DirContextParentLink, SigningKey},
     dir::{DirV1, ENCRYPTION_TYPE_XCHACHA20_POLY1305},
 };
 #[cfg(not(target_arch = "wasm32"))]
 use s5_core::PinContext;
-use s5_core::{Hash, StreamMessage};
+use s5_core::{Hash, StreamKey, StreamMessage};
 
 use super::{DirActor, DirActorHandle};
 use futures::future::join_all;
 
 type EncodedDir = (Bytes, Option<std::collections::BTreeMap<u8, [u8; 32]>>);
 
+/// Publish attempts for a registry-backed root before giving up with
+/// [`ConcurrentWriteConflict`].
+pub const REGISTRY_SAVE_ATTEMPTS: u32 = 5;
+
+/// Saving a registry-backed root kept losing races against other writers.
+///
+/// Returned inside [`FSResult`] errors; use
+/// `err.downcast_ref::<ConcurrentWriteConflict>()` to tell it apart. Local
+/// changes are kept (merged with everything seen so far), so the save can
+/// simply be retried.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("concurrent writers: registry head kept moving after {attempts} save attempts")]
+pub struct ConcurrentWriteConflict {
+    pub attempts: u32,
+}
+
 impl DirActor {
     /// Loads the directory state from storage.
     pub(super) async fn load(&mut self) -> FSResult<()> {
@@ -51,12 +67,8 @@ impl DirActor {
 
             DirContextParentLink::RegistryKey { public_key, .. } => {
                 if let Some(entry) = self.context.registry.get(public_key).await? {
-                    let bytes = self
-                        .context
-                        .meta_blob_store
-                        .read_as_bytes(entry.hash, 0, None)
-                        .await?;
-                    let decrypted = Self::decrypt_if_needed(bytes, &self.context)?;
+                    self.current_hash = Some(entry.hash);
+                    let decrypted = self.read_registry_snapshot(entry.hash).await?;
                     self.last_serialized_len = decrypted.len();
                     DirV1::from_bytes(&decrypted)?
                 } else {
@@ -68,6 +80,16 @@ impl DirActor {
         Ok(())
     }
 
+    /// Reads and decrypts the snapshot blob a registry entry points at.
+    async fn read_registry_snapshot(&self, hash: Hash) -> FSResult<Bytes> {
+        let bytes = self
+            .context
+            .meta_blob_store
+            .read_as_bytes(hash, 0, None)
+            .await?;
+        Self::decrypt_if_needed(bytes, &self.context)
+    }
+
     /// Decrypts directory bytes if encryption is enabled.
     fn decrypt_if_needed(bytes: Bytes, context: &crate::context::DirContext) -> FSResult<Bytes> {
         if let Some(enc_type) = context.encryption_type {
@@ -150,6 +172,7 @@ impl DirActor {
 
     /// Saves the current directory state to storage.
     pub(super) async fn save(&mut self, notify_parent: bool) -> FSResult<Option<Hash>> {
+        self.store_quota();
         let bytes = self.encode_state_bytes()?;
 
         match &mut self.context.link {
@@ -215,22 +238,85 @@ impl DirActor {
             DirContextParentLink::RegistryKey {
                 public_key,
                 signing_key,
-            } => {
-                let hash = self.context.meta_blob_store.import_bytes(bytes).await?;
-                if let Some(signing_key) = signing_key.as_ref() {
-                    let current = self.context.registry.get(public_key).await?;
-                    let revision = current.as_ref().map_or(0, |entry| entry.revision + 1);
-                    let dalek_key = ed25519_dalek::SigningKey::from_bytes(signing_key.as_bytes());
-                    // Legacy s5_fs (v1) registry-backed dirs use the
-                    // non-vault PublicKeyEd25519 entry shape — they
-                    // predate the per-vault namespace tag.
-                    let entry =
-                        StreamMessage::sign_ed25519_legacy(&dalek_key, hash.hash, revision)?;
-                    self.context.registry.set(entry).await?;
+            } => match signing_key.clone() {
+                Some(signing_key) => {
+                    let public_key = *public_key;
+                    self.save_to_registry(public_key, signing_key, bytes)
+                        .await?;
+                    Ok(None)
                 }
-                Ok(None)
+                None => {
+                    self.context.meta_blob_store.import_bytes(bytes).await?;
+                    Ok(None)
+                }
+            },
+        }
+    }
+
+    /// Publishes the root snapshot under `public_key` with optimistic
+    /// concurrency.
+    ///
+    /// `current_hash` is the registry head our state was loaded from (or
+    /// last published). If the head moved since, another writer got there
+    /// first: their snapshot is merged into ours (LWW, as in
+    /// [`Self::merge_snapshot`]) and we publish on top of their revision.
+    /// After publishing, the head is read back; losing a race to a writer
+    /// with the same revision retries, up to [`REGISTRY_SAVE_ATTEMPTS`]
+    /// times before failing with [`ConcurrentWriteConflict`].
+    async fn save_to_registry(
+        &mut self,
+        public_key: StreamKey,
+        signing_key: SigningKey,
+        mut bytes: Bytes,
+    ) -> FSResult<()> {
+        let dalek_key = ed25519_dalek::SigningKey::from_bytes(signing_key.as_bytes());
+        for attempt in 1..=REGISTRY_SAVE_ATTEMPTS {
+            let current = self.context.registry.get(&public_key).await?;
+            if let Some(entry) = &current
+                && self.current_hash != Some(entry.hash)
+            {
+                tracing::debug!(
+                    attempt,
+                    "registry head moved to {}; merging concurrent write",
+                    entry.hash
+                );
+                let remote = self.read_registry_snapshot(entry.hash).await?;
+                self.merge_snapshot(DirV1::from_bytes(&remote)?).await?;
+                // Shard merges land in the shard actors; pull their new
+                // hashes into our header before re-encoding.
+                self.save_children().await;
+                self.current_hash = Some(entry.hash);
+                self.store_quota();
+                bytes = self.encode_state_bytes()?;
+            }
+
+            let hash = self
+                .context
+                .meta_blob_store
+                .import_bytes(bytes.clone())
+                .await?
+                .hash;
+            let revision = current.as_ref().map_or(0, |entry| entry.revision + 1);
+            // Legacy s5_fs (v1) registry-backed dirs use the
+            // non-vault PublicKeyEd25519 entry shape — they
+            // predate the per-vault namespace tag.
+            let entry = StreamMessage::sign_ed25519_legacy(&dalek_key, hash, revision)?;
+            self.context.registry.set(entry).await?;
+
+            match self.context.registry.get(&public_key).await? {
+                Some(head) if head.hash == hash => {
+                    self.current_hash = Some(hash);
+                    return Ok(());
+                }
+                // Another writer published the same revision first; their
+                // entry is merged on the next attempt.
+                _ => tracing::debug!(attempt, "lost registry race at revision {revision}"),
             }
         }
+        Err(ConcurrentWriteConflict {
+            attempts: REGISTRY_SAVE_ATTEMPTS,
+        }
+        .into())
     }
 
     /// Flushes this directory and children when dirty.
@@ -249,61 +335,7 @@ impl DirActor {
 
         if self.dirty || is_root {
             self.shard_if_needed().await?;
-
-            // Collect handles to iterate over results later
-            let shard_handles: Vec<(u8, DirActorHandle)> = self
-                .dir_shard_handles
-                .iter()
-                .map(|(k, v)| (*k, v.clone()))
-                .collect();
-            let dir_handles: Vec<(String, DirActorHandle)> = self
-                .dir_handles
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
-
-            let shard_futures = shard_handles.iter().map(|(_, h)| h.save_if_dirty());
-            let dir_futures = dir_handles.iter().map(|(_, h)| h.save_if_dirty());
-
-            let shard_results = join_all(shard_futures).await;
-            let dir_results = join_all(dir_futures).await;
-
-            // Process shard updates
-            for ((index, _), result) in shard_handles.into_iter().zip(shard_results) {
-                match result {
-                    Ok(Some(hash)) => {
-                        if let Some(shards) = self.state.header.shards.as_mut()
-                            && let Some(dir_ref) = shards.get_mut(&index)
-                            && dir_ref.hash != *hash.as_bytes()
-                        {
-                            dir_ref.hash = hash.into();
-                            self.dirty = true;
-                        }
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        tracing::error!("failed to save shard {index}: {e}");
-                    }
-                }
-            }
-
-            // Process dir updates
-            for ((name, _), result) in dir_handles.into_iter().zip(dir_results) {
-                match result {
-                    Ok(Some(hash)) => {
-                        if let Some(dir_ref) = self.state.dirs.get_mut(&name)
-                            && dir_ref.hash != *hash.as_bytes()
-                        {
-                            dir_ref.hash = hash.into();
-                            self.dirty = true;
-                        }
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        tracing::error!("failed to save child dir {name}: {e}");
-                    }
-                }
-            }
+            self.save_children().await;
 
             if self.dirty {
                 let res = self.save(false).await?;
@@ -315,4 +347,64 @@ impl DirActor {
 
         Ok(None)
     }
+
+    /// Saves dirty shard and child actors, updating their refs (and
+    /// marking this directory dirty) where a hash changed. Failures are
+    /// logged; the child stays dirty and is retried on the next save.
+    async fn save_children(&mut self) {
+        // Collect handles to iterate over results later
+        let shard_handles: Vec<(u8, DirActorHandle)> = self
+            .dir_shard_handles
+            .iter()
+            .map(|(k, v)| (*k, v.clone()))
+            .collect();
+        let dir_handles: Vec<(String, DirActorHandle)> = self
+            .dir_handles
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let shard_futures = shard_handles.iter().map(|(_, h)| h.save_if_dirty());
+        let dir_futures = dir_handles.iter().map(|(_, h)| h.save_if_dirty());
+
+        let shard_results = join_all(shard_futures).await;
+        let dir_results = join_all(dir_futures).await;
+
+        // Process shard updates
+        for ((index, _), result) in shard_handles.into_iter().zip(shard_results) {
+            match result {
+                Ok(Some(hash)) => {
+                    if let Some(shards) = self.state.header.shards.as_mut()
+                        && let Some(dir_ref) = shards.get_mut(&index)
+                        && dir_ref.hash != *hash.as_bytes()
+                    {
+                        dir_ref.hash = hash.into();
+                        self.dirty = true;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!("failed to save shard {index}: {e}");
+                }
+            }
+        }
+
+        // Process dir updates
+        for ((name, _), result) in dir_handles.into_iter().zip(dir_results) {
+            match result {
+                Ok(Some(hash)) => {
+                    if let Some(dir_ref) = self.state.dirs.get_mut(&name)
+                        && dir_ref.hash != *hash.as_bytes()
+                    {
+                        dir_ref.hash = hash.into();
+                        self.dirty = true;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!("failed to save child dir {name}: {e}");
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use anyhow::anyhow;
+use tokio::sync::oneshot;
+
+use crate::{
+    FSResult,
+    dir::{FileRef, Quota, Usage},
+    quota::UsageDelta,
+};
+
+use super::{ActorMessage, DirActor};
+
+impl DirActor {
+    /// Seeds the shared tracker from the root header. A root that was never
+    /// counted but is still empty starts at zero; anything else stays
+    /// uncounted until a quota is set.
+    pub(super) fn load_quota(&self) {
+        if !self.context.quota_root {
+            return;
+        }
+        let header = &self.state.header;
+        let is_empty =
+            self.state.files.is_empty() && self.state.dirs.is_empty() && header.shards.is_none();
+        let usage = header.usage.or_else(|| is_empty.then(Usage::default));
+        self.context
+            .quota
+            .load(header.quota.unwrap_or_default(), usage);
+    }
+
+    /// Copies the tracker back into the root header before it is encoded.
+    pub(super) fn store_quota(&mut self) {
+        if !self.context.quota_root {
+            return;
+        }
+        let quota = self.context.quota.quota();
+        self.state.header.quota = (quota != Quota::default()).then_some(quota);
+        self.state.header.usage = self.context.quota.usage();
+    }
+
+    /// Replaces the entry at `path` with `file_ref` if the root's quota
+    /// allows the growth.
+    pub(super) async fn put_file(&mut self, path: String, file_ref: FileRef) -> FSResult<()> {
+        let delta = UsageDelta::between(self.state.files.get(&path), Some(&file_ref));
+        self.context.quota.try_apply(delta)?;
+        self.state.files.insert(path.clone(), file_ref);
+        self.check_auto_promote(&path).await?;
+        self.mark_as_dirty().await;
+        Ok(())
+    }
+
+    pub(super) async fn set_quota(&mut self, quota: Quota) -> FSResult<()> {
+        if !self.context.quota_root {
+            return Err(anyhow!("quotas can only be set on the root of an FS5 tree"));
+        }
+        // Recount on every change: cheap next to how rarely quotas change,
+        // and it corrects any drift from merges that replaced whole dirs.
+        let usage = self.subtree_usage().await?;
+        self.context.quota.set_usage(usage);
+        self.context.quota.set_quota(quota);
+        self.mark_as_dirty().await;
+        Ok(())
+    }
+
+    /// Live usage of this directory and everything below it.
+    pub(super) async fn subtree_usage(&mut self) -> FSResult<Usage> {
+        let mut usage = Usage::default();
+        for file_ref in self.state.files.values() {
+            let file = Usage::of_file(Some(file_ref));
+            usage.bytes += file.bytes;
+            usage.files += file.files;
+        }
+
+        let mut children = Vec::new();
+        for name in self.state.dirs.keys().cloned().collect::<Vec<_>>() {
+            children.push(self.open_dir(&name, None).await?);
+        }
+        let shard_indices: Vec<u8> = self
+            .state
+            .header
+            .shards
+            .as_ref()
+            .map(|shards| shards.keys().copied().collect())
+            .unwrap_or_default();
+        for index in shard_indices {
+            children.push(self.open_dir_shard(index, None).await?);
+        }
+
+        for handle in children {
+            let (responder, receiver) = oneshot::channel();
+            handle
+                .send_msg(ActorMessage::SubtreeUsage { responder })
+                .await?;
+            let child = receiver.await??;
+            usage.bytes += child.bytes;
+            usage.files += child.files;
+        }
+        Ok(usage)
+    }
+}
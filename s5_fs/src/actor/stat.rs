@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use tokio::sync::oneshot;
+
+use super::{ActorMessage, ChildRoute, DirActor};
+use crate::dir::FileRef;
+
+/// Answers to an `ActorMessage::StatMany`, keyed by the caller's index.
+pub(crate) type StatResult = Vec<(usize, Option<FileRef>)>;
+
+impl DirActor {
+    /// Answers the paths stored in this directory and forwards the rest
+    /// to the child actors that own them, one message per child however
+    /// many paths it gets.
+    ///
+    /// The combined reply is assembled in a spawned task, so this actor
+    /// never blocks on its children (which may themselves be waiting to
+    /// send this actor a hash update). Paths whose child cannot be opened
+    /// or has shut down answer `None`, as `file_get` would.
+    pub(super) async fn stat_many(
+        &mut self,
+        paths: Vec<(usize, String)>,
+        responder: oneshot::Sender<StatResult>,
+    ) {
+        let mut results: StatResult = Vec::with_capacity(paths.len());
+        let mut groups: HashMap<ChildRoute, Vec<(usize, String)>> = HashMap::new();
+        for (index, path) in paths {
+            match self.child_route(&path) {
+                Some((route, rest)) => groups.entry(route).or_default().push((index, rest)),
+                None => {
+                    let file_ref = self
+                        .state
+                        .files
+                        .get(&path)
+                        .filter(|f| !f.is_tombstone())
+                        .cloned();
+                    results.push((index, file_ref));
+                }
+            }
+        }
+
+        if groups.is_empty() {
+            let _ = responder.send(results);
+            return;
+        }
+
+        let mut pending = Vec::with_capacity(groups.len());
+        for (route, paths) in groups {
+            let indices: Vec<usize> = paths.iter().map(|(i, _)| *i).collect();
+            let (tx, rx) = oneshot::channel();
+            let sent = match self.open_child(&route).await {
+                Ok(handle) => handle
+                    .send_msg(ActorMessage::StatMany {
+                        paths,
+                        responder: tx,
+                    })
+                    .await
+                    .is_ok(),
+                Err(err) => {
+                    tracing::debug!("fs5: stat_many could not open {route:?}: {err}");
+                    false
+                }
+            };
+            if sent {
+                pending.push((indices, rx));
+            } else {
+                results.extend(indices.into_iter().map(|i| (i, None)));
+            }
+        }
+
+        crate::spawn::spawn_task(async move {
+            for (indices, rx) in pending {
+                match rx.await {
+                    Ok(answers) => results.extend(answers),
+                    Err(_) => results.extend(indices.into_iter().map(|i| (i, None))),
+                }
+            }
+            let _ = responder.send(results);
+        });
+    }
+}
@@ -4,12 +4,15 @@ use crate::{
     FSResult,
     actor::{ActorMessage, ActorMessageOp, DirActorHandle},
     context::DirContext,
-    dir::{DirV1, FileRef},
+    dir::{DirV1, EncryptionKeySource, EncryptionPolicy, FileRef, Usage},
+    journal::{Journal, JournalOp},
+    quota::{Quota, QuotaTracker},
 };
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as B64_URL};
 use chrono::Utc;
 use minicbor::{CborLen, Decode, Encode};
 use s5_core::Hash;
+use std::sync::Arc;
 use tokio::sync::oneshot;
 
 /// The main API for interacting with the S5 file system.
@@ -20,6 +23,8 @@ use tokio::sync::oneshot;
 #[derive(Clone)]
 pub struct FS5 {
     root: DirActorHandle,
+    quota: Arc<QuotaTracker>,
+    journal: Option<Journal>,
 }
 
 #[derive(Encode, Decode, CborLen, Clone, Debug)]
@@ -74,8 +79,73 @@ impl FS5 {
     /// # Ok(()) }
     /// ```
     pub fn open(context: DirContext) -> Self {
+        let quota = context.quota.clone();
         let root = DirActorHandle::spawn(context, None, None);
-        Self { root }
+        Self {
+            root,
+            quota,
+            journal: None,
+        }
+    }
+
+    /// Records every mutation in `journal` until the next successful
+    /// [`FS5::save`], so a client that crashes before saving can
+    /// [`replay_journal`](Self::replay_journal) on restart. Meant for
+    /// registry-backed roots; see [`crate::journal`].
+    ///
+    /// With autosave enabled the journal is only trimmed by explicit saves.
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Mutations not yet covered by a successful save, oldest first.
+    /// Empty without a journal.
+    pub fn pending_changes(&self) -> Vec<JournalOp> {
+        self.journal
+            .as_ref()
+            .map(Journal::pending)
+            .unwrap_or_default()
+    }
+
+    /// Applies the journal's pending operations (left over from a session
+    /// that never saved them) and saves. Returns how many were replayed.
+    ///
+    /// Call once, right after opening the root with
+    /// [`with_journal`](Self::with_journal).
+    pub async fn replay_journal(&self) -> FSResult<usize> {
+        let Some(journal) = &self.journal else {
+            return Ok(0);
+        };
+        let ops = journal.pending();
+        // Already journaled, so apply them without recording again.
+        let unjournaled = FS5 {
+            journal: None,
+            ..self.clone()
+        };
+        for op in &ops {
+            match op.clone() {
+                JournalOp::Put { path, file_ref } => {
+                    unjournaled.file_put_sync(&path, file_ref).await?
+                }
+                JournalOp::Delete { path } => unjournaled.file_delete(&path).await?,
+                JournalOp::CreateDir {
+                    path,
+                    enable_encryption,
+                } => unjournaled.create_dir(&path, enable_encryption).await?,
+            }
+        }
+        if !ops.is_empty() {
+            tracing::info!("fs5: replayed {} journaled operations", ops.len());
+            self.save().await?;
+        }
+        Ok(ops.len())
+    }
+
+    fn record(&self, op: impl FnOnce() -> JournalOp) {
+        if let Some(journal) = &self.journal {
+            journal.record(op());
+        }
     }
 
     /// Enables debounced autosave.
@@ -108,7 +178,17 @@ impl FS5 {
     /// # Ok(()) }
     /// ```
     pub async fn save(&self) -> FSResult<()> {
+        // Only a root save makes journaled operations durable; anything
+        // recorded while it runs stays pending.
+        let covered = self
+            .journal
+            .as_ref()
+            .filter(|j| j.is_root())
+            .map(Journal::len);
         self.root.save_if_dirty().await?;
+        if let (Some(journal), Some(n)) = (&self.journal, covered) {
+            journal.trim(n);
+        }
         Ok(())
     }
 
@@ -199,6 +279,8 @@ impl FS5 {
     ///
     /// - Idempotent: creating the same directory again is a no-op.
     /// - If files exist under the `path/` prefix, they are migrated into the new subdir.
+    /// - Fails when `enable_encryption` is false and the parent is encrypted;
+    ///   opting a subtree out of encryption goes through [`FS5::set_encryption`].
     ///
     /// ```rust,no_run
     /// # use s5_fs::{DirContext, FS5};
@@ -223,6 +305,63 @@ impl FS5 {
                 },
             })
             .await?;
+        receiver.await??;
+        self.record(|| JournalOp::CreateDir {
+            path: path.to_owned(),
+            enable_encryption,
+        });
+        Ok(())
+    }
+
+    /// Turns encryption on or off for the existing directory at `path` and
+    /// re-encodes it under the new key. Already-matching settings are a no-op.
+    ///
+    /// - This is the only way to get a plaintext directory under an encrypted
+    ///   parent; [`FS5::encryption_policy`] reports it as
+    ///   [`EncryptionPolicy::PlaintextOptOut`].
+    /// - Only the directory itself is re-keyed. Its subdirectories keep their
+    ///   own keys, which now sit in the re-encoded listing.
+    /// - The root can't be changed here; its encryption is fixed by the
+    ///   [`DirContext`] it was opened with.
+    ///
+    /// ```rust,no_run
+    /// # use s5_fs::{DirContext, EncryptionKeySource, FS5};
+    /// # use tempfile::tempdir;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let tmp = tempdir()?; let ctx = DirContext::open_local_root(tmp.path())?; let fs = FS5::open(ctx);
+    /// fs.create_dir("photos", false).await?;
+    /// fs.set_encryption("photos", true, EncryptionKeySource::Generate).await?;
+    /// fs.save().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn set_encryption(
+        &self,
+        path: &str,
+        enabled: bool,
+        key_source: EncryptionKeySource,
+    ) -> FSResult<()> {
+        let (responder, receiver) = oneshot::channel();
+        self.root
+            .send_msg(ActorMessage::SetEncryption {
+                path: path.trim_matches('/').to_owned(),
+                enabled,
+                key_source,
+                responder,
+            })
+            .await?;
+        receiver.await?
+    }
+
+    /// Effective encryption policy of the directory at `path` (`""` for the root).
+    pub async fn encryption_policy(&self, path: &str) -> FSResult<EncryptionPolicy> {
+        let (responder, receiver) = oneshot::channel();
+        self.root
+            .send_msg(ActorMessage::EncryptionPolicy {
+                path: path.trim_matches('/').to_owned(),
+                responder,
+            })
+            .await?;
         receiver.await?
     }
 
@@ -230,6 +369,8 @@ impl FS5 {
     ///
     /// - Returns immediately after enqueueing; use [`FS5::file_put_sync`] to await application.
     /// - Call [`FS5::save`] to persist metadata when batching multiple writes.
+    /// - Fails with [`QuotaExceeded`](crate::QuotaExceeded) when the write would take the
+    ///   root over its quota. Writes close to the limit wait for the exact check.
     ///
     /// ```rust,no_run
     /// # use s5_fs::{DirContext, FS5, FileRef};
@@ -242,12 +383,21 @@ impl FS5 {
     /// # Ok(()) }
     /// ```
     pub async fn file_put(&self, path: &str, file_ref: FileRef) -> FSResult<()> {
-        if let Err(err) = self
-            .root
-            .execute_and_forget(path.to_string(), |value| *value = Some(file_ref))
-            .await
-        {
+        // If the write fits even as a brand-new file, no replacement can
+        // push it over, so it's safe to not wait. Otherwise only the actor
+        // knows what it replaces.
+        let growth = Usage::of_file(Some(&file_ref));
+        if self.quota.check(growth.bytes, growth.files).is_err() {
+            return self.file_put_sync(path, file_ref).await;
+        }
+        let journaled = self.journal.as_ref().map(|_| file_ref.clone());
+        if let Err(err) = self.root.put_file(path.to_string(), file_ref, false).await {
             tracing::error!("fs5: file_put failed for path {}: {}", path, err);
+        } else if let Some(file_ref) = journaled {
+            self.record(|| JournalOp::Put {
+                path: path.to_owned(),
+                file_ref,
+            });
         }
         Ok(())
     }
@@ -255,6 +405,8 @@ impl FS5 {
     /// Inserts or updates a file at `path` and waits for the mutation to apply.
     ///
     /// Use this for acknowledged writes; pair with [`FS5::save`] for durability.
+    /// Fails with [`QuotaExceeded`](crate::QuotaExceeded) if the root's quota
+    /// doesn't allow the growth.
     ///
     /// ```rust,no_run
     /// # use s5_fs::{DirContext, FS5, FileRef};
@@ -267,12 +419,59 @@ impl FS5 {
     /// # Ok(()) }
     /// ```
     pub async fn file_put_sync(&self, path: &str, file_ref: FileRef) -> FSResult<()> {
+        let journaled = self.journal.as_ref().map(|_| file_ref.clone());
+        self.root.put_file(path.to_string(), file_ref, true).await?;
+        if let Some(file_ref) = journaled {
+            self.record(|| JournalOp::Put {
+                path: path.to_owned(),
+                file_ref,
+            });
+        }
+        Ok(())
+    }
+
+    /// Sets the storage quota of this root. Usage is recounted from the
+    /// tree first, so this also works on roots created before usage was
+    /// tracked. Lowering a quota below current usage only blocks growth.
+    ///
+    /// Only valid on the handle returned by [`FS5::open`], not on a
+    /// [`FS5::subdir`]; the quota covers the whole root.
+    ///
+    /// ```rust,no_run
+    /// # use s5_fs::{DirContext, FS5, Quota};
+    /// # use tempfile::tempdir;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let tmp = tempdir()?; let ctx = DirContext::open_local_root(tmp.path())?; let fs = FS5::open(ctx);
+    /// fs.set_quota(Quota { max_bytes: Some(10 << 30), max_files: None }).await?;
+    /// fs.save().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn set_quota(&self, quota: Quota) -> FSResult<()> {
+        let (responder, receiver) = oneshot::channel();
         self.root
-            .execute(path.to_string(), |value| {
-                *value = Some(file_ref);
-            })
+            .send_msg(ActorMessage::SetQuota { quota, responder })
             .await?;
-        Ok(())
+        receiver.await?
+    }
+
+    /// The root's quota and its current usage (`None` if never counted).
+    pub fn quota(&self) -> (Quota, Option<Usage>) {
+        (self.quota.quota(), self.quota.usage())
+    }
+
+    /// Fails with [`QuotaExceeded`](crate::QuotaExceeded) if writing a
+    /// `size`-byte file at `path` would exceed the quota. Importers call this
+    /// before uploading a blob so they don't store content they then can't
+    /// reference. Skips the lookup entirely when no quota is set.
+    pub async fn check_quota(&self, path: &str, size: u64) -> FSResult<()> {
+        if self.quota.quota() == Quota::default() {
+            return Ok(());
+        }
+        let current = Usage::of_file(self.file_get(path).await.as_ref());
+        Ok(self
+            .quota
+            .check(size.saturating_sub(current.bytes), 1 - current.files)?)
     }
 
     /// Executes multiple operations and persists once at the end.
@@ -322,7 +521,11 @@ impl FS5 {
             })
             .await?;
         let handle = receiver.await??;
-        Ok(FS5 { root: handle })
+        Ok(FS5 {
+            root: handle,
+            quota: self.quota.clone(),
+            journal: self.journal.as_ref().map(|j| j.scoped(normalized)),
+        })
     }
 
     /// Retrieves the file reference at `path`, if present.
@@ -352,6 +555,52 @@ impl FS5 {
             })
     }
 
+    /// Retrieves the file references at many `paths` at once; entry `i` of
+    /// the result is what [`FS5::file_get`] would return for `paths[i]`.
+    ///
+    /// Paths are grouped by the directory (or shard) actor that owns them,
+    /// so each actor is messaged once per call rather than once per path.
+    /// Unlike `file_get`, the lookup is read-only and never marks a
+    /// directory dirty.
+    ///
+    /// ```rust,no_run
+    /// # use s5_fs::{DirContext, FS5, FileRef};
+    /// # use tempfile::tempdir; use bytes::Bytes;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// # let tmp = tempdir()?; let ctx = DirContext::open_local_root(tmp.path())?; let fs = FS5::open(ctx);
+    /// fs.file_put_sync("a/x.txt", FileRef::new_inline_blob(Bytes::from_static(b"x"))).await?;
+    /// let refs = fs.stat_many(&["a/x.txt", "a/missing.txt"]).await;
+    /// assert!(refs[0].is_some() && refs[1].is_none());
+    /// # Ok(()) }
+    /// ```
+    pub async fn stat_many<S: AsRef<str>>(&self, paths: &[S]) -> Vec<Option<FileRef>> {
+        let mut out = vec![None; paths.len()];
+        if paths.is_empty() {
+            return out;
+        }
+        let (responder, receiver) = oneshot::channel();
+        let paths = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (i, path.as_ref().to_owned()))
+            .collect();
+        if self
+            .root
+            .send_msg(ActorMessage::StatMany { paths, responder })
+            .await
+            .is_err()
+        {
+            return out;
+        }
+        if let Ok(answers) = receiver.await {
+            for (i, file_ref) in answers {
+                out[i] = file_ref;
+            }
+        }
+        out
+    }
+
     /// Deletes the file at `path`, if present, by creating a tombstone entry.
     ///
     /// - Idempotent: deleting a non-existent path is a no-op.
@@ -372,6 +621,9 @@ impl FS5 {
                 }
             })
             .await?;
+        self.record(|| JournalOp::Delete {
+            path: path.to_owned(),
+        });
         Ok(())
     }
 
@@ -428,6 +680,37 @@ impl FS5 {
         receiver.await?
     }
 
+    /// Like [`FS5::list_at`], with the encryption policy of each directory
+    /// entry alongside (`None` for files).
+    pub async fn list_at_with_policy(
+        &self,
+        path: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> FSResult<(
+        Vec<(String, CursorKind, Option<EncryptionPolicy>)>,
+        Option<String>,
+    )> {
+        let path = path.trim_matches('/');
+        let (entries, next) = self.list_at(path, cursor, limit).await?;
+        let mut out = Vec::with_capacity(entries.len());
+        for (name, kind) in entries {
+            let policy = match kind {
+                CursorKind::Directory => {
+                    let child = if path.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{path}/{name}")
+                    };
+                    Some(self.encryption_policy(&child).await?)
+                }
+                CursorKind::File => None,
+            };
+            out.push((name, kind, policy));
+        }
+        Ok((out, next))
+    }
+
     /// Exports the snapshot of a directory at `path`.
     pub async fn export_snapshot_at(&self, path: &str) -> FSResult<DirV1> {
         let (responder, receiver) = oneshot::channel();
@@ -0,0 +1,478 @@
+//! Portable single-file bundles of one local FS5 snapshot.
+//!
+//! [`write_bundle`] walks the snapshot named in `snapshots.fs5.cbor` and
+//! writes its root `DirV1`, every metadata blob below it and every content
+//! blob its `FileRef`s (including historical versions) point at into one
+//! file, followed by an index. The file can be carried to an air-gapped
+//! machine, where [`restore_bundle`] imports the blobs into that root's
+//! meta store and the given content store, checking every hash, and
+//! records the snapshot under its original name.
+//!
+//! Layout, all integers big-endian:
+//!
+//! ```text
+//! "S5BUNDLE" version:u8
+//! ( hash:[u8; 32] len:u64 bytes )*      blob records
+//! index (CBOR BundleIndex)
+//! index_len:u64 "S5BUNDLE"              trailer
+//! ```
+//!
+//! [`write_diff_bundle`] packages snapshot `to` relative to an older
+//! snapshot `from`: blobs already reachable from `from` are left out and
+//! the index records `from`'s root. Applying it requires a root that
+//! already holds `from`, and the restored tree is walked before the
+//! snapshot is listed, so a diff applied on the wrong base fails instead
+//! of producing a snapshot with holes.
+//!
+//! Blobs are copied verbatim, so encrypted directories stay encrypted and
+//! the restoring root needs the same root key to open them. Inline files
+//! travel inside their directory's metadata; registry-backed `DirRef`s are
+//! mutable pointers and are not followed, as in [`crate::pin_tree`].
+//!
+//! This module is only available on native platforms (not WASM) as it
+//! requires filesystem access.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use anyhow::{Context, anyhow, bail};
+use bytes::Bytes;
+use minicbor::bytes::ByteArray;
+use minicbor::{Decode, Encode};
+use s5_core::{Hash, blob::BlobStore};
+use s5_store_local::{LocalStore, LocalStoreConfig};
+
+use crate::FSResult;
+use crate::dir::{DirRef, DirRefType, DirV1, decrypt_dir_bytes};
+use crate::gc::collect_hashes_from_dir;
+use crate::restore::collect_inline_hashes;
+use crate::snapshots::SnapshotIndex;
+
+const MAGIC: &[u8; 8] = b"S5BUNDLE";
+const VERSION: u8 = 1;
+const TRAILER_LEN: u64 = 8 + MAGIC.len() as u64;
+
+/// Where one blob sits in the bundle file.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[cbor(map)]
+pub struct BundleBlob {
+    #[n(0)]
+    pub hash: ByteArray<32>,
+    /// Offset of the blob bytes (past the record header) from the start
+    /// of the file.
+    #[n(1)]
+    pub offset: u64,
+    #[n(2)]
+    pub len: u64,
+}
+
+/// Table of contents written at the end of a bundle.
+#[derive(Encode, Decode, Clone, Debug)]
+#[cbor(map)]
+pub struct BundleIndex {
+    /// Snapshot name as listed by `snapshots.fs5.cbor`.
+    #[n(0)]
+    pub name: String,
+    /// The snapshot's index entry: root hash, timestamp and keys.
+    #[n(1)]
+    pub snapshot: DirRef,
+    /// `DirV1` blobs, root first.
+    #[n(2)]
+    pub meta: Vec<BundleBlob>,
+    /// Content blobs referenced by the tree's files.
+    #[n(3)]
+    pub content: Vec<BundleBlob>,
+    /// Root of the snapshot a diff bundle was made against; blobs
+    /// reachable from it are not in the bundle. `None` for full bundles.
+    #[n(4)]
+    pub base: Option<ByteArray<32>>,
+}
+
+impl BundleIndex {
+    pub fn root_hash(&self) -> Hash {
+        Hash::from_bytes(self.snapshot.hash)
+    }
+
+    pub fn base_hash(&self) -> Option<Hash> {
+        self.base.map(|h| Hash::from_bytes(*h))
+    }
+}
+
+/// Counts reported by [`write_bundle`] and [`restore_bundle`].
+#[derive(Debug, Clone)]
+pub struct BundleSummary {
+    pub name: String,
+    pub root: Hash,
+    /// Base snapshot root of a diff bundle.
+    pub base: Option<Hash>,
+    pub meta_blobs: usize,
+    pub content_blobs: usize,
+    /// Total blob bytes in the bundle.
+    pub bytes: u64,
+}
+
+impl BundleSummary {
+    fn from_index(index: &BundleIndex) -> Self {
+        Self {
+            name: index.name.clone(),
+            root: index.root_hash(),
+            base: index.base_hash(),
+            meta_blobs: index.meta.len(),
+            content_blobs: index.content.len(),
+            bytes: index.meta.iter().chain(&index.content).map(|b| b.len).sum(),
+        }
+    }
+}
+
+/// The meta blob store co-located with `root.fs5.cbor`.
+fn open_meta_store(fs_root: &Path) -> BlobStore {
+    BlobStore::new(LocalStore::create(LocalStoreConfig {
+        base_path: fs_root.to_string_lossy().into(),
+        ..Default::default()
+    }))
+}
+
+/// Writes snapshot `name` of the FS5 root at `fs_root` to `out`.
+///
+/// Directory blobs come from the root's meta store (decrypted with
+/// `root_key` to find children), content blobs from `content`. Unlike
+/// GC's reachability walk, anything that cannot be loaded is an error: a
+/// bundle missing a subtree would restore as a broken snapshot. The file
+/// is written to a temporary sibling and renamed into place when complete.
+pub async fn write_bundle(
+    fs_root: &Path,
+    name: &str,
+    root_key: Option<&[u8; 32]>,
+    content: &BlobStore,
+    out: &Path,
+) -> FSResult<BundleSummary> {
+    write_bundle_inner(fs_root, None, name, root_key, content, out).await
+}
+
+/// Writes snapshot `to` to `out`, leaving out every blob reachable from
+/// snapshot `from`. Otherwise like [`write_bundle`].
+pub async fn write_diff_bundle(
+    fs_root: &Path,
+    from: &str,
+    to: &str,
+    root_key: Option<&[u8; 32]>,
+    content: &BlobStore,
+    out: &Path,
+) -> FSResult<BundleSummary> {
+    write_bundle_inner(fs_root, Some(from), to, root_key, content, out).await
+}
+
+async fn write_bundle_inner(
+    fs_root: &Path,
+    base: Option<&str>,
+    name: &str,
+    root_key: Option<&[u8; 32]>,
+    content: &BlobStore,
+    out: &Path,
+) -> FSResult<BundleSummary> {
+    let snapshots = SnapshotIndex::open(fs_root)?;
+    let lookup = |name: &str| {
+        snapshots
+            .dir
+            .dirs
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("no snapshot named \"{name}\" in {}", fs_root.display()))
+    };
+    let snapshot = lookup(name)?;
+    let meta_blobs = open_meta_store(fs_root);
+
+    let tree = walk_tree(&meta_blobs, Hash::from_bytes(snapshot.hash), root_key).await?;
+    let (base, base_tree) = match base {
+        Some(base) => {
+            let base = Hash::from_bytes(lookup(base)?.hash);
+            (Some(base), walk_tree(&meta_blobs, base, root_key).await?)
+        }
+        None => (None, TreeBlobs::default()),
+    };
+
+    let parent = out
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut temp = tempfile::NamedTempFile::new_in(parent)?;
+    let mut writer = BundleWriter::new(BufWriter::new(temp.as_file_mut()))?;
+
+    let mut index = BundleIndex {
+        name: name.to_owned(),
+        snapshot,
+        meta: Vec::new(),
+        content: Vec::new(),
+        base: base.map(|h| (*h.as_bytes()).into()),
+    };
+
+    let base_meta: HashSet<Hash> = base_tree.meta.iter().copied().collect();
+    for hash in tree.meta.iter().filter(|h| !base_meta.contains(h)) {
+        let bytes = meta_blobs
+            .read_as_bytes(*hash, 0, None)
+            .await
+            .with_context(|| format!("failed to read DirV1 {hash}"))?;
+        index.meta.push(writer.write_blob(*hash, &bytes)?);
+    }
+
+    for hash in tree.content.difference(&base_tree.content) {
+        if !content.contains(*hash).await? {
+            if tree.inline.contains(hash) {
+                continue;
+            }
+            bail!("content blob {hash} is missing from the content store");
+        }
+        let bytes = content
+            .read_as_bytes(*hash, 0, None)
+            .await
+            .with_context(|| format!("failed to read content blob {hash}"))?;
+        index.content.push(writer.write_blob(*hash, &bytes)?);
+    }
+
+    writer.finish(&index)?;
+    temp.as_file().sync_all()?;
+    temp.persist(out)?;
+
+    Ok(BundleSummary::from_index(&index))
+}
+
+/// The blobs making up one snapshot tree.
+#[derive(Default)]
+struct TreeBlobs {
+    /// `DirV1` blobs in walk order, root first.
+    meta: Vec<Hash>,
+    /// Every content hash referenced by the tree's files.
+    content: BTreeSet<Hash>,
+    /// The subset of `content` stored inline in some `FileRef`.
+    inline: HashSet<Hash>,
+}
+
+/// Loads every `DirV1` under `root` from `meta_blobs`, failing on any
+/// directory that cannot be read, decrypted or decoded.
+async fn walk_tree(
+    meta_blobs: &BlobStore,
+    root: Hash,
+    root_key: Option<&[u8; 32]>,
+) -> FSResult<TreeBlobs> {
+    let mut tree = TreeBlobs::default();
+    let mut referenced = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut queue: Vec<(Hash, Option<[u8; 32]>)> = vec![(root, root_key.copied())];
+    while let Some((hash, key)) = queue.pop() {
+        if !visited.insert(hash) {
+            continue;
+        }
+        let bytes = meta_blobs
+            .read_as_bytes(hash, 0, None)
+            .await
+            .with_context(|| format!("failed to read DirV1 {hash}"))?;
+        let decrypted = decrypt_dir_bytes(bytes, key.as_ref())
+            .with_context(|| format!("failed to decrypt DirV1 {hash}"))?;
+        let dir = DirV1::from_bytes(&decrypted)
+            .map_err(|e| anyhow!("failed to decode DirV1 {hash}: {e}"))?;
+        tree.meta.push(hash);
+
+        collect_hashes_from_dir(&dir, &mut referenced);
+        collect_inline_hashes(&dir, &mut tree.inline);
+
+        let children = dir
+            .header
+            .shards
+            .iter()
+            .flat_map(|shards| shards.values())
+            .chain(dir.dirs.values());
+        for child in children {
+            if matches!(child.ref_type(), DirRefType::RegistryKey) {
+                continue;
+            }
+            let key = child.keys.as_ref().and_then(|k| k.get(&0x0e).copied());
+            queue.push((Hash::from_bytes(child.hash), key));
+        }
+    }
+    tree.content = referenced.into_iter().collect();
+    Ok(tree)
+}
+
+/// Reads only the index of the bundle at `path`.
+pub fn read_bundle_index(path: &Path) -> FSResult<BundleIndex> {
+    let mut file = File::open(path)?;
+    read_index(&mut file)
+}
+
+/// Imports the bundle at `path` into the FS5 root at `fs_root`: directory
+/// blobs into its meta store, content blobs into `content`, and the
+/// snapshot into `snapshots.fs5.cbor` under its original name.
+///
+/// Every blob is hashed before it is stored, and the index entry is only
+/// written once all blobs are in place, so a corrupt or truncated bundle
+/// never leaves a listed but incomplete snapshot behind. Restoring the
+/// same bundle twice is a no-op; a different snapshot already using the
+/// name is an error.
+///
+/// A diff bundle additionally needs its base snapshot listed in
+/// `fs_root`; after import the whole tree is walked (decrypting with
+/// `root_key`) and every blob it needs must be present.
+pub async fn restore_bundle(
+    path: &Path,
+    fs_root: &Path,
+    root_key: Option<&[u8; 32]>,
+    content: &BlobStore,
+) -> FSResult<BundleSummary> {
+    let mut file = BufReader::new(File::open(path)?);
+    let index = read_index(&mut file)?;
+
+    let mut snapshots = SnapshotIndex::open(fs_root)?;
+    if let Some(existing) = snapshots.dir.dirs.get(&index.name)
+        && existing.hash != index.snapshot.hash
+    {
+        bail!(
+            "snapshot \"{}\" already exists with a different root ({})",
+            index.name,
+            Hash::from_bytes(existing.hash)
+        );
+    }
+    if let Some(base) = index.base
+        && !snapshots.dir.dirs.values().any(|d| d.hash == *base)
+    {
+        bail!(
+            "diff bundle needs base snapshot {} which is not in {}",
+            Hash::from_bytes(*base),
+            fs_root.display()
+        );
+    }
+
+    let meta_blobs = open_meta_store(fs_root);
+    for (blobs, store) in [(&index.meta, &meta_blobs), (&index.content, content)] {
+        for blob in blobs {
+            let expected = Hash::from_bytes(*blob.hash);
+            if store.contains(expected).await? {
+                continue;
+            }
+            let bytes = read_blob(&mut file, blob)?;
+            let actual = Hash::new(&bytes);
+            if actual != expected {
+                bail!("bundle blob {expected} is corrupt (hashes to {actual})");
+            }
+            store.import_bytes(bytes).await?;
+        }
+    }
+
+    if index.base.is_some() {
+        let tree = walk_tree(&meta_blobs, index.root_hash(), root_key)
+            .await
+            .context("diff bundle does not apply to this root")?;
+        for hash in &tree.content {
+            if !tree.inline.contains(hash) && !content.contains(*hash).await? {
+                bail!("diff bundle does not apply to this root: content blob {hash} is missing");
+            }
+        }
+    }
+
+    snapshots
+        .dir
+        .dirs
+        .insert(index.name.clone(), index.snapshot.clone());
+    snapshots.persist()?;
+
+    Ok(BundleSummary::from_index(&index))
+}
+
+struct BundleWriter<W: Write> {
+    inner: W,
+    offset: u64,
+}
+
+impl<W: Write> BundleWriter<W> {
+    fn new(mut inner: W) -> FSResult<Self> {
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        Ok(Self {
+            inner,
+            offset: MAGIC.len() as u64 + 1,
+        })
+    }
+
+    fn write_blob(&mut self, hash: Hash, bytes: &[u8]) -> FSResult<BundleBlob> {
+        let len = bytes.len() as u64;
+        self.inner.write_all(hash.as_bytes())?;
+        self.inner.write_all(&len.to_be_bytes())?;
+        self.inner.write_all(bytes)?;
+        let blob = BundleBlob {
+            hash: (*hash.as_bytes()).into(),
+            offset: self.offset + 32 + 8,
+            len,
+        };
+        self.offset = blob.offset + len;
+        Ok(blob)
+    }
+
+    fn finish(mut self, index: &BundleIndex) -> FSResult<()> {
+        let encoded = minicbor::to_vec(index)?;
+        self.inner.write_all(&encoded)?;
+        self.inner
+            .write_all(&(encoded.len() as u64).to_be_bytes())?;
+        self.inner.write_all(MAGIC)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
+fn read_index(file: &mut (impl Read + Seek)) -> FSResult<BundleIndex> {
+    let mut header = [0u8; MAGIC.len() + 1];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)
+        .context("not an s5 bundle: file too short")?;
+    if &header[..MAGIC.len()] != MAGIC {
+        bail!("not an s5 bundle: bad magic");
+    }
+    if header[MAGIC.len()] != VERSION {
+        bail!("unsupported s5 bundle version {}", header[MAGIC.len()]);
+    }
+
+    let file_len = file.seek(SeekFrom::End(0))?;
+    if file_len < header.len() as u64 + TRAILER_LEN {
+        bail!("s5 bundle is truncated");
+    }
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    file.read_exact(&mut trailer)?;
+    if &trailer[8..] != MAGIC {
+        bail!("s5 bundle is truncated: missing trailer");
+    }
+    let index_len = u64::from_be_bytes(trailer[..8].try_into().expect("8 bytes"));
+    let index_start = (file_len - TRAILER_LEN)
+        .checked_sub(index_len)
+        .filter(|start| *start >= header.len() as u64)
+        .ok_or_else(|| anyhow!("s5 bundle index length {index_len} is out of range"))?;
+
+    let mut encoded = vec![0u8; index_len as usize];
+    file.seek(SeekFrom::Start(index_start))?;
+    file.read_exact(&mut encoded)?;
+    let index: BundleIndex =
+        minicbor::decode(&encoded).map_err(|e| anyhow!("failed to decode s5 bundle index: {e}"))?;
+
+    for blob in index.meta.iter().chain(&index.content) {
+        if blob
+            .offset
+            .checked_add(blob.len)
+            .is_none_or(|end| end > index_start)
+        {
+            bail!(
+                "s5 bundle entry {} points past the blob section",
+                Hash::from_bytes(*blob.hash)
+            );
+        }
+    }
+    Ok(index)
+}
+
+fn read_blob(file: &mut (impl Read + Seek), blob: &BundleBlob) -> FSResult<Bytes> {
+    let mut bytes = vec![0u8; blob.len as usize];
+    file.seek(SeekFrom::Start(blob.offset))?;
+    file.read_exact(&mut bytes)?;
+    Ok(bytes.into())
+}
@@ -0,0 +1,307 @@
+//! Share capabilities: everything needed to open someone else's FS5 root.
+//!
+//! A [`Capability`] names a registry-backed root by its stream key and
+//! optionally carries the directory encryption key (needed to read an
+//! encrypted root) and the iroh node id of a peer that hosts the root's
+//! registry entry and blobs. Capabilities never carry a signing key, so a
+//! root opened from one is read-only.
+//!
+//! ## String form
+//!
+//! `fs5:` followed by the URL-safe, unpadded base64 of:
+//!
+//! ```text
+//! u8      version (1)
+//! u8      flags: 0x01 encryption key present, 0x02 peer present
+//! u8      stream key length
+//! [u8]    stream key (`StreamKey::storage_key`)
+//! [u8;32] encryption key   (if flagged)
+//! [u8;32] peer node id     (if flagged)
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as B64_URL};
+use s5_core::StreamKey;
+use zeroize::Zeroize;
+
+#[cfg(feature = "remote")]
+use crate::{
+    FS5, FSResult,
+    context::{DirContext, DirContextParentLink},
+};
+
+/// Prefix of the string form.
+pub const CAPABILITY_PREFIX: &str = "fs5:";
+
+const VERSION: u8 = 1;
+const FLAG_ENCRYPTION_KEY: u8 = 0x01;
+const FLAG_PEER: u8 = 0x02;
+
+/// Why a capability string could not be parsed.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CapabilityError {
+    #[error("capability must start with \"{CAPABILITY_PREFIX}\"")]
+    MissingPrefix,
+    #[error("capability is not valid base64")]
+    Encoding,
+    #[error("unsupported capability version {0}")]
+    UnsupportedVersion(u8),
+    #[error("capability is truncated")]
+    Truncated,
+    #[error("capability has {0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("invalid stream key in capability: {0}")]
+    StreamKey(String),
+}
+
+/// A read capability for a registry-backed FS5 root.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Capability {
+    /// Registry key of the root.
+    pub stream_key: StreamKey,
+    /// XChaCha20-Poly1305 key of the root directory, for encrypted roots.
+    pub encryption_key: Option<[u8; 32]>,
+    /// Iroh node id of a peer serving the root's registry entry and blobs.
+    pub peer: Option<[u8; 32]>,
+}
+
+impl Capability {
+    pub fn new(stream_key: StreamKey) -> Self {
+        Self {
+            stream_key,
+            encryption_key: None,
+            peer: None,
+        }
+    }
+
+    pub fn with_encryption_key(mut self, key: [u8; 32]) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    pub fn with_peer(mut self, node_id: [u8; 32]) -> Self {
+        self.peer = Some(node_id);
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let stream_key = self.stream_key.storage_key();
+        let mut flags = 0;
+        if self.encryption_key.is_some() {
+            flags |= FLAG_ENCRYPTION_KEY;
+        }
+        if self.peer.is_some() {
+            flags |= FLAG_PEER;
+        }
+        let mut out = Vec::with_capacity(3 + stream_key.len() + 64);
+        out.push(VERSION);
+        out.push(flags);
+        // Storage keys are at most 49 bytes (`Vault`).
+        out.push(stream_key.len() as u8);
+        out.extend_from_slice(&stream_key);
+        if let Some(key) = &self.encryption_key {
+            out.extend_from_slice(key);
+        }
+        if let Some(peer) = &self.peer {
+            out.extend_from_slice(peer);
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CapabilityError> {
+        let mut rest = bytes;
+        let [version, flags, key_len] = take(&mut rest)?;
+        if version != VERSION {
+            return Err(CapabilityError::UnsupportedVersion(version));
+        }
+        let stream_key = take_slice(&mut rest, key_len as usize)?;
+        let stream_key = StreamKey::from_storage_key(stream_key)
+            .map_err(|e| CapabilityError::StreamKey(e.to_string()))?;
+        let encryption_key = match flags & FLAG_ENCRYPTION_KEY {
+            0 => None,
+            _ => Some(take(&mut rest)?),
+        };
+        let peer = match flags & FLAG_PEER {
+            0 => None,
+            _ => Some(take(&mut rest)?),
+        };
+        if !rest.is_empty() {
+            return Err(CapabilityError::TrailingBytes(rest.len()));
+        }
+        Ok(Self {
+            stream_key,
+            encryption_key,
+            peer,
+        })
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{CAPABILITY_PREFIX}{}", B64_URL.encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for Capability {
+    type Err = CapabilityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .trim()
+            .strip_prefix(CAPABILITY_PREFIX)
+            .ok_or(CapabilityError::MissingPrefix)?;
+        let bytes = B64_URL
+            .decode(encoded)
+            .map_err(|_| CapabilityError::Encoding)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Keeps the encryption key out of logs.
+impl fmt::Debug for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Capability")
+            .field("stream_key", &self.stream_key)
+            .field("encryption_key", &self.encryption_key.map(|_| "<redacted>"))
+            .field("peer", &self.peer)
+            .finish()
+    }
+}
+
+impl Drop for Capability {
+    fn drop(&mut self) {
+        if let Some(key) = self.encryption_key.as_mut() {
+            key.zeroize();
+        }
+    }
+}
+
+fn take<const N: usize>(rest: &mut &[u8]) -> Result<[u8; N], CapabilityError> {
+    let bytes = take_slice(rest, N)?;
+    Ok(bytes.try_into().expect("take_slice returns N bytes"))
+}
+
+fn take_slice<'a>(rest: &mut &'a [u8], n: usize) -> Result<&'a [u8], CapabilityError> {
+    if rest.len() < n {
+        return Err(CapabilityError::Truncated);
+    }
+    let (head, tail) = rest.split_at(n);
+    *rest = tail;
+    Ok(head)
+}
+
+#[cfg(feature = "remote")]
+impl Capability {
+    /// A read-only [`DirContext`] for this root backed by the peer's
+    /// registry and blob store, plus the blobs client so callers can also
+    /// fetch file content from the peer.
+    ///
+    /// Fails if the capability has no peer hint.
+    pub fn remote_context(
+        &self,
+        endpoint: iroh::Endpoint,
+    ) -> FSResult<(DirContext, s5_blobs::Client)> {
+        use crate::dir::ENCRYPTION_TYPE_XCHACHA20_POLY1305;
+        use s5_core::blob::BlobStore;
+        use std::sync::Arc;
+
+        let peer = self
+            .peer
+            .ok_or_else(|| anyhow::anyhow!("capability has no peer hint"))?;
+        let blobs = s5_blobs::Client::connect_to_peer_public(endpoint.clone(), peer)?;
+        let peer_id = iroh::EndpointId::from_bytes(&peer)
+            .map_err(|e| anyhow::anyhow!("invalid peer node id: {e}"))?;
+        let registry = s5_registry::RemoteRegistry::connect(endpoint, peer_id);
+
+        let mut ctx = DirContext::new(
+            DirContextParentLink::RegistryKey {
+                public_key: self.stream_key,
+                signing_key: None,
+            },
+            BlobStore::new(s5_blobs::RemoteBlobStore::new(blobs.clone())),
+            Arc::new(registry),
+        );
+        if let Some(key) = self.encryption_key {
+            ctx.encryption_type = Some(ENCRYPTION_TYPE_XCHACHA20_POLY1305);
+            ctx.keys.insert(0x0e, key);
+        }
+        Ok((ctx, blobs))
+    }
+}
+
+#[cfg(feature = "remote")]
+impl FS5 {
+    /// Opens the (read-only) root named by `capability` from its peer,
+    /// wiring a [`s5_registry::RemoteRegistry`] and
+    /// [`s5_blobs::RemoteBlobStore`] over `endpoint`.
+    ///
+    /// ```ignore
+    /// let capability: Capability = share_string.parse()?;
+    /// let fs = FS5::open_remote(&capability, endpoint)?;
+    /// let (entries, _) = fs.list(None, 100).await?;
+    /// ```
+    pub fn open_remote(capability: &Capability, endpoint: iroh::Endpoint) -> FSResult<Self> {
+        let (ctx, _blobs) = capability.remote_context(endpoint)?;
+        Ok(Self::open(ctx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capability_string_round_trip() {
+        let vault = StreamKey::Vault {
+            pubkey: [7; 32],
+            vault_id: [9; 16],
+        };
+        for cap in [
+            Capability::new(StreamKey::Local([1; 32])),
+            Capability::new(vault).with_encryption_key([2; 32]),
+            Capability::new(vault)
+                .with_encryption_key([2; 32])
+                .with_peer([3; 32]),
+        ] {
+            let s = cap.to_string();
+            assert!(s.starts_with(CAPABILITY_PREFIX));
+            assert_eq!(s.parse::<Capability>().unwrap(), cap);
+        }
+        let debug = format!("{:?}", Capability::new(vault).with_encryption_key([2; 32]));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn rejects_malformed_capabilities() {
+        let good = Capability::new(StreamKey::Local([1; 32]))
+            .with_peer([3; 32])
+            .to_bytes();
+        assert_eq!(
+            "s5:abc".parse::<Capability>(),
+            Err(CapabilityError::MissingPrefix)
+        );
+        assert_eq!(
+            "fs5:!!".parse::<Capability>(),
+            Err(CapabilityError::Encoding)
+        );
+        assert_eq!(
+            Capability::from_bytes(&good[..good.len() - 1]),
+            Err(CapabilityError::Truncated)
+        );
+        let mut long = good.clone();
+        long.push(0);
+        assert_eq!(
+            Capability::from_bytes(&long),
+            Err(CapabilityError::TrailingBytes(1))
+        );
+        let mut future = good;
+        future[0] = 2;
+        assert_eq!(
+            Capability::from_bytes(&future),
+            Err(CapabilityError::UnsupportedVersion(2))
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::{FSResult, dir::DirV1};
 use crate::{
     actor::{DirActorHandle, WeakDirActorHandle},
     dir::DirRef,
+    quota::QuotaTracker,
 };
 #[cfg(not(target_arch = "wasm32"))]
 use anyhow::Context;
@@ -60,6 +61,12 @@ pub struct DirContext {
     pub pins: Option<Arc<dyn Pins + Send + Sync>>,
     pub signing_key: Option<SigningKey>,
     pub registry_dir_handles: Arc<DashMap<StreamKey, DirActorHandle>>,
+    /// Quota limits and live usage of the root this context belongs to,
+    /// shared with every context derived from it.
+    pub quota: Arc<QuotaTracker>,
+    /// Whether this is the root's own context, which loads and persists
+    /// the quota header. Cleared by [`DirContext::with_new_ref`].
+    pub(crate) quota_root: bool,
 }
 
 /// Defines how a directory is linked to its parent.
@@ -121,6 +128,7 @@ impl DirContext {
 
         let meta_store = LocalStore::create(LocalStoreConfig {
             base_path: path.to_string_lossy().into(),
+            ..Default::default()
         });
 
         // Use a RegistryPinner over the local RedbRegistry so that the
@@ -158,6 +166,8 @@ impl DirContext {
             pins: None,
             signing_key: None,
             registry_dir_handles: Arc::new(DashMap::new()),
+            quota: Arc::new(QuotaTracker::default()),
+            quota_root: true,
         }
     }
 
@@ -215,6 +225,8 @@ impl DirContext {
             pins: self.pins.clone(),
             signing_key: inherited_signing_key,
             registry_dir_handles: self.registry_dir_handles.clone(),
+            quota: self.quota.clone(),
+            quota_root: false,
             link,
         };
         if let Some(dir_keys) = &dir_ref.keys {
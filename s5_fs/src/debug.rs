@@ -1,6 +1,6 @@
 use crate::{
     FS5, FSResult,
-    dir::{DirRef, DirRefType, DirV1, FileRef, FileRefType},
+    dir::{DirRef, DirRefType, DirV1, EncryptionPolicy, FileRef, FileRefType},
 };
 use std::collections::BTreeMap;
 
@@ -23,21 +23,27 @@ pub async fn print_tree(fs: &FS5, path: &str) -> FSResult<()> {
         format!("./{}", norm)
     };
 
-    print_summary(&dir, &label);
-    print_dir_iterative(fs, dir, norm).await
+    let policy = fs.encryption_policy(&norm).await?;
+    print_summary(&dir, &label, policy);
+    print_dir_iterative(fs, dir, norm, policy.is_encrypted()).await
 }
 
 /// Print a one-line summary for a DirV1 snapshot, including counts and
 /// shard metadata. This is useful when inspecting GC/verify behavior to
 /// quickly understand the logical shape of the tree.
-fn print_summary(dir: &DirV1, label: &str) {
+fn print_summary(dir: &DirV1, label: &str, policy: EncryptionPolicy) {
     let dirs = dir.dirs.len();
     let files = dir.files.len();
     let shard_level = dir.header.shard_level.unwrap_or(0);
     let shard_count = dir.header.shards.as_ref().map(|m| m.len()).unwrap_or(0);
     println!(
-        "{} [DirV1 dirs={} files={} shard_level={} shards={}]",
-        label, dirs, files, shard_level, shard_count
+        "{} [DirV1 {} dirs={} files={} shard_level={} shards={}]",
+        label,
+        policy.label(),
+        dirs,
+        files,
+        shard_level,
+        shard_count
     );
 }
 
@@ -109,16 +115,25 @@ struct Frame {
     dir: DirV1,
     path: String,
     prefix: String,
+    /// Whether `dir` itself is encrypted, so children can be labelled
+    /// relative to it.
+    encrypted: bool,
     entries: Vec<Entry>,
     index: usize,
 }
 
-async fn print_dir_iterative(fs: &FS5, root_dir: DirV1, root_path: String) -> FSResult<()> {
+async fn print_dir_iterative(
+    fs: &FS5,
+    root_dir: DirV1,
+    root_path: String,
+    root_encrypted: bool,
+) -> FSResult<()> {
     let mut stack: Vec<Frame> = Vec::new();
     stack.push(Frame {
         dir: root_dir,
         path: root_path,
         prefix: String::new(),
+        encrypted: root_encrypted,
         entries: Vec::new(),
         index: 0,
     });
@@ -139,6 +154,7 @@ async fn print_dir_iterative(fs: &FS5, root_dir: DirV1, root_path: String) -> FS
         let current_prefix = frame.prefix.clone();
         let current_path = frame.path.clone();
         let current_dir = frame.dir.clone();
+        let current_encrypted = frame.encrypted;
 
         let entry = frame.entries[frame.index].clone();
         let is_last = frame.index + 1 == frame.entries.len();
@@ -174,11 +190,8 @@ async fn print_dir_iterative(fs: &FS5, root_dir: DirV1, root_path: String) -> FS
                     DirRefType::RegistryKey => "registry",
                 };
                 let hash_short = short_hash_bytes(&dir_ref.hash);
-                let enc = if dir_ref.encryption_type.is_some() {
-                    "enc"
-                } else {
-                    "plain"
-                };
+                let encrypted = dir_ref.encryption_type.is_some();
+                let enc = EncryptionPolicy::resolve(current_encrypted, encrypted).label();
                 println!(
                     "{} [DirRef type={} {} hash={}...]",
                     entry.name, ty, enc, hash_short
@@ -196,6 +209,7 @@ async fn print_dir_iterative(fs: &FS5, root_dir: DirV1, root_path: String) -> FS
                             dir: sub_dir,
                             path: next_path,
                             prefix: child_prefix,
+                            encrypted,
                             entries: Vec::new(),
                             index: 0,
                         });
@@ -214,11 +228,8 @@ async fn print_dir_iterative(fs: &FS5, root_dir: DirV1, root_path: String) -> FS
                     DirRefType::RegistryKey => "registry",
                 };
                 let hash_short = short_hash_bytes(&dir_ref.hash);
-                let enc = if dir_ref.encryption_type.is_some() {
-                    "enc"
-                } else {
-                    "plain"
-                };
+                let encrypted = dir_ref.encryption_type.is_some();
+                let enc = EncryptionPolicy::resolve(current_encrypted, encrypted).label();
                 println!(
                     "{} [Shard index=0x{:02x} type={} {} hash={}...]",
                     entry.name, index, ty, enc, hash_short
@@ -253,6 +264,7 @@ async fn print_dir_iterative(fs: &FS5, root_dir: DirV1, root_path: String) -> FS
                             dir: shard_dir,
                             path: current_path.clone(),
                             prefix: child_prefix,
+                            encrypted: current_encrypted,
                             entries: Vec::new(),
                             index: 0,
                         });
@@ -55,6 +55,8 @@ impl DirV1 {
                 ops_counter: None,
                 last_written_by: None,
                 shards: None,
+                quota: None,
+                usage: None,
             },
             dirs: BTreeMap::new(),
             files: BTreeMap::new(),
@@ -96,6 +98,15 @@ pub struct DirHeader {
     pub ops_counter: Option<u64>,
     #[n(0x0d)] // TODO implement
     pub last_written_by: Option<BTreeMap<[u8; 16], u64>>,
+
+    /// Storage limits for the whole root; only read from the root header.
+    #[n(0x10)]
+    pub quota: Option<Quota>,
+    /// Live usage of the whole root, kept up to date on every write so the
+    /// quota can be enforced without walking the tree. Root header only;
+    /// `None` on roots written before usage was tracked.
+    #[n(0x11)]
+    pub usage: Option<Usage>,
 }
 
 impl Default for DirHeader {
@@ -114,6 +125,45 @@ impl DirHeader {
             try_files: None,
             ops_counter: None,
             last_written_by: None,
+            quota: None,
+            usage: None,
+        }
+    }
+}
+
+/// Per-root storage limits. `None` means unlimited.
+#[derive(
+    Encode, Decode, Serialize, Deserialize, CborLen, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[cbor(map)]
+pub struct Quota {
+    #[n(0)]
+    pub max_bytes: Option<u64>,
+    #[n(1)]
+    pub max_files: Option<u64>,
+}
+
+/// Total size and count of live (non-tombstone) files.
+#[derive(
+    Encode, Decode, Serialize, Deserialize, CborLen, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[cbor(map)]
+pub struct Usage {
+    #[n(0)]
+    pub bytes: u64,
+    #[n(1)]
+    pub files: u64,
+}
+
+impl Usage {
+    /// What a single directory entry counts towards usage.
+    pub fn of_file(file_ref: Option<&FileRef>) -> Self {
+        match file_ref {
+            Some(f) if !f.is_tombstone() => Self {
+                bytes: f.size,
+                files: 1,
+            },
+            _ => Self::default(),
         }
     }
 }
@@ -141,6 +191,60 @@ pub struct DirRef {
 
 pub const ENCRYPTION_TYPE_XCHACHA20_POLY1305: u8 = 0x02;
 
+/// Where [`FS5::set_encryption`](crate::FS5::set_encryption) takes the key
+/// for a directory it switches to encrypted.
+#[derive(Clone)]
+pub enum EncryptionKeySource {
+    /// A fresh random XChaCha20-Poly1305 key (what `create_dir` uses).
+    Generate,
+    /// A caller-supplied key, e.g. one derived from a shared secret so
+    /// another device can open the directory independently.
+    Explicit([u8; 32]),
+}
+
+impl std::fmt::Debug for EncryptionKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Generate => f.write_str("Generate"),
+            Self::Explicit(_) => f.write_str("Explicit(..)"),
+        }
+    }
+}
+
+/// Effective encryption of one directory relative to its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncryptionPolicy {
+    Encrypted,
+    Plaintext,
+    /// Plaintext below an encrypted parent. Only reachable through an
+    /// explicit `set_encryption(path, false, ..)`; implicit creation
+    /// paths inherit the parent's encryption or refuse.
+    PlaintextOptOut,
+}
+
+impl EncryptionPolicy {
+    pub(crate) fn resolve(parent_encrypted: bool, encrypted: bool) -> Self {
+        match (parent_encrypted, encrypted) {
+            (_, true) => Self::Encrypted,
+            (true, false) => Self::PlaintextOptOut,
+            (false, false) => Self::Plaintext,
+        }
+    }
+
+    pub fn is_encrypted(self) -> bool {
+        self == Self::Encrypted
+    }
+
+    /// Short tag used by listings and the debug tree.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Encrypted => "enc",
+            Self::Plaintext => "plain",
+            Self::PlaintextOptOut => "plain(opt-out)",
+        }
+    }
+}
+
 #[repr(u8)]
 #[derive(Encode, Decode, Serialize, Deserialize, CborLen, Clone, Debug)]
 #[cbor(index_only)]
@@ -298,6 +402,16 @@ impl FileRef {
         }
     }
 
+    /// The file content if it is stored inline (an `IdentityRawBinary`
+    /// location). Readers should check this before fetching by hash —
+    /// inline files usually have no copy in any blob store.
+    pub fn inline_data(&self) -> Option<&[u8]> {
+        self.locations.as_ref()?.iter().find_map(|loc| match loc {
+            BlobLocation::IdentityRawBinary(data) => Some(data.as_slice()),
+            _ => None,
+        })
+    }
+
     pub fn ref_type(&self) -> FileRefType {
         self.ref_type.clone().unwrap_or(FileRefType::Blake3Hash)
     }
@@ -342,6 +456,15 @@ impl From<s5_core::BlobId> for FileRef {
     }
 }
 
+impl From<s5_core::blob::ImportedBlob> for FileRef {
+    fn from(imported: s5_core::blob::ImportedBlob) -> Self {
+        match imported {
+            s5_core::blob::ImportedBlob::Inline { data, .. } => Self::new_inline_blob(data),
+            s5_core::blob::ImportedBlob::Stored(blob_id) => blob_id.into(),
+        }
+    }
+}
+
 impl From<FileRef> for s5_core::BlobId {
     fn from(val: FileRef) -> Self {
         s5_core::BlobId::new(
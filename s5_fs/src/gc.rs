@@ -8,6 +8,8 @@
 use std::collections::{HashSet, VecDeque};
 use std::path::Path;
 
+use futures::StreamExt;
+
 use crate::FSResult;
 use crate::dir::{DirRef, DirV1, FileRef, decrypt_dir_bytes};
 use s5_core::{Hash, Pins, blob::BlobStore};
@@ -76,6 +78,7 @@ pub async fn collect_fs_reachable_hashes<P: AsRef<Path>>(
     // Meta blob store co-located with root.fs5.cbor
     let meta_store = LocalStore::create(LocalStoreConfig {
         base_path: fs_root.to_string_lossy().into(),
+        ..Default::default()
     });
     let meta_blobs = BlobStore::new(meta_store);
 
@@ -175,6 +178,10 @@ pub struct GcReport {
     pub kept_by_reachability: usize,
     /// Blobs that were (or would be) deleted in this run.
     pub deleted: usize,
+    /// Bytes held by the candidates.
+    pub bytes_candidate: u64,
+    /// Bytes freed by the blobs actually deleted.
+    pub bytes_reclaimed: u64,
     /// Blobs considered deletable by invariants (pins + reachability).
     ///
     /// This is populated regardless of `dry_run`; when `dry_run` is true,
@@ -201,9 +208,10 @@ pub async fn gc_store(
 ) -> FSResult<GcReport> {
     let mut report = GcReport::default();
 
-    let all_hashes = blob_store.list_hashes().await?;
+    let mut blobs = blob_store.list_with_meta().await?;
 
-    for h in all_hashes {
+    while let Some(item) = blobs.next().await {
+        let (h, meta) = item?;
         report.total += 1;
 
         // Respect all existing pins in the node registry.
@@ -221,6 +229,7 @@ pub async fn gc_store(
 
         // This blob is a GC candidate.
         report.candidates.push(h);
+        report.bytes_candidate += meta.size;
 
         if dry_run {
             continue;
@@ -229,6 +238,7 @@ pub async fn gc_store(
         match blob_store.delete(h).await {
             Ok(()) => {
                 report.deleted += 1;
+                report.bytes_reclaimed += meta.size;
             }
             Err(e) => {
                 report.delete_errors.push((h, e));
@@ -0,0 +1,282 @@
+//! Write-ahead journal of unsaved mutations, for registry-backed roots.
+//!
+//! A registry-backed [`FS5`](crate::FS5) only persists on save; a client
+//! that dies between a mutation and the next save loses it. With a
+//! [`Journal`] attached ([`FS5::with_journal`](crate::FS5::with_journal)),
+//! every `file_put`, `file_delete` and `create_dir` is recorded, the
+//! journal is trimmed when a save of the root succeeds, and whatever is
+//! left when the client comes back is applied again by
+//! [`FS5::replay_journal`](crate::FS5::replay_journal).
+//!
+//! The journal itself is in memory; a [`JournalBackend`] mirrors it to
+//! durable storage after every change. [`RedbJournalBackend`] covers
+//! native clients; browsers plug in their own (e.g. IndexedDB).
+//!
+//! Replay re-applies operations verbatim, so a replayed put wins over
+//! anything another writer saved for that path in the meantime.
+
+use std::sync::{Arc, Mutex};
+
+use minicbor::{CborLen, Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use crate::{FSResult, dir::FileRef};
+
+/// One unsaved mutation. Paths are relative to the journaled root.
+#[derive(Encode, Decode, CborLen, Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalOp {
+    #[n(0)]
+    Put {
+        #[n(0)]
+        path: String,
+        #[n(1)]
+        file_ref: FileRef,
+    },
+    #[n(1)]
+    Delete {
+        #[n(0)]
+        path: String,
+    },
+    #[n(2)]
+    CreateDir {
+        #[n(0)]
+        path: String,
+        #[n(1)]
+        enable_encryption: bool,
+    },
+}
+
+impl JournalOp {
+    pub fn path(&self) -> &str {
+        match self {
+            JournalOp::Put { path, .. }
+            | JournalOp::Delete { path }
+            | JournalOp::CreateDir { path, .. } => path,
+        }
+    }
+
+    fn with_prefix(mut self, prefix: &str) -> Self {
+        if !prefix.is_empty() {
+            let path = match &mut self {
+                JournalOp::Put { path, .. }
+                | JournalOp::Delete { path }
+                | JournalOp::CreateDir { path, .. } => path,
+            };
+            *path = format!("{prefix}/{path}");
+        }
+        self
+    }
+}
+
+/// Durable mirror of a [`Journal`].
+pub trait JournalBackend: Send + Sync {
+    /// Replaces the persisted journal with `encoded` (CBOR list of
+    /// [`JournalOp`]s). An empty list means nothing is pending.
+    fn store(&self, encoded: &[u8]) -> FSResult<()>;
+}
+
+struct JournalInner {
+    ops: Vec<JournalOp>,
+    backend: Option<Box<dyn JournalBackend>>,
+}
+
+/// Shared handle to a root's journal. Clones (and the scoped copies
+/// handed to [`FS5::subdir`](crate::FS5::subdir) handles) record into the
+/// same list.
+#[derive(Clone)]
+pub struct Journal {
+    inner: Arc<Mutex<JournalInner>>,
+    /// Path of the handle this copy belongs to, relative to the root.
+    prefix: String,
+}
+
+impl Journal {
+    /// A journal that only lives as long as the process. Still useful to
+    /// inspect what an unsaved client would lose.
+    pub fn in_memory() -> Self {
+        Self::from_parts(Vec::new(), None)
+    }
+
+    /// A journal mirrored to `backend`, starting from what the backend
+    /// held before (`persisted`, as previously passed to
+    /// [`JournalBackend::store`]).
+    pub fn new(backend: impl JournalBackend + 'static, persisted: Option<&[u8]>) -> FSResult<Self> {
+        let ops = match persisted {
+            Some(bytes) if !bytes.is_empty() => minicbor::decode(bytes)?,
+            _ => Vec::new(),
+        };
+        Ok(Self::from_parts(ops, Some(Box::new(backend))))
+    }
+
+    /// Opens the journal of `root` (any stable identifier, e.g. the root's
+    /// stream key bytes) in the redb file at `path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_redb(path: impl AsRef<std::path::Path>, root: &[u8]) -> FSResult<Self> {
+        let (backend, persisted) = RedbJournalBackend::open(path, root)?;
+        Self::new(backend, persisted.as_deref())
+    }
+
+    fn from_parts(ops: Vec<JournalOp>, backend: Option<Box<dyn JournalBackend>>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(JournalInner { ops, backend })),
+            prefix: String::new(),
+        }
+    }
+
+    /// Operations recorded since the last successful save, oldest first.
+    pub fn pending(&self) -> Vec<JournalOp> {
+        self.lock().ops.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn scoped(&self, path: &str) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            prefix: match self.prefix.as_str() {
+                "" => path.to_string(),
+                prefix => format!("{prefix}/{path}"),
+            },
+        }
+    }
+
+    pub(crate) fn is_root(&self) -> bool {
+        self.prefix.is_empty()
+    }
+
+    pub(crate) fn record(&self, op: JournalOp) {
+        let mut inner = self.lock();
+        inner.ops.push(op.with_prefix(&self.prefix));
+        Self::mirror(&inner);
+    }
+
+    /// Drops the first `n` operations, once a save covering them succeeded.
+    pub(crate) fn trim(&self, n: usize) {
+        let mut inner = self.lock();
+        let n = n.min(inner.ops.len());
+        if n == 0 {
+            return;
+        }
+        inner.ops.drain(..n);
+        Self::mirror(&inner);
+    }
+
+    /// A failed mirror write is logged, not surfaced: the mutation itself
+    /// succeeded and is still in memory.
+    fn mirror(inner: &JournalInner) {
+        let Some(backend) = &inner.backend else {
+            return;
+        };
+        let result = minicbor::to_vec(&inner.ops)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| backend.store(&bytes));
+        if let Err(e) = result {
+            tracing::warn!("fs5: writing journal failed: {e:#}");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JournalInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const JOURNAL_TABLE: redb::TableDefinition<&[u8], &[u8]> = redb::TableDefinition::new("journal");
+
+/// Keeps journals in a redb file, one row per root.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct RedbJournalBackend {
+    db: Arc<redb::Database>,
+    root: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RedbJournalBackend {
+    /// Opens (or creates) the file at `path` and returns the backend for
+    /// `root` together with its persisted journal, if any.
+    pub fn open(
+        path: impl AsRef<std::path::Path>,
+        root: &[u8],
+    ) -> FSResult<(Self, Option<Vec<u8>>)> {
+        use redb::{ReadableDatabase, ReadableTable};
+
+        let db = redb::Database::create(path)?;
+        let txn = db.begin_write()?;
+        txn.open_table(JOURNAL_TABLE)?;
+        txn.commit()?;
+
+        let persisted = {
+            let txn = db.begin_read()?;
+            let table = txn.open_table(JOURNAL_TABLE)?;
+            table.get(root)?.map(|v| v.value().to_vec())
+        };
+        Ok((
+            Self {
+                db: Arc::new(db),
+                root: root.to_vec(),
+            },
+            persisted,
+        ))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl JournalBackend for RedbJournalBackend {
+    fn store(&self, encoded: &[u8]) -> FSResult<()> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(JOURNAL_TABLE)?;
+            table.insert(self.root.as_slice(), encoded)?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_journal_survives_reopen_and_trims() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("journal.redb");
+
+        let journal = Journal::open_redb(&path, b"root").unwrap();
+        journal.record(JournalOp::CreateDir {
+            path: "docs".into(),
+            enable_encryption: true,
+        });
+        journal.scoped("docs").record(JournalOp::Put {
+            path: "a.txt".into(),
+            file_ref: FileRef::new_inline_blob(Bytes::from_static(b"a")),
+        });
+        drop(journal);
+
+        let journal = Journal::open_redb(&path, b"root").unwrap();
+        let paths: Vec<String> = journal
+            .pending()
+            .iter()
+            .map(|op| op.path().to_string())
+            .collect();
+        assert_eq!(paths, ["docs", "docs/a.txt"]);
+
+        journal.trim(1);
+        drop(journal);
+        let journal = Journal::open_redb(&path, b"root").unwrap();
+        assert_eq!(journal.len(), 1);
+        assert!(matches!(journal.pending()[0], JournalOp::Put { .. }));
+        drop(journal);
+
+        // Journals of other roots in the same file are separate.
+        assert!(Journal::open_redb(&path, b"other").unwrap().is_empty());
+    }
+}
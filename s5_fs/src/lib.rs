@@ -13,16 +13,28 @@
 
 mod actor;
 mod api;
+pub mod bundle;
+pub mod capability;
 mod context;
 pub mod debug;
 pub mod dir;
 pub mod gc;
+pub mod journal;
+pub mod pin_tree;
+pub mod quota;
+pub mod restore;
+#[cfg(all(feature = "sim", not(target_arch = "wasm32")))]
+pub mod sim;
 pub mod snapshots;
 mod spawn;
 
+pub use actor::{ConcurrentWriteConflict, REGISTRY_SAVE_ATTEMPTS};
 pub use api::{CursorKind, FS5};
+pub use capability::{Capability, CapabilityError};
 pub use context::{DirContext, DirContextParentLink, SigningKey};
-pub use dir::FileRef;
+pub use dir::{EncryptionKeySource, EncryptionPolicy, FileRef};
+pub use journal::{Journal, JournalOp};
+pub use quota::{Quota, QuotaExceeded};
 
 /// Backwards-compatible alias after the `DirContext` rename.
 pub type DirActorContext = DirContext;
@@ -0,0 +1,222 @@
+//! Recursive pinning of FS5 subtrees.
+//!
+//! [`Pins`] works on single hashes, but keeping a directory tree alive
+//! means pinning every `DirV1` metadata blob in it plus every content blob
+//! its `FileRef`s (and their version chains) point at. [`pin_tree`] walks
+//! the tree once and returns a [`TreePin`] record of what it pinned, per
+//! directory. Because directories are content addressed, an unchanged
+//! subtree has an unchanged hash, so [`update_tree_pin`] only loads the
+//! directories that are new since the previous record and only touches
+//! pins for hashes that were added or dropped.
+//!
+//! Registry-backed `DirRef`s are mutable pointers rather than snapshots;
+//! they are not followed; whoever publishes them pins their contents.
+//!
+//! This module is only available on native platforms (not WASM), like
+//! [`crate::gc`] whose reachability rules it shares.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::{Context, anyhow};
+use bytes::Bytes;
+use minicbor::bytes::ByteArray;
+use minicbor::{Decode, Encode};
+use s5_core::{Hash, PinContext, Pins, blob::BlobStore};
+
+use crate::FSResult;
+use crate::dir::{DirRefType, DirV1, decrypt_dir_bytes};
+use crate::gc::collect_hashes_from_dir;
+
+/// What one `DirV1` blob contributes to a tree pin.
+#[derive(Encode, Decode, Clone, Debug, Default, PartialEq, Eq)]
+#[cbor(map)]
+pub struct DirPins {
+    /// Child directories and shards, followed when walking.
+    #[n(0)]
+    pub dirs: Vec<ByteArray<32>>,
+    /// Content blobs referenced by this directory's files.
+    #[n(1)]
+    pub blobs: Vec<ByteArray<32>>,
+}
+
+/// Record of a recursive pin over the tree rooted at `root`.
+///
+/// Keep it (e.g. next to the sync state) to update or drop the pin later;
+/// [`TreePin::to_bytes`] gives a compact CBOR encoding.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[cbor(map)]
+pub struct TreePin {
+    #[n(0)]
+    pub root: ByteArray<32>,
+    #[n(1)]
+    pub context: PinContext,
+    /// Every directory blob in the tree, keyed by hash.
+    #[n(2)]
+    pub dirs: BTreeMap<ByteArray<32>, DirPins>,
+}
+
+impl TreePin {
+    pub fn root_hash(&self) -> Hash {
+        Hash::from_bytes(*self.root)
+    }
+
+    /// All hashes this record pins: directory blobs and content blobs.
+    pub fn hashes(&self) -> BTreeSet<Hash> {
+        let mut out = BTreeSet::new();
+        for (dir, pins) in &self.dirs {
+            out.insert(Hash::from_bytes(**dir));
+            out.extend(pins.blobs.iter().map(|h| Hash::from_bytes(**h)));
+        }
+        out
+    }
+
+    pub fn to_bytes(&self) -> FSResult<Bytes> {
+        Ok(minicbor::to_vec(self)?.into())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> FSResult<Self> {
+        minicbor::decode(bytes).map_err(|e| anyhow!("failed to decode tree pin record: {e}"))
+    }
+}
+
+/// Pins the `DirV1` tree rooted at `root_hash` (read from `meta_blobs`,
+/// decrypted with `root_key` if the root is encrypted) and every content
+/// blob it references under `context`.
+///
+/// Unlike GC's reachability walk, a directory that cannot be loaded is an
+/// error: a pin that silently skipped a subtree would let GC collect it.
+pub async fn pin_tree(
+    meta_blobs: &BlobStore,
+    pins: &dyn Pins,
+    root_hash: Hash,
+    root_key: Option<&[u8; 32]>,
+    context: PinContext,
+) -> FSResult<TreePin> {
+    let dirs = walk_tree(meta_blobs, root_hash, root_key, &BTreeMap::new()).await?;
+    let record = TreePin {
+        root: (*root_hash.as_bytes()).into(),
+        context,
+        dirs,
+    };
+    for hash in record.hashes() {
+        pins.pin_hash(hash, record.context.clone()).await?;
+    }
+    Ok(record)
+}
+
+/// Moves the pin in `previous` to the tree rooted at `new_root`.
+///
+/// Directories already present in `previous` are not reloaded, and only
+/// the hashes that differ between the two trees are pinned or unpinned.
+/// New pins are added before stale ones are dropped, so nothing shared by
+/// both trees is ever unpinned in between.
+pub async fn update_tree_pin(
+    meta_blobs: &BlobStore,
+    pins: &dyn Pins,
+    previous: &TreePin,
+    new_root: Hash,
+    root_key: Option<&[u8; 32]>,
+) -> FSResult<TreePin> {
+    if previous.root_hash() == new_root {
+        return Ok(previous.clone());
+    }
+    let dirs = walk_tree(meta_blobs, new_root, root_key, &previous.dirs).await?;
+    let record = TreePin {
+        root: (*new_root.as_bytes()).into(),
+        context: previous.context.clone(),
+        dirs,
+    };
+
+    let old = previous.hashes();
+    let new = record.hashes();
+    for hash in new.difference(&old) {
+        pins.pin_hash(*hash, record.context.clone()).await?;
+    }
+    for hash in old.difference(&new) {
+        pins.unpin_hash(*hash, record.context.clone()).await?;
+    }
+    Ok(record)
+}
+
+/// Drops every pin held by `record`.
+pub async fn unpin_tree(pins: &dyn Pins, record: &TreePin) -> FSResult<()> {
+    for hash in record.hashes() {
+        pins.unpin_hash(hash, record.context.clone()).await?;
+    }
+    Ok(())
+}
+
+/// Collects the `DirPins` of every directory under `root`, reusing entries
+/// (and their whole subtrees) from `known` instead of loading them.
+async fn walk_tree(
+    meta_blobs: &BlobStore,
+    root: Hash,
+    root_key: Option<&[u8; 32]>,
+    known: &BTreeMap<ByteArray<32>, DirPins>,
+) -> FSResult<BTreeMap<ByteArray<32>, DirPins>> {
+    let mut out: BTreeMap<ByteArray<32>, DirPins> = BTreeMap::new();
+    let mut queue: Vec<(Hash, Option<[u8; 32]>)> = vec![(root, root_key.copied())];
+
+    while let Some((hash, key)) = queue.pop() {
+        let id: ByteArray<32> = (*hash.as_bytes()).into();
+        if out.contains_key(&id) {
+            continue;
+        }
+
+        if known.contains_key(&id) {
+            // Same hash, same subtree: copy it over without any reads.
+            let mut stack = vec![id];
+            while let Some(id) = stack.pop() {
+                if out.contains_key(&id) {
+                    continue;
+                }
+                let entry = known.get(&id).with_context(|| {
+                    format!(
+                        "tree pin record is missing directory {}",
+                        Hash::from_bytes(*id)
+                    )
+                })?;
+                stack.extend(entry.dirs.iter().copied());
+                out.insert(id, entry.clone());
+            }
+            continue;
+        }
+
+        let bytes = meta_blobs
+            .read_as_bytes(hash, 0, None)
+            .await
+            .with_context(|| format!("failed to read DirV1 {hash}"))?;
+        let decrypted = decrypt_dir_bytes(bytes, key.as_ref())
+            .with_context(|| format!("failed to decrypt DirV1 {hash}"))?;
+        let dir = DirV1::from_bytes(&decrypted)
+            .map_err(|e| anyhow!("failed to decode DirV1 {hash}: {e}"))?;
+
+        let mut blobs = HashSet::new();
+        collect_hashes_from_dir(&dir, &mut blobs);
+        let mut blobs: Vec<ByteArray<32>> =
+            blobs.into_iter().map(|h| (*h.as_bytes()).into()).collect();
+        blobs.sort_unstable();
+
+        let mut dirs = Vec::new();
+        let children = dir
+            .header
+            .shards
+            .iter()
+            .flat_map(|shards| shards.values())
+            .chain(dir.dirs.values());
+        for child in children {
+            if matches!(child.ref_type(), DirRefType::RegistryKey) {
+                continue;
+            }
+            dirs.push(ByteArray::from(child.hash));
+            let key = child.keys.as_ref().and_then(|k| k.get(&0x0e).copied());
+            queue.push((Hash::from_bytes(child.hash), key));
+        }
+
+        out.insert(id, DirPins { dirs, blobs });
+    }
+
+    Ok(out)
+}
@@ -0,0 +1,216 @@
+//! Per-root quotas.
+//!
+//! Every context derived from one root shares a single [`QuotaTracker`], so
+//! a write in any directory actor is checked against the root's totals.
+//! The root actor seeds the tracker from its header's [`Quota`] and
+//! [`Usage`] on load and writes both back on every save; writers in the
+//! tree only ever apply deltas.
+
+use std::sync::Mutex;
+
+use crate::dir::FileRef;
+
+pub use crate::dir::{Quota, Usage};
+
+/// Which limit a write ran into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QuotaResource {
+    Bytes,
+    Files,
+}
+
+impl std::fmt::Display for QuotaResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bytes => "bytes",
+            Self::Files => "files",
+        })
+    }
+}
+
+/// A write was rejected because it would take the root past its quota.
+///
+/// Returned inside [`FSResult`](crate::FSResult) errors; use
+/// `err.downcast_ref::<QuotaExceeded>()` to tell it apart.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("quota exceeded: {used} {resource} used + {requested} requested > limit of {limit}")]
+pub struct QuotaExceeded {
+    pub resource: QuotaResource,
+    pub limit: u64,
+    pub used: u64,
+    pub requested: u64,
+}
+
+/// Signed change in [`Usage`] caused by replacing one entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct UsageDelta {
+    bytes: i64,
+    files: i64,
+}
+
+impl UsageDelta {
+    pub(crate) fn between(old: Option<&FileRef>, new: Option<&FileRef>) -> Self {
+        let old = Usage::of_file(old);
+        let new = Usage::of_file(new);
+        Self {
+            bytes: new.bytes as i64 - old.bytes as i64,
+            files: new.files as i64 - old.files as i64,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    quota: Quota,
+    /// `None` until known: legacy roots carry no usage in their header and
+    /// are only counted once a quota is set on them.
+    usage: Option<Usage>,
+}
+
+/// Shared quota limits and live usage of one root.
+#[derive(Debug, Default)]
+pub struct QuotaTracker {
+    state: Mutex<TrackerState>,
+}
+
+impl QuotaTracker {
+    pub fn quota(&self) -> Quota {
+        self.state.lock().unwrap().quota
+    }
+
+    /// Current usage, or `None` if it hasn't been counted for this root.
+    pub fn usage(&self) -> Option<Usage> {
+        self.state.lock().unwrap().usage
+    }
+
+    pub(crate) fn load(&self, quota: Quota, usage: Option<Usage>) {
+        *self.state.lock().unwrap() = TrackerState { quota, usage };
+    }
+
+    pub(crate) fn set_quota(&self, quota: Quota) {
+        self.state.lock().unwrap().quota = quota;
+    }
+
+    pub(crate) fn set_usage(&self, usage: Usage) {
+        self.state.lock().unwrap().usage = Some(usage);
+    }
+
+    /// Fails if adding `bytes` and `files` would exceed a limit. Nothing is
+    /// recorded; this is the cheap pre-check before importing a blob.
+    pub fn check(&self, bytes: u64, files: u64) -> Result<(), QuotaExceeded> {
+        let state = self.state.lock().unwrap();
+        Self::check_locked(&state, bytes, files)
+    }
+
+    /// Applies `delta` if growth stays within the quota. Shrinking is always
+    /// allowed, even on a root that is already over (e.g. after lowering it).
+    pub(crate) fn try_apply(&self, delta: UsageDelta) -> Result<(), QuotaExceeded> {
+        let mut state = self.state.lock().unwrap();
+        Self::check_locked(&state, delta.bytes.max(0) as u64, delta.files.max(0) as u64)?;
+        Self::apply_locked(&mut state, delta);
+        Ok(())
+    }
+
+    /// Applies `delta` without checking, for changes that must not fail
+    /// (deletes, merges, migrations between directories).
+    pub(crate) fn apply(&self, delta: UsageDelta) {
+        Self::apply_locked(&mut self.state.lock().unwrap(), delta);
+    }
+
+    fn check_locked(state: &TrackerState, bytes: u64, files: u64) -> Result<(), QuotaExceeded> {
+        let used = state.usage.unwrap_or_default();
+        let limits = [
+            (
+                QuotaResource::Bytes,
+                state.quota.max_bytes,
+                used.bytes,
+                bytes,
+            ),
+            (
+                QuotaResource::Files,
+                state.quota.max_files,
+                used.files,
+                files,
+            ),
+        ];
+        for (resource, limit, used, requested) in limits {
+            if let Some(limit) = limit
+                && requested > 0
+                && used.saturating_add(requested) > limit
+            {
+                return Err(QuotaExceeded {
+                    resource,
+                    limit,
+                    used,
+                    requested,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_locked(state: &mut TrackerState, delta: UsageDelta) {
+        if let Some(usage) = state.usage.as_mut() {
+            usage.bytes = usage.bytes.saturating_add_signed(delta.bytes);
+            usage.files = usage.files.saturating_add_signed(delta.files);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    fn file(len: usize) -> FileRef {
+        FileRef::new_inline_blob(Bytes::from(vec![0u8; len]))
+    }
+
+    #[test]
+    fn growth_is_checked_and_shrinking_is_not() {
+        let tracker = QuotaTracker::default();
+        tracker.load(
+            Quota {
+                max_bytes: Some(10),
+                max_files: Some(2),
+            },
+            Some(Usage::default()),
+        );
+
+        let six = file(6);
+        tracker
+            .try_apply(UsageDelta::between(None, Some(&six)))
+            .unwrap();
+        let err = tracker
+            .try_apply(UsageDelta::between(None, Some(&six)))
+            .unwrap_err();
+        assert_eq!(err.resource, QuotaResource::Bytes);
+        assert_eq!((err.used, err.requested, err.limit), (6, 6, 10));
+
+        // Replacing in place only counts the growth.
+        let ten = file(10);
+        tracker
+            .try_apply(UsageDelta::between(Some(&six), Some(&ten)))
+            .unwrap();
+        assert_eq!(
+            tracker.usage(),
+            Some(Usage {
+                bytes: 10,
+                files: 1
+            })
+        );
+
+        tracker.set_quota(Quota {
+            max_bytes: Some(4),
+            max_files: Some(1),
+        });
+        tracker.apply(UsageDelta::between(Some(&ten), Some(&six)));
+        assert_eq!(tracker.usage(), Some(Usage { bytes: 6, files: 1 }));
+        assert_eq!(
+            tracker.check(0, 1).unwrap_err().resource,
+            QuotaResource::Files
+        );
+        assert!(tracker.check(0, 0).is_ok());
+    }
+}
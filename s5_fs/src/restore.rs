@@ -0,0 +1,399 @@
+//! Verified, recursive restore of an FS5 snapshot from a blob store.
+//!
+//! [`restore_tree`] copies the `DirV1` tree rooted at a snapshot hash from
+//! a source store into a root's meta store and then, unless
+//! [`RestoreOptions::metadata_only`] is set, every content blob its
+//! `FileRef`s (including historical versions) point at into a content
+//! store. Each blob is hashed as it arrives and only stored if it matches.
+//!
+//! With [`RestoreOptions::reencrypt`] the tree is rewritten on the way in
+//! instead of copied verbatim: each directory is decrypted with the key it
+//! was written under (starting from `root_key`), every encrypted directory
+//! gets a fresh key recorded in its parent, and the root is encrypted with
+//! `dest_root_key`. The result is a new root hash that the old identity's
+//! keys cannot open, so roots can move between identities. Directories
+//! stored in plaintext stay in plaintext, keeping per-directory encryption
+//! policy intact; file contents are content addressed and copied as is.
+//!
+//! Nothing is fatal per blob: a directory or content blob that cannot be
+//! fetched, or that arrives corrupt, is recorded in
+//! [`RestoreReport::unreachable`] and the walk continues, so one run lists
+//! everything that is missing. Callers should only treat the snapshot as
+//! restored once that list is empty.
+//!
+//! This module is only available on native platforms (not WASM), like
+//! [`crate::bundle`] whose walk rules it shares.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::anyhow;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+use futures::StreamExt;
+use s5_core::{Hash, blob::BlobStore};
+
+use crate::FSResult;
+use crate::dir::{DirRef, DirRefType, DirV1, FileRef, decrypt_dir_bytes, encrypt_dir_bytes};
+use crate::gc::collect_hashes_from_dir;
+
+/// Content fetches in flight when [`RestoreOptions::concurrency`] is unset.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RestoreOptions<'a> {
+    /// Decrypts the snapshot root if the tree is encrypted.
+    pub root_key: Option<&'a [u8; 32]>,
+    /// Copy and verify only the `DirV1` tree, not file contents.
+    pub metadata_only: bool,
+    /// Content blobs fetched in parallel.
+    pub concurrency: usize,
+    /// Rewrite the tree under new keys instead of copying it verbatim.
+    pub reencrypt: bool,
+    /// Key for the rewritten root when `reencrypt` is set; `None` stores
+    /// the root in plaintext.
+    pub dest_root_key: Option<&'a [u8; 32]>,
+}
+
+impl Default for RestoreOptions<'_> {
+    fn default() -> Self {
+        Self {
+            root_key: None,
+            metadata_only: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            reencrypt: false,
+            dest_root_key: None,
+        }
+    }
+}
+
+/// Running totals, passed to the progress callback after every blob.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreProgress {
+    pub dirs: usize,
+    /// Content blobs to check; known once the directory walk is done.
+    pub blobs_total: usize,
+    /// Content blobs checked so far, whether fetched, already present or
+    /// unreachable.
+    pub blobs_done: usize,
+    /// Bytes fetched from the source, directories included.
+    pub bytes: u64,
+}
+
+/// A blob that could not be restored, and why.
+#[derive(Debug, Clone)]
+pub struct Unreachable {
+    pub hash: Hash,
+    /// Whether it is a `DirV1` blob (its whole subtree is then unchecked)
+    /// or file content.
+    pub is_dir: bool,
+    pub error: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestoreReport {
+    /// Root of the restored tree: the source root, or the rewritten one
+    /// when re-encrypting.
+    pub root: Hash,
+    pub progress: RestoreProgress,
+    /// Blobs copied from the source.
+    pub fetched: usize,
+    /// Blobs the targets already held.
+    pub present: usize,
+    pub unreachable: Vec<Unreachable>,
+}
+
+impl RestoreReport {
+    pub fn is_complete(&self) -> bool {
+        self.unreachable.is_empty()
+    }
+}
+
+/// Restores the tree rooted at `root` from `source`: directories into
+/// `meta_target`, content into `content_target`.
+///
+/// Blobs the targets already hold are not fetched again, so an
+/// interrupted restore can simply be rerun.
+pub async fn restore_tree(
+    source: &BlobStore,
+    meta_target: &BlobStore,
+    content_target: &BlobStore,
+    root: Hash,
+    options: RestoreOptions<'_>,
+    progress: &(dyn Fn(&RestoreProgress) + Sync),
+) -> FSResult<RestoreReport> {
+    let mut report = RestoreReport {
+        root,
+        progress: RestoreProgress::default(),
+        fetched: 0,
+        present: 0,
+        unreachable: Vec::new(),
+    };
+    let mut walk = Walk::default();
+    if options.reencrypt {
+        let mut rekeyed = HashMap::new();
+        let rewritten = rekey_dir(
+            source,
+            meta_target,
+            root,
+            options.root_key.copied(),
+            options.dest_root_key.copied(),
+            &mut rekeyed,
+            &mut walk,
+            &mut report,
+            progress,
+        )
+        .await;
+        match rewritten {
+            Ok(new_root) => report.root = new_root,
+            Err(e) => report.unreachable.push(Unreachable {
+                hash: root,
+                is_dir: true,
+                error: format!("{e:#}"),
+            }),
+        }
+    } else {
+        copy_tree(
+            source,
+            meta_target,
+            root,
+            options.root_key,
+            &mut walk,
+            &mut report,
+            progress,
+        )
+        .await;
+    }
+
+    if options.metadata_only {
+        return Ok(report);
+    }
+
+    let content: BTreeSet<Hash> = walk.referenced.difference(&walk.inline).copied().collect();
+    report.progress.blobs_total = content.len();
+    progress(&report.progress);
+
+    let mut fetches = futures::stream::iter(content)
+        .map(|hash| async move { (hash, copy_blob(source, content_target, hash).await) })
+        .buffer_unordered(options.concurrency.max(1));
+    while let Some((hash, result)) = fetches.next().await {
+        report.progress.blobs_done += 1;
+        match result {
+            Ok(None) => report.present += 1,
+            Ok(Some(len)) => {
+                report.fetched += 1;
+                report.progress.bytes += len;
+            }
+            Err(e) => report.unreachable.push(Unreachable {
+                hash,
+                is_dir: false,
+                error: format!("{e:#}"),
+            }),
+        }
+        progress(&report.progress);
+    }
+
+    Ok(report)
+}
+
+/// Content hashes gathered while walking directories.
+#[derive(Default)]
+struct Walk {
+    referenced: HashSet<Hash>,
+    inline: HashSet<Hash>,
+}
+
+impl Walk {
+    fn add(&mut self, dir: &DirV1) {
+        collect_hashes_from_dir(dir, &mut self.referenced);
+        collect_inline_hashes(dir, &mut self.inline);
+    }
+}
+
+/// Child directories and shards worth following: registry-backed refs
+/// are mutable pointers, not part of the snapshot.
+fn child_refs(dir: &mut DirV1) -> impl Iterator<Item = &mut DirRef> {
+    dir.header
+        .shards
+        .iter_mut()
+        .flat_map(|shards| shards.values_mut())
+        .chain(dir.dirs.values_mut())
+        .filter(|child| !matches!(child.ref_type(), DirRefType::RegistryKey))
+}
+
+/// Copies the tree verbatim, recording unreachable directories.
+async fn copy_tree(
+    source: &BlobStore,
+    meta_target: &BlobStore,
+    root: Hash,
+    root_key: Option<&[u8; 32]>,
+    walk: &mut Walk,
+    report: &mut RestoreReport,
+    progress: &(dyn Fn(&RestoreProgress) + Sync),
+) {
+    let mut visited = HashSet::new();
+    let mut queue: Vec<(Hash, Option<[u8; 32]>)> = vec![(root, root_key.copied())];
+    while let Some((hash, key)) = queue.pop() {
+        if !visited.insert(hash) {
+            continue;
+        }
+        let mut dir = match copy_dir(source, meta_target, hash, key.as_ref(), report).await {
+            Ok(dir) => dir,
+            Err(e) => {
+                report.unreachable.push(Unreachable {
+                    hash,
+                    is_dir: true,
+                    error: format!("{e:#}"),
+                });
+                continue;
+            }
+        };
+        report.progress.dirs += 1;
+        progress(&report.progress);
+
+        walk.add(&dir);
+        for child in child_refs(&mut dir) {
+            let key = child.keys.as_ref().and_then(|k| k.get(&0x0e).copied());
+            queue.push((Hash::from_bytes(child.hash), key));
+        }
+    }
+}
+
+/// Rewrites the directory `hash` (encrypted with `key`, if any) and its
+/// subtree into `target`, encrypting it with `new_key`, and returns its
+/// new hash. Children that cannot be fetched keep their old `DirRef` and
+/// are reported unreachable. `rekeyed` maps already rewritten directories
+/// to their new hash and key, so a shared subtree is rewritten once.
+#[allow(clippy::too_many_arguments)]
+fn rekey_dir<'a>(
+    source: &'a BlobStore,
+    target: &'a BlobStore,
+    hash: Hash,
+    key: Option<[u8; 32]>,
+    new_key: Option<[u8; 32]>,
+    rekeyed: &'a mut HashMap<Hash, (Hash, Option<[u8; 32]>)>,
+    walk: &'a mut Walk,
+    report: &'a mut RestoreReport,
+    progress: &'a (dyn Fn(&RestoreProgress) + Sync),
+) -> Pin<Box<dyn Future<Output = FSResult<Hash>> + Send + 'a>> {
+    Box::pin(async move {
+        let bytes = fetch_verified(source, hash).await?;
+        report.progress.bytes += bytes.len() as u64;
+        let decrypted = decrypt_dir_bytes(bytes, key.as_ref())
+            .map_err(|e| anyhow!("failed to decrypt DirV1: {e}"))?;
+        let mut dir =
+            DirV1::from_bytes(&decrypted).map_err(|e| anyhow!("failed to decode DirV1: {e}"))?;
+        walk.add(&dir);
+
+        for child in child_refs(&mut dir) {
+            let old_hash = Hash::from_bytes(child.hash);
+            let old_key = child.keys.as_ref().and_then(|k| k.get(&0x0e).copied());
+            let (new_hash, child_key) = match rekeyed.get(&old_hash) {
+                Some(done) => *done,
+                None => {
+                    // Encrypted directories get a fresh key; plaintext
+                    // ones stay plaintext.
+                    let child_key =
+                        old_key.map(|_| XChaCha20Poly1305::generate_key(&mut OsRng).into());
+                    let rewritten = rekey_dir(
+                        source, target, old_hash, old_key, child_key, rekeyed, walk, report,
+                        progress,
+                    )
+                    .await;
+                    match rewritten {
+                        Ok(new_hash) => {
+                            rekeyed.insert(old_hash, (new_hash, child_key));
+                            (new_hash, child_key)
+                        }
+                        Err(e) => {
+                            report.unreachable.push(Unreachable {
+                                hash: old_hash,
+                                is_dir: true,
+                                error: format!("{e:#}"),
+                            });
+                            continue;
+                        }
+                    }
+                }
+            };
+            child.hash = *new_hash.as_bytes();
+            if let Some(child_key) = child_key {
+                child
+                    .keys
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert(0x0e, child_key);
+            }
+        }
+
+        let plain = dir.to_bytes()?;
+        let bytes = match &new_key {
+            Some(new_key) => encrypt_dir_bytes(new_key, &plain)?,
+            None => plain,
+        };
+        let new_hash = target.import_bytes(bytes).await?.hash;
+        report.fetched += 1;
+        report.progress.dirs += 1;
+        progress(&report.progress);
+        Ok(new_hash)
+    })
+}
+
+/// Copies one `DirV1` blob (unless already present) and decodes it.
+async fn copy_dir(
+    source: &BlobStore,
+    target: &BlobStore,
+    hash: Hash,
+    key: Option<&[u8; 32]>,
+    report: &mut RestoreReport,
+) -> FSResult<DirV1> {
+    let bytes = if target.contains(hash).await? {
+        report.present += 1;
+        target.read_as_bytes(hash, 0, None).await?
+    } else {
+        let bytes = fetch_verified(source, hash).await?;
+        target.import_bytes(bytes.clone()).await?;
+        report.fetched += 1;
+        report.progress.bytes += bytes.len() as u64;
+        bytes
+    };
+    let decrypted =
+        decrypt_dir_bytes(bytes, key).map_err(|e| anyhow!("failed to decrypt DirV1: {e}"))?;
+    DirV1::from_bytes(&decrypted).map_err(|e| anyhow!("failed to decode DirV1: {e}"))
+}
+
+/// Copies one content blob unless already present; returns the bytes
+/// fetched, or `None` if nothing was.
+async fn copy_blob(source: &BlobStore, target: &BlobStore, hash: Hash) -> FSResult<Option<u64>> {
+    if target.contains(hash).await? {
+        return Ok(None);
+    }
+    let bytes = fetch_verified(source, hash).await?;
+    let len = bytes.len() as u64;
+    target.import_bytes(bytes).await?;
+    Ok(Some(len))
+}
+
+async fn fetch_verified(source: &BlobStore, hash: Hash) -> FSResult<bytes::Bytes> {
+    let bytes = source.read_as_bytes(hash, 0, None).await?;
+    let actual = Hash::new(&bytes);
+    if actual != hash {
+        return Err(anyhow!("source returned corrupt data (hashes to {actual})"));
+    }
+    Ok(bytes)
+}
+
+/// Hashes of files (and their versions) whose content is stored inline
+/// in the metadata rather than in a blob store.
+pub(crate) fn collect_inline_hashes(dir: &DirV1, acc: &mut HashSet<Hash>) {
+    let mut stack: Vec<&FileRef> = dir.files.values().collect();
+    while let Some(file_ref) = stack.pop() {
+        if file_ref.inline_data().is_some() {
+            acc.insert(Hash::from_bytes(file_ref.hash));
+        }
+        stack.extend(file_ref.prev.as_deref());
+        stack.extend(file_ref.first_version.as_deref());
+    }
+}
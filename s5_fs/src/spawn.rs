@@ -2,6 +2,9 @@
 //!
 //! Provides unified APIs for spawning async tasks that work on both
 //! native (tokio) and WASM (wasm-bindgen-futures) platforms.
+//!
+//! With the `sim` feature, native spawns are perturbed by the running
+//! [`crate::sim::Simulation`] (if any) to explore different interleavings.
 
 use std::future::Future;
 
@@ -22,6 +25,17 @@ pub fn spawn_task<F>(future: F)
 where
     F: Future<Output = ()> + Send + 'static,
 {
+    #[cfg(feature = "sim")]
+    if crate::sim::is_active() {
+        let yields = crate::sim::spawn_yields();
+        tokio::spawn(async move {
+            for _ in 0..yields {
+                tokio::task::yield_now().await;
+            }
+            future.await;
+        });
+        return;
+    }
     tokio::spawn(future);
 }
 
@@ -45,6 +59,8 @@ pub fn spawn_delayed<F>(delay_ms: u64, future: F)
 where
     F: Future<Output = ()> + Send + 'static,
 {
+    #[cfg(feature = "sim")]
+    let delay_ms = delay_ms + crate::sim::delay_jitter_ms();
     tokio::spawn(async move {
         tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
         future.await;
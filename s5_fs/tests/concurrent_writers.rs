@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use s5_core::{RegistryApi, StreamKey, blob::BlobStore};
+use s5_fs::{DirContext, FS5, FileRef, SigningKey};
+use s5_registry_redb::RedbRegistry;
+use s5_store_memory::MemoryStore;
+use tempfile::tempdir;
+
+/// Two writers sharing one registry-backed root, as two devices opening
+/// the same seed would.
+fn open_writer(blobs: &BlobStore, registry: &Arc<dyn RegistryApi + Send + Sync>) -> FS5 {
+    let (encryption_key, signing_key, public_key) = s5_fs::derive_sync_keys(b"shared secret");
+    FS5::open(DirContext::new_encrypted_registry(
+        StreamKey::PublicKeyEd25519(public_key),
+        SigningKey::new(signing_key),
+        encryption_key,
+        blobs.clone(),
+        registry.clone(),
+    ))
+}
+
+#[tokio::test]
+async fn test_concurrent_saves_merge_instead_of_overwriting() {
+    let tmp = tempdir().unwrap();
+    let registry: Arc<dyn RegistryApi + Send + Sync> =
+        Arc::new(RedbRegistry::open(tmp.path()).unwrap());
+    let blobs = BlobStore::new(MemoryStore::new());
+
+    // Both writers load the (empty) root before either saves.
+    let a = open_writer(&blobs, &registry);
+    let b = open_writer(&blobs, &registry);
+    assert!(!a.file_exists("from_a.txt").await);
+    assert!(!b.file_exists("from_b.txt").await);
+
+    a.file_put_sync(
+        "from_a.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"a")),
+    )
+    .await
+    .unwrap();
+    a.save().await.unwrap();
+
+    // `b` is now behind the registry head; its save merges `a`'s write.
+    b.file_put_sync(
+        "from_b.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"b")),
+    )
+    .await
+    .unwrap();
+    b.save().await.unwrap();
+    assert!(b.file_exists("from_a.txt").await);
+
+    let reopened = open_writer(&blobs, &registry);
+    assert!(reopened.file_exists("from_a.txt").await);
+    assert!(reopened.file_exists("from_b.txt").await);
+
+    // `a` catches up the same way on its next save.
+    a.file_put_sync(
+        "again_a.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"a2")),
+    )
+    .await
+    .unwrap();
+    a.save().await.unwrap();
+    let reopened = open_writer(&blobs, &registry);
+    for name in ["from_a.txt", "from_b.txt", "again_a.txt"] {
+        assert!(reopened.file_exists(name).await, "{name} lost");
+    }
+}
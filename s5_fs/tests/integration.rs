@@ -16,7 +16,7 @@
 //!     and `s5_store_local` (for the underlying blob and registry storage).
 
 use bytes::Bytes;
-use s5_fs::{DirContext, FS5, FileRef};
+use s5_fs::{DirContext, EncryptionKeySource, EncryptionPolicy, FS5, FileRef};
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -240,6 +240,68 @@ async fn encrypted_round_trip() {
     assert!(fs2.file_exists("enc/one.txt").await);
 }
 
+#[tokio::test]
+async fn encryption_policy_controls() {
+    let temp_dir = tempdir().expect("tmp");
+    let ctx = DirContext::open_local_root(temp_dir.path()).expect("ctx");
+    let fs = FS5::open(ctx);
+
+    fs.create_dir("enc", true).await.unwrap();
+    fs.file_put_sync(
+        "enc/docs/a.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"a")),
+    )
+    .await
+    .unwrap();
+
+    // Plaintext children can't be created under an encrypted parent by accident.
+    assert!(fs.create_dir("enc/plain", false).await.is_err());
+    // Files already under `docs/` move into the new encrypted subdir.
+    fs.create_dir("enc/docs", true).await.unwrap();
+    assert_eq!(
+        fs.encryption_policy("enc/docs").await.unwrap(),
+        EncryptionPolicy::Encrypted
+    );
+    assert_eq!(
+        fs.encryption_policy("").await.unwrap(),
+        EncryptionPolicy::Plaintext
+    );
+
+    // An explicit opt-out is allowed and reported as such.
+    fs.set_encryption("enc/docs", false, EncryptionKeySource::Generate)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.encryption_policy("enc/docs").await.unwrap(),
+        EncryptionPolicy::PlaintextOptOut
+    );
+    let (entries, _) = fs.list_at_with_policy("enc", None, 10).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].2, Some(EncryptionPolicy::PlaintextOptOut));
+
+    // Re-keying with an explicit key keeps the contents readable.
+    fs.set_encryption("enc/docs", true, EncryptionKeySource::Explicit([7u8; 32]))
+        .await
+        .unwrap();
+    let snap = fs.export_snapshot_at("enc").await.unwrap();
+    let docs = &snap.dirs["docs"];
+    assert!(docs.encryption_type.is_some());
+    assert_eq!(docs.keys.as_ref().unwrap()[&0x0e], [7u8; 32]);
+    assert_eq!(
+        fs.file_get("enc/docs/a.txt").await.unwrap().inline_data(),
+        Some(&b"a"[..])
+    );
+
+    // The root itself is fixed by its context.
+    assert!(
+        fs.set_encryption("", true, EncryptionKeySource::Generate)
+            .await
+            .is_err()
+    );
+    fs.save().await.unwrap();
+    fs.shutdown().await.unwrap();
+}
+
 #[tokio::test]
 async fn test_auto_promotion() {
     let temp_dir = tempdir().expect("tmp");
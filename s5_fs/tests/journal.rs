@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use s5_core::{RegistryApi, StreamKey, blob::BlobStore};
+use s5_fs::{DirContext, FS5, FileRef, Journal, JournalOp, SigningKey};
+use s5_registry_redb::RedbRegistry;
+use s5_store_memory::MemoryStore;
+use tempfile::tempdir;
+
+fn open_root(blobs: &BlobStore, registry: &Arc<dyn RegistryApi + Send + Sync>) -> FS5 {
+    let (encryption_key, signing_key, public_key) = s5_fs::derive_sync_keys(b"journal secret");
+    FS5::open(DirContext::new_encrypted_registry(
+        StreamKey::PublicKeyEd25519(public_key),
+        SigningKey::new(signing_key),
+        encryption_key,
+        blobs.clone(),
+        registry.clone(),
+    ))
+}
+
+#[tokio::test]
+async fn test_unsaved_changes_replay_after_crash() {
+    let tmp = tempdir().unwrap();
+    let registry: Arc<dyn RegistryApi + Send + Sync> =
+        Arc::new(RedbRegistry::open(tmp.path().join("registry")).unwrap());
+    let blobs = BlobStore::new(MemoryStore::new());
+    let journal_path = tmp.path().join("journal.redb");
+
+    {
+        let fs = open_root(&blobs, &registry)
+            .with_journal(Journal::open_redb(&journal_path, b"root").unwrap());
+        fs.file_put_sync("saved.txt", FileRef::new_inline_blob(Bytes::from_static(b"s")))
+            .await
+            .unwrap();
+        fs.save().await.unwrap();
+        assert!(fs.pending_changes().is_empty());
+
+        let docs = fs.subdir("docs").await.unwrap();
+        docs.file_put_sync("draft.txt", FileRef::new_inline_blob(Bytes::from_static(b"d")))
+            .await
+            .unwrap();
+        fs.file_delete("saved.txt").await.unwrap();
+        let pending: Vec<String> = fs
+            .pending_changes()
+            .iter()
+            .map(|op| op.path().to_string())
+            .collect();
+        assert_eq!(pending, ["docs/draft.txt", "saved.txt"]);
+        // Dropped without saving.
+    }
+
+    let fs = open_root(&blobs, &registry)
+        .with_journal(Journal::open_redb(&journal_path, b"root").unwrap());
+    assert!(fs.file_exists("saved.txt").await);
+    assert!(matches!(
+        fs.pending_changes().last(),
+        Some(JournalOp::Delete { .. })
+    ));
+    assert_eq!(fs.replay_journal().await.unwrap(), 2);
+    assert!(fs.pending_changes().is_empty());
+
+    let reopened = open_root(&blobs, &registry);
+    assert!(reopened.file_exists("docs/draft.txt").await);
+    assert!(!reopened.file_exists("saved.txt").await);
+}
@@ -0,0 +1,86 @@
+use bytes::Bytes;
+use s5_core::{Hash, PinContext};
+use s5_fs::pin_tree::{pin_tree, unpin_tree, update_tree_pin};
+use s5_fs::{DirContext, FS5, FileRef};
+use s5_store_local::{LocalStore, LocalStoreConfig};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn tree_pins_follow_the_tree_incrementally() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let temp_dir = tempdir().expect("tmp");
+    let base = temp_dir.path().to_path_buf();
+
+    let ctx = DirContext::open_local_root(&base).expect("ctx");
+    let pins = ctx.pins.clone().expect("pins");
+    let fs = FS5::open(ctx);
+    let meta = LocalStore::create(LocalStoreConfig {
+        base_path: base.to_string_lossy().into(),
+        ..Default::default()
+    })
+    .to_blob_store();
+
+    fs.create_dir("docs", false).await.unwrap();
+    fs.file_put_sync("a.txt", FileRef::new_inline_blob(Bytes::from_static(b"a")))
+        .await
+        .unwrap();
+    fs.file_put_sync(
+        "docs/b.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"b")),
+    )
+    .await
+    .unwrap();
+    fs.save().await.unwrap();
+
+    let context = PinContext::NodeId([1u8; 32]);
+    let root_v1 = fs.snapshot_hash().await.unwrap();
+    let v1 = pin_tree(&meta, pins.as_ref(), root_v1, None, context.clone())
+        .await
+        .unwrap();
+
+    // Root and `docs` metadata, plus both files' content.
+    assert_eq!(v1.dirs.len(), 2);
+    let a = Hash::new(b"a");
+    let b = Hash::new(b"b");
+    let hashes = v1.hashes();
+    assert!(hashes.contains(&root_v1) && hashes.contains(&a) && hashes.contains(&b));
+    for hash in &hashes {
+        assert!(pins.is_pinned(*hash, context.clone()).await.unwrap());
+    }
+
+    // The record survives a round trip through its encoding.
+    let decoded = s5_fs::pin_tree::TreePin::from_bytes(&v1.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded, v1);
+
+    // Change only the root: `docs` keeps its hash and its record entry.
+    fs.file_put_sync("c.txt", FileRef::new_inline_blob(Bytes::from_static(b"c")))
+        .await
+        .unwrap();
+    fs.file_delete("a.txt").await.unwrap();
+    fs.save().await.unwrap();
+    let root_v2 = fs.snapshot_hash().await.unwrap();
+    let v2 = update_tree_pin(&meta, pins.as_ref(), &v1, root_v2, None)
+        .await
+        .unwrap();
+
+    let docs_v1: Vec<_> = v1.dirs.keys().filter(|h| **h != v1.root).collect();
+    assert!(v2.dirs.contains_key(docs_v1[0]));
+    assert!(!pins.is_pinned(root_v1, context.clone()).await.unwrap());
+    assert!(pins.is_pinned(root_v2, context.clone()).await.unwrap());
+    assert!(pins.is_pinned(b, context.clone()).await.unwrap());
+    assert!(
+        pins.is_pinned(Hash::new(b"c"), context.clone())
+            .await
+            .unwrap()
+    );
+    // `a.txt` is a tombstone now, but its history still holds the content.
+    assert!(pins.is_pinned(a, context.clone()).await.unwrap());
+
+    unpin_tree(pins.as_ref(), &v2).await.unwrap();
+    for hash in v2.hashes() {
+        assert!(!pins.is_pinned(hash, context.clone()).await.unwrap());
+    }
+
+    fs.shutdown().await.unwrap();
+}
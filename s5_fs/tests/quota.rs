@@ -0,0 +1,66 @@
+use bytes::Bytes;
+use s5_fs::dir::{DirV1, Usage};
+use s5_fs::quota::QuotaResource;
+use s5_fs::{DirContext, FS5, FileRef, Quota, QuotaExceeded};
+use tempfile::tempdir;
+
+fn file(len: usize) -> FileRef {
+    FileRef::new_inline_blob(Bytes::from(vec![7u8; len]))
+}
+
+fn exceeded(err: anyhow::Error) -> QuotaExceeded {
+    err.downcast::<QuotaExceeded>()
+        .expect("expected a QuotaExceeded error")
+}
+
+#[tokio::test]
+async fn writes_are_capped_by_the_root_quota() {
+    let temp_dir = tempdir().expect("tmp");
+    let base = temp_dir.path().to_path_buf();
+    let fs = FS5::open(DirContext::open_local_root(&base).expect("ctx"));
+
+    // Existing content is counted when the quota is set.
+    fs.file_put_sync("old.txt", file(2)).await.unwrap();
+    fs.set_quota(Quota {
+        max_bytes: Some(12),
+        max_files: Some(3),
+    })
+    .await
+    .unwrap();
+    assert_eq!(fs.quota().1, Some(Usage { bytes: 2, files: 1 }));
+
+    fs.file_put_sync("a.txt", file(6)).await.unwrap();
+    let err = exceeded(fs.file_put_sync("b.txt", file(6)).await.unwrap_err());
+    assert_eq!(err.resource, QuotaResource::Bytes);
+    assert!(!fs.file_exists("b.txt").await);
+
+    // Growing in place only needs room for the difference.
+    fs.file_put_sync("a.txt", file(10)).await.unwrap();
+
+    // Nested directories count towards the same root, and the
+    // fire-and-forget path reports rejections too.
+    fs.create_dir("sub", false).await.unwrap();
+    fs.file_put("sub/empty", file(0)).await.unwrap();
+    let err = exceeded(fs.file_put("sub/more", file(0)).await.unwrap_err());
+    assert_eq!(err.resource, QuotaResource::Files);
+    assert!(fs.check_quota("sub/more", 0).await.is_err());
+    assert!(fs.check_quota("a.txt", 10).await.is_ok());
+
+    // Deleting frees room.
+    fs.file_delete("a.txt").await.unwrap();
+    fs.file_put_sync("b.txt", file(6)).await.unwrap();
+    assert_eq!(fs.quota().1, Some(Usage { bytes: 8, files: 3 }));
+
+    // Subdirectory handles share the quota but can't change it.
+    let sub = fs.subdir("sub").await.unwrap();
+    assert!(sub.set_quota(Quota::default()).await.is_err());
+    assert!(sub.file_put_sync("big", file(5)).await.is_err());
+
+    // Quota and usage are persisted in the root header.
+    fs.save().await.unwrap();
+    let root = DirV1::from_bytes(&std::fs::read(base.join("root.fs5.cbor")).unwrap()).unwrap();
+    assert_eq!(root.header.quota.unwrap().max_bytes, Some(12));
+    assert_eq!(root.header.usage, Some(Usage { bytes: 8, files: 3 }));
+
+    fs.shutdown().await.unwrap();
+}
@@ -79,3 +79,60 @@ async fn test_sharding_behavior() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stat_many_matches_file_get_across_dirs_and_shards() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let ctx = DirContext::open_local_root(tmp.path())?;
+    let fs = FS5::open(ctx).with_autosave(10).await?;
+
+    let count = 2000;
+    fs.batch(|fs| async move {
+        for i in 0..count {
+            let name = format!("big/file_{:04}.txt", i);
+            fs.file_put_sync(&name, FileRef::new_inline_blob(Bytes::from(format!("{i}"))))
+                .await?;
+        }
+        fs.file_put_sync("top.txt", FileRef::new_inline_blob(Bytes::from("t")))
+            .await?;
+        fs.file_put_sync("a/b/deep.txt", FileRef::new_inline_blob(Bytes::from("d")))
+            .await?;
+        fs.file_put_sync("gone.txt", FileRef::new_inline_blob(Bytes::from("g")))
+            .await?;
+        Ok(())
+    })
+    .await?;
+    fs.file_delete("gone.txt").await?;
+    fs.save().await?;
+
+    let mut paths: Vec<String> = (0..count)
+        .step_by(97)
+        .map(|i| format!("big/file_{:04}.txt", i))
+        .collect();
+    paths.extend(
+        [
+            "top.txt",
+            "a/b/deep.txt",
+            "gone.txt",
+            "missing.txt",
+            "a/missing.txt",
+            "nope/x.txt",
+        ]
+        .map(String::from),
+    );
+
+    let refs = fs.stat_many(&paths).await;
+    assert_eq!(refs.len(), paths.len());
+    for (path, stat) in paths.iter().zip(&refs) {
+        let expected = fs.file_get(path).await;
+        assert_eq!(
+            stat.as_ref().map(|f| f.hash),
+            expected.as_ref().map(|f| f.hash),
+            "{path}"
+        );
+    }
+    let found = refs.iter().filter(|r| r.is_some()).count();
+    assert_eq!(found, paths.len() - 4);
+    assert!(fs.stat_many::<&str>(&[]).await.is_empty());
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Autosave / write races replayed under many deterministic schedules.
+//!
+//! Run with `cargo test -p s5_fs --features sim --test simulation`. A failing
+//! seed is printed in the assertion message and replays exactly.
+#![cfg(feature = "sim")]
+
+use bytes::Bytes;
+use s5_fs::sim::{Simulation, random_below};
+use s5_fs::{DirContext, FS5, FileRef};
+use std::time::Duration;
+use tempfile::tempdir;
+
+const SEEDS: u64 = 64;
+
+#[test]
+fn autosave_persists_every_write_under_adversarial_schedules() {
+    for seed in 0..SEEDS {
+        let fs_dir = tempdir().unwrap();
+        let path = fs_dir.path().to_path_buf();
+
+        Simulation::new(seed).max_delay_jitter_ms(150).run(async {
+            let ctx = DirContext::open_local_root(&path).unwrap();
+            let fs = FS5::open(ctx).with_autosave(100).await.unwrap();
+
+            // Writes land around (before, during, after) debounce windows.
+            for i in 0..12 {
+                let name = format!("f{i}.txt");
+                let blob = FileRef::new_inline_blob(Bytes::from(name.clone()));
+                fs.file_put_sync(&name, blob).await.unwrap();
+                let pause = random_below(250).unwrap();
+                tokio::time::sleep(Duration::from_millis(pause)).await;
+            }
+
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            fs.shutdown().await.unwrap();
+        });
+
+        Simulation::new(seed).run(async {
+            let fs = FS5::open(DirContext::open_local_root(&path).unwrap());
+            for i in 0..12 {
+                assert!(
+                    fs.file_exists(&format!("f{i}.txt")).await,
+                    "seed {seed}: f{i}.txt lost"
+                );
+            }
+        });
+    }
+}
+
+#[test]
+fn subdir_updates_reach_root_under_adversarial_schedules() {
+    for seed in 0..SEEDS {
+        let fs_dir = tempdir().unwrap();
+        let path = fs_dir.path().to_path_buf();
+
+        Simulation::new(seed).run(async {
+            let fs = FS5::open(DirContext::open_local_root(&path).unwrap())
+                .with_autosave(50)
+                .await
+                .unwrap();
+            let a = fs.subdir("a").await.unwrap();
+            let b = fs.subdir("b").await.unwrap();
+
+            // Two child actors racing parent hash updates.
+            let put_a = async {
+                for i in 0..6 {
+                    a.file_put_sync(&format!("{i}"), FileRef::new_inline_blob(Bytes::from("a")))
+                        .await
+                        .unwrap();
+                    tokio::task::yield_now().await;
+                }
+            };
+            let put_b = async {
+                for i in 0..6 {
+                    b.file_put_sync(&format!("{i}"), FileRef::new_inline_blob(Bytes::from("b")))
+                        .await
+                        .unwrap();
+                    tokio::task::yield_now().await;
+                }
+            };
+            tokio::join!(put_a, put_b);
+
+            fs.save().await.unwrap();
+            fs.shutdown().await.unwrap();
+        });
+
+        Simulation::new(seed).run(async {
+            let fs = FS5::open(DirContext::open_local_root(&path).unwrap());
+            for i in 0..6 {
+                assert!(
+                    fs.file_exists(&format!("a/{i}")).await,
+                    "seed {seed}: a/{i}"
+                );
+                assert!(
+                    fs.file_exists(&format!("b/{i}")).await,
+                    "seed {seed}: b/{i}"
+                );
+            }
+        });
+    }
+}
@@ -1,7 +1,8 @@
 use bytes::Bytes;
-use s5_core::{Hash, PinContext};
+use s5_core::{Hash, PinContext, blob::BlobStore};
 use s5_fs::{DirContext, FS5, FileRef};
 use s5_store_local::{LocalStore, LocalStoreConfig};
+use s5_store_memory::MemoryStore;
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -26,6 +27,7 @@ async fn snapshot_hash_persists_in_meta_store() {
     // The meta blob store for this root is a LocalStore rooted at `base`.
     let meta = LocalStore::create(LocalStoreConfig {
         base_path: base.to_string_lossy().into(),
+        ..Default::default()
     })
     .to_blob_store();
 
@@ -185,3 +187,332 @@ async fn delete_snapshot_removes_index_and_unpins() {
         "snapshot LocalFsSnapshot pin should be removed after delete_snapshot",
     );
 }
+
+#[tokio::test]
+async fn bundle_round_trips_snapshot_to_fresh_root() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source_dir = tempdir().expect("tmp");
+    let source = source_dir.path().to_path_buf();
+    let source_content = BlobStore::new(MemoryStore::new());
+
+    let fs = FS5::open(DirContext::open_local_root(&source).expect("ctx"));
+    let big = Bytes::from(vec![7u8; 64 * 1024]);
+    let big_id = source_content
+        .import_bytes(big.clone())
+        .await
+        .expect("import");
+    fs.file_put_sync("dir/big.bin", FileRef::new(big_id.hash, big.len() as u64))
+        .await
+        .unwrap();
+    fs.file_put_sync(
+        "small.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"s")),
+    )
+    .await
+    .unwrap();
+    let (name, root_hash) = fs.create_snapshot().await.expect("create_snapshot");
+    fs.shutdown().await.unwrap();
+
+    let out = source.join("snap.s5");
+    let written = s5_fs::bundle::write_bundle(&source, &name, None, &source_content, &out)
+        .await
+        .expect("write_bundle");
+    assert_eq!(written.root, root_hash);
+    // The inline file travels in the metadata, not as a content blob.
+    assert_eq!(written.content_blobs, 1);
+    assert!(
+        written.meta_blobs >= 2,
+        "root and dir/ must both be bundled"
+    );
+
+    let target_dir = tempdir().expect("tmp");
+    let target = target_dir.path().to_path_buf();
+    let target_content = BlobStore::new(MemoryStore::new());
+    let restored = s5_fs::bundle::restore_bundle(&out, &target, None, &target_content)
+        .await
+        .expect("restore_bundle");
+    assert_eq!(restored.root, root_hash);
+
+    assert_eq!(
+        s5_fs::snapshots::list_snapshots(&target).unwrap(),
+        vec![(name.clone(), root_hash)]
+    );
+    assert_eq!(
+        target_content
+            .read_as_bytes(big_id.hash, 0, None)
+            .await
+            .expect("content restored"),
+        big
+    );
+    let meta = LocalStore::create(LocalStoreConfig {
+        base_path: target.to_string_lossy().into(),
+        ..Default::default()
+    })
+    .to_blob_store();
+    assert!(meta.contains(root_hash).await.expect("contains"));
+
+    // Restoring again is a no-op.
+    s5_fs::bundle::restore_bundle(&out, &target, None, &target_content)
+        .await
+        .expect("second restore");
+}
+
+#[tokio::test]
+async fn bundle_restore_rejects_corrupt_blob() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source_dir = tempdir().expect("tmp");
+    let source = source_dir.path().to_path_buf();
+    let content = BlobStore::new(MemoryStore::new());
+
+    let fs = FS5::open(DirContext::open_local_root(&source).expect("ctx"));
+    let id = content
+        .import_bytes(Bytes::from_static(b"payload that is not inline"))
+        .await
+        .expect("import");
+    fs.file_put_sync("a.bin", FileRef::new(id.hash, 26))
+        .await
+        .unwrap();
+    let (name, _) = fs.create_snapshot().await.expect("create_snapshot");
+    fs.shutdown().await.unwrap();
+
+    let out = source.join("snap.s5");
+    s5_fs::bundle::write_bundle(&source, &name, None, &content, &out)
+        .await
+        .expect("write_bundle");
+
+    // Flip the last byte of the content blob.
+    let index = s5_fs::bundle::read_bundle_index(&out).expect("index");
+    let blob = &index.content[0];
+    let mut bytes = std::fs::read(&out).unwrap();
+    bytes[(blob.offset + blob.len - 1) as usize] ^= 0xff;
+    std::fs::write(&out, bytes).unwrap();
+
+    let target_dir = tempdir().expect("tmp");
+    let err = s5_fs::bundle::restore_bundle(
+        &out,
+        target_dir.path(),
+        None,
+        &BlobStore::new(MemoryStore::new()),
+    )
+    .await
+    .expect_err("corrupt bundle must not restore");
+    assert!(err.to_string().contains("corrupt"), "{err}");
+    assert!(
+        s5_fs::snapshots::list_snapshots(target_dir.path())
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn diff_bundle_carries_only_new_blobs_and_needs_base() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source_dir = tempdir().expect("tmp");
+    let source = source_dir.path().to_path_buf();
+    let content = BlobStore::new(MemoryStore::new());
+    let fs = FS5::open(DirContext::open_local_root(&source).expect("ctx"));
+
+    let put = |path: &'static str, data: &'static [u8]| {
+        let (fs, content) = (fs.clone(), content.clone());
+        async move {
+            let id = content
+                .import_bytes(Bytes::from_static(data))
+                .await
+                .expect("import");
+            fs.file_put_sync(path, FileRef::new(id.hash, data.len() as u64))
+                .await
+                .unwrap();
+            id.hash
+        }
+    };
+    put("keep/old.bin", b"unchanged content that stays in the base").await;
+    let (base, _) = fs.create_snapshot().await.expect("base snapshot");
+    let added = put(
+        "new/added.bin",
+        b"content only present in the second snapshot",
+    )
+    .await;
+    let (next, next_root) = fs.create_snapshot().await.expect("next snapshot");
+    fs.shutdown().await.unwrap();
+
+    let full = source.join("base.s5");
+    s5_fs::bundle::write_bundle(&source, &base, None, &content, &full)
+        .await
+        .expect("write base bundle");
+    let diff = source.join("diff.s5");
+    let summary = s5_fs::bundle::write_diff_bundle(&source, &base, &next, None, &content, &diff)
+        .await
+        .expect("write_diff_bundle");
+    assert_eq!(summary.content_blobs, 1);
+    let index = s5_fs::bundle::read_bundle_index(&diff).expect("index");
+    assert_eq!(Hash::from_bytes(*index.content[0].hash), added);
+    // The new root and new/ only; the unchanged keep/ is in the base.
+    assert_eq!(index.meta.len(), 2);
+    assert_eq!(index.base_hash(), Some(summary.base.expect("diff base")));
+
+    // Without the base the diff is refused.
+    let target_dir = tempdir().expect("tmp");
+    let target = target_dir.path().to_path_buf();
+    let target_content = BlobStore::new(MemoryStore::new());
+    let err = s5_fs::bundle::restore_bundle(&diff, &target, None, &target_content)
+        .await
+        .expect_err("diff without base");
+    assert!(err.to_string().contains("base snapshot"), "{err}");
+
+    s5_fs::bundle::restore_bundle(&full, &target, None, &target_content)
+        .await
+        .expect("restore base");
+    let restored = s5_fs::bundle::restore_bundle(&diff, &target, None, &target_content)
+        .await
+        .expect("apply diff");
+    assert_eq!(restored.root, next_root);
+    assert_eq!(s5_fs::snapshots::list_snapshots(&target).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn restore_tree_copies_and_reports_unreachable_content() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source_dir = tempdir().expect("tmp");
+    let source = source_dir.path().to_path_buf();
+    let content = BlobStore::new(MemoryStore::new());
+    let fs = FS5::open(DirContext::open_local_root(&source).expect("ctx"));
+    let present = content
+        .import_bytes(Bytes::from_static(b"content the source still has"))
+        .await
+        .expect("import");
+    fs.file_put_sync("a/present.bin", FileRef::new(present.hash, 28))
+        .await
+        .unwrap();
+    let lost = Hash::new(b"content nobody has");
+    fs.file_put_sync("lost.bin", FileRef::new(lost, 18))
+        .await
+        .unwrap();
+    let root = fs.snapshot_hash().await.expect("snapshot_hash");
+    fs.shutdown().await.unwrap();
+
+    // One source store holding both the tree and its content.
+    let source_meta = LocalStore::create(LocalStoreConfig {
+        base_path: source.to_string_lossy().into(),
+        ..Default::default()
+    })
+    .to_blob_store();
+    for hash in source_meta.list_hashes().await.unwrap() {
+        let bytes = source_meta.read_as_bytes(hash, 0, None).await.unwrap();
+        content.import_bytes(bytes).await.unwrap();
+    }
+
+    let meta_target = BlobStore::new(MemoryStore::new());
+    let content_target = BlobStore::new(MemoryStore::new());
+    let metadata_only = s5_fs::restore::restore_tree(
+        &content,
+        &meta_target,
+        &content_target,
+        root,
+        s5_fs::restore::RestoreOptions {
+            metadata_only: true,
+            ..Default::default()
+        },
+        &|_| {},
+    )
+    .await
+    .expect("metadata-only restore");
+    assert!(metadata_only.is_complete());
+    assert_eq!(metadata_only.progress.dirs, 2);
+    assert!(meta_target.contains(root).await.unwrap());
+    assert!(!content_target.contains(present.hash).await.unwrap());
+
+    let report = s5_fs::restore::restore_tree(
+        &content,
+        &meta_target,
+        &content_target,
+        root,
+        Default::default(),
+        &|_| {},
+    )
+    .await
+    .expect("restore");
+    assert_eq!(report.progress.blobs_total, 2);
+    assert_eq!(report.progress.blobs_done, 2);
+    assert!(content_target.contains(present.hash).await.unwrap());
+    assert_eq!(report.unreachable.len(), 1);
+    assert_eq!(report.unreachable[0].hash, lost);
+    assert!(!report.unreachable[0].is_dir);
+}
+
+#[tokio::test]
+async fn restore_tree_reencrypts_under_new_keys() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source_dir = tempdir().expect("tmp");
+    let source = source_dir.path().to_path_buf();
+    let fs = FS5::open(DirContext::open_local_root(&source).expect("ctx"));
+    fs.create_dir("secret", true).await.unwrap();
+    fs.file_put_sync(
+        "secret/note.txt",
+        FileRef::new_inline_blob(Bytes::from_static(b"n")),
+    )
+    .await
+    .unwrap();
+    let root = fs.snapshot_hash().await.expect("snapshot_hash");
+    fs.shutdown().await.unwrap();
+
+    let source_meta = LocalStore::create(LocalStoreConfig {
+        base_path: source.to_string_lossy().into(),
+        ..Default::default()
+    })
+    .to_blob_store();
+    let old_root =
+        s5_fs::dir::DirV1::from_bytes(&source_meta.read_as_bytes(root, 0, None).await.unwrap())
+            .unwrap();
+    let old_secret = old_root.dirs["secret"].clone();
+    let old_key = old_secret.keys.as_ref().unwrap()[&0x0e];
+
+    let dest_key = [0x42; 32];
+    let target = BlobStore::new(MemoryStore::new());
+    let report = s5_fs::restore::restore_tree(
+        &source_meta,
+        &target,
+        &target,
+        root,
+        s5_fs::restore::RestoreOptions {
+            reencrypt: true,
+            dest_root_key: Some(&dest_key),
+            ..Default::default()
+        },
+        &|_| {},
+    )
+    .await
+    .expect("re-encrypting restore");
+    assert!(report.is_complete(), "{:?}", report.unreachable);
+    assert_ne!(report.root, root);
+
+    let new_root_bytes = target.read_as_bytes(report.root, 0, None).await.unwrap();
+    assert!(
+        s5_fs::dir::decrypt_dir_bytes(new_root_bytes.clone(), None)
+            .and_then(|b| s5_fs::dir::DirV1::from_bytes(&b))
+            .is_err()
+    );
+    let new_root = s5_fs::dir::DirV1::from_bytes(
+        &s5_fs::dir::decrypt_dir_bytes(new_root_bytes, Some(&dest_key)).unwrap(),
+    )
+    .unwrap();
+    let new_secret = &new_root.dirs["secret"];
+    let new_key = new_secret.keys.as_ref().unwrap()[&0x0e];
+    assert_ne!(new_key, old_key);
+    assert_ne!(new_secret.hash, old_secret.hash);
+
+    let secret_bytes = target
+        .read_as_bytes(Hash::from_bytes(new_secret.hash), 0, None)
+        .await
+        .unwrap();
+    assert!(s5_fs::dir::decrypt_dir_bytes(secret_bytes.clone(), Some(&old_key)).is_err());
+    let secret = s5_fs::dir::DirV1::from_bytes(
+        &s5_fs::dir::decrypt_dir_bytes(secret_bytes, Some(&new_key)).unwrap(),
+    )
+    .unwrap();
+    assert!(secret.files.contains_key("note.txt"));
+}
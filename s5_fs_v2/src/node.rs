@@ -468,6 +468,20 @@ impl NodeEntry {
         self.content.as_ref().map(|c| c.plaintext_hash())
     }
 
+    /// Returns the hole layout if this entry is a sparse file.
+    pub fn sparse(&self) -> Option<&SparseLayout> {
+        self.semantic.as_ref().and_then(|s| s.sparse.as_ref())
+    }
+
+    /// Returns the logical file size: the [`SparseLayout`] size for sparse
+    /// files, the content size otherwise, and 0 without content.
+    pub fn file_size(&self) -> u64 {
+        match self.sparse() {
+            Some(layout) => layout.size,
+            None => self.content.as_ref().map(|c| c.size).unwrap_or(0),
+        }
+    }
+
     /// Returns true if this is a tombstone (deletion marker).
     pub fn is_tombstone(&self) -> bool {
         self.tombstone.is_some()
@@ -537,6 +551,11 @@ pub struct SemanticMeta {
     /// Web Archive (WARC) metadata for HTTP responses.
     #[n(4)]
     pub warc: Option<WebArchiveMetadata>,
+
+    /// Hole layout of a sparse file. When set, the entry's content holds
+    /// only the file's data extents, back to back; see [`SparseLayout`].
+    #[n(5)]
+    pub sparse: Option<SparseLayout>,
     // TODO: Add recursive size fields for Link entries. Candidates:
     // - total_plaintext_size: sum of all ContentRef.size underneath (true content size)
     // - total_stored_size: sum of actual stored blob sizes (disk usage)
@@ -660,6 +679,120 @@ impl SemanticMeta {
     }
 }
 
+// =============================================================================
+// SparseLayout - Holes in Sparse Files
+// =============================================================================
+
+/// Where the data of a sparse file lives.
+///
+/// Disk images and preallocated databases are mostly holes. Rather than
+/// storing the zeros, the importer stores only the data extents,
+/// concatenated in offset order, and records them here. Readers place
+/// each extent at its offset and fill everything else with zeros, up to
+/// `size`.
+#[derive(Encode, Decode, CborLen, Clone, Debug, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SparseLayout {
+    /// Logical file size, holes included.
+    #[n(0)]
+    pub size: u64,
+
+    /// Data extents, sorted by offset and non-overlapping.
+    #[n(1)]
+    pub extents: Vec<DataExtent>,
+}
+
+/// A run of data in a sparse file.
+#[derive(Encode, Decode, CborLen, Clone, Copy, Debug, PartialEq, Eq)]
+#[cbor(map)]
+pub struct DataExtent {
+    /// Offset in the logical file.
+    #[n(0)]
+    pub offset: u64,
+
+    /// Length in bytes.
+    #[n(1)]
+    pub len: u64,
+}
+
+impl SparseLayout {
+    /// Bytes of data actually stored: the sum of the extent lengths.
+    pub fn stored_len(&self) -> u64 {
+        self.extents.iter().map(|e| e.len).sum()
+    }
+
+    /// Read `len` logical bytes at `offset` from `packed`, the stored
+    /// extents back to back. Holes read as zeros; reads past `size` are
+    /// cut short.
+    pub fn read_at(&self, packed: &[u8], offset: u64, len: usize) -> Vec<u8> {
+        self.read_window(packed, 0, offset, len)
+    }
+
+    /// The range of stored bytes that a read of `len` logical bytes at
+    /// `offset` needs. Empty when the read falls entirely in holes.
+    pub fn packed_span(&self, offset: u64, len: u64) -> std::ops::Range<u64> {
+        let start = offset.min(self.size);
+        let end = start.saturating_add(len).min(self.size);
+        let mut span: Option<std::ops::Range<u64>> = None;
+        let mut packed_pos = 0u64;
+        for extent in &self.extents {
+            if extent.offset >= end {
+                break;
+            }
+            let lo = extent.offset.max(start);
+            let hi = (extent.offset + extent.len).min(end);
+            if lo < hi {
+                let from = packed_pos + lo - extent.offset;
+                let to = packed_pos + hi - extent.offset;
+                span = Some(span.map_or(from, |s| s.start)..to);
+            }
+            packed_pos += extent.len;
+        }
+        span.unwrap_or(0..0)
+    }
+
+    /// Like [`read_at`](Self::read_at), but `window` holds only the
+    /// stored bytes from `window_start` on — typically the
+    /// [`packed_span`](Self::packed_span) of the same read.
+    pub fn read_window(
+        &self,
+        window: &[u8],
+        window_start: u64,
+        offset: u64,
+        len: usize,
+    ) -> Vec<u8> {
+        let start = offset.min(self.size);
+        let end = start.saturating_add(len as u64).min(self.size);
+        let mut out = vec![0u8; (end - start) as usize];
+        let mut packed_pos = 0u64;
+        for extent in &self.extents {
+            if extent.offset >= end {
+                break;
+            }
+            let lo = extent.offset.max(start);
+            let hi = (extent.offset + extent.len).min(end);
+            if lo < hi {
+                let n = (hi - lo) as usize;
+                let data = (packed_pos + lo - extent.offset)
+                    .checked_sub(window_start)
+                    .and_then(|src| window.get(src as usize..src as usize + n));
+                let Some(data) = data else {
+                    break;
+                };
+                let dst = (lo - start) as usize;
+                out[dst..dst + n].copy_from_slice(data);
+            }
+            packed_pos += extent.len;
+        }
+        out
+    }
+
+    /// The whole logical file, holes filled with zeros.
+    pub fn expand(&self, packed: &[u8]) -> Vec<u8> {
+        self.read_at(packed, 0, self.size as usize)
+    }
+}
+
 // =============================================================================
 // WebArchiveMetadata - WARC HTTP Response Data
 // =============================================================================
@@ -1106,3 +1239,39 @@ mod dchunk_purge_guard {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_layout_fills_holes_with_zeros() {
+        // "ab" at 2, "cde" at 8, trailing hole up to 12.
+        let layout = SparseLayout {
+            size: 12,
+            extents: vec![
+                DataExtent { offset: 2, len: 2 },
+                DataExtent { offset: 8, len: 3 },
+            ],
+        };
+        let packed = b"abcde";
+        assert_eq!(layout.stored_len(), 5);
+        assert_eq!(layout.expand(packed), b"\0\0ab\0\0\0\0cde\0");
+        assert_eq!(layout.read_at(packed, 3, 6), b"b\0\0\0\0c");
+        assert_eq!(layout.read_at(packed, 10, 100), b"e\0");
+        assert!(layout.read_at(packed, 20, 4).is_empty());
+
+        let span = layout.packed_span(3, 6);
+        assert_eq!(span, 1..3);
+        let window = &packed[span.start as usize..span.end as usize];
+        assert_eq!(layout.read_window(window, span.start, 3, 6), b"b\0\0\0\0c");
+        assert!(layout.packed_span(4, 4).is_empty());
+
+        let meta = SemanticMeta {
+            sparse: Some(layout.clone()),
+            ..Default::default()
+        };
+        let decoded: SemanticMeta = minicbor::decode(&minicbor::to_vec(&meta).unwrap()).unwrap();
+        assert_eq!(decoded.sparse, Some(layout));
+    }
+}
@@ -2311,6 +2311,86 @@ mod tests {
         );
     }
 
+    /// `export_range` over an encrypted, multi-level chunk tree returns
+    /// exactly the requested bytes — within one chunk, across a chunk
+    /// boundary, and clamped at EOF — while decrypting only the chunks
+    /// the range overlaps.
+    #[tokio::test]
+    async fn export_range_fetches_only_overlapping_chunks() {
+        use crate::node::{BlobPipeline, EncryptionStrategy, FileChunkingStrategy};
+        use crate::pipeline::ChunkCache;
+        use std::collections::{BTreeMap, HashMap};
+        use std::sync::Mutex;
+
+        const CHUNK: u64 = 16 * 1024;
+        let store = test_rw_store();
+        let mut keys = BTreeMap::new();
+        keys.insert(KEY_SLOT_LEAF, [7u8; 32]);
+        keys.insert(KEY_SLOT_NODE, [8u8; 32]);
+        let ctx = TraversalContext {
+            keys: Some(keys),
+            leaf: Some(BlobPipeline {
+                compression: Some(CompressionStrategy::Zstd),
+                padding: None,
+                encryption: Some((EncryptionStrategy::DeterministicChaCha20, KEY_SLOT_LEAF)),
+                skip_when_unhelpful: None,
+            }),
+            node: Some(BlobPipeline {
+                compression: None,
+                padding: None,
+                encryption: Some((EncryptionStrategy::DeterministicChaCha20, KEY_SLOT_NODE)),
+                skip_when_unhelpful: None,
+            }),
+            chunking: Some(FileChunkingStrategy::Fixed {
+                chunk_size: CHUNK as u32,
+            }),
+        };
+        let snap = Snapshot::empty(store.clone() as Arc<dyn s5_core::BlobsRead>, ctx);
+        // 300 chunks: enough for more than one ByteStream level.
+        let data: Vec<u8> = (0..300 * CHUNK as usize)
+            .map(|i| ((i / 251) ^ i) as u8)
+            .collect();
+        let entry = snap
+            .import_stream(std::io::Cursor::new(&data[..]), store.as_ref(), None)
+            .await
+            .unwrap();
+        assert_eq!(entry.content.as_ref().unwrap().structural, Structural::Link);
+        let pipe = snap.as_pipeline();
+
+        #[derive(Default)]
+        struct CountCache {
+            map: Mutex<HashMap<[u8; 32], Bytes>>,
+            inserts: Mutex<usize>,
+        }
+        impl ChunkCache for CountCache {
+            fn get(&self, k: &[u8; 32]) -> Option<Bytes> {
+                self.map.lock().unwrap().get(k).cloned()
+            }
+            fn insert(&self, k: [u8; 32], v: Bytes) {
+                self.map.lock().unwrap().insert(k, v);
+                *self.inserts.lock().unwrap() += 1;
+            }
+        }
+
+        let len = data.len() as u64;
+        for (offset, n, chunks) in [
+            (200 * CHUNK + 100, 1000, 1),
+            (77 * CHUNK - 10, 20, 2),
+            (len - 50, 4096, 1),
+            (len + 10, 10, 0),
+        ] {
+            let cache = CountCache::default();
+            let got = pipe
+                .export_range(&entry, offset, n, Some(&cache))
+                .await
+                .unwrap();
+            let lo = (offset as usize).min(data.len());
+            let hi = (lo + n as usize).min(data.len());
+            assert_eq!(&got[..], &data[lo..hi], "range at {offset}");
+            assert_eq!(*cache.inserts.lock().unwrap(), chunks, "chunks at {offset}");
+        }
+    }
+
     /// `import_stream_with_override` stamps the supplied override onto
     /// `entry.child_context` and the bytes round-trip via the parent
     /// snapshot (which has a *different* default context — proving the
@@ -38,12 +38,15 @@ pub(crate) const BLOCK_SIZE: u32 = 4096;
 pub(crate) const ENTRY_TTL: Duration = Duration::from_secs(1);
 
 pub(crate) fn file_attr(entry: &NodeEntry) -> FileAttr {
-    let size = entry.content.as_ref().map(|c| c.size).unwrap_or(0);
+    let size = entry.file_size();
+    // Sparse files occupy only their data extents, so `du` on the mount
+    // matches the source rather than the logical size.
+    let allocated = entry.sparse().map(|s| s.stored_len()).unwrap_or(size);
     let mtime = entry_mtime(entry);
     let ctime = entry_ctime(entry).unwrap_or(mtime);
     FileAttr {
         size,
-        blocks: size.div_ceil(BLOCK_SIZE as u64),
+        blocks: allocated.div_ceil(BLOCK_SIZE as u64),
         atime: mtime,
         mtime,
         ctime,
@@ -0,0 +1,45 @@
+//! Decoded-chunk cache shared by a mount's read callbacks.
+//!
+//! The kernel reads a file in requests of up to 128 KiB, and neighbouring
+//! requests usually land in the same chunk. Keeping recently decoded
+//! chunks means a sequential pass downloads and decrypts each chunk once
+//! instead of once per request, and a player seeking back a few seconds
+//! hits memory. Keys are ciphertext hashes, so entries never go stale.
+
+use bytes::Bytes;
+use s5_fs_v2::pipeline::ChunkCache;
+
+/// Plaintext bytes kept per mount.
+pub(crate) const CHUNK_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Byte-bounded LRU of decoded chunk plaintext.
+pub(crate) struct ChunkLru {
+    cache: moka::sync::Cache<[u8; 32], Bytes>,
+}
+
+impl ChunkLru {
+    pub(crate) fn new(max_bytes: u64) -> Self {
+        Self {
+            cache: moka::sync::Cache::builder()
+                .max_capacity(max_bytes)
+                .weigher(|_, v: &Bytes| v.len().try_into().unwrap_or(u32::MAX))
+                .build(),
+        }
+    }
+}
+
+impl Default for ChunkLru {
+    fn default() -> Self {
+        Self::new(CHUNK_CACHE_BYTES)
+    }
+}
+
+impl ChunkCache for ChunkLru {
+    fn get(&self, key: &[u8; 32]) -> Option<Bytes> {
+        self.cache.get(key)
+    }
+
+    fn insert(&self, key: [u8; 32], value: Bytes) {
+        self.cache.insert(key, value);
+    }
+}
@@ -119,6 +119,7 @@ mod tests {
         let store_dir = tempfile::tempdir().unwrap();
         let store = BlobStore::new(LocalStore::create(LocalStoreConfig {
             base_path: store_dir.path().to_string_lossy().into_owned(),
+            ..Default::default()
         }));
         std::mem::forget(store_dir);
 
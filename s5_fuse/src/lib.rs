@@ -7,6 +7,7 @@
 //! - [`path`] — snapshot-key resolution helpers shared across read and
 //!   write adapters.
 //! - [`read`] — [`read::ReadOnlyFs`] (the read-only adapter).
+//! - [`chunk_cache`] — decoded-chunk LRU behind ranged reads.
 //! - [`mount`] — mount entry points (currently [`mount::mount`] for the
 //!   read-only path; writable mount lands next).
 //!
@@ -23,6 +24,7 @@
 //! replace only), no chmod/chown persistence beyond size-truncate.
 
 mod attr;
+mod chunk_cache;
 mod path;
 pub mod read;
 pub mod write;
@@ -58,9 +58,11 @@ use tokio::sync::{Mutex, Notify};
 use tracing::warn;
 
 use crate::attr::{BLOCK_SIZE, ENTRY_TTL, dir_attr, file_attr};
+use crate::chunk_cache::ChunkLru;
 use crate::path::{
     ResolvedEntry, join, list_children, list_children_with_entries, resolve, snapshot_key,
 };
+use crate::read::read_range;
 
 /// Build a `SemanticMeta` carrying the current wall-clock time as the
 /// modification timestamp. Subsequent `stat` calls will see this as the
@@ -76,6 +78,7 @@ fn now_semantic() -> SemanticMeta {
         media_type: None,
         unix: None,
         warc: None,
+        sparse: None,
     }
 }
 
@@ -91,6 +94,8 @@ fn merged_semantic(prev: Option<SemanticMeta>, next: SemanticMeta) -> SemanticMe
             media_type: prev.media_type.or(next.media_type),
             unix: prev.unix.or(next.unix),
             warc: prev.warc.or(next.warc),
+            // Never `prev`'s: the committed buffer is stored dense.
+            sparse: next.sparse,
         },
         None => next,
     }
@@ -126,6 +131,8 @@ pub struct WritableFs {
     /// `WritableOverlay::flush` on debounce. The overlay's `pipeline`
     /// holds the read side; this is the matching write capability.
     store: BlobStore,
+    /// Decoded chunks shared by reads of committed entries.
+    chunks: Arc<ChunkLru>,
 }
 
 impl WritableFs {
@@ -160,6 +167,7 @@ impl WritableFs {
             next_fh: Arc::new(AtomicU64::new(1)),
             write_signal: Arc::new(Notify::new()),
             store,
+            chunks: Arc::default(),
         }
     }
 
@@ -474,24 +482,13 @@ impl PathFilesystem for WritableFs {
                 Errno::from(libc::EIO)
             })?
             .ok_or_else(|| Errno::from(libc::ENOENT))?;
-        // TODO(perf): same whole-file materialisation problem as
-        // `ReadOnlyFs::read` (O(N²) on sequential reads) — make range-aware and
-        // share the per-fh decoded-chunk LRU. See the CRITICAL note in
-        // `read.rs`.
-        let bytes = self
-            .overlay
-            .pipeline()
-            .export_bytes(&entry)
+        let data = read_range(self.overlay.pipeline(), &self.chunks, &entry, offset, size)
             .await
             .map_err(|err| {
-                warn!(key, error = %err, "export_bytes failed");
+                warn!(key, error = %err, "ranged export failed");
                 Errno::from(libc::EIO)
             })?;
-        let start = (offset as usize).min(bytes.len());
-        let end = start.saturating_add(size as usize).min(bytes.len());
-        Ok(ReplyData {
-            data: bytes.slice(start..end),
-        })
+        Ok(ReplyData { data })
     }
 
     /// On the last close of the FD, FUSE calls `release`. We commit
@@ -736,6 +733,7 @@ mod tests {
         let store_dir = tempfile::tempdir().unwrap();
         let store = BlobStore::new(LocalStore::create(LocalStoreConfig {
             base_path: store_dir.path().to_string_lossy().into_owned(),
+            ..Default::default()
         }));
         // Leak the tempdir so the store stays valid for the test's lifetime.
         std::mem::forget(store_dir);
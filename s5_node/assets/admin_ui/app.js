@@ -0,0 +1,228 @@
+// S5 node admin UI. Polls the node's read-only JSON API (`/api/*`) with the
+// control token, which arrives once in the URL fragment (`#token=<hex>`)
+// and is kept in sessionStorage for the tab's lifetime.
+"use strict";
+
+const REFRESH_MS = 5000;
+const STORES_REFRESH_MS = 60000;
+let token = sessionStorage.getItem("s5-token");
+let browse = { vault: null, path: "" };
+
+function $(sel) { return document.querySelector(sel); }
+
+function el(tag, text, cls) {
+  const node = document.createElement(tag);
+  if (text !== undefined && text !== null) node.textContent = String(text);
+  if (cls) node.className = cls;
+  return node;
+}
+
+function fill(table, rows, empty) {
+  const body = $(table + " tbody");
+  body.replaceChildren();
+  if (rows.length === 0) {
+    const tr = el("tr");
+    const td = el("td", empty, "note");
+    td.colSpan = $(table + " thead tr").children.length;
+    tr.append(td);
+    body.append(tr);
+    return;
+  }
+  for (const cells of rows) {
+    const tr = el("tr");
+    for (const cell of cells) tr.append(cell instanceof Node ? wrap(cell) : el("td", cell));
+    body.append(tr);
+  }
+}
+
+function wrap(node) { const td = el("td"); td.append(node); return td; }
+
+function bytes(n) {
+  if (n === null || n === undefined) return "—";
+  const units = ["B", "KiB", "MiB", "GiB", "TiB"];
+  let i = 0;
+  while (n >= 1024 && i < units.length - 1) { n /= 1024; i++; }
+  return (i === 0 ? n : n.toFixed(1)) + " " + units[i];
+}
+
+function ago(unix) {
+  if (!unix) return "—";
+  const secs = Math.max(0, Math.round(Date.now() / 1000 - unix));
+  if (secs < 60) return secs + "s ago";
+  if (secs < 3600) return Math.round(secs / 60) + "m ago";
+  if (secs < 86400) return Math.round(secs / 3600) + "h ago";
+  return new Date(unix * 1000).toLocaleString();
+}
+
+function ms(n) { return n >= 1000 ? (n / 1000).toFixed(1) + " s" : n + " ms"; }
+
+function short(hex) { return hex.length > 16 ? hex.slice(0, 8) + "…" + hex.slice(-8) : hex; }
+
+function state(s) {
+  if (typeof s === "string") return el("span", s, "state " + s.toLowerCase());
+  const [name, detail] = Object.entries(s)[0];
+  const span = el("span", name, "state " + name.toLowerCase());
+  span.title = detail.error || JSON.stringify(detail);
+  return span;
+}
+
+async function api(path) {
+  const resp = await fetch("api/" + path, { headers: { Authorization: "Bearer " + token } });
+  if (resp.status === 401) {
+    sessionStorage.removeItem("s5-token");
+    token = null;
+    showLogin();
+    throw new Error("unauthorized");
+  }
+  if (!resp.ok) throw new Error(await resp.text());
+  return resp.json();
+}
+
+async function refreshStatus() {
+  const status = await api("status");
+  $("#endpoint").textContent = "endpoint " + short(status.endpoint_id);
+}
+
+async function refreshStores() {
+  const stores = await api("stores");
+  fill("#stores", stores.map(s => [
+    s.name,
+    s.backend,
+    s.reachable ? el("span", "reachable", "state completed")
+                : Object.assign(el("span", "unreachable", "state failed"), { title: s.error || "" }),
+    bytes(s.disk_bytes),
+    s.disk ? Object.assign(el("span", bytes(s.disk.available_bytes),
+                              s.disk.level === "ok" ? "" : "state failed"),
+                           { title: "of " + bytes(s.disk.total_bytes) + " (" + s.disk.level + ")" })
+           : "—",
+    s.staging ? bytes(s.staging.staged_bytes) + (s.staging.inflight ? " (uploading)" : "") : "—",
+    s.vaults.join(", ") || "—",
+  ]), "No stores configured.");
+}
+
+async function refreshPeers() {
+  const { peers } = await api("peers");
+  fill("#peers", peers.map(p => [
+    Object.assign(el("code", short(p.pubkey_hex)), { title: p.pubkey_hex }),
+    p.alpns.map(a => a.alpn).join(", "),
+    p.alpns.reduce((n, a) => n + a.handshakes, 0),
+    ago(Math.max(...p.alpns.map(a => a.last_seen_unix))),
+  ]), "No peers seen yet.");
+}
+
+async function refreshJobs() {
+  const [{ jobs }, { tasks }] = await Promise.all([api("jobs"), api("tasks")]);
+  fill("#jobs", jobs.map(j => [
+    j.name,
+    el("code", j.cron),
+    j.running ? state("Running")
+      : j.last_outcome ? state(j.last_outcome === "Succeeded" ? "Completed" : j.last_outcome)
+      : "never run",
+    j.next_run_unix ? new Date(j.next_run_unix * 1000).toLocaleString() : "—",
+  ]), "No scheduled jobs.");
+  fill("#tasks", tasks.slice().reverse().map(t => [
+    "#" + t.task_id,
+    state(t.state),
+    (t.progress || []).map(p => {
+      const label = p.display_label || p.label;
+      const fmt = p.progress_type === "bytes" ? bytes : String;
+      return label + " " + fmt(p.progress) + (p.total !== null ? " / " + fmt(p.total) : "");
+    }).join(", "),
+  ]), "No tasks since the node started.");
+}
+
+async function refreshLatency() {
+  const latency = await api("latency");
+  fill("#latency", latency.ops.map(o => [
+    o.op, o.count, ms(o.p50_ms), ms(o.p90_ms), ms(o.p99_ms), ms(o.max_ms),
+  ]), "Nothing timed in the last " + latency.window_secs / 60 + " minutes.");
+  fill("#transfers", latency.recent.map(t => [
+    ago(t.at_unix_secs),
+    t.op === "blob_get" ? "download" : "upload",
+    el("code", short(t.detail)),
+    ms(t.duration_ms),
+  ]), "No transfers since the node started.");
+}
+
+async function loadVaults() {
+  const vaults = await api("vaults");
+  const select = $("#vault");
+  select.replaceChildren(...vaults.map(v => el("option", v)));
+  select.disabled = vaults.length === 0;
+  if (vaults.length === 0) {
+    fill("#files", [], "No unencrypted vaults.");
+    return;
+  }
+  select.onchange = () => openDir(select.value, "");
+  openDir(vaults[0], "");
+}
+
+async function openDir(vault, path) {
+  browse = { vault, path };
+  const query = "vault=" + encodeURIComponent(vault) + (path ? "&path=" + encodeURIComponent(path) : "");
+  const { entries } = await api("tree?" + query);
+
+  const crumbs = $("#crumbs");
+  crumbs.replaceChildren();
+  const parts = path ? path.split("/") : [];
+  const root = el("a", vault);
+  root.href = "#";
+  root.onclick = e => { e.preventDefault(); openDir(vault, ""); };
+  crumbs.append(root);
+  parts.forEach((part, i) => {
+    const link = el("a", part);
+    link.href = "#";
+    link.onclick = e => { e.preventDefault(); openDir(vault, parts.slice(0, i + 1).join("/")); };
+    crumbs.append(" / ", link);
+  });
+
+  const sorted = entries.slice().sort((a, b) => (b.is_dir - a.is_dir) || a.path.localeCompare(b.path));
+  fill("#files", sorted.map(e => {
+    if (!e.is_dir) return [e.path, bytes(e.size)];
+    const link = el("a", e.path + "/");
+    link.href = "#";
+    link.onclick = ev => { ev.preventDefault(); openDir(vault, path ? path + "/" + e.path : e.path); };
+    return [link, "—"];
+  }), "Empty directory.");
+}
+
+function showLogin() {
+  $("#dashboard").hidden = true;
+  $("#login").hidden = false;
+}
+
+async function tick(fn) {
+  try {
+    await fn();
+    $("#updated").textContent = "updated " + new Date().toLocaleTimeString();
+  } catch (err) {
+    if (err.message !== "unauthorized") $("#updated").textContent = "error: " + err.message;
+  }
+}
+
+function start() {
+  $("#login").hidden = true;
+  $("#dashboard").hidden = false;
+  const fast = () => Promise.all([refreshStatus(), refreshPeers(), refreshJobs(), refreshLatency()]);
+  tick(fast);
+  tick(refreshStores);
+  tick(loadVaults);
+  setInterval(() => token && tick(fast), REFRESH_MS);
+  setInterval(() => token && tick(refreshStores), STORES_REFRESH_MS);
+}
+
+const fragment = new URLSearchParams(location.hash.slice(1));
+if (fragment.get("token")) {
+  token = fragment.get("token");
+  sessionStorage.setItem("s5-token", token);
+  history.replaceState(null, "", location.pathname);
+}
+
+$("#login").onsubmit = e => {
+  e.preventDefault();
+  token = $("#token").value.trim();
+  sessionStorage.setItem("s5-token", token);
+  start();
+};
+
+if (token) start(); else showLogin();
@@ -0,0 +1,208 @@
+//! Persisted blob access times (`[store.*] access_tracking`).
+//!
+//! [`RedbAccessLog`] is the [`AccessTracker`] the node attaches to each
+//! path-backed `BlobStore`. Touches land in an in-memory map and are written
+//! to a redb table keyed by hash in batches — every [`FLUSH_INTERVAL`], or
+//! sooner once [`FLUSH_BATCH`] distinct hashes are pending — so a hot read
+//! path costs one mutex-guarded map insert, not a write transaction.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use s5_core::Hash;
+use s5_core::blob::AccessTracker;
+use s5_core::blob::access::{from_unix_secs, to_unix_secs};
+use s5_core::store::StoreResult;
+use tokio::sync::Notify;
+
+/// Blob hash → last access, in Unix seconds.
+const TABLE: TableDefinition<&[u8; 32], u64> = TableDefinition::new("blob_access");
+
+/// Pending hashes that trigger an early flush.
+pub const FLUSH_BATCH: usize = 1024;
+
+/// Longest a touch stays buffered before it is written.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Redb-backed, batch-flushed [`AccessTracker`].
+#[derive(Clone)]
+pub struct RedbAccessLog {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    db: Database,
+    pending: Mutex<HashMap<Hash, u64>>,
+    wake: Arc<Notify>,
+}
+
+impl RedbAccessLog {
+    /// Open (or create) the access table at `path` and start its flush loop.
+    /// Must be called within a tokio runtime.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let db = Database::create(path)
+            .with_context(|| format!("opening access log {}", path.display()))?;
+        Self::from_db(db)
+    }
+
+    /// An access log that lives in memory only, for stores without a local
+    /// directory to keep it in (memory stores, tests).
+    pub fn in_memory() -> anyhow::Result<Self> {
+        let db = Database::builder().create_with_backend(redb::backends::InMemoryBackend::new())?;
+        Self::from_db(db)
+    }
+
+    fn from_db(db: Database) -> anyhow::Result<Self> {
+        let txn = db.begin_write()?;
+        txn.open_table(TABLE)?;
+        txn.commit()?;
+        let wake = Arc::new(Notify::new());
+        let inner = Arc::new(Inner {
+            db,
+            pending: Mutex::new(HashMap::new()),
+            wake: wake.clone(),
+        });
+        tokio::spawn(flush_loop(Arc::downgrade(&inner), wake));
+        Ok(Self { inner })
+    }
+}
+
+impl std::fmt::Debug for RedbAccessLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedbAccessLog").finish_non_exhaustive()
+    }
+}
+
+impl Inner {
+    fn take_pending(&self) -> HashMap<Hash, u64> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Write `batch` in one transaction, never moving a time backwards.
+    fn write(&self, batch: HashMap<Hash, u64>) -> anyhow::Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(TABLE)?;
+            for (hash, secs) in batch {
+                let prev = table.get(hash.as_bytes())?.map(|v| v.value());
+                if prev.is_none_or(|prev| prev < secs) {
+                    table.insert(hash.as_bytes(), secs)?;
+                }
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    async fn flush(self: &Arc<Self>) -> anyhow::Result<()> {
+        let batch = self.take_pending();
+        let inner = self.clone();
+        tokio::task::spawn_blocking(move || inner.write(batch))
+            .await
+            .map_err(|e| anyhow::anyhow!("access log flush task failed: {e}"))?
+    }
+}
+
+/// The last handle going away writes whatever is still buffered.
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Err(err) = self.write(self.take_pending()) {
+            tracing::warn!("access log: final flush failed, touches dropped: {err:#}");
+        }
+        self.wake.notify_one();
+    }
+}
+
+/// Flush on every tick or early wake-up until the log is dropped. Holds the
+/// log only while flushing, so dropping it closes the database promptly.
+async fn flush_loop(inner: Weak<Inner>, wake: Arc<Notify>) {
+    let mut tick = tokio::time::interval(FLUSH_INTERVAL);
+    tick.tick().await;
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {}
+            _ = wake.notified() => {}
+        }
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        if let Err(err) = inner.flush().await {
+            tracing::warn!("access log: flush failed, touches dropped: {err:#}");
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AccessTracker for RedbAccessLog {
+    fn touch(&self, hash: Hash, at: SystemTime) {
+        let secs = to_unix_secs(at);
+        let full = {
+            let mut pending = self.inner.pending.lock().unwrap();
+            let entry = pending.entry(hash).or_insert(secs);
+            *entry = (*entry).max(secs);
+            pending.len() >= FLUSH_BATCH
+        };
+        if full {
+            self.inner.wake.notify_one();
+        }
+    }
+
+    async fn last_access(&self, hash: Hash) -> StoreResult<Option<SystemTime>> {
+        if let Some(secs) = self.inner.pending.lock().unwrap().get(&hash) {
+            return Ok(Some(from_unix_secs(*secs)));
+        }
+        let inner = self.inner.clone();
+        let secs = tokio::task::spawn_blocking(move || -> anyhow::Result<Option<u64>> {
+            let txn = inner.db.begin_read()?;
+            let table = txn.open_table(TABLE)?;
+            Ok(table.get(hash.as_bytes())?.map(|v| v.value()))
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("access log read task failed: {e}"))??;
+        Ok(secs.map(from_unix_secs))
+    }
+
+    async fn flush(&self) -> StoreResult<()> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s5_core::blob::BlobStore;
+    use s5_store_memory::MemoryStore;
+
+    /// Reads through a tracked `BlobStore` are visible before a flush and
+    /// survive reopening the table after one; untouched blobs stay `None`.
+    #[tokio::test]
+    async fn reads_are_tracked_and_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("access.redb");
+        let log = RedbAccessLog::open(&path).unwrap();
+        let store = BlobStore::new(MemoryStore::new()).with_access_tracker(Arc::new(log.clone()));
+
+        let read = store.import_bytes("read".into()).await.unwrap().hash;
+        let unread = store.import_bytes("unread".into()).await.unwrap().hash;
+        let before = to_unix_secs(SystemTime::now());
+        store.read_as_bytes(read, 0, None).await.unwrap();
+
+        let seen = store.blob_last_access(read).await.unwrap().unwrap();
+        assert!(to_unix_secs(seen) >= before);
+        assert_eq!(store.blob_last_access(unread).await.unwrap(), None);
+
+        store.sync().await.unwrap();
+        drop(store);
+        drop(log);
+        let reopened = RedbAccessLog::open(&path).unwrap();
+        assert_eq!(reopened.last_access(read).await.unwrap(), Some(seen));
+        assert_eq!(reopened.last_access(unread).await.unwrap(), None);
+    }
+}
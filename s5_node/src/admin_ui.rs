@@ -0,0 +1,394 @@
+//! Embedded web admin UI (`[admin_ui] listen`).
+//!
+//! A small HTTP/1.1 listener serving static assets baked into the binary
+//! (`s5_node/assets/admin_ui/`) and a read-only JSON API over the control
+//! RPC, reached through an in-process irpc client — the page shows exactly
+//! what `vup status` / `vup doctor` see: peers, stores with usage, task and
+//! job status, recent transfers and a file browser for unencrypted vaults.
+//!
+//! The assets are public; every `/api/` call must carry the per-run control
+//! token as `Authorization: Bearer <hex>`. The daemon logs the UI URL with
+//! the token in its `#fragment`, which browsers never send to the server,
+//! so the token stays out of request lines and `Referer` headers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::header::{AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use s5_node_api::{CONTROL_TOKEN_LEN, S5NodeClient};
+use serde::Serialize;
+use tokio::net::TcpListener;
+use tokio::sync::RwLock;
+
+use crate::config::{NodeConfigStoreBackend, NodeConfigVault, S5NodeConfig};
+use crate::s5_server::{S5NodeServer, constant_time_eq};
+
+/// How long a store's on-disk usage is reused before it is walked again.
+pub const USAGE_TTL: Duration = Duration::from_secs(5 * 60);
+
+const INDEX_HTML: &str = include_str!("../assets/admin_ui/index.html");
+const APP_JS: &str = include_str!("../assets/admin_ui/app.js");
+const STYLE_CSS: &str = include_str!("../assets/admin_ui/style.css");
+
+/// The admin UI's HTTP front end. Cheap to clone; clones share the usage
+/// cache.
+#[derive(Clone)]
+pub struct AdminUi {
+    client: S5NodeClient,
+    token: [u8; CONTROL_TOKEN_LEN],
+    config: Arc<RwLock<S5NodeConfig>>,
+    usage: Arc<Mutex<HashMap<PathBuf, (Instant, u64)>>>,
+}
+
+impl std::fmt::Debug for AdminUi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminUi").finish_non_exhaustive()
+    }
+}
+
+/// One store as the UI shows it: health plus how much it holds.
+#[derive(Debug, Serialize)]
+struct StoreView {
+    #[serde(flatten)]
+    health: s5_node_api::StoreHealth,
+    /// Backend type (`local`, `s3`, …).
+    backend: &'static str,
+    /// Bytes on local disk; `None` for remote backends.
+    disk_bytes: Option<u64>,
+    /// Vaults whose data lands here.
+    vaults: Vec<String>,
+}
+
+impl AdminUi {
+    /// Serve the UI for `server`, authorizing API calls with the control
+    /// plane's `token`.
+    pub fn new(
+        server: &S5NodeServer,
+        token: [u8; CONTROL_TOKEN_LEN],
+        config: Arc<RwLock<S5NodeConfig>>,
+    ) -> Self {
+        let local = Arc::new(server.clone()).serve_local();
+        Self::with_client(
+            S5NodeClient::local(irpc::Client::local(local)),
+            token,
+            config,
+        )
+    }
+
+    fn with_client(
+        client: S5NodeClient,
+        token: [u8; CONTROL_TOKEN_LEN],
+        config: Arc<RwLock<S5NodeConfig>>,
+    ) -> Self {
+        Self {
+            client,
+            token,
+            config,
+            usage: Default::default(),
+        }
+    }
+
+    /// Accept connections on `listener` until the task is dropped.
+    pub async fn serve(self, listener: TcpListener) {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(err) => {
+                    tracing::warn!("admin ui: accept failed: {err}");
+                    continue;
+                }
+            };
+            let ui = self.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |req| {
+                    let ui = ui.clone();
+                    async move { Ok::<_, Infallible>(ui.handle(req).await) }
+                });
+                if let Err(err) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    tracing::debug!(%peer, "admin ui: connection error: {err}");
+                }
+            });
+        }
+    }
+
+    async fn handle(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        if req.method() != Method::GET {
+            return text(StatusCode::METHOD_NOT_ALLOWED, "only GET is supported");
+        }
+        let path = req.uri().path();
+        let Some(endpoint) = path.strip_prefix("/api/") else {
+            return match path {
+                "/" | "/index.html" => asset("text/html; charset=utf-8", INDEX_HTML),
+                "/app.js" => asset("text/javascript; charset=utf-8", APP_JS),
+                "/style.css" => asset("text/css; charset=utf-8", STYLE_CSS),
+                _ => text(StatusCode::NOT_FOUND, "not found"),
+            };
+        };
+        if !self.authorized(&req) {
+            return text(StatusCode::UNAUTHORIZED, "missing or wrong control token");
+        }
+        let query: BTreeMap<String, String> = req
+            .uri()
+            .query()
+            .map(|q| form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+            .unwrap_or_default();
+
+        let result = match endpoint {
+            "status" => json(self.client.get_status().await),
+            "peers" => json(self.client.debug_peers().await),
+            "stores" => json(self.stores().await),
+            "tasks" => json(self.client.list_tasks().await),
+            "jobs" => json(self.client.list_jobs().await),
+            "latency" => json(self.client.get_latency().await),
+            "vaults" => json(Ok::<_, anyhow::Error>(self.browsable_vaults().await)),
+            "tree" => self.tree(&query).await,
+            _ => return text(StatusCode::NOT_FOUND, "no such endpoint"),
+        };
+        result.unwrap_or_else(|(status, msg)| text(status, &msg))
+    }
+
+    fn authorized(&self, req: &Request<Incoming>) -> bool {
+        let Some(presented) = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .and_then(|v| hex::decode(v.trim()).ok())
+        else {
+            return false;
+        };
+        constant_time_eq(&presented, &self.token)
+    }
+
+    async fn stores(&self) -> anyhow::Result<Vec<StoreView>> {
+        let health = self.client.get_health().await?;
+        let config = self.config.read().await.clone();
+        let mut views = Vec::new();
+        for store in health.stores {
+            let backend = config.store.get(&store.name).map(|s| &s.backend);
+            let disk_bytes = match backend.and_then(NodeConfigStoreBackend::local_path) {
+                Some(path) => Some(self.disk_usage(path).await?),
+                None => None,
+            };
+            let vaults = config
+                .vault
+                .iter()
+                .filter(|(name, vault)| {
+                    config.vault_data_store(name, vault).ok() == Some(store.name.as_str())
+                })
+                .map(|(name, _)| name.clone())
+                .collect();
+            views.push(StoreView {
+                backend: backend.map_or("unknown", backend_kind),
+                health: store,
+                disk_bytes,
+                vaults,
+            });
+        }
+        Ok(views)
+    }
+
+    /// Bytes under `path`, walked at most once per [`USAGE_TTL`].
+    async fn disk_usage(&self, path: PathBuf) -> anyhow::Result<u64> {
+        if let Some((at, bytes)) = self.usage.lock().unwrap().get(&path)
+            && at.elapsed() < USAGE_TTL
+        {
+            return Ok(*bytes);
+        }
+        let walked = path.clone();
+        let bytes = tokio::task::spawn_blocking(move || dir_size(&walked)).await??;
+        self.usage
+            .lock()
+            .unwrap()
+            .insert(path, (Instant::now(), bytes));
+        Ok(bytes)
+    }
+
+    /// Vaults whose tree and published root are both plaintext — the only
+    /// ones the file browser will list.
+    async fn browsable_vaults(&self) -> Vec<String> {
+        let config = self.config.read().await;
+        config
+            .vault
+            .iter()
+            .filter(|(_, vault)| is_unencrypted(vault))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    async fn tree(&self, query: &BTreeMap<String, String>) -> Result<Response<Full<Bytes>>, Error> {
+        let vault = query
+            .get("vault")
+            .ok_or((StatusCode::BAD_REQUEST, "missing `vault`".to_string()))?;
+        if !self.browsable_vaults().await.contains(vault) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!(
+                    "vault '{vault}' is encrypted or unknown; only unencrypted vaults can be browsed"
+                ),
+            ));
+        }
+        let subtree = query
+            .get("path")
+            .map(|p| p.trim_matches('/').to_string())
+            .filter(|p| !p.is_empty());
+        json(
+            self.client
+                .list_tree(vault.clone(), None, subtree, Some(1))
+                .await,
+        )
+    }
+}
+
+type Error = (StatusCode, String);
+
+/// Both `plaintext_tree` and `plaintext_published_tn`: nothing in the vault
+/// is encrypted, so listing it over HTTP reveals nothing a reader of the
+/// store couldn't see already.
+fn is_unencrypted(vault: &NodeConfigVault) -> bool {
+    vault.plaintext_tree && vault.plaintext_published_tn
+}
+
+fn backend_kind(backend: &NodeConfigStoreBackend) -> &'static str {
+    match backend {
+        NodeConfigStoreBackend::Local(_) => "local",
+        NodeConfigStoreBackend::Fjall(_) => "fjall",
+        NodeConfigStoreBackend::Memory => "memory",
+        _ => "remote",
+    }
+}
+
+fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += meta.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+fn asset(content_type: &'static str, body: &'static str) -> Response<Full<Bytes>> {
+    Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .header(CACHE_CONTROL, "no-cache")
+        .body(Full::new(Bytes::from_static(body.as_bytes())))
+        .expect("static response")
+}
+
+fn text(status: StatusCode, body: &str) -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .expect("static response")
+}
+
+fn json<T: Serialize, E: std::fmt::Display>(
+    result: Result<T, E>,
+) -> Result<Response<Full<Bytes>>, Error> {
+    let value = result.map_err(|e| (StatusCode::BAD_GATEWAY, format!("{e:#}")))?;
+    let body = serde_json::to_vec(&value)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .header(CACHE_CONTROL, "no-store")
+        .body(Full::new(Bytes::from(body)))
+        .expect("static response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn get(addr: std::net::SocketAddr, path: &str, token: Option<&str>) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {t}\r\n"))
+            .unwrap_or_default();
+        let req = format!("GET {path} HTTP/1.1\r\nHost: ui\r\n{auth}Connection: close\r\n\r\n");
+        stream.write_all(req.as_bytes()).await.unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).await.unwrap();
+        resp
+    }
+
+    /// Assets are served without a token; the API refuses calls without the
+    /// right one, and the file browser refuses encrypted vaults.
+    #[tokio::test]
+    async fn assets_are_public_and_api_needs_the_token() {
+        let config: S5NodeConfig = toml::from_str(
+            r#"
+            [identity]
+            [store.local]
+            type = "memory"
+            [vault.secret]
+            root_path = "/tmp/secret"
+            key = "k"
+            [vault.public]
+            root_path = "/tmp/public"
+            key = "k"
+            plaintext_tree = true
+            plaintext_published_tn = true
+            "#,
+        )
+        .unwrap();
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let token = [7u8; CONTROL_TOKEN_LEN];
+        let ui = AdminUi::with_client(
+            S5NodeClient::local(irpc::Client::local(tx)),
+            token,
+            Arc::new(RwLock::new(config)),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(ui.serve(listener));
+
+        let index = get(addr, "/", None).await;
+        assert!(index.starts_with("HTTP/1.1 200"), "{index}");
+        assert!(index.contains("<script src=\"app.js\">"));
+
+        let hex_token = hex::encode(token);
+        let wrong = hex::encode([8u8; CONTROL_TOKEN_LEN]);
+        assert!(
+            get(addr, "/api/vaults", None)
+                .await
+                .starts_with("HTTP/1.1 401")
+        );
+        assert!(
+            get(addr, "/api/vaults", Some(&wrong))
+                .await
+                .starts_with("HTTP/1.1 401")
+        );
+        let vaults = get(addr, "/api/vaults", Some(&hex_token)).await;
+        assert!(vaults.ends_with("[\"public\"]"), "{vaults}");
+        let tree = get(addr, "/api/tree?vault=secret", Some(&hex_token)).await;
+        assert!(tree.starts_with("HTTP/1.1 403"), "{tree}");
+
+        server.abort();
+    }
+}
@@ -1,14 +1,17 @@
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use s5_core::blob::BlobLayoutSpec;
 use serde::{Deserialize, Serialize};
 
 // Re-export config types from s5_node_api so downstream users can access
 // everything through `s5_node::config::*` as before.
 pub use s5_node_api::config::{
-    BlobPipelineConfig, CompressionConfig, FileChunkingConfig, NodeConfigIdentity, NodeConfigKey,
-    NodeConfigRegistry, NodeConfigSource, NodeConfigTask, NodeConfigVault, PipelineRouteConfig,
-    TaskSpec, TaskTrigger,
+    BlobPipelineConfig, CompressionConfig, FileChunkingConfig, NodeConfigAdminUi, NodeConfigDns,
+    NodeConfigDnsProvider, NodeConfigIdentity, NodeConfigJobs, NodeConfigKey, NodeConfigLog,
+    NodeConfigLogFormat, NodeConfigLogRotation, NodeConfigNotify, NodeConfigNotifyTarget,
+    NodeConfigNotifyTransport, NodeConfigRegistry, NodeConfigSmtpTls, NodeConfigSource,
+    NodeConfigTask, NodeConfigVault, NodeEventKind, PipelineRouteConfig, TaskSpec, TaskTrigger,
 };
 
 /// Returns the path for the default registry.
@@ -58,6 +61,21 @@ pub struct S5NodeConfig {
     /// `did:s5:` value; vault `members` lists reference these by name.
     #[serde(default)]
     pub friend: BTreeMap<String, s5_node_api::config::NodeConfigFriend>,
+    /// Cron job scheduler settings (`[jobs]`).
+    #[serde(default)]
+    pub jobs: NodeConfigJobs,
+    /// Daemon log file settings (`[log]`).
+    #[serde(default, skip_serializing_if = "NodeConfigLog::is_default")]
+    pub log: NodeConfigLog,
+    /// Embedded web admin UI (`[admin_ui]`).
+    #[serde(default, skip_serializing_if = "NodeConfigAdminUi::is_default")]
+    pub admin_ui: NodeConfigAdminUi,
+    /// DNS records tracking published vault heads (`[dns.<site>]`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dns: BTreeMap<String, NodeConfigDns>,
+    /// Event notifications (`[notify]`).
+    #[serde(default, skip_serializing_if = "NodeConfigNotify::is_default")]
+    pub notify: NodeConfigNotify,
 }
 
 // ---------------------------------------------------------------------------
@@ -172,6 +190,26 @@ pub struct NodeConfigStore {
     #[serde(default)]
     pub read_cache_bytes: Option<u64>,
 
+    /// How blob paths are laid out in this store (`flat`, `fan_out`,
+    /// `content_type_prefixed`). The first open records the layout in the
+    /// store's `s5-store.json`, which is authoritative from then on; a
+    /// different explicit setting here is refused. Default `auto`: whatever
+    /// the manifest says, else the backend's natural layout.
+    #[serde(default, skip_serializing_if = "BlobLayoutSpec::is_auto")]
+    pub layout: BlobLayoutSpec,
+
+    /// Record when each blob was last read or served, for tiering and
+    /// GC-grace policies (`BlobStore::blob_last_access`).
+    ///
+    /// Touches are buffered in memory and written in batches to
+    /// `access.redb` inside a `local` store's directory (next to a `fjall`
+    /// store's as `<path>.access.redb`); memory stores keep them in RAM.
+    /// Remote backends have nowhere local to keep the table and are never
+    /// tracked. Default `true`; set `access_tracking = false` on read-heavy
+    /// nodes that don't need access times.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub access_tracking: bool,
+
     /// Friend-hosted-storage push ACL: local `[friend.<nick>]` nicknames
     /// authorised to push blobs into this store when we host it for them.
     ///
@@ -213,6 +251,17 @@ pub enum NodeConfigStoreBackend {
     Indexd(IndexdStoreConfig),
 }
 
+impl NodeConfigStoreBackend {
+    /// The directory the backend keeps its data in, for stores on local disk.
+    pub fn local_path(&self) -> Option<PathBuf> {
+        match self {
+            NodeConfigStoreBackend::Local(cfg) => Some(PathBuf::from(&cfg.base_path)),
+            NodeConfigStoreBackend::Fjall(cfg) => Some(PathBuf::from(&cfg.path)),
+            _ => None,
+        }
+    }
+}
+
 impl NodeConfigStore {
     /// Convenience constructor preserving the old enum-only ergonomics
     /// (defaults `outboard = false`). Mostly useful in tests.
@@ -221,11 +270,21 @@ impl NodeConfigStore {
             backend,
             outboard: false,
             read_cache_bytes: None,
+            layout: BlobLayoutSpec::Auto,
+            access_tracking: true,
             allow: Vec::new(),
         }
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
 /// Configuration for an indexd (Sia) blob store — see
 /// [`NodeConfigStoreBackend::Indexd`] and `s5_store_indexd::IndexdStore`.
 ///
@@ -551,6 +610,16 @@ impl S5NodeConfig {
                         ));
                     }
                 }
+                TaskTrigger::Cron => match task_config.cron.as_deref() {
+                    None => errors.push(format!(
+                        "task.{task_name}: trigger = \"cron\" requires `cron`"
+                    )),
+                    Some(expr) => {
+                        if let Err(e) = crate::jobs::CronSchedule::parse(expr) {
+                            errors.push(format!("task.{task_name}: invalid cron \"{expr}\": {e}"));
+                        }
+                    }
+                },
                 TaskTrigger::Manual => {}
             }
         }
@@ -582,6 +651,13 @@ impl S5NodeConfig {
                     self.validate_registry(name, backend, errors);
                 }
             }
+            NodeConfigRegistry::Remote { peer, .. } => {
+                if peer.parse::<iroh::EndpointId>().is_err() {
+                    errors.push(format!(
+                        "registry.{name}: peer \"{peer}\" is not an iroh endpoint id"
+                    ));
+                }
+            }
             _ => {} // Local, Redb, StoreLocal, Memory — no cross-references
         }
     }
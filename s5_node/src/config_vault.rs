@@ -411,6 +411,7 @@ mod tests {
         let local = NodeConfigStore::from_backend(NodeConfigStoreBackend::Local(
             s5_store_local::LocalStoreConfig {
                 base_path: "/data/s5/store".to_string(),
+                ..Default::default()
             },
         ));
         a.put("local", &local).await.unwrap();
@@ -0,0 +1,537 @@
+//! DNS bridge: keep `_s5.<domain>` TXT records pointing at a vault's
+//! published root head, for sites configured under `[dns.<site>]`.
+//!
+//! Called from the tail of a successful publish with the head that just
+//! won the registry race. The record value is
+//!
+//! ```text
+//! v=s5root1 key=<ed25519 pubkey hex><vault_id hex> root=<BLAKE3 hex> rev=<revision>
+//! ```
+//!
+//! `key` is the registry stream key the head lives under, so a resolver
+//! can check the (possibly stale) `root` against the signed registry
+//! entry. Provider failures never fail the publish — they are logged and
+//! the next head change tries again.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, anyhow, bail, ensure};
+use base64::Engine;
+use ring::{digest, hmac};
+use s5_core::Hash;
+use serde_json::json;
+
+use crate::config::{NodeConfigDns, NodeConfigDnsProvider, S5NodeConfig};
+
+const CLOUDFLARE_API: &str = "https://api.cloudflare.com/client/v4";
+const ROUTE53_HOST: &str = "route53.amazonaws.com";
+/// Route 53 is a global service signed in `us-east-1`.
+const ROUTE53_REGION: &str = "us-east-1";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A vault head as it was just published to the registry.
+#[derive(Debug, Clone)]
+pub struct PublishedHead {
+    pub public_key: [u8; 32],
+    pub vault_id: [u8; 16],
+    pub hash: Hash,
+    pub revision: u64,
+}
+
+impl PublishedHead {
+    /// The TXT record value (see the module docs).
+    pub fn txt_value(&self) -> String {
+        format!(
+            "v=s5root1 key={}{} root={} rev={}",
+            hex::encode(self.public_key),
+            hex::encode(self.vault_id),
+            self.hash.to_hex(),
+            self.revision
+        )
+    }
+}
+
+/// The record name for a site: `_s5.<domain>` without a trailing dot.
+pub fn record_name(domain: &str) -> String {
+    format!("_s5.{}", domain.trim_end_matches('.'))
+}
+
+/// Point every `[dns.*]` site of `vault_name` at `head`. Never fails:
+/// each provider error is logged.
+pub async fn update_for_vault(config: &S5NodeConfig, vault_name: &str, head: &PublishedHead) {
+    for (site, dns) in config.dns.iter().filter(|(_, d)| d.vault == vault_name) {
+        match update_record(dns, head).await {
+            Ok(()) => tracing::info!(
+                site = %site,
+                record = %record_name(&dns.domain),
+                revision = head.revision,
+                "DNS record updated to the published head"
+            ),
+            Err(e) => tracing::warn!(
+                site = %site,
+                record = %record_name(&dns.domain),
+                "could not update DNS record — publish still succeeded: {e:#}"
+            ),
+        }
+    }
+}
+
+/// Replace the site's TXT record with `head`'s value.
+pub async fn update_record(dns: &NodeConfigDns, head: &PublishedHead) -> anyhow::Result<()> {
+    let name = record_name(&dns.domain);
+    let value = head.txt_value();
+    match &dns.provider {
+        NodeConfigDnsProvider::Cloudflare { zone_id, api_token } => {
+            cloudflare_upsert(zone_id, api_token, &name, &value, dns.ttl).await
+        }
+        NodeConfigDnsProvider::Route53 {
+            hosted_zone_id,
+            access_key,
+            secret_key,
+        } => {
+            route53_upsert(
+                hosted_zone_id,
+                access_key,
+                secret_key,
+                &name,
+                &value,
+                dns.ttl,
+            )
+            .await
+        }
+        NodeConfigDnsProvider::Rfc2136 {
+            server,
+            zone,
+            tsig_key_name,
+            tsig_secret,
+        } => {
+            let secret = base64::engine::general_purpose::STANDARD
+                .decode(tsig_secret.trim())
+                .context("tsig_secret is not valid base64")?;
+            rfc2136_replace(server, zone, tsig_key_name, &secret, &name, &value, dns.ttl).await
+        }
+    }
+}
+
+fn http_client() -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent(concat!("s5-node/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT)
+        .build()?)
+}
+
+// ---------------------------------------------------------------------------
+// Cloudflare
+// ---------------------------------------------------------------------------
+
+async fn cloudflare_upsert(
+    zone_id: &str,
+    api_token: &str,
+    name: &str,
+    value: &str,
+    ttl: u32,
+) -> anyhow::Result<()> {
+    let client = http_client()?;
+    let records = format!("{CLOUDFLARE_API}/zones/{zone_id}/dns_records");
+
+    let found = cloudflare_call(
+        client
+            .get(&records)
+            .query(&[("type", "TXT"), ("name", name)])
+            .bearer_auth(api_token),
+    )
+    .await
+    .context("listing Cloudflare TXT records")?;
+    let existing = found["result"]
+        .as_array()
+        .and_then(|r| r.first())
+        .and_then(|r| r["id"].as_str())
+        .map(str::to_string);
+
+    let body = json!({ "type": "TXT", "name": name, "content": value, "ttl": ttl });
+    let req = match &existing {
+        Some(id) => client.put(format!("{records}/{id}")),
+        None => client.post(&records),
+    };
+    cloudflare_call(
+        req.bearer_auth(api_token)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string()),
+    )
+    .await
+    .context("writing Cloudflare TXT record")?;
+    Ok(())
+}
+
+/// Send a Cloudflare API request and unwrap its `{ success, errors }`
+/// envelope.
+async fn cloudflare_call(req: reqwest::RequestBuilder) -> anyhow::Result<serde_json::Value> {
+    let resp = req.send().await?;
+    let status = resp.status();
+    let body: serde_json::Value = serde_json::from_slice(&resp.bytes().await?)
+        .with_context(|| format!("unparseable response (HTTP {status})"))?;
+    if !status.is_success() || body["success"].as_bool() != Some(true) {
+        bail!("HTTP {status}: {}", body["errors"]);
+    }
+    Ok(body)
+}
+
+// ---------------------------------------------------------------------------
+// Route 53
+// ---------------------------------------------------------------------------
+
+async fn route53_upsert(
+    hosted_zone_id: &str,
+    access_key: &str,
+    secret_key: &str,
+    name: &str,
+    value: &str,
+    ttl: u32,
+) -> anyhow::Result<()> {
+    let zone = hosted_zone_id.trim_start_matches("/hostedzone/");
+    let path = format!("/2013-04-01/hostedzone/{zone}/rrset/");
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <ChangeResourceRecordSetsRequest xmlns=\"https://route53.amazonaws.com/doc/2013-04-01/\">\
+         <ChangeBatch><Changes><Change><Action>UPSERT</Action><ResourceRecordSet>\
+         <Name>{name}</Name><Type>TXT</Type><TTL>{ttl}</TTL>\
+         <ResourceRecords><ResourceRecord><Value>\"{value}\"</Value></ResourceRecord></ResourceRecords>\
+         </ResourceRecordSet></Change></Changes></ChangeBatch></ChangeResourceRecordSetsRequest>"
+    );
+
+    let amz_date = time::OffsetDateTime::now_utc()
+        .format(time::macros::format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .context("formatting request time")?;
+    let authorization = sigv4_authorization(
+        access_key,
+        secret_key,
+        &amz_date,
+        &path,
+        "text/xml",
+        body.as_bytes(),
+    );
+
+    let resp = http_client()?
+        .post(format!("https://{ROUTE53_HOST}{path}"))
+        .header(reqwest::header::CONTENT_TYPE, "text/xml")
+        .header("x-amz-date", &amz_date)
+        .header(reqwest::header::AUTHORIZATION, authorization)
+        .body(body)
+        .send()
+        .await
+        .context("calling Route 53")?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        bail!("Route 53 returned HTTP {status}: {text}");
+    }
+    Ok(())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+        .as_ref()
+        .to_vec()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest::digest(&digest::SHA256, data))
+}
+
+/// AWS Signature Version 4 signing key for `date` (`YYYYMMDD`).
+fn sigv4_signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date.as_bytes());
+    let k = hmac_sha256(&k, region.as_bytes());
+    let k = hmac_sha256(&k, service.as_bytes());
+    hmac_sha256(&k, b"aws4_request")
+}
+
+/// `Authorization` header for a Route 53 `POST` to `path`, signing the
+/// `content-type`, `host` and `x-amz-date` headers.
+fn sigv4_authorization(
+    access_key: &str,
+    secret_key: &str,
+    amz_date: &str,
+    path: &str,
+    content_type: &str,
+    body: &[u8],
+) -> String {
+    let date = &amz_date[..8];
+    let signed_headers = "content-type;host;x-amz-date";
+    let canonical = format!(
+        "POST\n{path}\n\ncontent-type:{content_type}\nhost:{ROUTE53_HOST}\nx-amz-date:{amz_date}\n\n\
+         {signed_headers}\n{}",
+        sha256_hex(body)
+    );
+    let scope = format!("{date}/{ROUTE53_REGION}/route53/aws4_request");
+    let to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        sha256_hex(canonical.as_bytes())
+    );
+    let key = sigv4_signing_key(secret_key, date, ROUTE53_REGION, "route53");
+    let signature = hex::encode(hmac_sha256(&key, to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={access_key}/{scope}, SignedHeaders={signed_headers}, \
+         Signature={signature}"
+    )
+}
+
+// ---------------------------------------------------------------------------
+// RFC 2136 + TSIG
+// ---------------------------------------------------------------------------
+
+const TYPE_SOA: u16 = 6;
+const TYPE_TXT: u16 = 16;
+const TYPE_TSIG: u16 = 250;
+const CLASS_IN: u16 = 1;
+const CLASS_ANY: u16 = 255;
+const OPCODE_UPDATE: u16 = 5 << 11;
+const TSIG_ALGORITHM: &str = "hmac-sha256";
+const TSIG_FUDGE: u16 = 300;
+
+async fn rfc2136_replace(
+    server: &str,
+    zone: &str,
+    key_name: &str,
+    secret: &[u8],
+    name: &str,
+    value: &str,
+    ttl: u32,
+) -> anyhow::Result<()> {
+    let id: u16 = rand::random();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let msg = build_txt_update(id, zone, name, value, ttl)?;
+    let msg = tsig_sign(msg, key_name, secret, now)?;
+
+    let addr = tokio::net::lookup_host(server)
+        .await
+        .with_context(|| format!("resolving {server}"))?
+        .next()
+        .ok_or_else(|| anyhow!("{server} did not resolve"))?;
+    let bind = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let sock = tokio::net::UdpSocket::bind(bind).await?;
+    sock.connect(addr).await?;
+    sock.send(&msg).await?;
+    let mut buf = [0u8; 4096];
+    let n = tokio::time::timeout(REQUEST_TIMEOUT, sock.recv(&mut buf))
+        .await
+        .map_err(|_| anyhow!("no answer from {server}"))??;
+    check_update_response(id, &buf[..n])
+}
+
+/// Append `name` in uncompressed, lower-cased wire form.
+fn put_name(buf: &mut Vec<u8>, name: &str) -> anyhow::Result<()> {
+    for label in name.trim_end_matches('.').split('.') {
+        ensure!(
+            !label.is_empty() && label.len() <= 63,
+            "invalid DNS name '{name}'"
+        );
+        buf.push(label.len() as u8);
+        buf.extend(label.bytes().map(|b| b.to_ascii_lowercase()));
+    }
+    buf.push(0);
+    Ok(())
+}
+
+/// An UPDATE for `zone` that deletes every TXT record at `name` and adds
+/// one holding `value` — a replace, in one atomic transaction.
+fn build_txt_update(
+    id: u16,
+    zone: &str,
+    name: &str,
+    value: &str,
+    ttl: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let zone_n = zone.trim_end_matches('.').to_ascii_lowercase();
+    let name_n = name.trim_end_matches('.').to_ascii_lowercase();
+    ensure!(
+        name_n == zone_n || name_n.ends_with(&format!(".{zone_n}")),
+        "record {name} is not inside zone {zone}"
+    );
+
+    let mut m = Vec::with_capacity(512);
+    m.extend(id.to_be_bytes());
+    m.extend(OPCODE_UPDATE.to_be_bytes());
+    // ZOCOUNT, PRCOUNT, UPCOUNT, ADCOUNT
+    for count in [1u16, 0, 2, 0] {
+        m.extend(count.to_be_bytes());
+    }
+
+    put_name(&mut m, zone)?;
+    m.extend(TYPE_SOA.to_be_bytes());
+    m.extend(CLASS_IN.to_be_bytes());
+
+    // Delete the RRset: class ANY, TTL 0, no RDATA.
+    put_name(&mut m, name)?;
+    m.extend(TYPE_TXT.to_be_bytes());
+    m.extend(CLASS_ANY.to_be_bytes());
+    m.extend(0u32.to_be_bytes());
+    m.extend(0u16.to_be_bytes());
+
+    // Add the new record; TXT RDATA is length-prefixed strings of ≤ 255.
+    let mut rdata = Vec::new();
+    for chunk in value.as_bytes().chunks(255) {
+        rdata.push(chunk.len() as u8);
+        rdata.extend_from_slice(chunk);
+    }
+    put_name(&mut m, name)?;
+    m.extend(TYPE_TXT.to_be_bytes());
+    m.extend(CLASS_IN.to_be_bytes());
+    m.extend(ttl.to_be_bytes());
+    m.extend((rdata.len() as u16).to_be_bytes());
+    m.extend(rdata);
+    Ok(m)
+}
+
+/// Sign `msg` with a TSIG record (RFC 8945) and append it.
+fn tsig_sign(mut msg: Vec<u8>, key_name: &str, secret: &[u8], now: u64) -> anyhow::Result<Vec<u8>> {
+    let mut key_wire = Vec::new();
+    put_name(&mut key_wire, key_name)?;
+    let mut alg_wire = Vec::new();
+    put_name(&mut alg_wire, TSIG_ALGORITHM)?;
+    let time_signed = &now.to_be_bytes()[2..];
+
+    // MAC over the unsigned message followed by the TSIG variables.
+    let mut signed = msg.clone();
+    signed.extend(&key_wire);
+    signed.extend(CLASS_ANY.to_be_bytes());
+    signed.extend(0u32.to_be_bytes());
+    signed.extend(&alg_wire);
+    signed.extend(time_signed);
+    signed.extend(TSIG_FUDGE.to_be_bytes());
+    signed.extend(0u16.to_be_bytes()); // error
+    signed.extend(0u16.to_be_bytes()); // other len
+    let mac = hmac_sha256(secret, &signed);
+
+    let mut rdata = alg_wire;
+    rdata.extend(time_signed);
+    rdata.extend(TSIG_FUDGE.to_be_bytes());
+    rdata.extend((mac.len() as u16).to_be_bytes());
+    rdata.extend(&mac);
+    rdata.extend_from_slice(&msg[..2]); // original id
+    rdata.extend(0u16.to_be_bytes()); // error
+    rdata.extend(0u16.to_be_bytes()); // other len
+
+    msg.extend(key_wire);
+    msg.extend(TYPE_TSIG.to_be_bytes());
+    msg.extend(CLASS_ANY.to_be_bytes());
+    msg.extend(0u32.to_be_bytes());
+    msg.extend((rdata.len() as u16).to_be_bytes());
+    msg.extend(rdata);
+    let arcount = u16::from_be_bytes([msg[10], msg[11]]) + 1;
+    msg[10..12].copy_from_slice(&arcount.to_be_bytes());
+    Ok(msg)
+}
+
+fn check_update_response(id: u16, resp: &[u8]) -> anyhow::Result<()> {
+    ensure!(resp.len() >= 12, "truncated DNS response");
+    ensure!(
+        u16::from_be_bytes([resp[0], resp[1]]) == id,
+        "DNS response for a different request"
+    );
+    let rcode = resp[3] & 0x0f;
+    let name = match rcode {
+        0 => return Ok(()),
+        1 => "FORMERR",
+        2 => "SERVFAIL",
+        3 => "NXDOMAIN",
+        4 => "NOTIMP",
+        5 => "REFUSED",
+        6 => "YXDOMAIN",
+        7 => "YXRRSET",
+        8 => "NXRRSET",
+        9 => "NOTAUTH",
+        10 => "NOTZONE",
+        _ => "unknown rcode",
+    };
+    bail!("server rejected the update: {name} ({rcode})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn txt_value_names_stream_key_and_head() {
+        let head = PublishedHead {
+            public_key: [0xab; 32],
+            vault_id: [0x01; 16],
+            hash: Hash::new(b"tn"),
+            revision: 7,
+        };
+        let v = head.txt_value();
+        assert!(v.starts_with(&format!(
+            "v=s5root1 key={}{} root=",
+            "ab".repeat(32),
+            "01".repeat(16)
+        )));
+        assert!(v.ends_with(&format!("root={} rev=7", Hash::new(b"tn").to_hex())));
+        assert_eq!(record_name("blog.example.com."), "_s5.blog.example.com");
+    }
+
+    #[test]
+    fn sigv4_signing_key_matches_aws_example() {
+        // From the AWS SigV4 documentation ("Examples of how to derive a
+        // signing key").
+        let key = sigv4_signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn update_message_is_signed_replace() {
+        let msg =
+            build_txt_update(0x1234, "example.com", "_s5.Blog.example.com", "v=x", 60).unwrap();
+        let signed = tsig_sign(msg.clone(), "upd", b"secret", 1_700_000_000).unwrap();
+        assert_eq!(&signed[..msg.len()], {
+            let mut m = msg.clone();
+            m[11] = 1;
+            m
+        });
+        // ZOCOUNT 1, UPCOUNT 2, ARCOUNT 1 (the TSIG).
+        assert_eq!(&signed[4..12], &[0, 1, 0, 0, 0, 2, 0, 1]);
+        assert_eq!(signed[2] >> 3, 5, "opcode UPDATE");
+        let needle = b"\x03_s5\x04blog\x07example\x03com\x00";
+        assert!(msg.windows(needle.len()).any(|w| w == needle));
+        assert!(build_txt_update(1, "example.org", "_s5.example.com", "v", 60).is_err());
+    }
+
+    #[tokio::test]
+    async fn rfc2136_reports_server_rcode() {
+        let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 4096];
+            let (n, peer) = server.recv_from(&mut buf).await.unwrap();
+            let mut resp = buf[..12.min(n)].to_vec();
+            resp[2] |= 0x80; // QR
+            resp[3] = 9; // NOTAUTH
+            server.send_to(&resp, peer).await.unwrap();
+        });
+        let err = rfc2136_replace(
+            &addr,
+            "example.com",
+            "upd",
+            b"secret",
+            "_s5.example.com",
+            "v",
+            60,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("NOTAUTH"), "{err}");
+    }
+}
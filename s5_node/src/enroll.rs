@@ -715,6 +715,7 @@ mod tests {
             store: NodeConfigStore::from_backend(crate::config::NodeConfigStoreBackend::Local(
                 s5_store_local::LocalStoreConfig {
                     base_path: "/data".to_string(),
+                    ..Default::default()
                 },
             )),
             recovery_recipient: Some("age1paper".to_string()),
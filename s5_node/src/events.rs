@@ -0,0 +1,274 @@
+//! Structured node event log.
+//!
+//! Subsystems report operator-relevant happenings — a GC pass freed
+//! space, a blob failed its integrity check, a task keeps failing, a disk
+//! is filling up — as typed [`NodeEvent`]s on the daemon's [`EventLog`].
+//! Each event is written to the tracing log under the `s5_node::events`
+//! target and fanned out to subscribers; the [`notifications`](crate::notifications)
+//! dispatcher is one.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tokio::sync::{RwLock, broadcast};
+
+use crate::config::{NodeEventKind, S5NodeConfig};
+
+/// Events buffered per subscriber before the slowest one starts missing
+/// them.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// How often [`spawn_disk_monitor`] checks free space.
+pub const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Something an operator may want to hear about.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NodeEvent {
+    /// A cold-GC pass deleted unreachable blobs.
+    GcCompleted {
+        vault: String,
+        deleted: usize,
+        bytes_reclaimed: u64,
+    },
+    /// A full blob read returned bytes that do not hash to the blob's id.
+    Corruption { hash: String, error: String },
+    /// The same task failed `consecutive` times in a row.
+    TaskFailed {
+        task: String,
+        consecutive: u32,
+        error: String,
+    },
+    /// A local store's filesystem is nearly full.
+    DiskLow {
+        store: String,
+        path: PathBuf,
+        available_bytes: u64,
+        total_bytes: u64,
+    },
+}
+
+impl NodeEvent {
+    pub fn kind(&self) -> NodeEventKind {
+        match self {
+            NodeEvent::GcCompleted { .. } => NodeEventKind::GcCompleted,
+            NodeEvent::Corruption { .. } => NodeEventKind::Corruption,
+            NodeEvent::TaskFailed { .. } => NodeEventKind::TaskFailed,
+            NodeEvent::DiskLow { .. } => NodeEventKind::DiskLow,
+        }
+    }
+
+    /// One line for a log, mail subject or chat message.
+    pub fn summary(&self) -> String {
+        match self {
+            NodeEvent::GcCompleted {
+                vault,
+                deleted,
+                bytes_reclaimed,
+            } => format!("GC of vault '{vault}' freed {bytes_reclaimed} bytes ({deleted} blobs)"),
+            NodeEvent::Corruption { hash, .. } => format!("blob {hash} failed its integrity check"),
+            NodeEvent::TaskFailed {
+                task, consecutive, ..
+            } => format!("task {task} failed {consecutive} times in a row"),
+            NodeEvent::DiskLow {
+                store,
+                available_bytes,
+                total_bytes,
+                ..
+            } => format!(
+                "store '{store}' is low on disk: {available_bytes} of {total_bytes} bytes free"
+            ),
+        }
+    }
+}
+
+/// An event with the time it was emitted.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRecord {
+    pub at_unix_secs: u64,
+    #[serde(flatten)]
+    pub event: NodeEvent,
+}
+
+/// The daemon's event log. Cheap to clone; every clone feeds the same
+/// subscribers.
+#[derive(Clone)]
+pub struct EventLog {
+    tx: broadcast::Sender<EventRecord>,
+    /// Consecutive failures per task label, reset on success.
+    task_failures: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for EventLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventLog").finish_non_exhaustive()
+    }
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self {
+            tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            task_failures: Default::default(),
+        }
+    }
+
+    /// Log `event` and hand it to every subscriber.
+    pub fn emit(&self, event: NodeEvent) {
+        let at_unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        tracing::info!(
+            target: "s5_node::events",
+            kind = ?event.kind(),
+            "{}",
+            event.summary()
+        );
+        // No subscribers is fine: the tracing line above is the record.
+        let _ = self.tx.send(EventRecord {
+            at_unix_secs,
+            event,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EventRecord> {
+        self.tx.subscribe()
+    }
+
+    /// Record a task outcome. A failure emits [`NodeEvent::TaskFailed`]
+    /// with the running count of consecutive failures of `task`; a
+    /// success resets the count.
+    pub fn task_finished(&self, task: &str, error: Option<&str>) {
+        let mut failures = self.task_failures.lock().expect("poisoned");
+        let Some(error) = error else {
+            failures.remove(task);
+            return;
+        };
+        let consecutive = failures.entry(task.to_string()).or_insert(0);
+        *consecutive += 1;
+        let consecutive = *consecutive;
+        drop(failures);
+        self.emit(NodeEvent::TaskFailed {
+            task: task.to_string(),
+            consecutive,
+            error: error.to_string(),
+        });
+    }
+}
+
+/// Check the free space under every local store every
+/// [`DISK_CHECK_INTERVAL`] and emit [`NodeEvent::DiskLow`] when it drops
+/// below `[notify].disk_low_percent`. Each store raises it once per dip:
+/// it re-arms after free space recovers.
+pub fn spawn_disk_monitor(
+    events: EventLog,
+    config: Arc<RwLock<S5NodeConfig>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut low: HashMap<String, bool> = HashMap::new();
+        loop {
+            let (percent, stores) = {
+                let cfg = config.read().await;
+                let stores: Vec<(String, PathBuf)> = cfg
+                    .store
+                    .iter()
+                    .filter_map(|(name, s)| Some((name.clone(), s.backend.local_path()?)))
+                    .collect();
+                (cfg.notify.disk_low_percent, stores)
+            };
+            for (name, path) in stores {
+                let stats = match fs4::statvfs(&path) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::debug!(store = %name, path = %path.display(), "statvfs failed: {e}");
+                        continue;
+                    }
+                };
+                let (available, total) = (stats.available_space(), stats.total_space());
+                let is_low = is_low(available, total, percent);
+                let was_low = low.insert(name.clone(), is_low).unwrap_or(false);
+                if is_low && !was_low {
+                    events.emit(NodeEvent::DiskLow {
+                        store: name,
+                        path,
+                        available_bytes: available,
+                        total_bytes: total,
+                    });
+                }
+            }
+            tokio::time::sleep(DISK_CHECK_INTERVAL).await;
+        }
+    })
+}
+
+fn is_low(available: u64, total: u64, percent: u8) -> bool {
+    total > 0 && (available as u128) * 100 < (total as u128) * percent as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn task_failures_count_until_success() {
+        let log = EventLog::new();
+        let mut rx = log.subscribe();
+        log.task_finished("backup docs", Some("store offline"));
+        log.task_finished("backup docs", Some("store offline"));
+        log.task_finished("publish docs", Some("no registry"));
+        log.task_finished("backup docs", None);
+        log.task_finished("backup docs", Some("store offline"));
+
+        let mut counts = Vec::new();
+        while let Ok(rec) = rx.try_recv() {
+            let NodeEvent::TaskFailed {
+                task, consecutive, ..
+            } = rec.event
+            else {
+                panic!("unexpected event");
+            };
+            counts.push((task, consecutive));
+        }
+        assert_eq!(
+            counts,
+            [
+                ("backup docs".to_string(), 1),
+                ("backup docs".to_string(), 2),
+                ("publish docs".to_string(), 1),
+                ("backup docs".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn disk_low_threshold() {
+        assert!(is_low(9, 100, 10));
+        assert!(!is_low(10, 100, 10));
+        assert!(!is_low(0, 0, 10));
+    }
+
+    #[test]
+    fn record_serializes_flat() {
+        let rec = EventRecord {
+            at_unix_secs: 5,
+            event: NodeEvent::Corruption {
+                hash: "ab".into(),
+                error: "bad".into(),
+            },
+        };
+        let json = serde_json::to_value(&rec).unwrap();
+        assert_eq!(json["event"], "corruption");
+        assert_eq!(json["hash"], "ab");
+        assert_eq!(json["at_unix_secs"], 5);
+    }
+}
@@ -268,10 +268,16 @@ mod tests {
             vault: vaults,
             task: BTreeMap::new(),
             friend: BTreeMap::new(),
+            jobs: Default::default(),
+            log: Default::default(),
+            admin_ui: Default::default(),
+            dns: Default::default(),
+            notify: Default::default(),
         };
 
         let blob_store = BlobStore::new(LocalStore::create(LocalStoreConfig {
             base_path: store_dir.path().to_string_lossy().into_owned(),
+            ..Default::default()
         }));
         let mut stores_map: HashMap<String, Arc<dyn Blobs>> = HashMap::new();
         stores_map.insert("primary".to_string(), Arc::new(blob_store.clone()));
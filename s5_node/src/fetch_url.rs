@@ -0,0 +1,351 @@
+//! Server-side remote fetch: the node downloads an http(s) URL itself and
+//! stores the body, so a thin client (admin UI, phone) never has to relay
+//! the bytes.
+//!
+//! The body is streamed to a staging file while it is hashed, then either
+//! uploaded as a plain blob to a store or imported into a vault at a path
+//! through the vault's own pipeline (encrypted vaults never see the
+//! plaintext blob). The vault root is saved but not published — the next
+//! backup or publish picks the change up like any local edit.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow, bail};
+use irpc::channel::mpsc;
+use s5_core::blob::tee::TeeBlobsWrite;
+use s5_core::{BlobsRead, FallbackBlobsRead, Hash};
+use s5_fs_v2::layer::MapLayer;
+use s5_fs_v2::node::SemanticMeta;
+use s5_fs_v2::snapshot::Snapshot;
+use s5_node_api::{FetchUrl, FetchUrlEvent};
+use tokio::io::AsyncWriteExt;
+
+use crate::tasks::TaskExecutorContext;
+use crate::tasks::vault_persist::{load_vault_root, save_vault_root, vault_root_path};
+use crate::tasks::{resolve_store, resolve_vault, resolve_vault_key_info, vault_meta_store_open};
+
+/// Minimum spacing between `Progress` events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A body downloaded to a staging file.
+#[derive(Debug)]
+pub struct Downloaded {
+    pub path: PathBuf,
+    pub hash: Hash,
+    pub size: u64,
+    /// `Content-Type` without parameters, if the server sent one.
+    pub media_type: Option<String>,
+}
+
+/// Fetch `req.url` and store it as `req` asks, reporting progress on
+/// `events`. Returns the final `Done` event; errors become `Failed` at
+/// the caller.
+pub async fn fetch_url(
+    ctx: &TaskExecutorContext,
+    req: FetchUrl,
+    events: &mpsc::Sender<FetchUrlEvent>,
+) -> anyhow::Result<FetchUrlEvent> {
+    if req.path.is_some() && req.vault.is_none() {
+        bail!("`path` needs a `vault` to import into");
+    }
+    let vault_path = match &req.vault {
+        Some(_) => Some(vault_file_path(req.path.as_deref(), &req.url)?),
+        None => None,
+    };
+
+    // Resolve everything from config up front (and drop the lock) so a bad
+    // name fails before any bytes are downloaded.
+    let (store_name, vault) = {
+        let config = ctx.config.read().await;
+        let vault = match &req.vault {
+            Some(name) => {
+                let v = resolve_vault(&config, name)?.clone();
+                let (recipients, identity_files) = resolve_vault_key_info(&config, name)?;
+                Some((name.clone(), v, recipients, identity_files))
+            }
+            None => None,
+        };
+        let store_name = match (&req.store, &vault) {
+            (Some(s), _) => s.clone(),
+            (None, Some((name, v, ..))) => config.vault_data_store(name, v)?.to_string(),
+            (None, None) => config
+                .default_store_name()
+                .ok_or_else(|| anyhow!("no store given and no node-level `default_store`"))?
+                .to_string(),
+        };
+        (store_name, vault)
+    };
+    let blob_store = resolve_store(&ctx.stores, &store_name)?.clone();
+
+    let staging = std::env::temp_dir();
+    let dl = download(&req.url, &staging, events).await?;
+    let result = async {
+        match (vault, vault_path) {
+            (Some((vault_name, vault, recipients, identity_files)), Some(path)) => {
+                let meta = vault_meta_store_open(&vault)?;
+                let meta_read: Arc<dyn BlobsRead> = Arc::new(meta.clone());
+                let blob_read: Arc<dyn BlobsRead> = blob_store.clone();
+                let read: Arc<dyn BlobsRead> =
+                    Arc::new(FallbackBlobsRead::new(meta_read, blob_read));
+
+                let current = vault_root_path(&vault.root_path);
+                let snap = match load_vault_root(&current, &identity_files)
+                    .context("reading vault root")?
+                {
+                    Some((root, ph, snap_ctx)) => Snapshot::new(root, read.clone(), snap_ctx, ph),
+                    None => crate::tasks::ingest::initial_snapshot_for_vault(
+                        &vault,
+                        &vault_name,
+                        read.clone(),
+                    ),
+                };
+
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                let semantic = SemanticMeta {
+                    timestamp: Some(now.as_secs() as u32),
+                    timestamp_subsec_nanos: Some(now.subsec_nanos()),
+                    media_type: dl.media_type.clone(),
+                    ..Default::default()
+                };
+                let file = tokio::fs::File::open(&dl.path).await?;
+                let entry = snap
+                    .import_stream(file, blob_store.as_ref(), Some(semantic))
+                    .await
+                    .context("importing into vault")?;
+
+                let layer = MapLayer::new(BTreeMap::from([(path.clone(), entry)]));
+                let snap_ctx = snap.context().clone();
+                let (new_root, new_ph, _stats) = snap
+                    .merge_and_persist(&layer, &TeeBlobsWrite::new(&meta, blob_store.as_ref()))
+                    .await
+                    .context("merging into the vault tree")?
+                    .ok_or_else(|| anyhow!("import produced an empty vault tree"))?;
+                let new_snap = Snapshot::new(new_root, read, snap_ctx, Some(new_ph));
+                std::fs::create_dir_all(&vault.root_path)
+                    .with_context(|| format!("creating vault root at {}", vault.root_path))?;
+                save_vault_root(&current, &new_snap, &recipients).context("saving vault root")?;
+
+                tracing::info!(
+                    url = %req.url,
+                    vault = %vault_name,
+                    path = %path,
+                    size = dl.size,
+                    "fetched URL into vault"
+                );
+                Ok(FetchUrlEvent::Done {
+                    hash: dl.hash.to_hex().to_string(),
+                    size: dl.size,
+                    store: store_name,
+                    media_type: dl.media_type.clone(),
+                    vault: Some(vault_name),
+                    path: Some(path),
+                })
+            }
+            _ => {
+                let id = blob_store
+                    .blob_upload_file(dl.path.clone())
+                    .await
+                    .context("uploading fetched blob")?;
+                tracing::info!(url = %req.url, hash = %id.hash, size = id.size, "fetched URL");
+                Ok(FetchUrlEvent::Done {
+                    hash: id.hash.to_hex().to_string(),
+                    size: id.size,
+                    store: store_name,
+                    media_type: dl.media_type.clone(),
+                    vault: None,
+                    path: None,
+                })
+            }
+        }
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&dl.path).await;
+    result
+}
+
+/// Stream `url` into a fresh file under `dir`, hashing as it goes and
+/// sending `Progress` events on `events` (at most every
+/// [`PROGRESS_INTERVAL`], plus once at the end). The file is removed if
+/// the download fails.
+pub async fn download(
+    url: &str,
+    dir: &Path,
+    events: &mpsc::Sender<FetchUrlEvent>,
+) -> anyhow::Result<Downloaded> {
+    let parsed = reqwest::Url::parse(url).with_context(|| format!("invalid URL '{url}'"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!(
+            "unsupported URL scheme '{}' (http/https only)",
+            parsed.scheme()
+        );
+    }
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("s5-node/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(30))
+        .build()?;
+    let mut resp = client
+        .get(parsed)
+        .send()
+        .await
+        .with_context(|| format!("fetching {url}"))?
+        .error_for_status()?;
+    let total = resp.content_length();
+    let media_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| !v.is_empty());
+
+    let mut name = [0u8; 8];
+    rand::Rng::fill_bytes(&mut rand::rng(), &mut name);
+    let path = dir.join(format!("s5-fetch-{}.part", hex::encode(name)));
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .with_context(|| format!("creating {}", path.display()))?;
+
+    let streamed = async {
+        let mut hasher = blake3::Hasher::new();
+        let mut bytes = 0u64;
+        let mut last = tokio::time::Instant::now();
+        while let Some(chunk) = resp.chunk().await.context("reading response body")? {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+            bytes += chunk.len() as u64;
+            if last.elapsed() >= PROGRESS_INTERVAL {
+                last = tokio::time::Instant::now();
+                let _ = events.send(FetchUrlEvent::Progress { bytes, total }).await;
+            }
+        }
+        file.flush().await?;
+        if let Some(total) = total
+            && bytes != total
+        {
+            bail!("body was {bytes} bytes, Content-Length said {total}");
+        }
+        let _ = events.send(FetchUrlEvent::Progress { bytes, total }).await;
+        Ok((Hash::from(hasher.finalize()), bytes))
+    }
+    .await;
+    drop(file);
+    match streamed {
+        Ok((hash, size)) => Ok(Downloaded {
+            path,
+            hash,
+            size,
+            media_type,
+        }),
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&path).await;
+            Err(e)
+        }
+    }
+}
+
+/// The vault key for a fetched file: `path` with leading slashes trimmed;
+/// when it is absent or names a directory (trailing `/`), the URL's last
+/// path segment is appended.
+fn vault_file_path(path: Option<&str>, url: &str) -> anyhow::Result<String> {
+    let dir_or_file = path.unwrap_or("").trim_start_matches('/');
+    let key = if dir_or_file.is_empty() || dir_or_file.ends_with('/') {
+        let name = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| {
+                u.path_segments()
+                    .and_then(|mut s| s.next_back().map(str::to_string))
+            })
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| anyhow!("URL has no file name; give a `path` for the vault entry"))?;
+        format!("{dir_or_file}{name}")
+    } else {
+        dir_or_file.to_string()
+    };
+    if key
+        .split('/')
+        .any(|seg| seg.is_empty() || seg == "." || seg == "..")
+    {
+        bail!("invalid vault path '{key}'");
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s5_core::BlobsWrite;
+    use s5_core::blob::BlobStore;
+    use s5_store_memory::MemoryStore;
+    use tokio::io::AsyncReadExt;
+
+    /// One-shot HTTP/1.1 server answering any request with `body`.
+    async fn serve_once(body: &'static [u8]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = sock.read(&mut buf).await.unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: Text/Plain; charset=utf-8\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            sock.write_all(head.as_bytes()).await.unwrap();
+            sock.write_all(body).await.unwrap();
+        });
+        format!("http://{addr}/files/hello.txt")
+    }
+
+    #[tokio::test]
+    async fn downloads_hashes_and_uploads() {
+        let body: &'static [u8] = b"hello from a remote server\n";
+        let url = serve_once(body).await;
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::channel(16);
+
+        let dl = download(&url, dir.path(), &tx).await.unwrap();
+        assert_eq!(dl.hash, Hash::new(body));
+        assert_eq!(dl.size, body.len() as u64);
+        assert_eq!(dl.media_type.as_deref(), Some("text/plain"));
+        match rx.recv().await.unwrap() {
+            Some(FetchUrlEvent::Progress { bytes, total }) => {
+                assert_eq!(bytes, body.len() as u64);
+                assert_eq!(total, Some(body.len() as u64));
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+
+        let store = BlobStore::new(MemoryStore::new());
+        let id = store.blob_upload_file(dl.path.clone()).await.unwrap();
+        assert_eq!(id.hash, dl.hash);
+        assert_eq!(store.read_as_bytes(id.hash, 0, None).await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn rejects_non_http_schemes() {
+        let (tx, _rx) = mpsc::channel(1);
+        let err = download("file:///etc/passwd", Path::new("/tmp"), &tx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unsupported URL scheme"), "{err}");
+    }
+
+    #[test]
+    fn vault_paths() {
+        let url = "https://example.com/dl/file.iso?x=1";
+        assert_eq!(vault_file_path(None, url).unwrap(), "file.iso");
+        assert_eq!(
+            vault_file_path(Some("/isos/"), url).unwrap(),
+            "isos/file.iso"
+        );
+        assert_eq!(vault_file_path(Some("a/b.bin"), url).unwrap(), "a/b.bin");
+        assert!(vault_file_path(Some("a/../b"), url).is_err());
+        assert!(vault_file_path(None, "https://example.com/").is_err());
+    }
+}
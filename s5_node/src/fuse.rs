@@ -281,6 +281,7 @@ impl MountManager {
         let meta_store: Arc<dyn BlobsRead> = Arc::new(BlobStore::new(
             s5_store_local::LocalStore::create(s5_store_local::LocalStoreConfig {
                 base_path: meta_path.to_string_lossy().into_owned(),
+                ..Default::default()
             }),
         ));
 
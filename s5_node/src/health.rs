@@ -13,9 +13,12 @@ use std::sync::Arc;
 use s5_core::Hash;
 use s5_core::blob::Blobs;
 use s5_node_api::config::{TaskSpec, TaskTrigger};
-use s5_node_api::{GetHealthResponse, ScheduledRun, StagingGauges, StoreHealth};
+use s5_node_api::{
+    DiskGauges, DiskLevel, GetHealthResponse, ScheduledRun, StagingGauges, StoreHealth,
+};
+use s5_store_local::{DiskSpaceLevel, LocalStore};
 
-use crate::config::S5NodeConfig;
+use crate::config::{NodeConfigStoreBackend, S5NodeConfig};
 
 /// Probe every configured `[store.*]` for reachability + staging state and
 /// collect the configured scheduled backups.
@@ -28,6 +31,8 @@ use crate::config::S5NodeConfig;
 /// - **Staging** comes from [`s5_core::blob::BlobsWrite::staging_stats`] —
 ///   `Some` only for a write-buffering backend (a packing store); `None` for a
 ///   direct store whose writes are durable on return.
+/// - **Disk** is free space against the write watermarks, for `local`
+///   stores only.
 /// - **Schedules** are every `[task.*]` automation with `trigger = "every"`
 ///   (the D20 `automate add … --every` / `share … --live` surface) plus any
 ///   vault still carrying the legacy `snap_interval_secs` knob.
@@ -44,6 +49,10 @@ pub async fn gather_health(
     let names: BTreeSet<&String> = config.store.keys().chain(stores.keys()).collect();
     let mut store_health = Vec::with_capacity(names.len());
     for name in names {
+        let disk = match config.store.get(name).map(|s| &s.backend) {
+            Some(NodeConfigStoreBackend::Local(cfg)) => disk_gauges(name, cfg),
+            _ => None,
+        };
         let entry = match stores.get(name) {
             Some(blobs) => {
                 // A HEAD on the empty-blob hash: cheap on every backend, and a
@@ -63,6 +72,7 @@ pub async fn gather_health(
                     reachable,
                     error,
                     staging,
+                    disk,
                 }
             }
             None => StoreHealth {
@@ -70,6 +80,7 @@ pub async fn gather_health(
                 reachable: false,
                 error: Some("configured but not resolved in the daemon store registry".to_string()),
                 staging: None,
+                disk,
             },
         };
         store_health.push(entry);
@@ -101,9 +112,34 @@ pub async fn gather_health(
     GetHealthResponse {
         stores: store_health,
         schedules,
+        // Filled in by the `GetHealth` handler, which owns the links.
+        remotes: Vec::new(),
     }
 }
 
+/// Free space under a `local` store; `None` (logged) when the filesystem
+/// can't be queried.
+fn disk_gauges(name: &str, cfg: &s5_store_local::LocalStoreConfig) -> Option<DiskGauges> {
+    let usage = match LocalStore::create(cfg.clone()).disk_usage() {
+        Ok(u) => u,
+        Err(e) => {
+            tracing::debug!(store = %name, "disk usage unavailable: {e:#}");
+            return None;
+        }
+    };
+    Some(DiskGauges {
+        total_bytes: usage.total_bytes,
+        available_bytes: usage.available_bytes,
+        low_watermark_bytes: usage.low_watermark_bytes,
+        critical_watermark_bytes: usage.critical_watermark_bytes,
+        level: match usage.level {
+            DiskSpaceLevel::Ok => DiskLevel::Ok,
+            DiskSpaceLevel::Low => DiskLevel::Low,
+            DiskSpaceLevel::Critical => DiskLevel::Critical,
+        },
+    })
+}
+
 /// The vault a scheduled `[task.*]` automation targets (for the doctor/status
 /// "scheduled backups" list). A `Copy` automation (e.g. `share … --live`)
 /// maintains its destination vault.
@@ -0,0 +1,204 @@
+//! Five-field cron expressions, evaluated in UTC.
+//!
+//! `minute hour day-of-month month day-of-week`, each a `*`, a number, a
+//! range `a-b`, or a comma list of those, optionally stepped (`*/15`,
+//! `1-20/5`). Day-of-week takes `0`-`7` (both `0` and `7` are Sunday).
+//! Like classic cron, when both day fields are restricted a day matches if
+//! *either* does. The `@hourly`, `@daily`, `@weekly`, `@monthly` and
+//! `@yearly` shorthands are accepted too.
+
+use anyhow::{Result, anyhow, bail};
+use time::{Date, Duration, Month, OffsetDateTime, Time};
+
+/// How far ahead [`CronSchedule::next_after`] looks before giving up on an
+/// expression that never fires (e.g. `0 0 30 2 *`).
+const SEARCH_YEARS: i32 = 5;
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day fields were `*`, which decides how they combine.
+    dom_any: bool,
+    dow_any: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            bail!("expected 5 fields, got {}", fields.len());
+        };
+        let mut days_of_week = parse_field(dow, 0, 7).map_err(|e| anyhow!("day-of-week: {e}"))?;
+        // 7 is an alias of Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59).map_err(|e| anyhow!("minute: {e}"))?,
+            hours: parse_field(hour, 0, 23).map_err(|e| anyhow!("hour: {e}"))?,
+            days_of_month: parse_field(dom, 1, 31).map_err(|e| anyhow!("day-of-month: {e}"))?,
+            months: parse_field(month, 1, 12).map_err(|e| anyhow!("month: {e}"))?,
+            days_of_week,
+            dom_any: dom == "*",
+            dow_any: dow == "*",
+        })
+    }
+
+    /// The first fire time strictly after `unix` (seconds), or `None` if the
+    /// expression never fires in the next few years.
+    pub fn next_after(&self, unix: u64) -> Option<u64> {
+        let start = OffsetDateTime::from_unix_timestamp(i64::try_from(unix).ok()?).ok()?;
+        let mut t = start.replace_second(0).ok()?.replace_nanosecond(0).ok()? + Duration::MINUTE;
+        let give_up = start.year() + SEARCH_YEARS;
+        while t.year() <= give_up {
+            if !bit(self.months, u8::from(t.month())) {
+                t = first_of_next_month(t.date())?;
+            } else if !self.day_matches(t.date()) {
+                t = t.date().next_day()?.midnight().assume_utc();
+            } else if !bit(self.hours, t.hour()) {
+                t = t.replace_minute(0).ok()? + Duration::HOUR;
+            } else if !bit(self.minutes, t.minute()) {
+                t += Duration::MINUTE;
+            } else {
+                return u64::try_from(t.unix_timestamp()).ok();
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: Date) -> bool {
+        let dom = bit(self.days_of_month, date.day());
+        let dow = bit(self.days_of_week, date.weekday().number_days_from_sunday());
+        match (self.dom_any, self.dow_any) {
+            (false, false) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+}
+
+fn bit(mask: u64, value: u8) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn first_of_next_month(date: Date) -> Option<OffsetDateTime> {
+    let (year, month) = match date.month() {
+        Month::December => (date.year() + 1, Month::January),
+        m => (date.year(), m.next()),
+    };
+    let date = Date::from_calendar_date(year, month, 1).ok()?;
+    Some(date.with_time(Time::MIDNIGHT).assume_utc())
+}
+
+/// Parses one field into a bitmask of the allowed values in `min..=max`.
+fn parse_field(field: &str, min: u8, max: u8) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u8 = step
+                    .parse()
+                    .map_err(|_| anyhow!("invalid step \"{step}\""))?;
+                if step == 0 {
+                    bail!("step must be positive");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((lo, hi)) => (parse_value(lo, min, max)?, parse_value(hi, min, max)?),
+                // `5/10` means "from 5 to the end, every 10".
+                None if step > 1 => (parse_value(range, min, max)?, max),
+                None => {
+                    let v = parse_value(range, min, max)?;
+                    (v, v)
+                }
+            },
+        };
+        if lo > hi {
+            bail!("range {lo}-{hi} is backwards");
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(s: &str, min: u8, max: u8) -> Result<u8> {
+    let v: u8 = s.parse().map_err(|_| anyhow!("invalid value \"{s}\""))?;
+    if !(min..=max).contains(&v) {
+        bail!("{v} is outside {min}-{max}");
+    }
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn unix(t: OffsetDateTime) -> u64 {
+        t.unix_timestamp() as u64
+    }
+
+    fn next(expr: &str, after: OffsetDateTime) -> OffsetDateTime {
+        let next = CronSchedule::parse(expr)
+            .unwrap()
+            .next_after(unix(after))
+            .unwrap();
+        OffsetDateTime::from_unix_timestamp(next as i64).unwrap()
+    }
+
+    #[test]
+    fn next_fire_times() {
+        let t = datetime!(2026-03-14 10:17:42 UTC);
+        assert_eq!(next("* * * * *", t), datetime!(2026-03-14 10:18 UTC));
+        assert_eq!(next("*/15 * * * *", t), datetime!(2026-03-14 10:30 UTC));
+        assert_eq!(next("0 3 * * *", t), datetime!(2026-03-15 03:00 UTC));
+        assert_eq!(next("@monthly", t), datetime!(2026-04-01 00:00 UTC));
+        assert_eq!(next("30 4 1 1 *", t), datetime!(2027-01-01 04:30 UTC));
+        // 2026-03-14 is a Saturday; 7 and 0 are both Sunday.
+        assert_eq!(next("0 0 * * 7", t), datetime!(2026-03-15 00:00 UTC));
+        assert_eq!(next("0 9 * * 1-5", t), datetime!(2026-03-16 09:00 UTC));
+        // Restricted day-of-month and day-of-week combine with OR.
+        assert_eq!(next("0 0 20 * 1", t), datetime!(2026-03-16 00:00 UTC));
+        // Exactly on a fire time, the next one is returned.
+        assert_eq!(
+            next("0 3 * * *", datetime!(2026-03-15 03:00 UTC)),
+            datetime!(2026-03-16 03:00 UTC)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for bad in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(CronSchedule::parse(bad).is_err(), "{bad:?} parsed");
+        }
+        let never = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(never.next_after(0), None);
+    }
+}
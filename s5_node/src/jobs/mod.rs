@@ -0,0 +1,316 @@
+//! Node-local job scheduler — cron-triggered tasks with persisted run state.
+//!
+//! A *job* is a `[task.*]` entry with `trigger = "cron"` (and not `paused`).
+//! The [`JobScheduler`] reconciles them from config the way the
+//! [`AutomationManager`](crate::watch::AutomationManager) does automations:
+//! one loop per job, respawned when its task changes. Each loop sleeps until
+//! the next fire time, dispatches the task to the `TaskExecutor` and awaits
+//! its terminal state, so a job never overlaps itself.
+//!
+//! - Each fire time is pushed back by a random `0..=jitter_secs`.
+//! - At most `[jobs].max_concurrent` jobs run at once; the rest queue.
+//! - `RunJobNow` wakes the loop early; a request during a run queues one
+//!   more run right after it.
+//! - Next run, last run, duration and outcome are kept in redb
+//!   ([`JobStateStore`]), so `vup jobs list` survives restarts and a run
+//!   that came due while the node was down happens once on start.
+//!
+//! Features that need periodic work (GC, scrubbing, snapshots, sync,
+//! tiering) plug in as [`TaskSpec`]s a cron job can name.
+
+pub mod cron;
+pub mod state;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Result, anyhow};
+use rand::RngExt;
+use s5_node_api::config::{NodeConfigTask, TaskSpec, TaskTrigger};
+use s5_node_api::{JobOutcome, JobStatus};
+use tokio::sync::{Notify, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub use cron::CronSchedule;
+pub use state::{JobRecord, JobStateStore};
+
+use crate::config::S5NodeConfig;
+use crate::tasks::TaskExecutor;
+
+/// `[jobs].max_concurrent` when unset.
+pub const DEFAULT_MAX_CONCURRENT: usize = 2;
+/// Longest single sleep while waiting for a fire time, so wall-clock jumps
+/// (suspend, NTP steps) are noticed within a minute.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// What a job loop needs to run its task; cheap to clone into the loop.
+#[derive(Clone)]
+struct RunContext {
+    executor: Arc<TaskExecutor>,
+    state: JobStateStore,
+    permits: Arc<Semaphore>,
+}
+
+struct Job {
+    name: String,
+    cron: String,
+    schedule: CronSchedule,
+    jitter_secs: u64,
+    spec: TaskSpec,
+    run_now: Notify,
+    running: AtomicBool,
+}
+
+struct ActiveJob {
+    cancel: CancellationToken,
+    join: JoinHandle<()>,
+    /// The task this loop was spawned from — reconcile respawns when it
+    /// differs.
+    task: NodeConfigTask,
+    job: Arc<Job>,
+}
+
+/// Daemon-owned registry of cron job loops, keyed by task name.
+pub struct JobScheduler {
+    ctx: RunContext,
+    jobs: RwLock<HashMap<String, ActiveJob>>,
+}
+
+impl JobScheduler {
+    /// Opens (or creates) the job state at `state_path`. `max_concurrent`
+    /// is clamped to at least one.
+    pub fn open(
+        executor: Arc<TaskExecutor>,
+        state_path: &Path,
+        max_concurrent: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            ctx: RunContext {
+                executor,
+                state: JobStateStore::open(state_path)?,
+                permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            },
+            jobs: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Drive the live job set toward the config's `cron` tasks: cancel loops
+    /// that vanished, were paused or changed, and spawn new ones.
+    pub async fn reconcile(&self, config: &S5NodeConfig) {
+        let desired: HashMap<&String, &NodeConfigTask> = config
+            .task
+            .iter()
+            .filter(|(_, t)| t.trigger == TaskTrigger::Cron && !t.paused)
+            .collect();
+
+        let to_cancel: Vec<String> = self
+            .jobs
+            .read()
+            .await
+            .iter()
+            .filter(|(name, active)| desired.get(name).is_none_or(|t| **t != active.task))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in to_cancel {
+            self.cancel_one(&name).await;
+        }
+
+        for (name, task) in desired {
+            if self.jobs.read().await.contains_key(name) {
+                continue;
+            }
+            let cron = task.cron.clone().unwrap_or_default();
+            let schedule = match CronSchedule::parse(&cron) {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!(job = name.as_str(), "invalid cron \"{cron}\": {e:#}");
+                    continue;
+                }
+            };
+            let job = Arc::new(Job {
+                name: name.clone(),
+                cron,
+                schedule,
+                jitter_secs: task.jitter_secs.unwrap_or(0),
+                spec: task.spec.clone(),
+                run_now: Notify::new(),
+                running: AtomicBool::new(false),
+            });
+            let cancel = CancellationToken::new();
+            let join = tokio::spawn(job_loop(self.ctx.clone(), job.clone(), cancel.clone()));
+            self.jobs.write().await.insert(
+                name.clone(),
+                ActiveJob {
+                    cancel,
+                    join,
+                    task: task.clone(),
+                    job,
+                },
+            );
+            tracing::info!(job = name.as_str(), "cron job scheduled");
+        }
+    }
+
+    /// Queue `name` to run now, outside its schedule.
+    pub async fn run_now(&self, name: &str) -> Result<()> {
+        let jobs = self.jobs.read().await;
+        let active = jobs
+            .get(name)
+            .ok_or_else(|| anyhow!("no scheduled cron job named '{name}'"))?;
+        active.job.run_now.notify_one();
+        Ok(())
+    }
+
+    /// Every scheduled job with its persisted state, name-sorted.
+    pub async fn list(&self) -> Vec<JobStatus> {
+        let jobs = self.jobs.read().await;
+        let mut out: Vec<JobStatus> = jobs
+            .values()
+            .map(|active| {
+                let job = &active.job;
+                let record = self.ctx.state.get(&job.name).unwrap_or_else(|e| {
+                    tracing::warn!(job = job.name.as_str(), "reading job state: {e:#}");
+                    JobRecord::default()
+                });
+                JobStatus {
+                    name: job.name.clone(),
+                    cron: job.cron.clone(),
+                    running: job.running.load(Ordering::Relaxed),
+                    next_run_unix: record.next_run_unix,
+                    last_run_unix: record.last_run_unix,
+                    last_duration_secs: record.last_duration_secs,
+                    last_outcome: record.last_outcome,
+                    runs: record.runs,
+                    failures: record.failures,
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        out
+    }
+
+    async fn cancel_one(&self, name: &str) {
+        let entry = self.jobs.write().await.remove(name);
+        if let Some(active) = entry {
+            active.cancel.cancel();
+            let _ = active.join.await;
+            tracing::debug!(job = name, "cron job stopped");
+        }
+    }
+
+    /// Cancel every loop and await it. Idempotent.
+    pub async fn shutdown(&self) {
+        let entries: Vec<(String, ActiveJob)> = self.jobs.write().await.drain().collect();
+        for (name, active) in entries {
+            active.cancel.cancel();
+            let _ = active.join.await;
+            tracing::debug!(job = name.as_str(), "cron job stopped");
+        }
+    }
+}
+
+async fn job_loop(ctx: RunContext, job: Arc<Job>, cancel: CancellationToken) {
+    let now = unix_now();
+    let persisted = ctx.state.get(&job.name).unwrap_or_else(|e| {
+        tracing::warn!(job = job.name.as_str(), "reading job state: {e:#}");
+        JobRecord::default()
+    });
+    let mut next = match persisted.next_run_unix {
+        // Came due while the node was down: catch up once.
+        Some(t) if t <= now => Some(now),
+        _ => next_fire(&job, now),
+    };
+    persist_next(&ctx, &job, next);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = job.run_now.notified() => {}
+            _ = sleep_until(next) => {}
+        }
+        run_once(&ctx, &job).await;
+        // A manual run leaves a pending scheduled one in place.
+        let now = unix_now();
+        if next.is_none_or(|t| t <= now) {
+            next = next_fire(&job, now);
+        }
+        persist_next(&ctx, &job, next);
+    }
+}
+
+async fn run_once(ctx: &RunContext, job: &Job) {
+    job.running.store(true, Ordering::Relaxed);
+    let _permit = ctx
+        .permits
+        .acquire()
+        .await
+        .expect("job semaphore is never closed");
+    let started_unix = unix_now();
+    let started = Instant::now();
+    persist(ctx, job, |r| r.last_run_unix = Some(started_unix));
+
+    let outcome = match crate::watch::dispatch(&ctx.executor, &job.spec, &job.name).await {
+        Ok(()) => JobOutcome::Succeeded,
+        Err(e) => {
+            tracing::warn!(job = job.name.as_str(), "cron job failed: {e:#}");
+            JobOutcome::Failed {
+                error: format!("{e:#}"),
+            }
+        }
+    };
+    persist(ctx, job, |r| {
+        r.runs += 1;
+        if matches!(outcome, JobOutcome::Failed { .. }) {
+            r.failures += 1;
+        }
+        r.last_duration_secs = Some(started.elapsed().as_secs());
+        r.last_outcome = Some(outcome);
+    });
+    job.running.store(false, Ordering::Relaxed);
+}
+
+/// The next fire time after `after`, with the job's jitter applied.
+fn next_fire(job: &Job, after: u64) -> Option<u64> {
+    let t = job.schedule.next_after(after)?;
+    let jitter = match job.jitter_secs {
+        0 => 0,
+        max => rand::rng().random_range(0..=max),
+    };
+    Some(t + jitter)
+}
+
+fn persist_next(ctx: &RunContext, job: &Job, next: Option<u64>) {
+    persist(ctx, job, |r| r.next_run_unix = next);
+}
+
+/// Job state is bookkeeping: a failed write is logged, never fatal.
+fn persist(ctx: &RunContext, job: &Job, f: impl FnOnce(&mut JobRecord)) {
+    if let Err(e) = ctx.state.update(&job.name, f) {
+        tracing::warn!(job = job.name.as_str(), "writing job state: {e:#}");
+    }
+}
+
+/// Sleeps until unix time `t`, or forever for a schedule that never fires.
+async fn sleep_until(t: Option<u64>) {
+    let Some(t) = t else {
+        return std::future::pending().await;
+    };
+    loop {
+        let now = unix_now();
+        if now >= t {
+            return;
+        }
+        tokio::time::sleep(Duration::from_secs(t - now).min(MAX_SLEEP)).await;
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,66 @@
+//! Persisted per-job run state, so schedules and outcomes survive restarts.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use s5_node_api::JobOutcome;
+use serde::{Deserialize, Serialize};
+
+/// Job name → JSON-encoded [`JobRecord`].
+const TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("jobs");
+
+/// What the scheduler remembers about one job between runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub next_run_unix: Option<u64>,
+    pub last_run_unix: Option<u64>,
+    pub last_duration_secs: Option<u64>,
+    pub last_outcome: Option<JobOutcome>,
+    pub runs: u64,
+    pub failures: u64,
+}
+
+/// The scheduler's redb table of [`JobRecord`]s.
+#[derive(Clone)]
+pub struct JobStateStore {
+    db: Arc<Database>,
+}
+
+impl JobStateStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = Database::create(path)
+            .with_context(|| format!("opening job state {}", path.display()))?;
+        let txn = db.begin_write()?;
+        txn.open_table(TABLE)?;
+        txn.commit()?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    pub fn get(&self, name: &str) -> Result<JobRecord> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(TABLE)?;
+        match table.get(name)? {
+            Some(value) => Ok(serde_json::from_slice(value.value())?),
+            None => Ok(JobRecord::default()),
+        }
+    }
+
+    /// Read-modify-write of one job's record in a single transaction.
+    pub fn update(&self, name: &str, f: impl FnOnce(&mut JobRecord)) -> Result<JobRecord> {
+        let txn = self.db.begin_write()?;
+        let record = {
+            let mut table = txn.open_table(TABLE)?;
+            let mut record: JobRecord = match table.get(name)? {
+                Some(value) => serde_json::from_slice(value.value())?,
+                None => JobRecord::default(),
+            };
+            f(&mut record);
+            table.insert(name, serde_json::to_vec(&record)?.as_slice())?;
+            record
+        };
+        txn.commit()?;
+        Ok(record)
+    }
+}
@@ -0,0 +1,450 @@
+//! Recent per-operation latency, for the `GetLatency` RPC.
+//!
+//! [`LatencyTracker`] keeps a fixed-bucket histogram per operation in
+//! one-minute slots over the last [`WINDOW`], plus the [`SLOWEST_KEPT`]
+//! slowest samples in that window and the [`RECENT_KEPT`] latest blob
+//! transfers — enough for a lightweight dashboard (the embedded admin UI)
+//! to show node health without a Prometheus setup. The daemon threads one
+//! tracker (Arc-shared, like [`crate::peer_observer::PeerObserver`]) into
+//! the vault-facing blob stores ([`TimedBlobs`]), the default registry
+//! ([`TimedRegistry`]) and the task executor (FS5 saves).
+
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use s5_core::blob::access::to_unix_secs;
+use s5_core::blob::{
+    BlobId, BlobResult, BlobsDelete, BlobsRead, BlobsWrite, ReachableStream, StagingStats,
+};
+use s5_core::{Blobs, Hash, RegistryApi, StreamKey, StreamMessage};
+use s5_node_api::{GetLatencyResponse, OpLatency, SlowRequest};
+use tokio::io::AsyncRead;
+
+/// Blob reads through a vault-facing store (`blob_download*`).
+pub const OP_BLOB_GET: &str = "blob_get";
+/// Blob writes through a vault-facing store (`blob_upload_*`).
+pub const OP_BLOB_PUT: &str = "blob_put";
+/// Lookups against the default registry.
+pub const OP_REGISTRY_GET: &str = "registry_get";
+/// Writes to the default registry.
+pub const OP_REGISTRY_SET: &str = "registry_set";
+/// Publishing a vault snapshot (the FS5 save: tree upload + registry write).
+pub const OP_FS5_SAVE: &str = "fs5_save";
+
+/// Upper bounds of the histogram buckets, in milliseconds. A final,
+/// unbounded bucket follows for anything slower.
+pub const BUCKET_UPPER_MS: [u64; 13] =
+    [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// How far back the histograms and slowest samples reach.
+pub const WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Slowest samples kept across all operations.
+pub const SLOWEST_KEPT: usize = 20;
+
+/// Latest blob transfers kept, regardless of age.
+pub const RECENT_KEPT: usize = 50;
+
+const SLOT_SECS: u64 = 60;
+const BUCKETS: usize = BUCKET_UPPER_MS.len() + 1;
+
+/// Shared recorder of recent operation latencies. Cheap to clone; clones
+/// record into the same window.
+#[derive(Clone, Default)]
+pub struct LatencyTracker {
+    inner: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    ops: BTreeMap<&'static str, VecDeque<Slot>>,
+    slowest: Vec<SlowRequest>,
+    recent: VecDeque<SlowRequest>,
+}
+
+struct Slot {
+    /// Unix minute this slot covers.
+    minute: u64,
+    buckets: [u64; BUCKETS],
+    max_ms: u64,
+}
+
+impl std::fmt::Debug for LatencyTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LatencyTracker").finish_non_exhaustive()
+    }
+}
+
+fn bucket_for(ms: u64) -> usize {
+    BUCKET_UPPER_MS
+        .iter()
+        .position(|&upper| ms <= upper)
+        .unwrap_or(BUCKET_UPPER_MS.len())
+}
+
+/// Upper bound of the bucket holding the `q` quantile; the observed max for
+/// the overflow bucket (and whenever it is tighter).
+fn quantile_ms(buckets: &[u64; BUCKETS], count: u64, max_ms: u64, q: f64) -> u64 {
+    let rank = ((count as f64 * q).ceil() as u64).max(1);
+    let mut seen = 0;
+    for (i, n) in buckets.iter().enumerate() {
+        seen += n;
+        if seen >= rank {
+            return BUCKET_UPPER_MS
+                .get(i)
+                .map_or(max_ms, |&upper| upper.min(max_ms));
+        }
+    }
+    max_ms
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed `op` that took `elapsed`. `detail` (a hash,
+    /// vault name, …) is kept only in the slowest and recent samples.
+    pub fn record(&self, op: &'static str, elapsed: Duration, detail: impl Into<String>) {
+        self.record_at(op, elapsed, detail.into(), SystemTime::now());
+    }
+
+    fn record_at(&self, op: &'static str, elapsed: Duration, detail: String, at: SystemTime) {
+        let now = to_unix_secs(at);
+        let minute = now / SLOT_SECS;
+        let ms = elapsed.as_millis() as u64;
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        let slots = state.ops.entry(op).or_default();
+        if slots.back().is_none_or(|s| s.minute != minute) {
+            slots.push_back(Slot {
+                minute,
+                buckets: [0; BUCKETS],
+                max_ms: 0,
+            });
+        }
+        let slot = slots.back_mut().expect("slot pushed above");
+        slot.buckets[bucket_for(ms)] += 1;
+        slot.max_ms = slot.max_ms.max(ms);
+        prune_slots(slots, minute);
+
+        let sample = SlowRequest {
+            op: op.to_string(),
+            detail,
+            duration_ms: ms,
+            at_unix_secs: now,
+        };
+        if op == OP_BLOB_GET || op == OP_BLOB_PUT {
+            state.recent.push_front(sample.clone());
+            state.recent.truncate(RECENT_KEPT);
+        }
+
+        let cutoff = now.saturating_sub(WINDOW.as_secs());
+        state.slowest.retain(|s| s.at_unix_secs > cutoff);
+        let fastest_kept = state.slowest.last().map_or(0, |s| s.duration_ms);
+        if state.slowest.len() < SLOWEST_KEPT || ms > fastest_kept {
+            let pos = state.slowest.partition_point(|s| s.duration_ms >= ms);
+            state.slowest.insert(pos, sample);
+            state.slowest.truncate(SLOWEST_KEPT);
+        }
+    }
+
+    /// Time `fut` as one `op`, whether it succeeds or fails.
+    pub async fn time<F: Future>(
+        &self,
+        op: &'static str,
+        detail: impl Into<String>,
+        fut: F,
+    ) -> F::Output {
+        let started = Instant::now();
+        let out = fut.await;
+        self.record(op, started.elapsed(), detail);
+        out
+    }
+
+    /// Histograms and slowest samples over the current window.
+    pub fn snapshot(&self) -> GetLatencyResponse {
+        self.snapshot_at(SystemTime::now())
+    }
+
+    fn snapshot_at(&self, at: SystemTime) -> GetLatencyResponse {
+        let now = to_unix_secs(at);
+        let minute = now / SLOT_SECS;
+        let cutoff = now.saturating_sub(WINDOW.as_secs());
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut ops = Vec::new();
+        for (op, slots) in state.ops.iter_mut() {
+            prune_slots(slots, minute);
+            let mut buckets = [0u64; BUCKETS];
+            let mut max_ms = 0;
+            for slot in slots.iter() {
+                for (total, n) in buckets.iter_mut().zip(slot.buckets) {
+                    *total += n;
+                }
+                max_ms = max_ms.max(slot.max_ms);
+            }
+            let count = buckets.iter().sum();
+            if count == 0 {
+                continue;
+            }
+            ops.push(OpLatency {
+                op: op.to_string(),
+                count,
+                buckets: buckets.to_vec(),
+                p50_ms: quantile_ms(&buckets, count, max_ms, 0.50),
+                p90_ms: quantile_ms(&buckets, count, max_ms, 0.90),
+                p99_ms: quantile_ms(&buckets, count, max_ms, 0.99),
+                max_ms,
+            });
+        }
+        state.slowest.retain(|s| s.at_unix_secs > cutoff);
+
+        GetLatencyResponse {
+            window_secs: WINDOW.as_secs(),
+            bucket_upper_ms: BUCKET_UPPER_MS.to_vec(),
+            ops,
+            slowest: state.slowest.clone(),
+            recent: state.recent.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Drop slots that have slid out of the window ending at `minute`.
+fn prune_slots(slots: &mut VecDeque<Slot>, minute: u64) {
+    let oldest = minute.saturating_sub(WINDOW.as_secs() / SLOT_SECS - 1);
+    while slots.front().is_some_and(|s| s.minute < oldest) {
+        slots.pop_front();
+    }
+}
+
+/// A vault-facing [`Blobs`] that records downloads as [`OP_BLOB_GET`] and
+/// uploads as [`OP_BLOB_PUT`]; everything else passes straight through.
+pub struct TimedBlobs {
+    inner: Arc<dyn Blobs>,
+    latency: LatencyTracker,
+    events: Option<crate::events::EventLog>,
+}
+
+impl TimedBlobs {
+    pub fn new(inner: Arc<dyn Blobs>, latency: LatencyTracker) -> Self {
+        Self {
+            inner,
+            latency,
+            events: None,
+        }
+    }
+
+    /// Also report full downloads that fail their integrity check as
+    /// [`NodeEvent::Corruption`](crate::events::NodeEvent::Corruption).
+    pub fn with_events(mut self, events: crate::events::EventLog) -> Self {
+        self.events = Some(events);
+        self
+    }
+}
+
+impl std::fmt::Debug for TimedBlobs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimedBlobs").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BlobsRead for TimedBlobs {
+    async fn blob_contains(&self, hash: Hash) -> BlobResult<bool> {
+        self.inner.blob_contains(hash).await
+    }
+
+    async fn blob_get_size(&self, hash: Hash) -> BlobResult<u64> {
+        self.inner.blob_get_size(hash).await
+    }
+
+    async fn blob_download(&self, hash: Hash) -> BlobResult<Bytes> {
+        let result = self
+            .latency
+            .time(OP_BLOB_GET, hash.to_hex(), self.inner.blob_download(hash))
+            .await;
+        if let (Err(e), Some(events)) = (&result, &self.events) {
+            let error = e.to_string();
+            if error.contains("integrity check failed") {
+                events.emit(crate::events::NodeEvent::Corruption {
+                    hash: hash.to_hex(),
+                    error,
+                });
+            }
+        }
+        result
+    }
+
+    async fn blob_download_slice(
+        &self,
+        hash: Hash,
+        offset: u64,
+        max_len: Option<u64>,
+    ) -> BlobResult<Bytes> {
+        self.latency
+            .time(
+                OP_BLOB_GET,
+                hash.to_hex(),
+                self.inner.blob_download_slice(hash, offset, max_len),
+            )
+            .await
+    }
+
+    async fn blob_read(&self, hash: Hash) -> BlobResult<Box<dyn AsyncRead + Send + Unpin>> {
+        self.inner.blob_read(hash).await
+    }
+
+    async fn blob_get_outboard(&self, hash: Hash, range: Range<u64>) -> BlobResult<Option<Bytes>> {
+        self.inner.blob_get_outboard(hash, range).await
+    }
+}
+
+#[async_trait]
+impl BlobsWrite for TimedBlobs {
+    async fn blob_upload_bytes(&self, bytes: Bytes) -> BlobResult<BlobId> {
+        let detail = format!("{} bytes", bytes.len());
+        self.latency
+            .time(OP_BLOB_PUT, detail, self.inner.blob_upload_bytes(bytes))
+            .await
+    }
+
+    async fn blob_upload_reader<R, F>(
+        &self,
+        _hash: Hash,
+        _size: u64,
+        _reader: R,
+        _on_progress: F,
+    ) -> BlobResult<BlobId>
+    where
+        Self: Sized,
+        R: AsyncRead + Send + Unpin + 'static,
+        F: Fn(u64) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        Err(anyhow::anyhow!(
+            "TimedBlobs does not support blob_upload_reader"
+        ))
+    }
+
+    async fn blob_upload_stream<S>(&self, _stream: S) -> BlobResult<BlobId>
+    where
+        Self: Sized,
+        S: futures_core::Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin + 'static,
+    {
+        Err(anyhow::anyhow!(
+            "TimedBlobs does not support blob_upload_stream"
+        ))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn blob_upload_file(&self, path: PathBuf) -> BlobResult<BlobId> {
+        let detail = path.display().to_string();
+        self.latency
+            .time(OP_BLOB_PUT, detail, self.inner.blob_upload_file(path))
+            .await
+    }
+
+    async fn blob_sync(&self) -> BlobResult<()> {
+        self.inner.blob_sync().await
+    }
+
+    fn staging_stats(&self) -> Option<StagingStats> {
+        self.inner.staging_stats()
+    }
+}
+
+#[async_trait]
+impl BlobsDelete for TimedBlobs {
+    async fn blob_delete(&self, hash: Hash) -> BlobResult<()> {
+        self.inner.blob_delete(hash).await
+    }
+
+    async fn blob_retain(&self, reachable: ReachableStream) -> BlobResult<()> {
+        self.inner.blob_retain(reachable).await
+    }
+}
+
+/// A [`RegistryApi`] that records `get` as [`OP_REGISTRY_GET`] and `set` as
+/// [`OP_REGISTRY_SET`].
+#[derive(Debug)]
+pub struct TimedRegistry {
+    inner: Arc<dyn RegistryApi + Send + Sync>,
+    latency: LatencyTracker,
+}
+
+impl TimedRegistry {
+    pub fn new(inner: Arc<dyn RegistryApi + Send + Sync>, latency: LatencyTracker) -> Self {
+        Self { inner, latency }
+    }
+}
+
+#[async_trait]
+impl RegistryApi for TimedRegistry {
+    async fn get(&self, key: &StreamKey) -> anyhow::Result<Option<StreamMessage>> {
+        self.latency
+            .time(OP_REGISTRY_GET, format!("{key:?}"), self.inner.get(key))
+            .await
+    }
+
+    async fn set(&self, message: StreamMessage) -> anyhow::Result<()> {
+        let detail = format!("{:?}", message.key);
+        self.latency
+            .time(OP_REGISTRY_SET, detail, self.inner.set(message))
+            .await
+    }
+
+    async fn delete(&self, key: &StreamKey) -> anyhow::Result<()> {
+        self.inner.delete(key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples land in the right buckets, percentiles come from bucket
+    /// bounds, the slowest list is ordered and capped, and everything
+    /// slides out of the window.
+    #[test]
+    fn window_histograms_and_slowest() {
+        let tracker = LatencyTracker::new();
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        for i in 0..100u64 {
+            let ms = if i < 90 { 3 } else { 400 + i };
+            tracker.record_at(OP_BLOB_GET, Duration::from_millis(ms), format!("#{i}"), t0);
+        }
+        tracker.record_at(OP_FS5_SAVE, Duration::from_secs(20), "docs".into(), t0);
+
+        let snap = tracker.snapshot_at(t0 + Duration::from_secs(120));
+        let get = snap.ops.iter().find(|o| o.op == OP_BLOB_GET).unwrap();
+        assert_eq!(get.count, 100);
+        assert_eq!(get.buckets[bucket_for(3)], 90);
+        assert_eq!(
+            (get.p50_ms, get.p90_ms, get.p99_ms, get.max_ms),
+            (5, 5, 499, 499)
+        );
+        let save = snap.ops.iter().find(|o| o.op == OP_FS5_SAVE).unwrap();
+        assert_eq!((save.buckets[BUCKETS - 1], save.p99_ms), (1, 20_000));
+
+        assert_eq!(snap.slowest.len(), SLOWEST_KEPT);
+        assert_eq!(snap.slowest[0].detail, "docs");
+        assert_eq!(snap.slowest[1].duration_ms, 499);
+        assert!(
+            snap.slowest
+                .windows(2)
+                .all(|w| w[0].duration_ms >= w[1].duration_ms)
+        );
+
+        assert_eq!(snap.recent.len(), RECENT_KEPT);
+        assert_eq!(snap.recent[0].detail, "#99");
+
+        let later = tracker.snapshot_at(t0 + WINDOW + Duration::from_secs(60));
+        assert!(later.ops.is_empty() && later.slowest.is_empty());
+    }
+}
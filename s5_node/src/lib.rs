@@ -36,24 +36,34 @@ use std::{collections::HashMap, path::Path, sync::Arc};
 use tokio::sync::{RwLock, oneshot};
 use tracing::info;
 
+pub mod access_log;
+#[cfg(feature = "admin_ui")]
+pub mod admin_ui;
 pub mod admission;
 pub mod bootstrap;
 pub mod config;
 pub mod config_vault;
 pub mod device_keyset;
+pub mod dns_publish;
 pub mod enroll;
+pub mod events;
 pub mod export;
+pub mod fetch_url;
 pub mod fuse;
 pub mod health;
 pub mod identity;
 pub mod identity_anchor;
 pub mod identity_secrets_vault;
 pub mod identity_vault;
+pub mod jobs;
+pub mod latency;
 pub mod membership;
 pub mod membership_subscribe;
 pub mod mnemonic;
+pub mod notifications;
 pub mod pair;
 pub mod peer_observer;
+pub mod remote_registry;
 pub mod s5_server;
 pub mod share;
 pub mod snapshot;
@@ -383,11 +393,17 @@ pub struct CreatedStore {
     /// `BlobStore` server map. `None` for a content-addressed backend (the Sia
     /// `PackingStore`) that is not a `Store`.
     pub store: Option<Arc<dyn s5_core::store::Store>>,
+    /// Blob layout of `store` as recorded in its manifest, for building
+    /// further `BlobStore` views over it. `Auto` without a path store.
+    pub layout: s5_core::blob::BlobLayoutSpec,
     /// The vault-facing content-addressed handle (read + write + delete by
     /// hash). Path backends ride in here as their `BlobStore`; indexd plugs in
     /// its `PackingStore` directly. Always present.
     pub blobs: Arc<dyn Blobs>,
     pub registry: Option<Arc<dyn RegistryApi + Send + Sync>>,
+    /// Access-time tracker shared by every `BlobStore` view of `store`
+    /// (`[store.*] access_tracking`). `None` when disabled or untracked.
+    pub access: Option<Arc<dyn s5_core::blob::AccessTracker>>,
 }
 
 pub async fn create_raw_store(
@@ -397,6 +413,12 @@ pub async fn create_raw_store(
     // Bound before the match consumes `config.backend`.
     let read_cache_bytes = config.read_cache_bytes;
     let outboard = config.outboard;
+    let layout = config.layout;
+    let access = if config.access_tracking {
+        open_access_log(&config.backend)?
+    } else {
+        None
+    };
     // Set by backends that natively back a durable registry (indexd).
     let mut registry: Option<Arc<dyn RegistryApi + Send + Sync>> = None;
     let store: Arc<dyn s5_core::store::Store> = match config.backend {
@@ -579,8 +601,10 @@ pub async fn create_raw_store(
             // has its own staging + in-memory index.
             return Ok(CreatedStore {
                 store: None,
+                layout: s5_core::blob::BlobLayoutSpec::Auto,
                 blobs: packing,
                 registry,
+                access: None,
             });
         }
     };
@@ -594,21 +618,51 @@ pub async fn create_raw_store(
                 read_cache_bytes = n,
                 "store: in-RAM read-through cache enabled"
             );
-            let cache: Arc<dyn s5_core::store::Store> = Arc::new(MemoryStore::with_budget(n));
+            let cache: Arc<dyn s5_core::store::Store> = Arc::new(
+                MemoryStore::builder(n)
+                    .on_evict(|path, len| tracing::trace!(path, len, "store: read cache evicted"))
+                    .build(),
+            );
             Arc::new(s5_core::CachingStore::new(cache, store)) as Arc<dyn s5_core::store::Store>
         }
         _ => store,
     };
-    // A path backend's vault handle is its `BlobStore` (per-store `outboard`).
-    let blobs: Arc<dyn Blobs> =
-        Arc::new(BlobStore::from_arc_with_outboard(store.clone(), outboard));
+    // A path backend's vault handle is its `BlobStore` (per-store `outboard`,
+    // layout per the store manifest).
+    let mut blob_store = BlobStore::open(store.clone(), layout, outboard).await?;
+    if let Some(access) = &access {
+        blob_store = blob_store.with_access_tracker(access.clone());
+    }
+    let layout = blob_store.layout().spec();
+    let blobs: Arc<dyn Blobs> = Arc::new(blob_store);
     Ok(CreatedStore {
         store: Some(store),
+        layout,
         blobs,
         registry,
+        access,
     })
 }
 
+/// The access log for a store with `access_tracking` on: a redb file beside
+/// local and fjall data, RAM for memory stores, nothing for remote backends.
+fn open_access_log(
+    backend: &NodeConfigStoreBackend,
+) -> StoreResult<Option<Arc<dyn s5_core::blob::AccessTracker>>> {
+    let log = match backend {
+        NodeConfigStoreBackend::Local(cfg) => {
+            std::fs::create_dir_all(&cfg.base_path)?;
+            access_log::RedbAccessLog::open(&Path::new(&cfg.base_path).join("access.redb"))?
+        }
+        NodeConfigStoreBackend::Fjall(cfg) => {
+            access_log::RedbAccessLog::open(Path::new(&format!("{}.access.redb", cfg.path)))?
+        }
+        NodeConfigStoreBackend::Memory => access_log::RedbAccessLog::in_memory()?,
+        _ => return Ok(None),
+    };
+    Ok(Some(Arc::new(log)))
+}
+
 /// Decode a hex-encoded 32-byte indexd AppKey from config into raw bytes.
 fn decode_app_key(hex_key: &str) -> StoreResult<[u8; 32]> {
     let bytes = hex::decode(hex_key.trim())
@@ -627,7 +681,14 @@ pub async fn create_store(config: NodeConfigStore) -> StoreResult<BlobStore> {
     let outboard = config.outboard;
     let created = create_raw_store(config, &HashMap::new()).await?;
     match created.store {
-        Some(store) => Ok(BlobStore::from_arc_with_outboard(store, outboard)),
+        Some(store) => {
+            let blob_store =
+                BlobStore::from_arc_with_outboard(store, outboard).with_layout(created.layout);
+            Ok(match created.access {
+                Some(access) => blob_store.with_access_tracker(access),
+                None => blob_store,
+            })
+        }
         None => Err(anyhow::anyhow!(
             "this store backend is content-addressed (no BlobStore view); \
              use create_raw_store and its `blobs` (dyn Blobs) handle instead"
@@ -643,21 +704,36 @@ pub struct RegistryContext<'a> {
     /// `StoreRegistry` — named-object writes are genuine path semantics,
     /// so the raw-store view is requested explicitly here.
     pub stores: &'a NodeStores,
+    /// The daemon's endpoint, for `type = "remote"` registries. `None`
+    /// where no endpoint exists yet; remote registries then fail to build.
+    pub endpoint: Option<&'a Endpoint>,
+    /// Where supervised remote registries register for `GetHealth`.
+    pub remote_links: &'a remote_registry::RemoteLinks,
+    /// Records every `get`/`set` on the built registry for `GetLatency`.
+    pub latency: &'a latency::LatencyTracker,
 }
 
 /// Creates a registry from configuration, wrapped in a
 /// [`BroadcastingRegistry`] so live subscribers see every write —
 /// whether the write came over the RPC server or directly from a
-/// local writer like the publish task.
+/// local writer like the publish task. Calls are timed into
+/// `ctx.latency`.
+///
+/// `name` is the `[registry.<name>]` key, used to label remote links in
+/// health output.
 pub fn create_registry(
+    name: &str,
     backend: NodeConfigRegistry,
     ctx: &RegistryContext<'_>,
 ) -> anyhow::Result<Arc<BroadcastingRegistry>> {
-    let inner = create_registry_inner(backend, ctx)?;
-    Ok(BroadcastingRegistry::wrap(inner))
+    let inner = create_registry_inner(&format!("registry.{name}"), backend, ctx)?;
+    Ok(BroadcastingRegistry::wrap(Arc::new(
+        latency::TimedRegistry::new(inner, ctx.latency.clone()),
+    )))
 }
 
 fn create_registry_inner(
+    label: &str,
     backend: NodeConfigRegistry,
     ctx: &RegistryContext<'_>,
 ) -> anyhow::Result<Arc<dyn RegistryApi + Send + Sync>> {
@@ -671,7 +747,10 @@ fn create_registry_inner(
         NodeConfigRegistry::StoreLocal { path, prefix } => {
             let registry_root = PathBuf::from(&path);
             std::fs::create_dir_all(&registry_root)?;
-            let store = LocalStore::create(s5_store_local::LocalStoreConfig { base_path: path });
+            let store = LocalStore::create(s5_store_local::LocalStoreConfig {
+                base_path: path,
+                ..Default::default()
+            });
             let store_registry = StoreRegistry::new(Arc::new(store), prefix);
             Ok(Arc::new(store_registry))
         }
@@ -695,6 +774,31 @@ fn create_registry_inner(
             let store_registry = StoreRegistry::new(raw_store, prefix);
             Ok(Arc::new(store_registry))
         }
+        NodeConfigRegistry::Remote {
+            peer,
+            keepalive_secs,
+            max_backoff_secs,
+        } => {
+            let endpoint = ctx
+                .endpoint
+                .ok_or_else(|| anyhow!("remote registry '{label}' needs the node's endpoint"))?;
+            let peer: iroh::EndpointId = peer
+                .parse()
+                .map_err(|e| anyhow!("invalid peer for remote registry '{label}': {e}"))?;
+            let registry = remote_registry::SupervisedRemoteRegistry::spawn(
+                label.to_string(),
+                endpoint.clone(),
+                peer,
+                keepalive_secs
+                    .map(std::time::Duration::from_secs)
+                    .unwrap_or(remote_registry::DEFAULT_KEEPALIVE),
+                max_backoff_secs
+                    .map(std::time::Duration::from_secs)
+                    .unwrap_or(remote_registry::DEFAULT_MAX_BACKOFF),
+                ctx.remote_links,
+            );
+            Ok(registry)
+        }
         NodeConfigRegistry::Multi {
             backends,
             write_policy,
@@ -716,8 +820,8 @@ fn create_registry_inner(
 
             // Create all backends recursively
             let mut registry_backends: Vec<Arc<dyn RegistryApi + Send + Sync>> = Vec::new();
-            for backend_config in backends {
-                let backend = create_registry_inner(backend_config, ctx)?;
+            for (i, backend_config) in backends.into_iter().enumerate() {
+                let backend = create_registry_inner(&format!("{label}[{i}]"), backend_config, ctx)?;
                 registry_backends.push(backend);
             }
 
@@ -925,14 +1029,35 @@ pub async fn run_node_with_local_client(
     // the task executor, membership/identity plumbing, and the embedded
     // substrate operate on. Every backend is present, so a Sia
     // `PackingStore` is reached the same way as a path store.
-    let vault_blobs: HashMap<String, Arc<dyn Blobs>> = node_stores.blobs_map();
+    // Each is timed into the daemon's latency tracker (`GetLatency`).
+    let latency = latency::LatencyTracker::new();
+    // Structured event log; `[notify]` targets are fed from it below.
+    let events = events::EventLog::new();
+    let vault_blobs: HashMap<String, Arc<dyn Blobs>> = node_stores
+        .blobs_map()
+        .into_iter()
+        .map(|(name, blobs)| {
+            let timed: Arc<dyn Blobs> = Arc::new(
+                latency::TimedBlobs::new(blobs, latency.clone()).with_events(events.clone()),
+            );
+            (name, timed)
+        })
+        .collect();
 
     // Create the default registry (if configured)
+    let remote_links = remote_registry::RemoteLinks::default();
     let registry_ctx = RegistryContext {
         stores: &node_stores,
+        endpoint: Some(&endpoint),
+        remote_links: &remote_links,
+        latency: &latency,
     };
     let registry = match config.registry.get("default") {
-        Some(reg_config) => Some(create_registry(reg_config.clone(), &registry_ctx)?),
+        Some(reg_config) => Some(create_registry(
+            "default",
+            reg_config.clone(),
+            &registry_ctx,
+        )?),
         None => {
             tracing::warn!("no [registry.default] configured — snapshot publishing disabled");
             None
@@ -965,13 +1090,40 @@ pub async fn run_node_with_local_client(
         membership: Some(membership_state.clone()),
         membership_refresh: Some(membership_refresh.clone()),
         discovery_seed: discovery_seed.clone(),
+        latency: latency.clone(),
+        events: events.clone(),
     });
+    notifications::spawn_notifier(&events, config.clone());
+    events::spawn_disk_monitor(events.clone(), config.clone());
     let executor = Arc::new(tasks::TaskExecutor::new(executor_ctx));
     // The daemon's automation engine — reconciles `[task.*]` automations (and
     // the legacy `watch`/`snap_interval_secs` shim) into live loops. Shared
     // with the RPC server (for `GetStatus` liveness) and driven by the
     // coordinator task below.
     let automation_manager = Arc::new(crate::watch::AutomationManager::new(executor.clone()));
+    // Cron jobs (`trigger = "cron"`), with run state in `[jobs].state_file`.
+    // A state file that can't be opened (e.g. held by another daemon on the
+    // same config dir) disables jobs rather than the node.
+    let job_scheduler = {
+        let cfg = config.read().await;
+        let state_path = config_dir
+            .unwrap_or(Path::new("."))
+            .join(cfg.jobs.state_file.as_deref().unwrap_or("jobs.redb"));
+        let max_concurrent = cfg
+            .jobs
+            .max_concurrent
+            .unwrap_or(jobs::DEFAULT_MAX_CONCURRENT);
+        match jobs::JobScheduler::open(executor.clone(), &state_path, max_concurrent) {
+            Ok(s) => Some(Arc::new(s)),
+            Err(e) => {
+                tracing::warn!(
+                    path = %state_path.display(),
+                    "job scheduler disabled: {e:#}"
+                );
+                None
+            }
+        }
+    };
     let mount_manager = Arc::new(fuse::MountManager::new(executor.clone()));
 
     // ---- Per-vault cold-store GC ----
@@ -1037,6 +1189,7 @@ pub async fn run_node_with_local_client(
                 min_age: std::time::Duration::from_secs(vault.gc_min_age_secs.unwrap_or(604_800)),
                 dry_run: vault.gc_dry_run,
                 reporter: gc_reporter.clone(),
+                events: Some(events.clone()),
             });
         }
     } else if config.read().await.vault.values().any(|v| v.gc_enabled) {
@@ -1309,6 +1462,7 @@ pub async fn run_node_with_local_client(
         .with_membership_refresh(membership_refresh.clone())
         .with_automation_refresh(automation_refresh.clone())
         .with_automation_manager(automation_manager.clone())
+        .with_job_scheduler(job_scheduler.clone())
         .with_pair_support(
             pending_pairs,
             endpoint.clone(),
@@ -1317,7 +1471,9 @@ pub async fn run_node_with_local_client(
             anchor_entry.clone(),
         )
         .with_enroll_support(enroll_listener.is_some().then(|| pending_enrolls.clone()))
-        .with_peer_observer(peer_observer.clone());
+        .with_peer_observer(peer_observer.clone())
+        .with_remote_links(remote_links.clone())
+        .with_latency(latency.clone());
 
     // If the caller asked for the in-process irpc back-channel, build
     // it now and send. The local sender is created from a fresh Arc<Self>
@@ -1399,6 +1555,12 @@ pub async fn run_node_with_local_client(
         tracing::warn!("failed to write service lock file: {e}");
     }
 
+    // Serve the embedded admin UI when `[admin_ui] listen` is set.
+    #[cfg(feature = "admin_ui")]
+    if let Err(e) = spawn_admin_ui(&node, config.clone()).await {
+        tracing::warn!("admin UI not started: {e:#}");
+    }
+
     // Spawn configured FUSE mounts (best-effort)
     if let Err(err) = crate::fuse::spawn_fuse_mounts(&node).await {
         tracing::warn!("failed to spawn FUSE mounts: {err}");
@@ -1553,10 +1715,14 @@ pub async fn run_node_with_local_client(
     {
         let cfg = config.read().await.clone();
         automation_manager.reconcile(&cfg).await;
+        if let Some(jobs) = job_scheduler.as_ref() {
+            jobs.reconcile(&cfg).await;
+        }
     }
     let automation_cancel = tokio_util::sync::CancellationToken::new();
     let automation_handle = {
         let manager = automation_manager.clone();
+        let jobs = job_scheduler.clone();
         let config = config.clone();
         let refresh = automation_refresh.clone();
         let cancel = automation_cancel.clone();
@@ -1569,6 +1735,9 @@ pub async fn run_node_with_local_client(
                         // guard across reconcile's join awaits (see above).
                         let cfg = config.read().await.clone();
                         manager.reconcile(&cfg).await;
+                        if let Some(jobs) = jobs.as_ref() {
+                            jobs.reconcile(&cfg).await;
+                        }
                     }
                 }
             }
@@ -1641,6 +1810,9 @@ pub async fn run_node_with_local_client(
     automation_cancel.cancel();
     automation_handle.abort();
     automation_manager.shutdown().await;
+    if let Some(jobs) = job_scheduler.as_ref() {
+        jobs.shutdown().await;
+    }
     // Best-effort drain: give staged packs one bounded chance to reach
     // durability (drill fix, layer 4). NOT a correctness requirement —
     // every published HEAD is already behind a blob_sync barrier, and the
@@ -1729,6 +1901,32 @@ fn device_keyset_for_boot(
     }
 }
 
+/// Bind `[admin_ui] listen` and serve the web UI on it in the background.
+/// A no-op when the UI isn't configured or the node has no control plane.
+#[cfg(feature = "admin_ui")]
+async fn spawn_admin_ui(node: &S5Node, config: Arc<RwLock<S5NodeConfig>>) -> anyhow::Result<()> {
+    let Some(listen) = config.read().await.admin_ui.listen.clone() else {
+        return Ok(());
+    };
+    let (Some(server), Some(control)) = (node.s5_server.as_ref(), node.control.as_ref()) else {
+        return Ok(());
+    };
+    let listener = tokio::net::TcpListener::bind(&listen)
+        .await
+        .with_context(|| format!("binding admin UI listener {listen}"))?;
+    let addr = listener.local_addr()?;
+    if !addr.ip().is_loopback() {
+        tracing::warn!(%addr, "admin UI listens beyond loopback over plain HTTP");
+    }
+    let ui = admin_ui::AdminUi::new(server, control.token, config);
+    tokio::spawn(ui.serve(listener));
+    info!(
+        "admin UI: http://{addr}/#token={}",
+        hex::encode(control.token)
+    );
+    Ok(())
+}
+
 /// Generate an ephemeral random ed25519 master signing key. Used at
 /// boot when no `master_key_file` path is resolvable — the daemon runs
 /// with a non-persistent DID until the operator configures persistence.
@@ -0,0 +1,405 @@
+//! Operator notifications for node events.
+//!
+//! [`spawn_notifier`] subscribes to the daemon's [`EventLog`] and delivers
+//! each event to every `[notify.target.<name>]` whose `events` list
+//! accepts it — a JSON POST for `type = "webhook"`, a plain-text mail for
+//! `type = "smtp"`. Delivery failures are logged and dropped; a flaky
+//! notification channel must never stall the node.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow, bail};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufStream};
+use tokio::net::TcpStream;
+use tokio::sync::{RwLock, broadcast};
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::{self, RootCertStore, pki_types::ServerName};
+
+use crate::config::{
+    NodeConfigNotify, NodeConfigNotifyTarget, NodeConfigNotifyTransport, NodeConfigSmtpTls,
+    S5NodeConfig,
+};
+use crate::events::{EventLog, EventRecord, NodeEvent};
+
+/// Upper bound on one delivery (connect + send + reply).
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Spawn the dispatcher. `[notify]` is re-read for every event so targets
+/// follow config reloads.
+pub fn spawn_notifier(
+    events: &EventLog,
+    config: Arc<RwLock<S5NodeConfig>>,
+) -> tokio::task::JoinHandle<()> {
+    let mut rx = events.subscribe();
+    tokio::spawn(async move {
+        let http = reqwest::Client::new();
+        loop {
+            let record = match rx.recv().await {
+                Ok(r) => r,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("notifier fell behind; {n} events not delivered");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            let notify = config.read().await.notify.clone();
+            for (name, target) in routes(&notify, &record.event) {
+                let result =
+                    tokio::time::timeout(DELIVERY_TIMEOUT, deliver(&http, target, &record)).await;
+                match result {
+                    Ok(Ok(())) => tracing::debug!(target = %name, "notification sent"),
+                    Ok(Err(e)) => tracing::warn!(target = %name, "notification failed: {e:#}"),
+                    Err(_) => tracing::warn!(target = %name, "notification timed out"),
+                }
+            }
+        }
+    })
+}
+
+/// The targets `event` goes to. `task_failed` is only routed when the
+/// failure streak reaches `task_failure_threshold` exactly, so a task stuck
+/// failing sends one notification, not one per run.
+fn routes<'a>(
+    notify: &'a NodeConfigNotify,
+    event: &NodeEvent,
+) -> Vec<(&'a String, &'a NodeConfigNotifyTarget)> {
+    if let NodeEvent::TaskFailed { consecutive, .. } = event
+        && *consecutive != notify.task_failure_threshold.max(1)
+    {
+        return Vec::new();
+    }
+    let kind = event.kind();
+    notify
+        .target
+        .iter()
+        .filter(|(_, t)| t.events.is_empty() || t.events.contains(&kind))
+        .collect()
+}
+
+async fn deliver(
+    http: &reqwest::Client,
+    target: &NodeConfigNotifyTarget,
+    record: &EventRecord,
+) -> anyhow::Result<()> {
+    match &target.transport {
+        NodeConfigNotifyTransport::Webhook { url, headers } => {
+            let mut body = serde_json::to_value(record)?;
+            body["summary"] = record.event.summary().into();
+            let mut req = http.post(url).json(&body);
+            for (k, v) in headers {
+                req = req.header(k, v);
+            }
+            req.send().await?.error_for_status()?;
+            Ok(())
+        }
+        NodeConfigNotifyTransport::Smtp {
+            server,
+            tls,
+            username,
+            password,
+            from,
+            to,
+        } => {
+            let message = mail_message(from, to, record)?;
+            let auth = username.as_deref().zip(password.as_deref());
+            send_mail(server, *tls, auth, from, to, &message).await
+        }
+    }
+}
+
+fn mail_message(from: &str, to: &[String], record: &EventRecord) -> anyhow::Result<String> {
+    let date = time::OffsetDateTime::from_unix_timestamp(record.at_unix_secs as i64)?
+        .format(&time::format_description::well_known::Rfc2822)?;
+    let details = serde_json::to_string_pretty(record)?;
+    Ok(format!(
+        "From: {from}\r\nTo: {}\r\nDate: {date}\r\nSubject: [s5] {}\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n\r\n{}\r\n",
+        to.join(", "),
+        record.event.summary(),
+        record.event.summary(),
+        details.replace('\n', "\r\n"),
+    ))
+}
+
+trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+/// Minimal SMTP submission: EHLO, optional STARTTLS, AUTH PLAIN, one
+/// message.
+async fn send_mail(
+    server: &str,
+    tls: NodeConfigSmtpTls,
+    auth: Option<(&str, &str)>,
+    from: &str,
+    to: &[String],
+    message: &str,
+) -> anyhow::Result<()> {
+    if to.is_empty() {
+        bail!("no recipients");
+    }
+    let (host, _) = server
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow!("smtp server '{server}' must be host:port"))?;
+    let tcp = TcpStream::connect(server)
+        .await
+        .with_context(|| format!("connecting to {server}"))?;
+    let io: Box<dyn Io> = match tls {
+        NodeConfigSmtpTls::Implicit => Box::new(tls_connect(host, tcp).await?),
+        _ => Box::new(tcp),
+    };
+    let mut conn = BufStream::new(io);
+    reply(&mut conn, 220).await?;
+    command(&mut conn, "EHLO s5-node", 250).await?;
+    if tls == NodeConfigSmtpTls::Starttls {
+        command(&mut conn, "STARTTLS", 220).await?;
+        let tcp = conn.into_inner();
+        conn = BufStream::new(Box::new(tls_connect(host, tcp).await?));
+        command(&mut conn, "EHLO s5-node", 250).await?;
+    }
+    if let Some((user, pass)) = auth {
+        use base64::Engine;
+        let token = base64::engine::general_purpose::STANDARD.encode(format!("\0{user}\0{pass}"));
+        command(&mut conn, &format!("AUTH PLAIN {token}"), 235).await?;
+    }
+    command(&mut conn, &format!("MAIL FROM:<{from}>"), 250).await?;
+    for rcpt in to {
+        command(&mut conn, &format!("RCPT TO:<{rcpt}>"), 250).await?;
+    }
+    command(&mut conn, "DATA", 354).await?;
+    for line in message.split("\r\n") {
+        // Dot-stuffing (RFC 5321 §4.5.2).
+        if line.starts_with('.') {
+            conn.write_all(b".").await?;
+        }
+        conn.write_all(line.as_bytes()).await?;
+        conn.write_all(b"\r\n").await?;
+    }
+    command(&mut conn, ".", 250).await?;
+    command(&mut conn, "QUIT", 221).await?;
+    Ok(())
+}
+
+async fn tls_connect<S: Io + 'static>(
+    host: &str,
+    stream: S,
+) -> anyhow::Result<tokio_rustls::client::TlsStream<S>> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    let name = ServerName::try_from(host.to_string())?;
+    Ok(TlsConnector::from(Arc::new(config))
+        .connect(name, stream)
+        .await?)
+}
+
+async fn command(conn: &mut BufStream<Box<dyn Io>>, line: &str, expect: u16) -> anyhow::Result<()> {
+    conn.write_all(line.as_bytes()).await?;
+    conn.write_all(b"\r\n").await?;
+    conn.flush().await?;
+    reply(conn, expect).await
+}
+
+/// Read one (possibly multi-line) reply and check its code.
+async fn reply(conn: &mut BufStream<Box<dyn Io>>, expect: u16) -> anyhow::Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if conn.read_line(&mut line).await? == 0 {
+            bail!("smtp server closed the connection");
+        }
+        // "250-..." continues, "250 ..." ends the reply.
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+    let code: u16 = line
+        .get(..3)
+        .and_then(|c| c.parse().ok())
+        .ok_or_else(|| anyhow!("malformed smtp reply: {}", line.trim_end()))?;
+    if code != expect {
+        bail!("smtp: expected {expect}, got {}", line.trim_end());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NodeEventKind;
+    use std::collections::BTreeMap;
+    use tokio::io::{AsyncReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn target(
+        events: Vec<NodeEventKind>,
+        transport: NodeConfigNotifyTransport,
+    ) -> NodeConfigNotifyTarget {
+        NodeConfigNotifyTarget { events, transport }
+    }
+
+    fn record(event: NodeEvent) -> EventRecord {
+        EventRecord {
+            at_unix_secs: 1_700_000_000,
+            event,
+        }
+    }
+
+    #[test]
+    fn routes_by_kind_and_failure_threshold() {
+        let hook = |url: &str| NodeConfigNotifyTransport::Webhook {
+            url: url.into(),
+            headers: BTreeMap::new(),
+        };
+        let mut notify = NodeConfigNotify::default();
+        notify
+            .target
+            .insert("all".into(), target(vec![], hook("http://a")));
+        notify.target.insert(
+            "disk".into(),
+            target(vec![NodeEventKind::DiskLow], hook("http://b")),
+        );
+
+        let gc = NodeEvent::GcCompleted {
+            vault: "docs".into(),
+            deleted: 1,
+            bytes_reclaimed: 10,
+        };
+        let names: Vec<_> = routes(&notify, &gc)
+            .into_iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(names, ["all"]);
+
+        let failed = |consecutive| NodeEvent::TaskFailed {
+            task: "backup docs".into(),
+            consecutive,
+            error: "offline".into(),
+        };
+        assert!(routes(&notify, &failed(2)).is_empty());
+        assert_eq!(routes(&notify, &failed(3)).len(), 1);
+        assert!(routes(&notify, &failed(4)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_event_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            // Read until the JSON body closes.
+            while !buf.ends_with(b"}") {
+                let n = sock.read(&mut chunk).await.unwrap();
+                assert!(n > 0);
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            sock.write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(buf).unwrap()
+        });
+
+        let t = target(
+            vec![],
+            NodeConfigNotifyTransport::Webhook {
+                url,
+                headers: BTreeMap::from([("x-token".into(), "secret".into())]),
+            },
+        );
+        let rec = record(NodeEvent::Corruption {
+            hash: "abcd".into(),
+            error: "blob integrity check failed".into(),
+        });
+        deliver(&reqwest::Client::new(), &t, &rec).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook"));
+        assert!(request.contains("x-token: secret"));
+        let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["event"], "corruption");
+        assert_eq!(json["hash"], "abcd");
+        assert_eq!(json["summary"], "blob abcd failed its integrity check");
+    }
+
+    #[tokio::test]
+    async fn smtp_sends_message() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (sock, _) = listener.accept().await.unwrap();
+            let mut sock = BufReader::new(sock);
+            let mut transcript = Vec::new();
+            sock.write_all(b"220 test ESMTP\r\n").await.unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if sock.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        transcript.push(line);
+                        continue;
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250-test\r\n250 AUTH PLAIN\r\n"
+                } else if line.starts_with("AUTH") {
+                    b"235 ok\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go\r\n"
+                } else if line == "QUIT" {
+                    sock.write_all(b"221 bye\r\n").await.unwrap();
+                    transcript.push(line);
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                transcript.push(line);
+                sock.write_all(reply).await.unwrap();
+            }
+            transcript
+        });
+
+        let t = target(
+            vec![NodeEventKind::GcCompleted],
+            NodeConfigNotifyTransport::Smtp {
+                server: server_addr,
+                tls: NodeConfigSmtpTls::None,
+                username: Some("ops".into()),
+                password: Some("pw".into()),
+                from: "node@example.org".into(),
+                to: vec!["ops@example.org".into()],
+            },
+        );
+        let rec = record(NodeEvent::GcCompleted {
+            vault: "docs".into(),
+            deleted: 2,
+            bytes_reclaimed: 4096,
+        });
+        deliver(&reqwest::Client::new(), &t, &rec).await.unwrap();
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains(&"MAIL FROM:<node@example.org>".to_string()));
+        assert!(transcript.contains(&"RCPT TO:<ops@example.org>".to_string()));
+        assert!(
+            transcript.contains(
+                &"Subject: [s5] GC of vault 'docs' freed 4096 bytes (2 blobs)".to_string()
+            )
+        );
+        assert_eq!(transcript.last().unwrap(), "QUIT");
+    }
+}
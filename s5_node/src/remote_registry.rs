@@ -0,0 +1,287 @@
+//! Supervised connections to remote registries (`type = "remote"`).
+//!
+//! A bare [`s5_registry::RemoteRegistry`] only dials when a call needs a
+//! stream, and keeps what it negotiated on the first connection forever.
+//! After the serving peer restarts, the first calls stall on the dead QUIC
+//! connection until it times out, and nothing notices until then.
+//! [`SupervisedRemoteRegistry`] adds a keepalive loop: it pings the peer
+//! every `keepalive`, and after a failed ping (or a failed call, which
+//! triggers an immediate ping) it replaces the client with a freshly
+//! dialed one, retrying with exponential backoff until the peer answers.
+//!
+//! Every link registers itself in the daemon's [`RemoteLinks`], which
+//! `GetHealth` snapshots for `vup doctor`.
+
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use iroh::{Endpoint, EndpointAddr, EndpointId};
+use s5_core::{RegistryApi, StreamKey, StreamMessage};
+use s5_node_api::{RemoteHealth, RemoteLinkState};
+use s5_registry::RemoteRegistry;
+use tokio::sync::Notify;
+
+/// Ping interval when the config sets no `keepalive_secs`.
+pub const DEFAULT_KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Backoff cap when the config sets no `max_backoff_secs`.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// First re-dial delay after the link degrades; doubled per failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// A ping that takes longer than this counts as failed.
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The daemon's supervised remote links, for `GetHealth`. Clones share
+/// the list.
+#[derive(Clone, Default)]
+pub struct RemoteLinks {
+    links: Arc<Mutex<Vec<Weak<SupervisedRemoteRegistry>>>>,
+}
+
+impl std::fmt::Debug for RemoteLinks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteLinks").finish_non_exhaustive()
+    }
+}
+
+impl RemoteLinks {
+    fn register(&self, link: &Arc<SupervisedRemoteRegistry>) {
+        let mut links = self.links.lock().unwrap_or_else(|e| e.into_inner());
+        links.retain(|l| l.strong_count() > 0);
+        links.push(Arc::downgrade(link));
+    }
+
+    /// Current state of every live link, in registration order.
+    pub fn health(&self) -> Vec<RemoteHealth> {
+        let links = self.links.lock().unwrap_or_else(|e| e.into_inner());
+        links
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|link| link.health())
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct LinkState {
+    state: RemoteLinkState,
+    last_rtt: Option<Duration>,
+    last_ok: Option<Instant>,
+    consecutive_failures: u32,
+    reconnects: u64,
+    last_error: Option<String>,
+}
+
+/// A [`RemoteRegistry`] kept connected by a background keepalive task.
+///
+/// The task holds only a weak reference and ends at its next wakeup once
+/// the registry is dropped.
+pub struct SupervisedRemoteRegistry {
+    name: String,
+    endpoint: Endpoint,
+    addr: EndpointAddr,
+    peer: EndpointId,
+    registry: RwLock<RemoteRegistry>,
+    link: Mutex<LinkState>,
+    /// Wakes the keepalive task early, after a failed call.
+    wake: Arc<Notify>,
+}
+
+impl std::fmt::Debug for SupervisedRemoteRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SupervisedRemoteRegistry")
+            .field("name", &self.name)
+            .field("peer", &self.peer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SupervisedRemoteRegistry {
+    /// Connects to the registry served at `addr` and starts supervising
+    /// the link. `name` labels it in health output (e.g.
+    /// `registry.default`). Must be called within a Tokio runtime.
+    pub fn spawn(
+        name: String,
+        endpoint: Endpoint,
+        addr: impl Into<EndpointAddr>,
+        keepalive: Duration,
+        max_backoff: Duration,
+        links: &RemoteLinks,
+    ) -> Arc<Self> {
+        let addr = addr.into();
+        let this = Arc::new(Self {
+            name,
+            registry: RwLock::new(RemoteRegistry::connect(endpoint.clone(), addr.clone())),
+            endpoint,
+            peer: addr.id,
+            addr,
+            link: Mutex::new(LinkState {
+                state: RemoteLinkState::Connecting,
+                last_rtt: None,
+                last_ok: None,
+                consecutive_failures: 0,
+                reconnects: 0,
+                last_error: None,
+            }),
+            wake: Arc::new(Notify::new()),
+        });
+        links.register(&this);
+        tokio::spawn(supervise(
+            Arc::downgrade(&this),
+            this.wake.clone(),
+            keepalive,
+            max_backoff.max(INITIAL_BACKOFF),
+        ));
+        this
+    }
+
+    pub fn health(&self) -> RemoteHealth {
+        let link = self.lock_link();
+        RemoteHealth {
+            name: self.name.clone(),
+            peer: self.peer.to_string(),
+            state: link.state,
+            last_rtt_ms: link.last_rtt.map(|rtt| rtt.as_millis() as u64),
+            since_last_ok_secs: link.last_ok.map(|at| at.elapsed().as_secs()),
+            consecutive_failures: link.consecutive_failures,
+            reconnects: link.reconnects,
+            last_error: link.last_error.clone(),
+        }
+    }
+
+    fn current(&self) -> RemoteRegistry {
+        self.registry
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn lock_link(&self) -> std::sync::MutexGuard<'_, LinkState> {
+        self.link.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replaces the client with a fresh one, so the next call dials a new
+    /// connection and renegotiates batching with the (maybe upgraded) peer.
+    fn redial(&self) {
+        *self.registry.write().unwrap_or_else(|e| e.into_inner()) =
+            RemoteRegistry::connect(self.endpoint.clone(), self.addr.clone());
+        self.lock_link().reconnects += 1;
+        tracing::debug!(name = %self.name, peer = %self.peer.fmt_short(), "re-dialing remote registry");
+    }
+
+    fn record_ok(&self, rtt: Duration) {
+        let mut link = self.lock_link();
+        if link.state == RemoteLinkState::Degraded {
+            tracing::info!(
+                name = %self.name,
+                peer = %self.peer.fmt_short(),
+                after_failures = link.consecutive_failures,
+                "remote registry reachable again"
+            );
+        }
+        link.state = RemoteLinkState::Healthy;
+        link.last_ok = Some(Instant::now());
+        link.consecutive_failures = 0;
+        link.last_error = None;
+        link.last_rtt = Some(rtt);
+    }
+
+    fn record_failure(&self, error: String) {
+        let mut link = self.lock_link();
+        if link.state != RemoteLinkState::Degraded {
+            tracing::warn!(
+                name = %self.name,
+                peer = %self.peer.fmt_short(),
+                "remote registry unreachable, re-dialing: {error}"
+            );
+        }
+        link.state = RemoteLinkState::Degraded;
+        link.consecutive_failures = link.consecutive_failures.saturating_add(1);
+        link.last_error = Some(error);
+    }
+
+    /// Only the keepalive ping decides whether the link is degraded: a
+    /// failed call may just be the peer refusing it (ACL), so it asks for
+    /// an immediate ping instead.
+    fn observe<T>(&self, result: Result<T>) -> Result<T> {
+        match &result {
+            Ok(_) => {
+                let mut link = self.lock_link();
+                if link.state == RemoteLinkState::Healthy {
+                    link.last_ok = Some(Instant::now());
+                }
+            }
+            Err(_) => self.wake.notify_one(),
+        }
+        result
+    }
+
+    async fn ping(&self) -> Result<Duration> {
+        let client = self.current();
+        let started = Instant::now();
+        match tokio::time::timeout(PING_TIMEOUT, client.client().ping()).await {
+            Ok(Ok(_)) => Ok(started.elapsed()),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(anyhow!("ping timed out after {PING_TIMEOUT:?}")),
+        }
+    }
+}
+
+async fn supervise(
+    link: Weak<SupervisedRemoteRegistry>,
+    wake: Arc<Notify>,
+    keepalive: Duration,
+    max_backoff: Duration,
+) {
+    // `Some` while degraded: the wait before the next re-dial.
+    let mut backoff: Option<Duration> = None;
+    // Probe right away so health shows the link's state without waiting
+    // a whole keepalive interval.
+    let mut wait = Duration::ZERO;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = wake.notified(), if backoff.is_none() => {}
+        }
+        let Some(link) = link.upgrade() else {
+            return;
+        };
+        if backoff.is_some() {
+            link.redial();
+        }
+        match link.ping().await {
+            Ok(rtt) => {
+                link.record_ok(rtt);
+                backoff = None;
+                wait = keepalive;
+            }
+            Err(e) => {
+                link.record_failure(format!("{e:#}"));
+                let next = backoff.map_or(INITIAL_BACKOFF, |b| (b * 2).min(max_backoff));
+                backoff = Some(next);
+                wait = next;
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RegistryApi for SupervisedRemoteRegistry {
+    async fn get(&self, key: &StreamKey) -> Result<Option<StreamMessage>> {
+        let result = self.current().get(key).await;
+        self.observe(result)
+    }
+
+    async fn set(&self, message: StreamMessage) -> Result<()> {
+        let result = self.current().set(message).await;
+        self.observe(result)
+    }
+
+    async fn delete(&self, key: &StreamKey) -> Result<()> {
+        let result = self.current().delete(key).await;
+        self.observe(result)
+    }
+}
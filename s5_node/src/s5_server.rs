@@ -16,12 +16,13 @@ use tracing::info;
 
 use s5_node_api::{
     AddFriend, CancelTask, DebugPeer, DebugPeerAlpn, DebugPeers, DebugPeersResponse, DeviceEntry,
-    DeviceInvite, DeviceInviteEvent, ExportVault, ExportedShare, GetConfig, GetConfigResponse,
-    GetHealth, GetHealthResponse, GetStatus, GetStatusResponse, GrantVault, JoinExport,
-    ListDevices, ListDevicesResponse, ListSnapshots, ListSnapshotsResponse, ListTasksResponse,
-    ListTree, ListTreeResponse, MountVault, MountedVault, Pair, PairEvent, PatchConfig, RedeemPair,
-    RevokeDevice, RevokeDeviceResponse, RunTask, S5NodeMessage, S5NodeProto, SnapshotInfo,
-    SpawnedTask, TaskState, TaskStatusResponse, UnmountVault, WatchTaskStatus,
+    DeviceInvite, DeviceInviteEvent, ExportVault, ExportedShare, FetchUrl, FetchUrlEvent,
+    GetConfig, GetConfigResponse, GetHealth, GetHealthResponse, GetLatency, GetLatencyResponse,
+    GetStatus, GetStatusResponse, GrantVault, JoinExport, ListDevices, ListDevicesResponse,
+    ListJobsResponse, ListSnapshots, ListSnapshotsResponse, ListTasksResponse, ListTree,
+    ListTreeResponse, MountVault, MountedVault, Pair, PairEvent, PatchConfig, RedeemPair,
+    RevokeDevice, RevokeDeviceResponse, RunJobNow, RunTask, S5NodeMessage, S5NodeProto,
+    SnapshotInfo, SpawnedTask, TaskState, TaskStatusResponse, UnmountVault, WatchTaskStatus,
 };
 
 use crate::config::S5NodeConfig;
@@ -58,6 +59,9 @@ pub struct S5NodeServer {
     /// The daemon's automation engine — read by `GetStatus` for per-automation
     /// liveness. `None` in harnesses without one.
     automation_manager: Option<Arc<crate::watch::AutomationManager>>,
+    /// The daemon's cron job scheduler, behind `ListJobs` / `RunJobNow`.
+    /// `None` in harnesses without one, or if its state file can't open.
+    job_scheduler: Option<Arc<crate::jobs::JobScheduler>>,
     /// The `(vault, source)` of the most recent inline (manual) `Backup` this
     /// server dispatched — surfaced by `GetStatus.last_backup` so the
     /// `automate` wizard can offer "keep doing that?".
@@ -90,6 +94,12 @@ pub struct S5NodeServer {
     /// handshake for in-band self-certification (D17). `None` until
     /// `with_pair_support` wires them.
     pair_identity: Option<([u8; 32], s5_core::StreamMessage)>,
+    /// Supervised remote-registry links, reported by `GetHealth`. Empty
+    /// until wired in `run_node`.
+    remote_links: crate::remote_registry::RemoteLinks,
+    /// Recent operation latencies, reported by `GetLatency`. Empty until
+    /// wired in `run_node`.
+    latency: crate::latency::LatencyTracker,
 }
 
 impl std::fmt::Debug for S5NodeServer {
@@ -119,6 +129,7 @@ impl S5NodeServer {
             membership_refresh: None,
             automation_refresh: None,
             automation_manager: None,
+            job_scheduler: None,
             last_backup: Arc::new(std::sync::Mutex::new(None)),
             pending_pairs: None,
             pending_enrolls: None,
@@ -126,9 +137,23 @@ impl S5NodeServer {
             peer_observer: None,
             master: None,
             pair_identity: None,
+            remote_links: Default::default(),
+            latency: Default::default(),
         }
     }
 
+    /// Attach the daemon's supervised remote links for `GetHealth`.
+    pub fn with_remote_links(mut self, links: crate::remote_registry::RemoteLinks) -> Self {
+        self.remote_links = links;
+        self
+    }
+
+    /// Attach the daemon's latency tracker for `GetLatency`.
+    pub fn with_latency(mut self, latency: crate::latency::LatencyTracker) -> Self {
+        self.latency = latency;
+        self
+    }
+
     /// Attach the daemon-wide peer observer for `DebugPeers`.
     pub fn with_peer_observer(mut self, observer: crate::peer_observer::PeerObserver) -> Self {
         self.peer_observer = Some(observer);
@@ -162,6 +187,13 @@ impl S5NodeServer {
         self
     }
 
+    /// Attach the daemon's cron job scheduler. Builder-style; called once in
+    /// `run_node`.
+    pub fn with_job_scheduler(mut self, scheduler: Option<Arc<crate::jobs::JobScheduler>>) -> Self {
+        self.job_scheduler = scheduler;
+        self
+    }
+
     /// Attach the pair-flow plumbing: the pending-pair table shared with the
     /// `s5/pair/0` listener, the iroh endpoint used for outbound `RedeemPair`
     /// dials, the WARM master signing key (both pair handlers sign the warm
@@ -245,6 +277,20 @@ impl S5NodeServer {
             .map_err(|e| format!("{e:#}"))
     }
 
+    async fn handle_fetch_url(&self, req: FetchUrl, tx: mpsc::Sender<FetchUrlEvent>) {
+        let url = req.url.clone();
+        let last = match crate::fetch_url::fetch_url(self.executor.ctx(), req, &tx).await {
+            Ok(done) => done,
+            Err(e) => {
+                tracing::warn!(url = %url, "fetch_url failed: {e:#}");
+                FetchUrlEvent::Failed {
+                    error: format!("{e:#}"),
+                }
+            }
+        };
+        let _ = tx.send(last).await;
+    }
+
     async fn handle_join_export(&self, req: JoinExport) -> Result<String, String> {
         // Peek at the label so we can pick a non-colliding vault name and a
         // root dir before doing the (heavier) fetch/decrypt.
@@ -493,7 +539,13 @@ impl S5NodeServer {
     async fn handle_get_health(&self, _req: GetHealth) -> GetHealthResponse {
         let config = self.config.read().await;
         let ctx = self.executor.ctx();
-        crate::health::gather_health(&config, &ctx.stores).await
+        let mut health = crate::health::gather_health(&config, &ctx.stores).await;
+        health.remotes = self.remote_links.health();
+        health
+    }
+
+    fn handle_get_latency(&self, _req: GetLatency) -> GetLatencyResponse {
+        self.latency.snapshot()
     }
 
     async fn handle_list_snapshots(&self, req: ListSnapshots) -> ListSnapshotsResponse {
@@ -1136,6 +1188,25 @@ impl S5NodeServer {
         DebugPeersResponse { peers }
     }
 
+    async fn handle_list_jobs(&self) -> ListJobsResponse {
+        let jobs = match self.job_scheduler.as_ref() {
+            Some(s) => s.list().await,
+            None => Vec::new(),
+        };
+        ListJobsResponse { jobs }
+    }
+
+    async fn handle_run_job_now(&self, req: RunJobNow) -> Result<(), String> {
+        let scheduler = self
+            .job_scheduler
+            .as_ref()
+            .ok_or_else(|| "the job scheduler is not running on this node".to_string())?;
+        scheduler
+            .run_now(&req.name)
+            .await
+            .map_err(|e| format!("{e:#}"))
+    }
+
     async fn handle_shutdown(&self) {
         info!("shutdown requested via S5 RPC");
         let mut guard = self.shutdown_tx.write().await;
@@ -1249,6 +1320,10 @@ impl S5NodeServer {
                 let resp = self.handle_get_health(inner).await;
                 let _ = oneshot::Sender::send(tx, resp).await;
             }
+            S5NodeMessage::GetLatency(irpc::WithChannels { inner, tx, .. }) => {
+                let resp = self.handle_get_latency(inner);
+                let _ = oneshot::Sender::send(tx, resp).await;
+            }
             S5NodeMessage::ListSnapshots(irpc::WithChannels { inner, tx, .. }) => {
                 let resp = self.handle_list_snapshots(inner).await;
                 let _ = oneshot::Sender::send(tx, resp).await;
@@ -1299,10 +1374,21 @@ impl S5NodeServer {
                 let resp = self.handle_join_export(inner).await;
                 let _ = oneshot::Sender::send(tx, resp).await;
             }
+            S5NodeMessage::FetchUrl(irpc::WithChannels { inner, tx, .. }) => {
+                self.handle_fetch_url(inner, tx).await;
+            }
             S5NodeMessage::DebugPeers(irpc::WithChannels { inner, tx, .. }) => {
                 let resp = self.handle_debug_peers(inner).await;
                 let _ = oneshot::Sender::send(tx, resp).await;
             }
+            S5NodeMessage::ListJobs(irpc::WithChannels { inner: _, tx, .. }) => {
+                let resp = self.handle_list_jobs().await;
+                let _ = oneshot::Sender::send(tx, resp).await;
+            }
+            S5NodeMessage::RunJobNow(irpc::WithChannels { inner, tx, .. }) => {
+                let resp = self.handle_run_job_now(inner).await;
+                let _ = oneshot::Sender::send(tx, resp).await;
+            }
             S5NodeMessage::Shutdown(irpc::WithChannels { inner: _, tx, .. }) => {
                 self.handle_shutdown().await;
                 let _ = oneshot::Sender::send(tx, ()).await;
@@ -1413,7 +1499,7 @@ impl<H: ProtocolHandler + Clone> ProtocolHandler for ControlAuthGate<H> {
 
 /// Timing-independent byte comparison for the auth preamble: XOR-fold the
 /// whole buffers instead of short-circuiting on the first mismatch.
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
@@ -24,7 +24,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use s5_core::RegistryApi;
-use s5_core::blob::{BlobStore, Blobs, BlobsRead, BlobsWrite};
+use s5_core::blob::{AccessTracker, BlobLayoutSpec, BlobStore, Blobs, BlobsRead, BlobsWrite};
 use s5_core::store::Store;
 
 use crate::CreatedStore;
@@ -35,15 +35,35 @@ struct StoreEntry {
     /// hash). Always present: a path backend rides in as its `BlobStore`,
     /// indexd as its `PackingStore`.
     blobs: Arc<dyn Blobs>,
-    /// The raw path `Store` plus the per-store `outboard` config flag, kept
-    /// so a path-`BlobStore` view can be handed out on explicit request.
-    /// `None` for content-addressed backends (the Sia `PackingStore`).
-    path: Option<(Arc<dyn Store>, bool)>,
+    /// Everything needed to hand out a path-`BlobStore` view on explicit
+    /// request. `None` for content-addressed backends (the Sia
+    /// `PackingStore`).
+    path: Option<PathView>,
     /// Native registry handle for backends that back one cheaply (indexd:
     /// metadata pointers sharing the store's connection + cache).
     registry: Option<Arc<dyn RegistryApi + Send + Sync>>,
 }
 
+/// The raw path `Store` plus the per-store `outboard` config flag, the
+/// store's recorded blob layout and its access tracker.
+struct PathView {
+    store: Arc<dyn Store>,
+    outboard: bool,
+    layout: BlobLayoutSpec,
+    access: Option<Arc<dyn AccessTracker>>,
+}
+
+impl PathView {
+    fn blob_store(&self) -> BlobStore {
+        let blob_store = BlobStore::from_arc_with_outboard(self.store.clone(), self.outboard)
+            .with_layout(self.layout.clone());
+        match &self.access {
+            Some(access) => blob_store.with_access_tracker(access.clone()),
+            None => blob_store,
+        }
+    }
+}
+
 /// The node's store registry: ONE map keyed by `[store.*]` name, exposing
 /// capability-trait views, with the path view available only on explicit
 /// request (D15).
@@ -60,7 +80,12 @@ impl NodeStores {
             name,
             StoreEntry {
                 blobs: created.blobs,
-                path: created.store.map(|s| (s, outboard)),
+                path: created.store.map(|store| PathView {
+                    store,
+                    outboard,
+                    layout: created.layout,
+                    access: created.access,
+                }),
                 registry: created.registry,
             },
         );
@@ -103,11 +128,9 @@ impl NodeStores {
     /// backends — "this feature does not apply here", not "store missing";
     /// use [`Self::blobs`] for content access.
     pub fn path_store(&self, name: &str) -> Option<BlobStore> {
-        self.entries.get(name).and_then(|e| {
-            e.path.as_ref().map(|(store, outboard)| {
-                BlobStore::from_arc_with_outboard(store.clone(), *outboard)
-            })
-        })
+        self.entries
+            .get(name)
+            .and_then(|e| e.path.as_ref().map(PathView::blob_store))
     }
 
     /// Every path-backed store as its `BlobStore` view — the blobs-server
@@ -116,14 +139,7 @@ impl NodeStores {
     pub fn path_stores(&self) -> HashMap<String, BlobStore> {
         self.entries
             .iter()
-            .filter_map(|(name, e)| {
-                e.path.as_ref().map(|(store, outboard)| {
-                    (
-                        name.clone(),
-                        BlobStore::from_arc_with_outboard(store.clone(), *outboard),
-                    )
-                })
-            })
+            .filter_map(|(name, e)| e.path.as_ref().map(|p| (name.clone(), p.blob_store())))
             .collect()
     }
 
@@ -133,18 +149,14 @@ impl NodeStores {
     pub fn raw_store(&self, name: &str) -> Option<Arc<dyn Store>> {
         self.entries
             .get(name)
-            .and_then(|e| e.path.as_ref().map(|(store, _)| store.clone()))
+            .and_then(|e| e.path.as_ref().map(|p| p.store.clone()))
     }
 
     /// Every raw path `Store`, keyed by `[store.*]` name.
     pub fn raw_stores(&self) -> HashMap<String, Arc<dyn Store>> {
         self.entries
             .iter()
-            .filter_map(|(name, e)| {
-                e.path
-                    .as_ref()
-                    .map(|(store, _)| (name.clone(), store.clone()))
-            })
+            .filter_map(|(name, e)| e.path.as_ref().map(|p| (name.clone(), p.store.clone())))
             .collect()
     }
 
@@ -172,15 +184,19 @@ mod tests {
         let raw: Arc<dyn Store> = Arc::new(MemoryStore::new());
         let path_backed = CreatedStore {
             store: Some(raw.clone()),
+            layout: BlobLayoutSpec::Auto,
             blobs: Arc::new(BlobStore::from_arc_with_outboard(raw, false)),
             registry: None,
+            access: None,
         };
         reg.insert("local".to_string(), path_backed, false);
 
         let content_addressed = CreatedStore {
             store: None,
+            layout: BlobLayoutSpec::Auto,
             blobs: Arc::new(BlobStore::without_outboard(MemoryStore::new())),
             registry: None,
+            access: None,
         };
         reg.insert("sia".to_string(), content_addressed, false);
 
@@ -103,7 +103,7 @@ pub struct ColdGcParams {
     pub tiered_store: Arc<dyn Blobs>,
     /// The cold-tier backend — the *only* place this task deletes. This is
     /// the explicit path-`BlobStore` view (D15): the mtime grace gate needs
-    /// `modified`, a genuine path-store capability.
+    /// object mtimes from `Store::list_with_meta`, a genuine path-store capability.
     pub cold_store: BlobStore,
     /// Pin registry; pinned blobs are never reclaimed.
     pub pins: Arc<dyn Pins>,
@@ -125,6 +125,9 @@ pub struct ColdGcParams {
     pub dry_run: bool,
     /// Optional metrics sink.
     pub reporter: Option<Arc<dyn GcReporter>>,
+    /// Passes that reclaim space are reported here as
+    /// [`NodeEvent::GcCompleted`](crate::events::NodeEvent::GcCompleted).
+    pub events: Option<crate::events::EventLog>,
 }
 
 /// Spawn the detached periodic GC task. Runs a first pass shortly after
@@ -147,6 +150,15 @@ pub fn spawn_cold_gc(params: ColdGcParams) -> tokio::task::JoinHandle<()> {
                     if let Some(reporter) = &params.reporter {
                         reporter.report(&report);
                     }
+                    if let Some(events) = &params.events
+                        && report.deleted > 0
+                    {
+                        events.emit(crate::events::NodeEvent::GcCompleted {
+                            vault: params.vault_name.clone(),
+                            deleted: report.deleted,
+                            bytes_reclaimed: report.bytes_reclaimed,
+                        });
+                    }
                 }
                 Ok(None) => {
                     tracing::info!(
@@ -286,11 +298,14 @@ async fn sweep_cold(
     dry_run: bool,
 ) -> anyhow::Result<GcReport> {
     let mut report = GcReport::default();
-    let all_hashes = cold_store.list_hashes().await?;
-    report.total = all_hashes.len();
+    // One listing carries each blob's size and mtime, so candidates cost no
+    // extra round trips before the age gate.
+    let mut blobs = cold_store.list_with_meta().await?;
     let now = SystemTime::now();
 
-    for h in all_hashes {
+    while let Some(item) = blobs.next().await {
+        let (h, meta) = item?;
+        report.total += 1;
         // Pinned blobs are never reclaimed.
         if !pins.get_pinners(h).await?.is_empty() {
             report.kept_by_pins += 1;
@@ -303,10 +318,10 @@ async fn sweep_cold(
         }
 
         // Unreachable + unpinned → garbage candidate, subject to the age gate.
-        let size = cold_store.size(h).await.unwrap_or(0);
+        let size = meta.size;
         report.bytes_candidate += size;
 
-        if !old_enough(cold_store.modified(h).await.ok().flatten(), now, min_age) {
+        if !old_enough(meta.modified, now, min_age) {
             report.aged_out_protected += 1;
             continue;
         }
@@ -354,6 +369,7 @@ mod tests {
     fn local_blobstore(dir: &std::path::Path) -> BlobStore {
         BlobStore::new(LocalStore::create(LocalStoreConfig {
             base_path: dir.to_string_lossy().into_owned(),
+            ..Default::default()
         }))
     }
 
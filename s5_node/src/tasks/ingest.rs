@@ -226,6 +226,7 @@ pub async fn run_ingest(
             one_file_system: source.one_file_system,
             follow_symlinks: source.follow_symlinks,
             detect_deletions: source.detect_deletions,
+            sparse_files: source.sparse_files,
             routes: compile_pipeline_routes(&vault.pipelines)
                 .with_context(|| format!("compiling vault.{vault_name}.pipelines"))?,
             exclude,
@@ -69,6 +69,12 @@ pub struct TaskExecutorContext {
     /// Empty until then (and in test harnesses) — `publish` simply skips the
     /// mirror while unset.
     pub discovery_seed: Arc<std::sync::OnceLock<[u8; 32]>>,
+    /// Where `publish` records each run as an FS5 save, for `GetLatency`.
+    /// Shared with the RPC server; a fresh default in test harnesses.
+    pub latency: crate::latency::LatencyTracker,
+    /// The daemon's event log. Task failures are recorded here so repeated
+    /// failures reach `[notify]` targets.
+    pub events: crate::events::EventLog,
 }
 
 // ---------------------------------------------------------------------------
@@ -185,13 +191,18 @@ impl TaskExecutor {
         let reporter_clone = reporter.clone();
 
         let join = tokio::spawn(async move {
+            let events = ctx.events.clone();
             let result = run_task(ctx, &spec_clone, reporter_clone.clone(), cancel_clone).await;
             match result {
                 Ok(true) => reporter_clone.set_state(TaskState::Cancelled),
-                Ok(false) => reporter_clone.set_state(TaskState::Completed),
+                Ok(false) => {
+                    events.task_finished(&task_label(&spec_clone), None);
+                    reporter_clone.set_state(TaskState::Completed)
+                }
                 Err(e) => {
                     let msg = format!("{e:#}");
                     tracing::error!(task_id = id, error = %e, "task failed");
+                    events.task_finished(&task_label(&spec_clone), Some(&msg));
                     reporter_clone.set_state(TaskState::Failed { error: msg });
                 }
             }
@@ -251,6 +262,21 @@ impl TaskExecutor {
 // ---------------------------------------------------------------------------
 
 /// Dispatch a task spec to the appropriate handler.
+/// Stable name for a task, used to count its consecutive failures.
+fn task_label(spec: &TaskSpec) -> String {
+    match spec {
+        TaskSpec::Ingest { vault, source, .. } => format!("ingest {source} -> {vault}"),
+        TaskSpec::Publish { vault, .. } => format!("publish {vault}"),
+        TaskSpec::Backup { vault, source, .. } => format!("backup {source} -> {vault}"),
+        TaskSpec::Restore { vault, .. } => format!("restore {vault}"),
+        TaskSpec::Copy {
+            src_vault,
+            dst_vault,
+            ..
+        } => format!("copy {src_vault} -> {dst_vault}"),
+    }
+}
+
 async fn run_task(
     ctx: Arc<TaskExecutorContext>,
     spec: &TaskSpec,
@@ -472,6 +498,7 @@ pub(crate) fn vault_meta_store_open(vault: &NodeConfigVault) -> anyhow::Result<B
         // and reads see the same blobs.
         let store = LocalStore::create(LocalStoreConfig {
             base_path: meta_path.to_string_lossy().into_owned(),
+            ..Default::default()
         });
         Ok(BlobStore::without_outboard(store))
     } else {
@@ -214,12 +214,13 @@ async fn publish_discovery_entry(
     let pubkey = VerifyingKey::from(&discovery_key).to_bytes();
     let stream_key = StreamKey::Vault { pubkey, vault_id };
 
-    let revision = match registry.get(&stream_key).await? {
-        Some(prev) if prev.hash == head_hash => return Ok(()), // already current
-        Some(prev) => prev.revision + 1,
-        None => 1,
-    };
-    let message = sign_registry_entry(&discovery_key, vault_id, head_hash, revision)?;
+    let current = registry.get(&stream_key).await?;
+    if current.as_ref().is_some_and(|prev| prev.hash == head_hash) {
+        return Ok(()); // already current
+    }
+    let message =
+        StreamMessage::next_root_head(&discovery_key, vault_id, head_hash, current.as_ref())
+            .map_err(|e| anyhow!("creating discovery registry entry: {e}"))?;
     registry
         .set(message)
         .await
@@ -604,10 +605,26 @@ pub(crate) fn vault_id_for_config(
 /// 8. Derive Ed25519 signing key from node secret + vault name.
 /// 9. Sign a registry entry pointing to the encrypted blob's hash.
 /// 10. Publish to registry.
+///
+/// The whole run is recorded in `ctx.latency` as an FS5 save.
 pub async fn run_publish(
     ctx: &TaskExecutorContext,
     vault_name: &str,
     key_names: &[String],
+) -> anyhow::Result<()> {
+    ctx.latency
+        .time(
+            crate::latency::OP_FS5_SAVE,
+            vault_name,
+            publish(ctx, vault_name, key_names),
+        )
+        .await
+}
+
+async fn publish(
+    ctx: &TaskExecutorContext,
+    vault_name: &str,
+    key_names: &[String],
 ) -> anyhow::Result<()> {
     let (vault, key_configs, vault_identity_files, data_store_name, meta_store_name) = {
         let config = ctx.config.read().await;
@@ -791,6 +808,7 @@ pub async fn run_publish(
 
     let mut encrypted_bytes: Option<Bytes> = None;
     let mut encrypted_hash: Option<Hash> = None;
+    let mut published_revision = 0;
     let mut won = false;
 
     for attempt in 0..MAX_PUBLISH_RETRIES {
@@ -1031,6 +1049,7 @@ pub async fn run_publish(
                 );
                 encrypted_bytes = Some(bytes);
                 encrypted_hash = Some(this_hash);
+                published_revision = new_revision;
                 won = true;
                 break;
             }
@@ -1143,6 +1162,20 @@ pub async fn run_publish(
         );
     }
 
+    // -- Point any `[dns.*]` sites of this vault at the new head --
+    {
+        let config = ctx.config.read().await;
+        if config.dns.values().any(|d| d.vault == vault_name) {
+            let head = crate::dns_publish::PublishedHead {
+                public_key: verifying_key.to_bytes(),
+                vault_id,
+                hash: encrypted_hash,
+                revision: published_revision,
+            };
+            crate::dns_publish::update_for_vault(&config, vault_name, &head).await;
+        }
+    }
+
     Ok(())
 }
 
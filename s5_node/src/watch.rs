@@ -2,7 +2,7 @@
 //!
 //! The [`AutomationManager`] owns one supervised loop per *automation*: a
 //! `[task.*]` entry whose `trigger` is `Watch` or `Every` (and that is not
-//! `paused`). It [`reconcile`](AutomationManager::reconcile)s the live set of
+//! `paused`). `Cron` tasks belong to the [`JobScheduler`](crate::jobs::JobScheduler). It [`reconcile`](AutomationManager::reconcile)s the live set of
 //! loops against config on demand — spawning newly-desired automations,
 //! cancelling ones that vanished or changed — so `automate add/pause/resume/rm`
 //! (which are all `patch_config` ops) take effect live the moment the daemon
@@ -234,20 +234,20 @@ impl AutomationManager {
                     )
                     .await;
                 }
-                TaskTrigger::Manual => {} // never in `desired`
+                TaskTrigger::Manual | TaskTrigger::Cron => {} // never in `desired`
             }
         }
     }
 
     /// The desired automation set: `name → task` for every non-paused
-    /// `[task.*]` with a non-`Manual` trigger, plus in-memory automations
+    /// `[task.*]` with a `Watch` or `Every` trigger, plus in-memory automations
     /// synthesized from the legacy `vault.watch` / `snap_interval_secs` knobs
     /// (only when no `[task.*]` already backs up that vault).
     fn desired_automations(&self, config: &S5NodeConfig) -> BTreeMap<String, NodeConfigTask> {
         let mut desired: BTreeMap<String, NodeConfigTask> = BTreeMap::new();
 
         for (name, task) in &config.task {
-            if task.trigger != TaskTrigger::Manual && !task.paused {
+            if matches!(task.trigger, TaskTrigger::Watch | TaskTrigger::Every) && !task.paused {
                 desired.insert(name.clone(), task.clone());
             }
         }
@@ -510,6 +510,8 @@ fn synth_legacy_task(
             TaskTrigger::Watch
         },
         interval_secs: interval,
+        cron: None,
+        jitter_secs: None,
         paused: false,
         spec: TaskSpec::Backup {
             vault: vault_name.to_string(),
@@ -692,7 +694,7 @@ async fn run_loop(
 /// never runs two backups for the same vault concurrently — concurrent
 /// load→merge→save of the vault root would lose updates. `spawn` is
 /// fire-and-forget, so we poll the task's status watch channel to completion.
-async fn dispatch(executor: &TaskExecutor, spec: &TaskSpec, name: &str) -> Result<()> {
+pub(crate) async fn dispatch(executor: &TaskExecutor, spec: &TaskSpec, name: &str) -> Result<()> {
     let (task_id, _) = executor.spawn(spec.clone()).await?;
     tracing::debug!(
         automation = name,
@@ -45,6 +45,8 @@ fn backup_automation(
         then: Vec::new(),
         trigger,
         interval_secs,
+        cron: None,
+        jitter_secs: None,
         paused,
         spec: TaskSpec::Backup {
             vault: "backup".to_string(),
@@ -8,6 +8,8 @@
 //! binary exercises every helper, so silence the per-binary dead-code lint.
 #![allow(dead_code)]
 
+pub mod cluster;
+
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
@@ -101,6 +103,7 @@ impl DurableBackend for LocalBackend {
     fn open(&self) -> (Arc<dyn Blobs>, Arc<dyn RegistryApi + Send + Sync>) {
         let raw: Arc<dyn Store> = Arc::new(LocalStore::create(LocalStoreConfig {
             base_path: self.dir.path().to_string_lossy().into_owned(),
+            ..Default::default()
         }));
         let blobs: Arc<dyn Blobs> = Arc::new(BlobStore::from_arc(raw.clone()));
         let registry: Arc<dyn RegistryApi + Send + Sync> =
@@ -210,6 +213,7 @@ pub fn make_config(
             max_concurrent_ops: None,
             follow_symlinks: false,
             detect_deletions: false,
+            sparse_files: false,
         },
     );
 
@@ -244,6 +248,11 @@ pub fn make_config(
         vault,
         task: BTreeMap::new(),
         friend: BTreeMap::new(),
+        jobs: Default::default(),
+        log: Default::default(),
+        admin_ui: Default::default(),
+        dns: Default::default(),
+        notify: Default::default(),
     }
 }
 
@@ -264,6 +273,8 @@ pub fn build_ctx(
         membership: None,
         membership_refresh: None,
         discovery_seed: Default::default(),
+        latency: Default::default(),
+        events: Default::default(),
     })
 }
 
@@ -81,6 +81,8 @@ fn build_ctx(
         membership: None,
         membership_refresh: None,
         discovery_seed: Default::default(),
+        latency: Default::default(),
+        events: Default::default(),
     })
 }
 
@@ -110,6 +112,7 @@ fn vault_config(
             one_file_system: false,
             follow_symlinks: false,
             detect_deletions: false,
+            sparse_files: false,
             max_concurrent_ops: None,
         },
     );
@@ -153,6 +156,11 @@ fn vault_config(
         vault,
         task: BTreeMap::new(),
         friend: BTreeMap::new(),
+        jobs: Default::default(),
+        log: Default::default(),
+        admin_ui: Default::default(),
+        dns: Default::default(),
+        notify: Default::default(),
     }
 }
 
@@ -311,6 +319,7 @@ async fn back_to_back_divergent_publishes_converge() -> Result<()> {
 
     let relay_raw: Arc<dyn Store> = Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: relay_dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     }));
     let relay_blob = BlobStore::from_arc(Arc::clone(&relay_raw));
 
@@ -404,6 +413,7 @@ async fn parallel_divergent_publishes_converge() -> Result<()> {
 
     let relay_raw: Arc<dyn Store> = Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: relay_dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     }));
     let relay_blob = BlobStore::from_arc(Arc::clone(&relay_raw));
 
@@ -503,6 +513,7 @@ async fn many_parallel_divergent_publishes_all_converge() -> Result<()> {
 
     let relay_raw: Arc<dyn Store> = Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: relay_dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     }));
     let relay_blob = BlobStore::from_arc(Arc::clone(&relay_raw));
 
@@ -125,6 +125,7 @@ fn enroll_config(
             max_concurrent_ops: None,
             follow_symlinks: false,
             detect_deletions: false,
+            sparse_files: false,
         },
     );
 
@@ -160,6 +161,11 @@ fn enroll_config(
         vault,
         task: BTreeMap::new(),
         friend: BTreeMap::new(),
+        jobs: Default::default(),
+        log: Default::default(),
+        admin_ui: Default::default(),
+        dns: Default::default(),
+        notify: Default::default(),
     }
 }
 
@@ -11,6 +11,7 @@
 //!     `staging = None` (its writes are durable on return);
 //!   - a store configured but absent from the resolved registry is reported
 //!     `reachable = false` with a note, not silently dropped;
+//!   - a `local` store reports free disk space against its watermarks;
 //!   - a vault's legacy `snap_interval_secs` AND a D20 `[task.*]` `every`
 //!     automation each surface as a `ScheduledRun`.
 //!
@@ -126,6 +127,39 @@ async fn health_walk(backend: &dyn DurableBackend) -> Result<()> {
         "[{label}] the resolved store stays reachable alongside the ghost"
     );
 
+    assert!(
+        ghost.disk.is_none(),
+        "[{label}] only local stores report disk space"
+    );
+
+    // ================= Local store under its low watermark =================
+    // Disk gauges come from config alone: free space under `base_path`
+    // against the watermarks, whether or not the store resolved.
+    let mut cfg_disk = ctx.config.read().await.clone();
+    cfg_disk.store.insert(
+        "nearly-full".to_string(),
+        s5_node::config::NodeConfigStore::from_backend(
+            s5_node::config::NodeConfigStoreBackend::Local(s5_store_local::LocalStoreConfig {
+                base_path: scratch
+                    .path()
+                    .join("nearly-full")
+                    .to_string_lossy()
+                    .into_owned(),
+                low_watermark_bytes: Some(u64::MAX),
+                ..Default::default()
+            }),
+        ),
+    );
+    let health_disk = gather_health(&cfg_disk, &resolved).await;
+    let disk = health_disk
+        .stores
+        .iter()
+        .find(|s| s.name == "nearly-full")
+        .and_then(|s| s.disk.as_ref())
+        .with_context(|| format!("[{label}] a local store must report disk space"))?;
+    assert_eq!(disk.level, s5_node_api::DiskLevel::Low);
+    assert!(disk.total_bytes >= disk.available_bytes);
+
     // ============== D20 `[task.*]` `every` automation surfaces ==============
     // The real D20 surface (`automate add … --every`, `share … --live`) writes a
     // `[task.*]` with `trigger = every` — NOT `vault.snap_interval_secs`. It must
@@ -138,6 +172,8 @@ async fn health_walk(backend: &dyn DurableBackend) -> Result<()> {
             then: Vec::new(),
             trigger: s5_node::config::TaskTrigger::Every,
             interval_secs: Some(1800),
+            cron: None,
+            jitter_secs: None,
             paused: false,
             spec: s5_node::config::TaskSpec::Backup {
                 vault: "backup".to_string(),
@@ -52,6 +52,11 @@ fn make_config() -> S5NodeConfig {
         vault: BTreeMap::<String, NodeConfigVault>::new(),
         task: BTreeMap::new(),
         friend: BTreeMap::new(),
+        jobs: Default::default(),
+        log: Default::default(),
+        admin_ui: Default::default(),
+        dns: Default::default(),
+        notify: Default::default(),
     }
 }
 
@@ -62,9 +67,11 @@ async fn publishes_identity_bundle_to_all_stores_and_registry() {
 
     let store_a = BlobStore::from_arc(Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: dir_a.path().to_string_lossy().into_owned(),
+        ..Default::default()
     })));
     let store_b = BlobStore::from_arc(Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: dir_b.path().to_string_lossy().into_owned(),
+        ..Default::default()
     })));
 
     let mut stores: HashMap<String, Arc<dyn s5_core::blob::Blobs>> = HashMap::new();
@@ -169,6 +176,7 @@ async fn second_run_with_unchanged_inputs_keeps_revision_at_one() {
     let dir = tempdir().unwrap();
     let store = BlobStore::from_arc(Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     })));
     let mut stores: HashMap<String, Arc<dyn s5_core::blob::Blobs>> = HashMap::new();
     stores.insert("only".to_string(), Arc::new(store));
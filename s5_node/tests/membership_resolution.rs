@@ -109,6 +109,11 @@ fn config_with(
         vault: vaults,
         task: BTreeMap::new(),
         friend: friends,
+        jobs: Default::default(),
+        log: Default::default(),
+        admin_ui: Default::default(),
+        dns: Default::default(),
+        notify: Default::default(),
     }
 }
 
@@ -117,6 +122,7 @@ async fn resolves_self_and_friend() {
     let dir = tempdir().unwrap();
     let store = BlobStore::from_arc(Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     })));
     let mut stores: HashMap<String, Arc<dyn s5_core::blob::Blobs>> = HashMap::new();
     stores.insert("only".to_string(), Arc::new(store));
@@ -222,6 +228,7 @@ async fn missing_friend_skipped_logged() {
     let dir = tempdir().unwrap();
     let store = BlobStore::from_arc(Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     })));
     let mut stores: HashMap<String, Arc<dyn s5_core::blob::Blobs>> = HashMap::new();
     stores.insert("only".to_string(), Arc::new(store));
@@ -271,6 +278,7 @@ async fn unpublished_friend_keeps_did_but_empty_acl() {
     let dir = tempdir().unwrap();
     let store = BlobStore::from_arc(Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     })));
     let mut stores: HashMap<String, Arc<dyn s5_core::blob::Blobs>> = HashMap::new();
     stores.insert("only".to_string(), Arc::new(store));
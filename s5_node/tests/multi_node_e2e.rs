@@ -0,0 +1,389 @@
+//! Cross-node E2E over real iroh loopback connections.
+//!
+//! Every test boots two or three in-process [`s5_node::S5Node`]s through
+//! [`common::cluster`] (memory store + memory registry each) and drives the
+//! wire protocols between them:
+//!
+//! * registry replication — remote SET / GET / Subscribe against a peer's
+//!   registry, including CAS on stale revisions;
+//! * blob transfer — public-ALPN download (plain and bao-verified ranges)
+//!   and re-serve across a chain of peers, and the default refusal of
+//!   unsolicited uploads;
+//! * a vault whose published root lives in a *remote* registry, written by a
+//!   backup job on one node and loaded cold from a third.
+
+mod common;
+
+use std::ops::Bound;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use bytes::Bytes;
+use common::cluster::{TestNode, shutdown_cluster, spawn_cluster, wait_for_revision};
+use common::{age_identity, build_ctx, make_config, run_task};
+use ed25519_dalek::SigningKey;
+use futures_util::StreamExt;
+use s5_core::blob::{Blobs, BlobsRead, BlobsWrite};
+use s5_core::{Hash, RegistryApi, StreamKey, StreamMessage};
+use s5_fs_v2::layer::ReadableLayer;
+use s5_node::config::TaskSpec;
+use s5_node::remote_registry::{RemoteLinks, SupervisedRemoteRegistry};
+use s5_node::tasks::TaskExecutor;
+use s5_node::tasks::peer_load::load_peer_snapshot;
+use s5_node::tasks::publish::{derive_vault_id, device_signing_key};
+use s5_node::tasks::vault_persist::{load_vault_root, vault_root_path};
+use s5_node_api::RemoteLinkState;
+use s5_registry::RegistryEvent;
+
+fn vault_entry(key: &SigningKey, vault_id: [u8; 16], body: &[u8], revision: u64) -> StreamMessage {
+    StreamMessage::sign_ed25519_registry(key, vault_id, Hash::new(body), revision).unwrap()
+}
+
+#[tokio::test]
+async fn registry_replicates_between_nodes() -> Result<()> {
+    let nodes = spawn_cluster(3).await?;
+    let (a, b, c) = (&nodes[0], &nodes[1], &nodes[2]);
+
+    let signer = SigningKey::from_bytes(&[0x21; 32]);
+    let vault_id = [0x5a; 16];
+    let key = StreamKey::Vault {
+        pubkey: signer.verifying_key().to_bytes(),
+        vault_id,
+    };
+
+    // B writes into A's registry over the wire; A sees it locally and C
+    // reads it back over its own connection.
+    b.remote_registry(a)
+        .set(vault_entry(&signer, vault_id, b"v1", 1))
+        .await?;
+    let local = a.registry().get(&key).await?.context("A missing entry")?;
+    assert_eq!(local.revision, 1);
+    let via_c = c
+        .remote_registry(a)
+        .get(&key)
+        .await?
+        .context("C cannot read A's entry")?;
+    assert_eq!(via_c.hash, Hash::new(b"v1"));
+
+    // C subscribes to A: first the current value, then B's next write.
+    let mut events = c
+        .remote_registry(a)
+        .client()
+        .subscribe(vec![key], 16)
+        .await?;
+    match events.recv().await? {
+        Some(RegistryEvent::Initial {
+            message: Some(_), ..
+        }) => {}
+        other => return Err(anyhow!("expected populated Initial, got {other:?}")),
+    }
+    b.remote_registry(a)
+        .set(vault_entry(&signer, vault_id, b"v2", 2))
+        .await?;
+    match events.recv().await? {
+        Some(RegistryEvent::Set { message, .. }) => {
+            let msg = StreamMessage::deserialize(Bytes::from(message))?;
+            assert_eq!(msg.revision, 2);
+            assert_eq!(msg.hash, Hash::new(b"v2"));
+        }
+        other => return Err(anyhow!("expected Set event, got {other:?}")),
+    }
+
+    // A stale write from C never regresses A's entry.
+    let _ = c
+        .remote_registry(a)
+        .set(vault_entry(&signer, vault_id, b"stale", 1))
+        .await;
+    let after = wait_for_revision(a.registry().as_ref(), &key, 2).await?;
+    assert_eq!(after.revision, 2);
+    assert_eq!(after.hash, Hash::new(b"v2"));
+
+    drop(events);
+    shutdown_cluster(nodes).await
+}
+
+/// Many concurrent registry calls on one client travel as compressed
+/// batches and come back in order.
+#[tokio::test]
+async fn registry_batches_concurrent_calls() -> Result<()> {
+    let nodes = spawn_cluster(2).await?;
+    let (a, b) = (&nodes[0], &nodes[1]);
+
+    let remote = b.remote_registry(a);
+    let client = remote.client();
+    let negotiated = client.negotiated().await.context("server refused hello")?;
+    assert_eq!(
+        negotiated.compression,
+        Some(s5_registry::batch::WireCompression::Zstd)
+    );
+
+    let signer = SigningKey::from_bytes(&[0x22; 32]);
+    let vault_ids: Vec<[u8; 16]> = (0..40u8).map(|i| [i; 16]).collect();
+    let entries = vault_ids
+        .iter()
+        .map(|id| vault_entry(&signer, *id, id, 1))
+        .collect();
+    for result in client.set_many(entries).await {
+        result?;
+    }
+
+    let keys: Vec<StreamKey> = vault_ids
+        .iter()
+        .map(|vault_id| StreamKey::Vault {
+            pubkey: signer.verifying_key().to_bytes(),
+            vault_id: *vault_id,
+        })
+        .collect();
+    let got = client.get_many(&keys).await;
+    assert_eq!(got.len(), keys.len());
+    for (vault_id, result) in vault_ids.iter().zip(got) {
+        let msg = result?.context("entry missing")?;
+        assert_eq!(msg.hash, Hash::new(vault_id));
+    }
+    assert!(a.registry().get(&keys[39]).await?.is_some());
+
+    shutdown_cluster(nodes).await
+}
+
+async fn wait_for_link(
+    links: &RemoteLinks,
+    what: &str,
+    done: impl Fn(&s5_node_api::RemoteHealth) -> bool,
+) -> Result<()> {
+    for _ in 0..200 {
+        if links.health().first().is_some_and(&done) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Err(anyhow!("remote link never {what}: {:?}", links.health()))
+}
+
+#[tokio::test]
+async fn remote_registry_reconnects_after_peer_restart() -> Result<()> {
+    let mut nodes = spawn_cluster(2).await?;
+    let a = nodes.remove(0);
+    let b = &nodes[0];
+
+    let links = RemoteLinks::default();
+    let remote = SupervisedRemoteRegistry::spawn(
+        "registry.default".into(),
+        b.node.endpoint.clone(),
+        a.addr(),
+        Duration::from_millis(100),
+        Duration::from_secs(1),
+        &links,
+    );
+    wait_for_link(&links, "became healthy", |h| {
+        h.state == RemoteLinkState::Healthy
+    })
+    .await?;
+
+    let signer = SigningKey::from_bytes(&[0x33; 32]);
+    remote.set(vault_entry(&signer, [1; 16], b"one", 1)).await?;
+
+    // Keep the peer down until the keepalive notices; a quick restart is
+    // absorbed by the lazy connection re-dialing on its own.
+    let stopped = a.stop().await?;
+    wait_for_link(&links, "noticed the peer going down", |h| {
+        h.state == RemoteLinkState::Degraded
+    })
+    .await?;
+    let a = stopped.start().await?;
+    wait_for_link(&links, "re-dialed the restarted peer", |h| {
+        h.state == RemoteLinkState::Healthy && h.reconnects > 0
+    })
+    .await?;
+    let health = &links.health()[0];
+    assert_eq!(health.consecutive_failures, 0);
+    assert!(health.last_error.is_none());
+
+    // The restarted peer starts empty; the link serves it normally.
+    let key = StreamKey::Vault {
+        pubkey: signer.verifying_key().to_bytes(),
+        vault_id: [1; 16],
+    };
+    assert!(remote.get(&key).await?.is_none());
+    remote.set(vault_entry(&signer, [1; 16], b"two", 2)).await?;
+    assert_eq!(
+        a.registry().get(&key).await?.context("entry missing")?.hash,
+        Hash::new(b"two")
+    );
+
+    nodes.push(a);
+    shutdown_cluster(nodes).await
+}
+
+#[tokio::test]
+async fn blobs_transfer_between_nodes() -> Result<()> {
+    let nodes = spawn_cluster(3).await?;
+    let (a, b, c) = (&nodes[0], &nodes[1], &nodes[2]);
+
+    // Larger than one download chunk so the stream is actually multi-part.
+    let payload = Bytes::from(
+        (0..200 * 1024u32)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>(),
+    );
+    let id = a.store().import_bytes(payload.clone()).await?;
+
+    // A → B over the public ALPN, verified on arrival.
+    let fetched = b.blobs_client(a).blob_download(id.hash).await?;
+    assert_eq!(fetched, payload);
+    let ranged = b
+        .blobs_client(a)
+        .blob_download_slice(id.hash, 70_000, Some(1000))
+        .await?;
+    assert_eq!(ranged, payload.slice(70_000..71_000));
+    // Same range with a bao proof: A builds the outboard on demand.
+    let verified = b
+        .blobs_client(a)
+        .download_slice_verified(id.hash, 70_000, Some(1000))
+        .await?;
+    assert_eq!(verified, payload.slice(70_000..71_000));
+    let tail = b
+        .blobs_client(a)
+        .download_slice_verified(id.hash, 150_000, None)
+        .await?;
+    assert_eq!(tail, payload.slice(150_000..));
+
+    // B re-serves it from its own store; C fetches from B without A.
+    let reimported = b.store().import_bytes(fetched).await?;
+    assert_eq!(reimported, id);
+    let via_b = c.blobs_client(b).blob_download(id.hash).await?;
+    assert_eq!(via_b, payload);
+
+    // Blobs the serving node does not hold are reported absent.
+    assert!(
+        !c.blobs_client(b)
+            .blob_contains(Hash::new(b"absent"))
+            .await?
+    );
+
+    // A node ships no per-peer upload config, so unsolicited uploads are
+    // refused and nothing lands in the target store.
+    let pushed = Bytes::from_static(b"unsolicited");
+    assert!(
+        c.blobs_client(a)
+            .blob_upload_bytes(pushed.clone())
+            .await
+            .is_err()
+    );
+    assert!(!a.store().blob_contains(Hash::new(&pushed)).await?);
+
+    shutdown_cluster(nodes).await
+}
+
+/// A backs up into its own store but publishes the vault root to B's
+/// registry; C — holding only the paper identity — resolves the root from B
+/// and reads the tree's blobs from A, all over the wire.
+#[tokio::test]
+async fn vault_root_published_to_remote_registry() -> Result<()> {
+    let nodes = spawn_cluster(3).await?;
+    let (a, b, c) = (&nodes[0], &nodes[1], &nodes[2]);
+
+    let scratch = tempfile::tempdir()?;
+    let (paper_recipient, paper_id) = age_identity(scratch.path(), "paper");
+    let (device_recipient, device_id) = age_identity(scratch.path(), "device");
+    let source = scratch.path().join("source");
+    std::fs::create_dir_all(source.join("nested"))?;
+    std::fs::write(source.join("readme.md"), b"# multi-node\n")?;
+    std::fs::write(source.join("nested/data.bin"), vec![0xcd; 96 * 1024])?;
+    let vault_root = scratch.path().join("vault");
+    std::fs::create_dir_all(&vault_root)?;
+
+    let config = make_config(
+        &vault_root.to_string_lossy(),
+        &paper_recipient,
+        &paper_id,
+        &device_recipient,
+        &device_id,
+        &source.to_string_lossy(),
+    );
+    let node_secret = [0x33u8; 32];
+    let blobs: Arc<dyn Blobs> = Arc::new(a.store());
+    let registry: Arc<dyn RegistryApi + Send + Sync> = Arc::new(a.remote_registry(b));
+    let executor = TaskExecutor::new(build_ctx(config, blobs, registry, node_secret));
+    let backup = TaskSpec::Backup {
+        vault: "backup".to_string(),
+        source: "docs".to_string(),
+        blob_store: "durable".to_string(),
+        keys: vec!["device".to_string(), "paper".to_string()],
+        target_path: None,
+        changed_paths: None,
+    };
+    run_task(&executor, backup.clone())
+        .await
+        .context("backup")?;
+
+    let pubkey = device_signing_key(&node_secret).verifying_key().to_bytes();
+    // The local vault root is sealed to the device key; the published root
+    // is also readable with the paper key, which is all C gets.
+    let (_, _, root_ctx) = load_vault_root(
+        &vault_root_path(&vault_root.to_string_lossy()),
+        std::slice::from_ref(&device_id),
+    )?
+    .ok_or_else(|| anyhow!("vault root missing after backup"))?;
+    let recovery = root_ctx
+        .keys
+        .as_ref()
+        .and_then(|m| m.get(&s5_fs_v2::snapshot::KEY_SLOT_RECOVERY))
+        .copied()
+        .ok_or_else(|| anyhow!("vault root has no recovery slot"))?;
+    let vault_id = derive_vault_id(&recovery);
+    let key = StreamKey::Vault { pubkey, vault_id };
+
+    // The publish went over the wire: B holds the entry, A does not.
+    let first = b.registry().get(&key).await?.context("B missing root")?;
+    assert!(a.registry().get(&key).await?.is_none());
+
+    // C cold-loads the tree: root from B, blobs from A.
+    let remote_blobs = c.remote_blob_store(a);
+    let read_store: Arc<dyn BlobsRead> = Arc::new(remote_blobs.clone());
+    let snapshot = load_peer_snapshot(
+        pubkey,
+        vault_id,
+        &c.remote_registry(b),
+        &remote_blobs,
+        std::slice::from_ref(&paper_id),
+        read_store,
+    )
+    .await?
+    .ok_or_else(|| anyhow!("C could not resolve the published root"))?;
+    let mut keys = Vec::new();
+    let mut scan = snapshot.scan(Bound::Unbounded, Bound::Unbounded);
+    while let Some(item) = scan.next().await {
+        keys.push(item?.0);
+    }
+    drop(scan);
+    assert!(keys.iter().any(|k| k == "readme.md"), "got {keys:?}");
+    assert!(keys.iter().any(|k| k == "nested/data.bin"), "got {keys:?}");
+
+    // Re-running the job after a change advances the remote root.
+    std::fs::write(source.join("added.txt"), b"second run")?;
+    run_task(&executor, backup).await.context("second backup")?;
+    let second = wait_for_revision(b.registry().as_ref(), &key, first.revision + 1).await?;
+    assert_ne!(second.hash, first.hash);
+
+    drop(executor);
+    shutdown_cluster(nodes).await
+}
+
+/// Guard for the harness itself: every node is individually dialable and
+/// ids are distinct, so a test mixing up peers fails loudly.
+#[tokio::test]
+async fn cluster_nodes_are_distinct() -> Result<()> {
+    let nodes = spawn_cluster(2).await?;
+    assert_ne!(nodes[0].id(), nodes[1].id());
+    let lonely = TestNode::spawn().await?;
+    assert!(
+        lonely
+            .remote_registry(&nodes[0])
+            .get(&StreamKey::Local([0; 32]))
+            .await?
+            .is_none()
+    );
+    lonely.shutdown().await?;
+    shutdown_cluster(nodes).await
+}
@@ -72,6 +72,8 @@ fn build_ctx(
         membership: None,
         membership_refresh: None,
         discovery_seed: Default::default(),
+        latency: Default::default(),
+        events: Default::default(),
     })
 }
 
@@ -102,6 +104,7 @@ fn vault_config(
             one_file_system: false,
             follow_symlinks: false,
             detect_deletions: false,
+            sparse_files: false,
             max_concurrent_ops: None,
         },
     );
@@ -145,6 +148,11 @@ fn vault_config(
         vault,
         task: BTreeMap::new(),
         friend: BTreeMap::new(),
+        jobs: Default::default(),
+        log: Default::default(),
+        admin_ui: Default::default(),
+        dns: Default::default(),
+        notify: Default::default(),
     }
 }
 
@@ -203,10 +211,12 @@ async fn peer_snapshot_round_trip_via_relay() -> Result<()> {
     // store-backed registry).
     let relay_a_raw: Arc<dyn Store> = Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: relay_dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     }));
     let relay_a_blob = BlobStore::from_arc(Arc::clone(&relay_a_raw));
     let mirror_a_blob = BlobStore::new(LocalStore::create(LocalStoreConfig {
         base_path: mirror_dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     }));
 
     // ---- A publishes -------------------------------------------------------
@@ -278,6 +288,7 @@ async fn peer_snapshot_round_trip_via_relay() -> Result<()> {
     // see — independent runtime state, shared CAS only.
     let relay_b_raw: Arc<dyn Store> = Arc::new(LocalStore::create(LocalStoreConfig {
         base_path: relay_dir.path().to_string_lossy().into_owned(),
+        ..Default::default()
     }));
     let relay_b_blob = BlobStore::from_arc(Arc::clone(&relay_b_raw));
     let read_store: Arc<dyn s5_core::BlobsRead> = Arc::new(relay_b_blob.clone());
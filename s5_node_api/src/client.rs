@@ -117,6 +117,24 @@ impl S5NodeClient {
             .context("list_tasks RPC failed")
     }
 
+    /// The node's cron jobs and their persisted run state.
+    pub async fn list_jobs(&self) -> Result<ListJobsResponse> {
+        self.inner
+            .rpc(ListJobs)
+            .await
+            .context("list_jobs RPC failed")
+    }
+
+    /// Queue the named cron job to run now.
+    pub async fn run_job_now(&self, name: impl Into<String>) -> Result<()> {
+        flatten_string_err(
+            self.inner
+                .rpc(RunJobNow { name: name.into() })
+                .await
+                .context("run_job_now RPC failed")?,
+        )
+    }
+
     /// Shut down the node.
     pub async fn shutdown(&self) -> Result<()> {
         self.inner
@@ -164,6 +182,14 @@ impl S5NodeClient {
             .context("get_health RPC failed")
     }
 
+    /// Recent per-operation latency histograms and slowest samples.
+    pub async fn get_latency(&self) -> Result<GetLatencyResponse> {
+        self.inner
+            .rpc(GetLatency)
+            .await
+            .context("get_latency RPC failed")
+    }
+
     /// List vault snapshots.
     pub async fn list_snapshots(&self, vault: Option<String>) -> Result<ListSnapshotsResponse> {
         self.inner
@@ -260,6 +286,19 @@ impl S5NodeClient {
             .context("pair RPC failed")
     }
 
+    /// Have the node fetch `url` into a store (and optionally a vault
+    /// path). Events arrive as the download progresses; the last one is
+    /// `Done` or `Failed`.
+    pub async fn fetch_url(
+        &self,
+        req: FetchUrl,
+    ) -> Result<irpc::channel::mpsc::Receiver<FetchUrlEvent>> {
+        self.inner
+            .server_streaming(req, 16)
+            .await
+            .context("fetch_url RPC failed")
+    }
+
     /// Receiver-side: parse `token`, dial the sender's iroh
     /// endpoint over `s5/pair/0`, present the secret, and return
     /// the sender's DID on success.
@@ -5,6 +5,8 @@
 //! The full `S5NodeConfig` (which adds `NodeConfigStore` and
 //! cross-reference validation) remains in `s5_node::config`.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 // ---------------------------------------------------------------------------
@@ -148,6 +150,14 @@ pub struct NodeConfigSource {
     /// can never reclaim the orphaned blobs). See `BackupConfig`.
     #[serde(default)]
     pub detect_deletions: bool,
+
+    /// Store sparse files (VM disk images, preallocated databases) without
+    /// their holes: only the data extents are uploaded, and restores and
+    /// mounts put the holes back. See `BackupConfig::sparse_files`.
+    ///
+    /// Default: `false`.
+    #[serde(default)]
+    pub sparse_files: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -500,6 +510,28 @@ pub enum NodeConfigRegistry {
         #[serde(default)]
         prefix: Option<String>,
     },
+    /// Registry served by another s5 node over the `s5/registry` ALPN.
+    ///
+    /// The daemon keeps the connection supervised: it pings the peer every
+    /// `keepalive_secs`, re-dials with exponential backoff after a failed
+    /// ping or call, and reports the link in `vup doctor`.
+    ///
+    /// ```toml
+    /// [registry.default]
+    /// type = "remote"
+    /// peer = "<64-char hex iroh endpoint id>"
+    /// ```
+    Remote {
+        /// Iroh endpoint id of the serving node (64-character hex).
+        peer: String,
+        /// Seconds between keepalive pings. Defaults to 30.
+        #[serde(default)]
+        keepalive_secs: Option<u64>,
+        /// Longest wait between re-dial attempts, in seconds. Defaults
+        /// to 300.
+        #[serde(default)]
+        max_backoff_secs: Option<u64>,
+    },
     /// Multi registry: fans out writes to N backends in parallel.
     Multi {
         /// List of backend configurations.
@@ -519,7 +551,8 @@ pub enum NodeConfigRegistry {
 /// A `Manual` task only runs when something asks for it (`RunTask` /
 /// `vup backup`). `Watch`/`Every` tasks are *automations*: the daemon's
 /// `AutomationManager` reconciles them from `[task.*]` and keeps a live loop
-/// running for each. Serialized as a bare snake_case string (`trigger =
+/// running for each. `Cron` tasks are *jobs*, run by the daemon's
+/// `JobScheduler` with persisted run state (`vup jobs`). Serialized as a bare snake_case string (`trigger =
 /// "watch"`), defaulting to `Manual` so every pre-existing task stays manual.
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
@@ -532,6 +565,8 @@ pub enum TaskTrigger {
     Watch,
     /// The daemon re-runs the task every `interval_secs` seconds.
     Every,
+    /// The daemon runs the task on the `cron` schedule.
+    Cron,
 }
 
 /// A named task — the unit of work in s5.
@@ -555,6 +590,19 @@ pub struct NodeConfigTask {
     #[serde(default)]
     pub interval_secs: Option<u64>,
 
+    /// Schedule for `trigger = "cron"`: a five-field cron expression
+    /// (`minute hour day-of-month month day-of-week`, UTC) or one of
+    /// `@hourly`, `@daily`, `@weekly`, `@monthly`, `@yearly`. Required for
+    /// `Cron` jobs; ignored otherwise.
+    #[serde(default)]
+    pub cron: Option<String>,
+
+    /// Upper bound, in seconds, of a random delay added to every `Cron`
+    /// fire time, so nodes sharing a schedule don't all hit a store at
+    /// the same instant.
+    #[serde(default)]
+    pub jitter_secs: Option<u64>,
+
     /// A paused automation stays configured but is not spawned by the
     /// reconciler. `automate pause`/`resume` flip this. No effect on
     /// `Manual` tasks.
@@ -566,6 +614,256 @@ pub struct NodeConfigTask {
     pub spec: TaskSpec,
 }
 
+/// `[jobs]` — node-wide settings of the cron job scheduler.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeConfigJobs {
+    /// How many jobs may run at once; further due jobs wait for a slot.
+    /// Defaults to 2. Read at daemon start.
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+    /// Path of the redb file holding each job's last run, next run and
+    /// outcome. Relative paths resolve against the config file's
+    /// directory; defaults to `jobs.redb` next to it.
+    #[serde(default)]
+    pub state_file: Option<String>,
+}
+
+/// `[log]` — the daemon's log file. Read at daemon start; `RUST_LOG`, when
+/// set, still replaces `level` and `modules`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeConfigLog {
+    /// Write the log to files under `dir`. Defaults to `true`; `false`
+    /// logs to stderr instead.
+    #[serde(default)]
+    pub file: Option<bool>,
+    /// Directory of the log files. Relative paths resolve against the
+    /// config file's directory; defaults to `<cache dir>/s5/logs`.
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub format: NodeConfigLogFormat,
+    #[serde(default)]
+    pub rotation: NodeConfigLogRotation,
+    /// Size a log file may reach before `rotation = "size"` starts the
+    /// next one. Defaults to 64 MiB.
+    #[serde(default)]
+    pub max_file_bytes: Option<u64>,
+    /// Log files kept, the current one included. Defaults to 7.
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    /// Baseline level (`trace` … `error`). Defaults to `debug`: the log is
+    /// the record to read after something stalled.
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Per-module levels, e.g. `iroh = "debug"` or `s5_node::jobs =
+    /// "trace"`. Replaces the built-in `warn` clamp for noisy dependencies
+    /// of the same name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub modules: BTreeMap<String, String>,
+}
+
+impl NodeConfigLog {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// `[admin_ui]` — the embedded web admin UI. Off unless `listen` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeConfigAdminUi {
+    /// Socket address for the UI's HTTP listener, e.g. `127.0.0.1:5080`.
+    /// The JSON API behind it is gated by the per-run control token, but
+    /// the listener speaks plain HTTP: keep it on loopback, or put a TLS
+    /// proxy in front.
+    #[serde(default)]
+    pub listen: Option<String>,
+}
+
+impl NodeConfigAdminUi {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// `[dns.<site>]` — keep a `_s5.<domain>` TXT record pointing at a
+/// vault's published root head. Updated after every publish that moves
+/// the head; a failed update is logged and retried on the next one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeConfigDns {
+    /// Vault whose published head the record tracks.
+    pub vault: String,
+    /// Domain the site is served under; the record is `_s5.<domain>`.
+    pub domain: String,
+    /// TTL for the TXT record, in seconds.
+    #[serde(default = "default_dns_ttl")]
+    pub ttl: u32,
+    /// Where the zone is hosted.
+    pub provider: NodeConfigDnsProvider,
+}
+
+fn default_dns_ttl() -> u32 {
+    300
+}
+
+/// DNS provider API used to update a `[dns.*]` record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeConfigDnsProvider {
+    /// Cloudflare API v4, with a token scoped to `Zone.DNS:Edit`.
+    Cloudflare { zone_id: String, api_token: String },
+    /// AWS Route 53 (`route53:ChangeResourceRecordSets` on the zone).
+    Route53 {
+        hosted_zone_id: String,
+        access_key: String,
+        secret_key: String,
+    },
+    /// RFC 2136 dynamic update to an authoritative server, signed with
+    /// TSIG (HMAC-SHA256).
+    Rfc2136 {
+        /// `host:port` of the primary, e.g. `ns1.example.com:53`.
+        server: String,
+        /// Zone the record lives in, e.g. `example.com`.
+        zone: String,
+        tsig_key_name: String,
+        /// Base64 TSIG secret, as in a BIND `key` clause.
+        tsig_secret: String,
+    },
+}
+
+/// `[notify]` — push node events to webhooks and mail. Nothing is sent
+/// unless at least one `[notify.target.<name>]` is configured.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeConfigNotify {
+    /// Raise `disk_low` when a local store's filesystem has less than this
+    /// share (percent) of its space free.
+    #[serde(default = "default_disk_low_percent")]
+    pub disk_low_percent: u8,
+    /// Raise `task_failed` once the same task has failed this many times
+    /// in a row (then again after it next succeeds and fails as often).
+    #[serde(default = "default_task_failure_threshold")]
+    pub task_failure_threshold: u32,
+    /// Delivery targets by name.
+    #[serde(default)]
+    pub target: BTreeMap<String, NodeConfigNotifyTarget>,
+}
+
+fn default_disk_low_percent() -> u8 {
+    10
+}
+
+fn default_task_failure_threshold() -> u32 {
+    3
+}
+
+impl Default for NodeConfigNotify {
+    fn default() -> Self {
+        Self {
+            disk_low_percent: default_disk_low_percent(),
+            task_failure_threshold: default_task_failure_threshold(),
+            target: BTreeMap::new(),
+        }
+    }
+}
+
+impl NodeConfigNotify {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Node event kinds a notify target can subscribe to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeEventKind {
+    /// A cold-GC pass deleted unreachable blobs.
+    GcCompleted,
+    /// A blob read failed its integrity check.
+    Corruption,
+    /// A task (backup, publish, …) failed repeatedly.
+    TaskFailed,
+    /// A local store's disk is nearly full.
+    DiskLow,
+}
+
+/// One `[notify.target.<name>]`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeConfigNotifyTarget {
+    /// Event kinds routed here. Empty = every kind.
+    #[serde(default)]
+    pub events: Vec<NodeEventKind>,
+    #[serde(flatten)]
+    pub transport: NodeConfigNotifyTransport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeConfigNotifyTransport {
+    /// `POST` each event as JSON.
+    Webhook {
+        url: String,
+        /// Extra request headers, e.g. `Authorization`.
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+    /// Send each event as a plain-text mail.
+    Smtp {
+        /// `host:port` of the submission server.
+        server: String,
+        #[serde(default)]
+        tls: NodeConfigSmtpTls,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+/// How an SMTP connection is secured.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeConfigSmtpTls {
+    /// Plain connect, then `STARTTLS` (port 587).
+    #[default]
+    Starttls,
+    /// TLS from the first byte (port 465).
+    Implicit,
+    /// No TLS. Only for a relay on the same host.
+    None,
+}
+
+/// How log lines are written.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeConfigLogFormat {
+    /// One line per event with all fields.
+    #[default]
+    Full,
+    /// Shorter lines; span fields are folded into the event.
+    Compact,
+    /// Multi-line, human-oriented.
+    Pretty,
+    /// One JSON object per line, for log shippers.
+    Json,
+}
+
+/// When the daemon starts a new log file.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeConfigLogRotation {
+    /// `node.log.YYYY-MM-DD-HH`.
+    Hourly,
+    /// `node.log.YYYY-MM-DD`.
+    #[default]
+    Daily,
+    /// When `node.log` reaches `max_file_bytes`; older files are
+    /// `node.log.1` (newest), `node.log.2`, ….
+    Size,
+    /// A single `node.log` that grows without bound.
+    Never,
+}
+
 /// Task specification — determines what the task does.
 ///
 /// Used both in config (`[task.*]`) and over RPC (`RunTask`).
@@ -65,6 +65,12 @@ pub enum S5NodeProto {
     #[rpc(tx = oneshot::Sender<GetHealthResponse>)]
     GetHealth(GetHealth),
 
+    /// Recent per-operation latency histograms (blob get/put, registry
+    /// get/set, FS5 save) and the slowest samples, over a sliding window.
+    /// Feeds lightweight health dashboards; always succeeds.
+    #[rpc(tx = oneshot::Sender<GetLatencyResponse>)]
+    GetLatency(GetLatency),
+
     /// List vault snapshots.
     #[rpc(tx = oneshot::Sender<ListSnapshotsResponse>)]
     ListSnapshots(ListSnapshots),
@@ -160,12 +166,29 @@ pub enum S5NodeProto {
     #[rpc(tx = oneshot::Sender<Result<String, String>>)]
     JoinExport(JoinExport),
 
+    /// Have the node download an http(s) URL itself and store it as a
+    /// blob, optionally importing it into a vault at a path. Server-
+    /// streaming: `Progress` events while bytes arrive, then exactly one
+    /// `Done { hash, .. }` or `Failed { error }`, then stream close.
+    #[rpc(tx = mpsc::Sender<FetchUrlEvent>)]
+    FetchUrl(FetchUrl),
+
     /// Snapshot of per-peer connection observation. Powers
     /// `vup debug peers` — what iroh pubkeys we've seen, on which
     /// ALPNs, when, which side. Always succeeds.
     #[rpc(tx = oneshot::Sender<DebugPeersResponse>)]
     DebugPeers(DebugPeers),
 
+    /// The cron jobs (`[task.*]` with `trigger = "cron"`) and their
+    /// persisted run state. Powers `vup jobs list`.
+    #[rpc(tx = oneshot::Sender<ListJobsResponse>)]
+    ListJobs(ListJobs),
+
+    /// Queue a cron job to run now, outside its schedule. Fails if no
+    /// such job is scheduled (unknown, not `cron`, or paused).
+    #[rpc(tx = oneshot::Sender<Result<(), String>>)]
+    RunJobNow(RunJobNow),
+
     /// Graceful shutdown.
     #[rpc(tx = oneshot::Sender<()>)]
     Shutdown(Shutdown),
@@ -459,6 +482,90 @@ pub struct GetHealthResponse {
     /// Configured scheduled backups (`snap_interval_secs`), one per vault
     /// that sets one. Empty when nothing is scheduled.
     pub schedules: Vec<ScheduledRun>,
+    /// Supervised links to remote registries (`type = "remote"`), in
+    /// config order. Empty when none are configured.
+    #[serde(default)]
+    pub remotes: Vec<RemoteHealth>,
+}
+
+/// Request the node's recent operation latencies.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetLatency;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetLatencyResponse {
+    /// How far back `ops` and `slowest` reach, in seconds.
+    pub window_secs: u64,
+    /// Upper bounds of the histogram buckets, in milliseconds. Each
+    /// `OpLatency.buckets` has one more entry, for anything slower.
+    pub bucket_upper_ms: Vec<u64>,
+    /// One entry per operation seen in the window, by name.
+    pub ops: Vec<OpLatency>,
+    /// The slowest samples in the window across all operations, slowest first.
+    pub slowest: Vec<SlowRequest>,
+    /// The latest blob transfers (`blob_get` / `blob_put`), newest first,
+    /// however old.
+    #[serde(default)]
+    pub recent: Vec<SlowRequest>,
+}
+
+/// Latency histogram of one operation over the window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpLatency {
+    /// `blob_get`, `blob_put`, `registry_get`, `registry_set` or `fs5_save`.
+    pub op: String,
+    pub count: u64,
+    /// Sample counts per bucket (see `GetLatencyResponse.bucket_upper_ms`).
+    pub buckets: Vec<u64>,
+    /// Percentiles, as the upper bound of the bucket they fall in (capped at
+    /// `max_ms`).
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+/// One timed operation: a notably slow one, or a recent transfer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowRequest {
+    pub op: String,
+    /// What was operated on: a blob hash, registry key or vault name.
+    pub detail: String,
+    pub duration_ms: u64,
+    /// When it completed, in Unix seconds.
+    pub at_unix_secs: u64,
+}
+
+/// State of one supervised connection to a remote peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteHealth {
+    /// Where the link is configured, e.g. `registry.default`.
+    pub name: String,
+    /// Iroh endpoint id of the peer.
+    pub peer: String,
+    pub state: RemoteLinkState,
+    /// Round trip of the last successful ping, in milliseconds.
+    pub last_rtt_ms: Option<u64>,
+    /// Seconds since the last successful ping or call; `None` if the peer
+    /// never answered.
+    pub since_last_ok_secs: Option<u64>,
+    /// Failed pings or calls since the last success.
+    pub consecutive_failures: u32,
+    /// Re-dials since the daemon started.
+    pub reconnects: u64,
+    /// The most recent failure; `None` once the peer answers again.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteLinkState {
+    /// No ping has completed yet.
+    Connecting,
+    /// The last ping or call succeeded.
+    Healthy,
+    /// The last ping or call failed; the daemon is re-dialing.
+    Degraded,
 }
 
 /// Health of one configured store: whether it answered a reachability probe
@@ -475,6 +582,10 @@ pub struct StoreHealth {
     /// Staging gauges when the backend buffers writes (a packing store);
     /// `None` for a direct store whose writes are durable on return.
     pub staging: Option<StagingGauges>,
+    /// Filesystem space for a store on local disk; `None` for other
+    /// backends.
+    #[serde(default)]
+    pub disk: Option<DiskGauges>,
 }
 
 /// Wire mirror of `s5_core::blob::StagingStats`: staged-but-not-durable bytes
@@ -489,6 +600,28 @@ pub struct StagingGauges {
     pub inflight: bool,
 }
 
+/// Wire mirror of `s5_store_local::DiskUsage`: free space on a local
+/// store's filesystem against its write watermarks.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiskGauges {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub low_watermark_bytes: Option<u64>,
+    pub critical_watermark_bytes: Option<u64>,
+    pub level: DiskLevel,
+}
+
+/// Which watermark a local store's free space is under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskLevel {
+    Ok,
+    /// New blobs are refused.
+    Low,
+    /// All writes are refused.
+    Critical,
+}
+
 /// A configured scheduled backup surfaced to `vup status`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScheduledRun {
@@ -659,6 +792,41 @@ pub struct RedeemPair {
     pub token: String,
 }
 
+// ── Remote fetch ──────────────────────────────────────────────────
+
+/// Download `url` on the node and store the body as a blob.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchUrl {
+    /// `http://` or `https://` URL to fetch.
+    pub url: String,
+    /// Store to put the blob in. `None` → the vault's data store when
+    /// `vault` is set, else the default store.
+    pub store: Option<String>,
+    /// Vault to import the file into. Requires `path`.
+    pub vault: Option<String>,
+    /// Path inside the vault (e.g. `downloads/file.iso`). An existing
+    /// entry at that path is replaced.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum FetchUrlEvent {
+    /// Bytes received so far; `total` is the `Content-Length`, if sent.
+    Progress { bytes: u64, total: Option<u64> },
+    /// The body is stored. `hash` is the blob's BLAKE3 hash (hex) in
+    /// `store`; `vault`/`path` echo where it was imported, if anywhere.
+    Done {
+        hash: String,
+        size: u64,
+        store: String,
+        media_type: Option<String>,
+        vault: Option<String>,
+        path: Option<String>,
+    },
+    /// The fetch or import failed. Stream closes after this event.
+    Failed { error: String },
+}
+
 // ── Device enrollment (D10) ───────────────────────────────────────
 
 /// Mint a one-time device-enroll token and await its redemption.
@@ -778,3 +946,47 @@ pub struct DebugPeerAlpn {
     pub last_seen_unix: u64,
     pub last_was_incoming: bool,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListJobs;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListJobsResponse {
+    /// One entry per scheduled job, name-sorted.
+    pub jobs: Vec<JobStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunJobNow {
+    /// The `[task.<name>]` key of the job.
+    pub name: String,
+}
+
+/// A cron job's schedule and persisted run state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatus {
+    /// The `[task.<name>]` key.
+    pub name: String,
+    /// The job's cron expression.
+    pub cron: String,
+    /// A run is in progress (or waiting for a concurrency slot).
+    pub running: bool,
+    /// Unix seconds of the next scheduled run, jitter included.
+    pub next_run_unix: Option<u64>,
+    /// Unix seconds the last run started.
+    pub last_run_unix: Option<u64>,
+    /// How long the last run took, in seconds.
+    pub last_duration_secs: Option<u64>,
+    /// How the last run ended.
+    pub last_outcome: Option<JobOutcome>,
+    /// Completed runs, successful or not.
+    pub runs: u64,
+    /// Runs that ended in [`JobOutcome::Failed`].
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobOutcome {
+    Succeeded,
+    Failed { error: String },
+}
@@ -0,0 +1,265 @@
+//! Batched and compressed registry requests.
+//!
+//! Each registry RPC is its own QUIC stream, so a client saving a deep
+//! FS5 tree (one registry entry per encrypted directory) pays a stream
+//! open and a round trip per entry. [`RpcProto::Batch`](crate::RpcProto)
+//! carries many get/set/delete operations in one frame, optionally
+//! zstd-compressed.
+//!
+//! A client learns what the server supports with one
+//! [`RpcProto::Hello`](crate::RpcProto) per connection. Servers that
+//! predate it fail the request, and the client then sticks to one RPC
+//! per operation. Calls issued concurrently on one [`Client`](crate::Client)
+//! are coalesced into batches by [`Coalescer`] without the caller doing
+//! anything; `Client::get_many` / `Client::set_many` are shorthands for
+//! that.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::sync::oneshot;
+
+use crate::{DeleteRequest, GetRequest, GetResponse, SetRequest};
+
+/// Most operations a server accepts in one batch.
+pub const MAX_BATCH_OPS: usize = 256;
+
+/// Upper bound on a decompressed batch body, so a small compressed frame
+/// cannot make the receiver allocate without limit.
+const MAX_BATCH_BYTES: usize = 16 << 20;
+
+/// Bodies smaller than this are not worth compressing.
+const COMPRESS_MIN_BYTES: usize = 1024;
+
+/// Compression of a batch body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireCompression {
+    Zstd,
+}
+
+/// Sent once per connection, before the first batch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HelloRequest {
+    /// Compressions the client can decode, preferred first.
+    pub compression: Vec<WireCompression>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HelloResponse {
+    /// Most operations the server takes per [`BatchRequest`].
+    pub max_batch: u32,
+    /// The requested compressions the server supports too.
+    pub compression: Vec<WireCompression>,
+}
+
+/// Postcard-encoded `Vec<BatchOp>`, compressed as `compression` says.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchRequest {
+    pub compression: Option<WireCompression>,
+    pub body: Vec<u8>,
+}
+
+/// Postcard-encoded `Vec<BatchResult>`, one per requested op in order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchResponse {
+    pub compression: Option<WireCompression>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum BatchOp {
+    Get(GetRequest),
+    Set(SetRequest),
+    Delete(DeleteRequest),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum BatchResult {
+    Get(GetResponse),
+    /// Outcome of a set or delete.
+    Done(Result<(), String>),
+}
+
+/// What the peer agreed to in its [`HelloResponse`]. `None` from
+/// negotiation means one RPC per operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated {
+    pub max_batch: usize,
+    pub compression: Option<WireCompression>,
+}
+
+impl Negotiated {
+    pub(crate) fn from_hello(response: HelloResponse) -> Self {
+        Self {
+            max_batch: (response.max_batch as usize).clamp(1, MAX_BATCH_OPS),
+            compression: response
+                .compression
+                .into_iter()
+                .find(|c| supported_compression().contains(c)),
+        }
+    }
+}
+
+/// Compressions this build can encode and decode.
+pub fn supported_compression() -> Vec<WireCompression> {
+    if cfg!(not(target_arch = "wasm32")) {
+        vec![WireCompression::Zstd]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Encodes `value`, compressing with `compression` when the body is large
+/// enough for it to pay off.
+pub(crate) fn encode<T: Serialize>(
+    value: &T,
+    compression: Option<WireCompression>,
+) -> Result<(Option<WireCompression>, Vec<u8>)> {
+    let body = postcard::to_allocvec(value)?;
+    if body.len() > MAX_BATCH_BYTES {
+        return Err(anyhow!(
+            "registry batch body too large: {} bytes",
+            body.len()
+        ));
+    }
+    match compression {
+        Some(WireCompression::Zstd) if body.len() >= COMPRESS_MIN_BYTES => {
+            let compressed = zstd_compress(&body)?;
+            if compressed.len() < body.len() {
+                return Ok((Some(WireCompression::Zstd), compressed));
+            }
+            Ok((None, body))
+        }
+        _ => Ok((None, body)),
+    }
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(
+    compression: Option<WireCompression>,
+    body: &[u8],
+) -> Result<T> {
+    match compression {
+        None => Ok(postcard::from_bytes(body)?),
+        Some(WireCompression::Zstd) => Ok(postcard::from_bytes(&zstd_decompress(body)?)?),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn zstd_compress(body: &[u8]) -> Result<Vec<u8>> {
+    Ok(zstd::bulk::compress(body, 3)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn zstd_decompress(body: &[u8]) -> Result<Vec<u8>> {
+    Ok(zstd::bulk::decompress(body, MAX_BATCH_BYTES)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn zstd_compress(_body: &[u8]) -> Result<Vec<u8>> {
+    Err(anyhow!("zstd is not available in this build"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn zstd_decompress(_body: &[u8]) -> Result<Vec<u8>> {
+    Err(anyhow!("zstd is not available in this build"))
+}
+
+pub(crate) type Pending = (BatchOp, oneshot::Sender<Result<BatchResult>>);
+
+#[derive(Default)]
+struct Queue {
+    ops: Vec<Pending>,
+    /// A caller is collecting `ops` and will send them.
+    leader: bool,
+}
+
+/// Collects operations issued concurrently on one client into batches.
+///
+/// The first caller to find the queue idle becomes the leader: it yields
+/// once so callers polled alongside it (e.g. by `join_all`) can enqueue,
+/// then takes everything queued and sends it. Everyone else waits for
+/// their result. No background task is involved, so this works on any
+/// executor.
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    queue: Mutex<Queue>,
+}
+
+impl std::fmt::Debug for Coalescer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Coalescer").finish_non_exhaustive()
+    }
+}
+
+impl Coalescer {
+    /// Queues `op` and returns its result. `send` is called by the leader
+    /// with everything collected and must answer every [`Pending`].
+    pub(crate) async fn submit<F, Fut>(&self, op: BatchOp, send: F) -> Result<BatchResult>
+    where
+        F: FnOnce(Vec<Pending>) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let (tx, rx) = oneshot::channel();
+        let lead = {
+            let mut queue = self.lock();
+            queue.ops.push((op, tx));
+            !std::mem::replace(&mut queue.leader, true)
+        };
+        if lead {
+            let guard = LeaderGuard(self);
+            YieldNow(false).await;
+            let ops = std::mem::take(&mut self.lock().ops);
+            guard.release();
+            send(ops).await;
+        }
+        rx.await
+            .map_err(|_| anyhow!("registry batch was cancelled"))?
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Hands leadership back when the leader is done collecting. If the
+/// leader is dropped mid-collection, the queued callers get an error
+/// instead of waiting forever.
+struct LeaderGuard<'a>(&'a Coalescer);
+
+impl LeaderGuard<'_> {
+    fn release(self) {
+        self.0.lock().leader = false;
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        let mut queue = self.0.lock();
+        queue.leader = false;
+        queue.ops.clear();
+    }
+}
+
+/// Returns `Pending` once, so other futures polled in the same pass get
+/// a turn before the leader takes the queue.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -11,7 +11,8 @@
 //!   [`s5_core::RegistryApi`] on top of [`Client`].
 //!
 //! The wire format is defined by [`RpcProto`] and the
-//! ALPN identifier is [`ALPN`].
+//! ALPN identifier is [`ALPN`]. Concurrent client calls are batched
+//! and compressed when the server supports it; see [`batch`].
 
 use std::{fmt, sync::Arc};
 
@@ -33,6 +34,13 @@ use s5_core::{RegistryApi, StreamKey, StreamMessage};
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
+pub mod batch;
+
+use batch::{
+    BatchOp, BatchRequest, BatchResponse, BatchResult, Coalescer, HelloRequest, HelloResponse,
+    Negotiated, Pending,
+};
+
 /// ALPN bumped to `s5/registry/1` for the v3 wire format change
 /// (`StreamKey::Vault` adds a 16-byte `vault_id` after the pubkey, so
 /// keys are no longer fixed at 32 bytes).
@@ -129,6 +137,12 @@ pub enum RpcProto {
     Delete(DeleteRequest),
     #[rpc(tx = mpsc::Sender<RegistryEvent>)]
     Subscribe(SubscribeRequest),
+    /// Feature negotiation; see [`batch`]. Appended after the original
+    /// variants so their wire tags are unchanged.
+    #[rpc(tx = oneshot::Sender<HelloResponse>)]
+    Hello(HelloRequest),
+    #[rpc(tx = oneshot::Sender<Result<BatchResponse, String>>)]
+    Batch(BatchRequest),
 }
 
 /// Server that exposes a [`BroadcastingRegistry`] over iroh.
@@ -344,6 +358,47 @@ impl RegistryServer {
         Ok(())
     }
 
+    fn handle_hello(&self, req: HelloRequest) -> HelloResponse {
+        let supported = batch::supported_compression();
+        HelloResponse {
+            max_batch: batch::MAX_BATCH_OPS as u32,
+            compression: req
+                .compression
+                .into_iter()
+                .filter(|c| supported.contains(c))
+                .collect(),
+        }
+    }
+
+    /// Runs the ops in order through the single-op handlers (ACL
+    /// included) and answers in the request's compression.
+    async fn handle_batch(
+        &self,
+        req: BatchRequest,
+        peer: &[u8; 32],
+    ) -> std::result::Result<BatchResponse, String> {
+        let ops: Vec<BatchOp> =
+            batch::decode(req.compression, &req.body).map_err(|err| err.to_string())?;
+        if ops.len() > batch::MAX_BATCH_OPS {
+            return Err(format!(
+                "registry batch of {} ops exceeds {}",
+                ops.len(),
+                batch::MAX_BATCH_OPS
+            ));
+        }
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            results.push(match op {
+                BatchOp::Get(req) => BatchResult::Get(self.handle_get(req, peer).await),
+                BatchOp::Set(req) => BatchResult::Done(self.handle_set(req, peer).await),
+                BatchOp::Delete(req) => BatchResult::Done(self.handle_delete(req, peer).await),
+            });
+        }
+        let (compression, body) =
+            batch::encode(&results, req.compression).map_err(|err| err.to_string())?;
+        Ok(BatchResponse { compression, body })
+    }
+
     async fn handle_subscribe(
         &self,
         req: SubscribeRequest,
@@ -461,6 +516,13 @@ impl ProtocolHandler for RegistryServer {
                 RegistryRpcMessage::Subscribe(irpc::WithChannels { inner, tx, .. }) => {
                     self.handle_subscribe(inner, &peer, tx).await;
                 }
+                RegistryRpcMessage::Hello(irpc::WithChannels { inner, tx, .. }) => {
+                    let _ = tx.send(self.handle_hello(inner)).await;
+                }
+                RegistryRpcMessage::Batch(irpc::WithChannels { inner, tx, .. }) => {
+                    let result = self.handle_batch(inner, &peer).await;
+                    let _ = tx.send(result).await;
+                }
             }
         }
         conn.closed().await;
@@ -468,9 +530,16 @@ impl ProtocolHandler for RegistryServer {
     }
 }
 
+/// RPC client for the registry protocol.
+///
+/// Clones share one connection and one batch queue: get/set/delete calls
+/// in flight at the same time go out as one [`RpcProto::Batch`] once the
+/// server has accepted the [`RpcProto::Hello`] sent before the first call.
 #[derive(Clone, Debug)]
 pub struct Client {
     inner: IrpcClient<RpcProto>,
+    negotiated: Arc<tokio::sync::OnceCell<Option<Negotiated>>>,
+    coalescer: Arc<Coalescer>,
 }
 
 impl Client {
@@ -478,6 +547,8 @@ impl Client {
         let conn = IrohLazyRemoteConnection::new(endpoint, addr.into(), ALPN.to_vec());
         Client {
             inner: IrpcClient::boxed(conn),
+            negotiated: Default::default(),
+            coalescer: Default::default(),
         }
     }
 
@@ -489,13 +560,111 @@ impl Client {
         Ok(Self::connect(endpoint, iroh::EndpointAddr::from(id)))
     }
 
-    pub async fn get(&self, key: StreamKey) -> Result<Option<StreamMessage>> {
+    /// What the server agreed to, asking it on first use. `None` for
+    /// servers without batching (one RPC per operation).
+    pub async fn negotiated(&self) -> Option<Negotiated> {
+        *self
+            .negotiated
+            .get_or_init(|| async {
+                let hello = HelloRequest {
+                    compression: batch::supported_compression(),
+                };
+                match self.inner.rpc(hello).await {
+                    Ok(response) => Some(Negotiated::from_hello(response)),
+                    Err(err) => {
+                        tracing::debug!("registry hello failed, not batching: {err}");
+                        None
+                    }
+                }
+            })
+            .await
+    }
+
+    /// One uncached [`RpcProto::Hello`] round trip: proves the server is
+    /// reachable and serving, for keepalive probes.
+    pub async fn ping(&self) -> Result<HelloResponse> {
+        let hello = HelloRequest {
+            compression: batch::supported_compression(),
+        };
+        Ok(self.inner.rpc(hello).await?)
+    }
+
+    async fn call(&self, op: BatchOp) -> Result<BatchResult> {
+        let Some(negotiated) = self.negotiated().await else {
+            return self.call_single(op).await;
+        };
+        self.coalescer
+            .submit(op, |ops| self.send_batches(ops, negotiated))
+            .await
+    }
+
+    async fn call_single(&self, op: BatchOp) -> Result<BatchResult> {
+        Ok(match op {
+            BatchOp::Get(req) => BatchResult::Get(self.inner.rpc(req).await?),
+            BatchOp::Set(req) => BatchResult::Done(self.inner.rpc(req).await?),
+            BatchOp::Delete(req) => BatchResult::Done(self.inner.rpc(req).await?),
+        })
+    }
+
+    /// Sends `ops` in batches of at most `max_batch`, answering each
+    /// pending caller. A lone op goes out as a plain RPC.
+    async fn send_batches(&self, mut ops: Vec<Pending>, negotiated: Negotiated) {
+        while !ops.is_empty() {
+            let rest = ops.split_off(ops.len().min(negotiated.max_batch));
+            let chunk = std::mem::replace(&mut ops, rest);
+            if chunk.len() == 1 {
+                for (op, tx) in chunk {
+                    let _ = tx.send(self.call_single(op).await);
+                }
+                continue;
+            }
+            let (reqs, txs): (Vec<BatchOp>, Vec<_>) = chunk.into_iter().unzip();
+            match self.send_batch(&reqs, negotiated).await {
+                Ok(results) if results.len() == txs.len() => {
+                    for (tx, result) in txs.into_iter().zip(results) {
+                        let _ = tx.send(Ok(result));
+                    }
+                }
+                Ok(results) => {
+                    let err = format!(
+                        "registry batch answered {} of {} ops",
+                        results.len(),
+                        txs.len()
+                    );
+                    for tx in txs {
+                        let _ = tx.send(Err(anyhow!("{err}")));
+                    }
+                }
+                Err(err) => {
+                    for tx in txs {
+                        let _ = tx.send(Err(anyhow!("{err:#}")));
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_batch(
+        &self,
+        ops: &[BatchOp],
+        negotiated: Negotiated,
+    ) -> Result<Vec<BatchResult>> {
+        let (compression, body) = batch::encode(&ops, negotiated.compression)?;
         let response = self
             .inner
-            .rpc(GetRequest {
-                key: key.storage_key(),
-            })
-            .await?;
+            .rpc(BatchRequest { compression, body })
+            .await?
+            .map_err(|err| anyhow!(err))?;
+        batch::decode(response.compression, &response.body)
+    }
+
+    pub async fn get(&self, key: StreamKey) -> Result<Option<StreamMessage>> {
+        let op = BatchOp::Get(GetRequest {
+            key: key.storage_key(),
+        });
+        let BatchResult::Get(response) = self.call(op).await? else {
+            return Err(anyhow!("registry answered a get with a write result"));
+        };
 
         if let Some(bytes) = response.message {
             let message = StreamMessage::deserialize(Bytes::from(bytes))
@@ -508,31 +677,38 @@ impl Client {
 
     pub async fn set(&self, message: StreamMessage) -> Result<()> {
         let bytes = message.serialize();
-        match self
-            .inner
-            .rpc(SetRequest {
-                message: bytes.to_vec(),
-            })
-            .await?
-        {
-            Ok(()) => Ok(()),
-            Err(err) => Err(anyhow!(err.to_string())),
-        }
+        let op = BatchOp::Set(SetRequest {
+            message: bytes.to_vec(),
+        });
+        Self::done(self.call(op).await?)
     }
 
     pub async fn delete(&self, key: StreamKey) -> Result<()> {
-        match self
-            .inner
-            .rpc(DeleteRequest {
-                key: key.storage_key(),
-            })
-            .await?
-        {
-            Ok(()) => Ok(()),
-            Err(err) => Err(anyhow!(err.to_string())),
+        let op = BatchOp::Delete(DeleteRequest {
+            key: key.storage_key(),
+        });
+        Self::done(self.call(op).await?)
+    }
+
+    fn done(result: BatchResult) -> Result<()> {
+        match result {
+            BatchResult::Done(Ok(())) => Ok(()),
+            BatchResult::Done(Err(err)) => Err(anyhow!(err)),
+            BatchResult::Get(_) => Err(anyhow!("registry answered a write with a get result")),
         }
     }
 
+    /// Gets all `keys`, in as few round trips as the server allows.
+    pub async fn get_many(&self, keys: &[StreamKey]) -> Vec<Result<Option<StreamMessage>>> {
+        futures::future::join_all(keys.iter().map(|key| self.get(*key))).await
+    }
+
+    /// Sets all `messages`, in as few round trips as the server allows.
+    /// Results are in input order.
+    pub async fn set_many(&self, messages: Vec<StreamMessage>) -> Vec<Result<()>> {
+        futures::future::join_all(messages.into_iter().map(|message| self.set(message))).await
+    }
+
     /// Subscribe to a fixed set of `StreamKey`s.
     ///
     /// Returns an mpsc receiver that yields `RegistryEvent`s: first
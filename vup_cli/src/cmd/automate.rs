@@ -233,6 +233,9 @@ async fn run_list(client: &S5NodeClient) -> Result<()> {
             .unwrap_or(false);
         let status = if paused {
             "paused".to_string()
+        } else if task.get("trigger").and_then(|v| v.as_str()) == Some("cron") {
+            // Cron jobs run under the job scheduler, not as live loops.
+            "see `vup jobs`".to_string()
         } else {
             match live.iter().find(|a| &a.name == name) {
                 Some(a) if a.alive => "running".to_string(),
@@ -462,9 +465,13 @@ fn task_vault_display(task: &serde_json::Value) -> String {
     }
 }
 
-/// Render a task's trigger for display (`watch` / `every 1h`).
+/// Render a task's trigger for display (`watch` / `every 1h` / `cron 0 3 * * *`).
 fn trigger_display(task: &serde_json::Value) -> String {
     match task.get("trigger").and_then(|v| v.as_str()) {
+        Some("cron") => match task.get("cron").and_then(|v| v.as_str()) {
+            Some(expr) => format!("cron {expr}"),
+            None => "cron".to_string(),
+        },
         Some("every") => task
             .get("interval_secs")
             .and_then(|v| v.as_u64())
@@ -1,10 +1,12 @@
 //! `vup doctor` — health walk.
 //!
 //! One line per signal so a glance answers "is my backup actually safe?":
-//! daemon reachable; each `[store.*]` reachable/UNREACHABLE; staging
-//! drained? + last-flush age; the OS service installed/active; iroh peers
-//! (formerly `vup debug peers`). Unreachable stores and undrained staging are
-//! flagged `WARN` so they stand out — everything else is a terse `ok`.
+//! daemon reachable; each `[store.*]` reachable/UNREACHABLE; free space of
+//! local stores; staging drained? + last-flush age; remote registry links
+//! ok/DEGRADED; the OS service installed/active; iroh peers (formerly
+//! `vup debug peers`). Unreachable stores, disks under a watermark,
+//! undrained staging and degraded links are flagged `WARN` so they stand
+//! out — everything else is a terse `ok`.
 //!
 //! The per-store + staging signals come from the daemon's `GetHealth` RPC
 //! (`s5_node::health::gather_health`); the service row reuses
@@ -51,6 +53,22 @@ pub async fn run_doctor(client: &S5NodeClient) -> Result<()> {
                         format!("{}:", st.name)
                     );
                 }
+                // Disk line only for stores on local disk.
+                if let Some(d) = &st.disk {
+                    let free = humansize::format_size(d.available_bytes, humansize::BINARY);
+                    let total = humansize::format_size(d.total_bytes, humansize::BINARY);
+                    match d.level {
+                        s5_node_api::DiskLevel::Ok => {
+                            println!("            disk:    {free} free of {total}")
+                        }
+                        s5_node_api::DiskLevel::Low => println!(
+                            "            disk:    {free} free of {total} — WARN (below low watermark, new blobs refused)"
+                        ),
+                        s5_node_api::DiskLevel::Critical => println!(
+                            "            disk:    {free} free of {total} — WARN (below critical watermark, all writes refused)"
+                        ),
+                    }
+                }
                 // Staging line only for backends that buffer writes.
                 if let Some(g) = &st.staging {
                     let flushed = format_age(g.since_last_flush_secs);
@@ -67,6 +85,30 @@ pub async fn run_doctor(client: &S5NodeClient) -> Result<()> {
                 }
             }
 
+            // ── Remote registries ────────────────────────────────────
+            for remote in &health.remotes {
+                let label = format!("{}:", remote.name);
+                match remote.state {
+                    s5_node_api::RemoteLinkState::Healthy => println!(
+                        "  remote {label:<10} ok (rtt {} ms, {} re-dial(s))",
+                        remote.last_rtt_ms.unwrap_or(0),
+                        remote.reconnects,
+                    ),
+                    s5_node_api::RemoteLinkState::Connecting => {
+                        println!("  remote {label:<10} connecting")
+                    }
+                    s5_node_api::RemoteLinkState::Degraded => println!(
+                        "  remote {label:<10} DEGRADED — WARN ({} failure(s), last ok {}; {})",
+                        remote.consecutive_failures,
+                        remote
+                            .since_last_ok_secs
+                            .map(|s| format!("{} ago", format_age(s)))
+                            .unwrap_or_else(|| "never".to_string()),
+                        remote.last_error.as_deref().unwrap_or("no response"),
+                    ),
+                }
+            }
+
             // ── Scheduled backups ────────────────────────────────────
             if !health.schedules.is_empty() {
                 println!();
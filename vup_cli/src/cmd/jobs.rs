@@ -0,0 +1,84 @@
+//! `vup jobs` — the daemon's cron jobs (`[task.*]` with `trigger = "cron"`).
+//!
+//! - `jobs list` → each job's schedule, next run and last outcome, from the
+//!   scheduler's persisted state.
+//! - `jobs run <name>` → queue a job to run now, outside its schedule.
+//!
+//! Jobs are added, paused and removed like any other automation, via
+//! `vup automate pause|resume|rm` or `vup config --patch`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use clap::Subcommand;
+use s5_node_api::{JobOutcome, S5NodeClient};
+
+use crate::cmd::doctor::format_age;
+
+#[derive(Subcommand, Debug)]
+pub enum JobsCmd {
+    /// List cron jobs with their next run and last outcome.
+    #[command(alias = "ls")]
+    List,
+    /// Run a cron job now, outside its schedule.
+    Run {
+        /// Job (`[task.*]`) name.
+        name: String,
+    },
+}
+
+pub async fn run_jobs(client: &S5NodeClient, cmd: Option<JobsCmd>) -> Result<()> {
+    match cmd.unwrap_or(JobsCmd::List) {
+        JobsCmd::List => run_list(client).await,
+        JobsCmd::Run { name } => {
+            client.run_job_now(&name).await?;
+            println!("{name}: queued; follow it in `vup jobs list` or `vup tasks`.");
+            Ok(())
+        }
+    }
+}
+
+async fn run_list(client: &S5NodeClient) -> Result<()> {
+    let jobs = client.list_jobs().await?.jobs;
+    if jobs.is_empty() {
+        println!("No cron jobs scheduled. Set `trigger = \"cron\"` and `cron` on a [task.*].");
+        return Ok(());
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    println!(
+        "{:<22} {:<16} {:<10} {:<12} LAST",
+        "NAME", "CRON", "NEXT", "RUNS"
+    );
+    for job in &jobs {
+        let next = if job.running {
+            "running".to_string()
+        } else {
+            match job.next_run_unix {
+                Some(t) => format!("in {}", format_age(t.saturating_sub(now))),
+                None => "never".to_string(),
+            }
+        };
+        let last = match (&job.last_outcome, job.last_run_unix) {
+            (Some(JobOutcome::Succeeded), Some(t)) => {
+                format!("ok, {} ago", format_age(now.saturating_sub(t)))
+            }
+            (Some(JobOutcome::Failed { error }), Some(t)) => {
+                format!("FAILED {} ago: {error}", format_age(now.saturating_sub(t)))
+            }
+            _ => "-".to_string(),
+        };
+        let runs = match job.failures {
+            0 => job.runs.to_string(),
+            f => format!("{} ({f} failed)", job.runs),
+        };
+        println!(
+            "{:<22} {:<16} {:<10} {:<12} {}",
+            job.name, job.cron, next, runs, last
+        );
+    }
+    Ok(())
+}
@@ -28,6 +28,7 @@ pub mod copy;
 pub mod device;
 pub mod device_bootstrap;
 pub mod doctor;
+pub mod jobs;
 pub mod lifecycle;
 pub mod membership;
 pub mod onboard;
@@ -229,8 +230,28 @@ pub async fn run_shutdown(client: &S5NodeClient) -> Result<()> {
     Ok(())
 }
 
+fn describe_log_rotation(log: &s5_node::config::NodeConfigLog) -> String {
+    use s5_node::config::NodeConfigLogRotation;
+
+    let kept = log.max_files.unwrap_or(crate::logging::DEFAULT_MAX_FILES);
+    match log.rotation {
+        NodeConfigLogRotation::Hourly => format!("node.log.<date-hour>, {kept} kept"),
+        NodeConfigLogRotation::Daily => format!("node.log.<date>, {kept} kept"),
+        NodeConfigLogRotation::Size => {
+            let max = log
+                .max_file_bytes
+                .unwrap_or(crate::logging::DEFAULT_MAX_FILE_BYTES);
+            format!(
+                "node.log, rolled at {}, {kept} kept",
+                humansize::format_size(max, humansize::BINARY)
+            )
+        }
+        NodeConfigLogRotation::Never => "node.log, not rotated".to_string(),
+    }
+}
+
 /// `vup status` — show node status summary.
-pub async fn run_status(client: &S5NodeClient) -> Result<()> {
+pub async fn run_status(client: &S5NodeClient, config_path: &std::path::Path) -> Result<()> {
     let resp = client.get_status().await?;
 
     println!("S5 Node Status");
@@ -239,19 +260,24 @@ pub async fn run_status(client: &S5NodeClient) -> Result<()> {
     println!("  Vaults:       {}", resp.vault_count);
     println!("  Sources:      {}", resp.source_count);
     println!("  Active tasks: {}", resp.running_tasks);
-    // Daily-rotated: files are `node.log.YYYY-MM-DD` inside this directory.
-    let log_dir = dirs::cache_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("s5")
-        .join("logs");
-    println!(
-        "  Logs:         {} (node.log.<date>, 7 kept)",
-        log_dir.display()
-    );
 
-    // Also show configured sources from config
     let config_resp = client.get_config().await?;
     let config: serde_json::Value = serde_json::from_str(&config_resp.config_json)?;
+    let log: s5_node::config::NodeConfigLog = config
+        .get("log")
+        .and_then(|l| serde_json::from_value(l.clone()).ok())
+        .unwrap_or_default();
+    if log.file == Some(false) {
+        println!("  Logs:         stderr of the daemon process");
+    } else {
+        println!(
+            "  Logs:         {} ({})",
+            crate::logging::log_dir(&log, config_path).display(),
+            describe_log_rotation(&log)
+        );
+    }
+
+    // Also show configured sources from config
     if let Some(obj) = config
         .get("source")
         .and_then(|s| s.as_object())
@@ -304,6 +330,26 @@ pub async fn run_status(client: &S5NodeClient) -> Result<()> {
                 }
             }
         }
+        let disks: Vec<_> = health
+            .stores
+            .iter()
+            .filter_map(|s| s.disk.as_ref().map(|d| (s.name.as_str(), d)))
+            .collect();
+        if !disks.is_empty() {
+            println!("\nDisk:");
+            for (name, d) in disks {
+                let level = match d.level {
+                    s5_node_api::DiskLevel::Ok => "",
+                    s5_node_api::DiskLevel::Low => " — LOW, new blobs refused",
+                    s5_node_api::DiskLevel::Critical => " — CRITICAL, writes refused",
+                };
+                println!(
+                    "  {name}: {} free of {}{level}",
+                    humansize::format_size(d.available_bytes, humansize::BINARY),
+                    humansize::format_size(d.total_bytes, humansize::BINARY),
+                );
+            }
+        }
         if !health.schedules.is_empty() {
             println!("\nScheduled backups:");
             for run in &health.schedules {
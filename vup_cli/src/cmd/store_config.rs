@@ -62,6 +62,7 @@ impl StoreChoice {
             StoreChoice::Local { path } => {
                 NodeConfigStoreBackend::Local(s5_store_local::LocalStoreConfig {
                     base_path: path.to_string_lossy().into_owned(),
+                    ..Default::default()
                 })
             }
             StoreChoice::S3 { s3, .. } => {
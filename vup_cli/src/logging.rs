@@ -0,0 +1,297 @@
+//! The daemon's tracing setup, driven by the config's `[log]` section.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use s5_node::config::{NodeConfigLog, NodeConfigLogFormat, NodeConfigLogRotation};
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{self, Rotation};
+use tracing_subscriber::EnvFilter;
+
+const LOG_FILE: &str = "node.log";
+pub const DEFAULT_MAX_FILES: usize = 7;
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Dependencies too chatty for the flight recorder; clamped to `warn`
+/// unless `[log.modules]` says otherwise.
+const NOISY_MODULES: &[&str] = &[
+    "iroh",
+    "iroh_blobs",
+    "iroh_relay",
+    "iroh_quinn",
+    "iroh_quinn_proto",
+    "iroh_dns_node_info",
+    "iroh_metrics",
+    "iroh_net_report",
+    "iroh_dns_server",
+    "pkarr",
+    "mainline",
+    "quinn",
+    "quinn_proto",
+    "hickory_resolver",
+    "hickory_proto",
+    "h2",
+    "hyper",
+    "hyper_util",
+    "rustls",
+    "swarm_discovery",
+    "watchable",
+];
+
+/// Reads only `[log]` from the config at `path`, so logging is up before
+/// the full config is parsed and validated (and can report why that
+/// failed). A missing or unparsable file yields the defaults.
+pub fn peek_log_config(path: &Path) -> NodeConfigLog {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|mut table| table.remove("log"))
+        .and_then(|log| log.try_into().ok())
+        .unwrap_or_default()
+}
+
+/// Where the daemon's log files go for `config` (loaded from
+/// `config_path`).
+pub fn log_dir(config: &NodeConfigLog, config_path: &Path) -> PathBuf {
+    match &config.dir {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            match config_path.parent() {
+                Some(base) if dir.is_relative() => base.join(dir),
+                _ => dir,
+            }
+        }
+        None => default_log_dir(),
+    }
+}
+
+pub fn default_log_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("s5")
+        .join("logs")
+}
+
+/// Installs the daemon's global subscriber. The returned guard flushes
+/// the background writer on drop; keep it for the process lifetime.
+pub fn init_daemon_logging(
+    config: &NodeConfigLog,
+    config_path: &Path,
+    verb: LevelFilter,
+) -> Result<WorkerGuard> {
+    let filter = daemon_log_filter(config, verb);
+    let (writer, guard, dir) = if config.file.unwrap_or(true) {
+        let dir = log_dir(config, config_path);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("creating log directory {}", dir.display()))?;
+        let (writer, guard) = tracing_appender::non_blocking(file_writer(config, &dir)?);
+        (writer, guard, Some(dir))
+    } else {
+        let (writer, guard) = tracing_appender::non_blocking(io::stderr());
+        (writer, guard, None)
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(false);
+    match config.format {
+        NodeConfigLogFormat::Full => builder.init(),
+        NodeConfigLogFormat::Compact => builder.compact().init(),
+        NodeConfigLogFormat::Pretty => builder.pretty().init(),
+        NodeConfigLogFormat::Json => builder.json().init(),
+    }
+
+    if let Some(dir) = dir {
+        tracing::info!(
+            log_dir = %dir.display(),
+            rotation = ?config.rotation,
+            max_files = config.max_files.unwrap_or(DEFAULT_MAX_FILES),
+            "s5_node daemon log open"
+        );
+    }
+    Ok(guard)
+}
+
+fn file_writer(config: &NodeConfigLog, dir: &Path) -> Result<Box<dyn Write + Send>> {
+    let max_files = config.max_files.unwrap_or(DEFAULT_MAX_FILES);
+    let rotation = match config.rotation {
+        NodeConfigLogRotation::Hourly => Rotation::HOURLY,
+        NodeConfigLogRotation::Daily => Rotation::DAILY,
+        NodeConfigLogRotation::Never => Rotation::NEVER,
+        NodeConfigLogRotation::Size => {
+            let max_bytes = config.max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES);
+            let writer =
+                SizeRollingWriter::open(dir.join(LOG_FILE), max_bytes, max_files.saturating_sub(1))
+                    .context("opening node.log")?;
+            return Ok(Box::new(writer));
+        }
+    };
+    let mut builder = rolling::Builder::new()
+        .rotation(rotation.clone())
+        .filename_prefix(LOG_FILE);
+    if rotation != Rotation::NEVER {
+        builder = builder.max_log_files(max_files.max(1));
+    }
+    let appender = builder
+        .build(dir)
+        .context("building the rotating node.log appender")?;
+    Ok(Box::new(appender))
+}
+
+/// Build the daemon's tracing filter.
+///
+/// Honours `RUST_LOG` if set; otherwise the node log always captures
+/// full debug detail for the s5/vup/sia_storage side (per-shard Sia
+/// uploads, packing dedup + flush decisions, indexd sync — the record
+/// you want after the fact when something stalled), while the chatty
+/// third-party dependencies (iroh internals, quinn, pkarr resolvers,
+/// hickory DNS) stay clamped to warn. `[log] level` and `[log.modules]`
+/// adjust both; a `-v` asking for more than the configured level wins.
+pub fn daemon_log_filter(config: &NodeConfigLog, verb: LevelFilter) -> EnvFilter {
+    if std::env::var("RUST_LOG").is_ok() {
+        return EnvFilter::from_default_env();
+    }
+
+    let configured = config
+        .level
+        .as_deref()
+        .and_then(|level| level.parse::<LevelFilter>().ok())
+        .unwrap_or(LevelFilter::DEBUG);
+    let baseline = verb.max(configured).to_string().to_lowercase();
+    let mut directives = vec![baseline.clone()];
+    directives.extend(
+        NOISY_MODULES
+            .iter()
+            .filter(|module| !config.modules.contains_key(**module))
+            .map(|module| format!("{module}=warn")),
+    );
+    directives.extend(
+        config
+            .modules
+            .iter()
+            .map(|(module, level)| format!("{module}={level}")),
+    );
+    EnvFilter::try_new(directives.join(",")).unwrap_or_else(|e| {
+        eprintln!("ignoring invalid [log.modules]: {e}");
+        EnvFilter::new(baseline)
+    })
+}
+
+/// `node.log`, moved to `node.log.1` (and older files one number up) once
+/// a write would take it past `max_bytes`. Rotated files beyond
+/// `max_rotated` are deleted.
+struct SizeRollingWriter {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    max_rotated: usize,
+}
+
+impl SizeRollingWriter {
+    fn open(path: PathBuf, max_bytes: u64, max_rotated: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+            max_rotated,
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_rotated == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated(self.max_rotated));
+            for n in (1..self.max_rotated).rev() {
+                let from = self.rotated(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated(1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A single line larger than the limit still lands in one file.
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.roll()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_rotation_shifts_and_prunes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE);
+        let mut writer = SizeRollingWriter::open(path.clone(), 10, 2).unwrap();
+        for line in ["first-123\n", "second-12\n", "third-123\n", "fourth-12\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "fourth-12\n");
+        assert_eq!(read(&writer.rotated(1)), "third-123\n");
+        assert_eq!(read(&writer.rotated(2)), "second-12\n");
+        assert!(!writer.rotated(3).exists());
+    }
+
+    #[test]
+    fn module_overrides_replace_the_noisy_clamp() {
+        let config: NodeConfigLog = toml::from_str(
+            r#"
+            level = "info"
+            format = "json"
+            rotation = "size"
+            [modules]
+            iroh = "debug"
+            "s5_node::jobs" = "trace"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.format, NodeConfigLogFormat::Json);
+        assert_eq!(config.rotation, NodeConfigLogRotation::Size);
+
+        let filter = daemon_log_filter(&config, LevelFilter::WARN).to_string();
+        assert!(filter.contains("iroh=debug"), "{filter}");
+        assert!(!filter.contains("iroh=warn"), "{filter}");
+        assert!(filter.contains("s5_node::jobs=trace"), "{filter}");
+        assert!(filter.contains("quinn=warn"), "{filter}");
+        assert!(filter.contains("info"), "{filter}");
+    }
+}
@@ -1,11 +1,12 @@
 mod cmd;
 mod interact;
+mod logging;
 mod node;
 mod progress;
 mod recovery;
 mod refs;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::{Parser, Subcommand};
 use clap_verbosity_flag::InfoLevel;
 use std::path::PathBuf;
@@ -266,6 +267,12 @@ enum Commands {
         patch_file: Option<PathBuf>,
     },
 
+    /// Cron jobs: list their schedule and last outcome, or run one now.
+    Jobs {
+        #[command(subcommand)]
+        cmd: Option<cmd::jobs::JobsCmd>,
+    },
+
     /// List node tasks, or show/follow one by id.
     #[command(alias = "t")]
     Tasks {
@@ -337,54 +344,6 @@ enum Commands {
     Daemon,
 }
 
-/// Build the daemon's tracing filter.
-///
-/// Honours `RUST_LOG` if set; otherwise the node log always captures
-/// full debug detail for the s5/vup/sia_storage side (per-shard Sia
-/// uploads, packing dedup + flush decisions, indexd sync — the record
-/// you want after the fact when something stalled), while the chatty
-/// third-party dependencies (iroh internals, quinn, pkarr resolvers,
-/// hickory DNS) stay clamped to warn. Operators who want raw iroh
-/// detail can set e.g. `RUST_LOG=debug,iroh=debug` without losing the
-/// s5-side context.
-fn daemon_log_filter(verb: tracing::level_filters::LevelFilter) -> tracing_subscriber::EnvFilter {
-    use tracing_subscriber::EnvFilter;
-
-    if std::env::var("RUST_LOG").is_ok() {
-        return EnvFilter::from_default_env();
-    }
-
-    // The file log is the flight recorder: never below debug (a `-v`
-    // asking for trace still wins).
-    let verb = verb.max(tracing::level_filters::LevelFilter::DEBUG);
-    let baseline = verb.to_string().to_lowercase();
-    let directives = [
-        baseline.as_str(),
-        "iroh=warn",
-        "iroh_blobs=warn",
-        "iroh_relay=warn",
-        "iroh_quinn=warn",
-        "iroh_quinn_proto=warn",
-        "iroh_dns_node_info=warn",
-        "iroh_metrics=warn",
-        "iroh_net_report=warn",
-        "iroh_dns_server=warn",
-        "pkarr=warn",
-        "mainline=warn",
-        "quinn=warn",
-        "quinn_proto=warn",
-        "hickory_resolver=warn",
-        "hickory_proto=warn",
-        "h2=warn",
-        "hyper=warn",
-        "hyper_util=warn",
-        "rustls=warn",
-        "swarm_discovery=warn",
-        "watchable=warn",
-    ];
-    EnvFilter::try_new(directives.join(",")).unwrap_or_else(|_| EnvFilter::new(baseline))
-}
-
 fn resolve_config(cli_override: Option<PathBuf>) -> Result<PathBuf> {
     match cli_override {
         Some(p) => Ok(p),
@@ -403,32 +362,19 @@ async fn main() -> Result<()> {
 
     let is_daemon = matches!(&cli.cmd, Commands::Daemon);
 
-    if is_daemon {
-        let log_dir = dirs::cache_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("s5")
-            .join("logs");
-        std::fs::create_dir_all(&log_dir)?;
-
-        // Daily rotation with bounded retention: the daemon always logs at
-        // debug for the domain crates (drill directive), so an unrotated
-        // node.log grows without bound. Files land as
-        // `node.log.YYYY-MM-DD`, newest 7 kept.
-        let file_appender = tracing_appender::rolling::Builder::new()
-            .rotation(tracing_appender::rolling::Rotation::DAILY)
-            .filename_prefix("node.log")
-            .max_log_files(7)
-            .build(&log_dir)
-            .context("building the rotating node.log appender")?;
-        let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
-
-        tracing_subscriber::fmt()
-            .with_env_filter(daemon_log_filter(cli.verbosity.tracing_level_filter()))
-            .with_writer(file_writer)
-            .with_ansi(false)
-            .init();
+    let config_path = resolve_config(cli.config)?;
 
-        tracing::info!(log_dir = %log_dir.display(), "s5_node daemon log open (node.log.<date>, daily rotation, 7 kept)");
+    if is_daemon {
+        // Default: `node.log.YYYY-MM-DD` under the cache dir, daily
+        // rotation, newest 7 kept. The daemon always logs at debug for the
+        // domain crates (drill directive), so an unrotated node.log grows
+        // without bound.
+        let log_config = logging::peek_log_config(&config_path);
+        let guard = logging::init_daemon_logging(
+            &log_config,
+            &config_path,
+            cli.verbosity.tracing_level_filter(),
+        )?;
 
         // Keep the appender guard alive for the program's lifetime.
         Box::leak(Box::new(guard));
@@ -438,8 +384,6 @@ async fn main() -> Result<()> {
             .init();
     }
 
-    let config_path = resolve_config(cli.config)?;
-
     interact::set_assume_yes(cli.yes);
 
     let result = run_command(cli.cmd, &config_path).await;
@@ -494,12 +438,16 @@ async fn run_command(cmd: Commands, config_path: &std::path::Path) -> Result<()>
     }
 
     let client = node::ensure_node_running(config_path).await?;
-    let result = dispatch(&client, cmd).await;
+    let result = dispatch(&client, cmd, config_path).await;
     client.close().await;
     result
 }
 
-async fn dispatch(client: &s5_node_api::S5NodeClient, cmd: Commands) -> Result<()> {
+async fn dispatch(
+    client: &s5_node_api::S5NodeClient,
+    cmd: Commands,
+    config_path: &std::path::Path,
+) -> Result<()> {
     match cmd {
         // Handled in run_command before the daemon connection.
         Commands::Daemon | Commands::Onboard | Commands::Recover | Commands::Service { .. } => {
@@ -628,7 +576,7 @@ async fn dispatch(client: &s5_node_api::S5NodeClient, cmd: Commands) -> Result<(
         Commands::Store { cmd } => cmd::store::run_store(client, cmd).await,
 
         // -- Ops ------------------------------------------------------------
-        Commands::Status => cmd::run_status(client).await,
+        Commands::Status => cmd::run_status(client, config_path).await,
         Commands::Doctor => cmd::doctor::run_doctor(client).await,
         Commands::Config {
             vault,
@@ -640,6 +588,7 @@ async fn dispatch(client: &s5_node_api::S5NodeClient, cmd: Commands) -> Result<(
             Some(id) => cmd::tasks::task_status(client, id).await,
             None => cmd::tasks::list_tasks(client).await,
         },
+        Commands::Jobs { cmd } => cmd::jobs::run_jobs(client, cmd).await,
         Commands::Cancel { task_id } => cmd::tasks::cancel_task(client, task_id).await,
         Commands::Shutdown => cmd::run_shutdown(client).await,
 